@@ -0,0 +1,471 @@
+//! Client-side summaries of result rows, such as the number of genomes and
+//! their mean genome size in each GTDB family
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::taxonomy::{parse_lineage, Rank};
+
+/// A result row, as column name and value pairs
+pub(crate) type Row = HashMap<String, String>;
+
+/// Aggregate of the rows of a group
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Aggregate {
+    Count,
+    Sum(String),
+    Mean(String),
+    Median(String),
+    Min(String),
+    Max(String),
+}
+
+impl Aggregate {
+    /// Parse an aggregate, e.g. "count" or "mean(genome_size)"
+    pub(crate) fn parse(aggregate: &str) -> Result<Self, String> {
+        let aggregate = aggregate.trim();
+        if aggregate == "count" {
+            return Ok(Aggregate::Count);
+        }
+        let invalid = || {
+            format!(
+                "Invalid aggregate {}, expected count or sum, mean, median, min or max of a column, e.g. mean(genome_size)",
+                aggregate
+            )
+        };
+        let (function, column) = aggregate
+            .strip_suffix(')')
+            .and_then(|x| x.split_once('('))
+            .ok_or_else(invalid)?;
+        let column = column.trim().to_string();
+        if column.is_empty() {
+            return Err(invalid());
+        }
+        match function.trim() {
+            "sum" => Ok(Aggregate::Sum(column)),
+            "mean" => Ok(Aggregate::Mean(column)),
+            "median" => Ok(Aggregate::Median(column)),
+            "min" => Ok(Aggregate::Min(column)),
+            "max" => Ok(Aggregate::Max(column)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Column read by the aggregate, if any
+    pub(crate) fn get_column(&self) -> Option<&str> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Sum(x)
+            | Aggregate::Mean(x)
+            | Aggregate::Median(x)
+            | Aggregate::Min(x)
+            | Aggregate::Max(x) => Some(x),
+        }
+    }
+
+    /// Name of the aggregate, as the header of its column
+    pub(crate) fn name(&self) -> String {
+        let (function, column) = match self {
+            Aggregate::Count => return "count".to_string(),
+            Aggregate::Sum(x) => ("sum", x),
+            Aggregate::Mean(x) => ("mean", x),
+            Aggregate::Median(x) => ("median", x),
+            Aggregate::Min(x) => ("min", x),
+            Aggregate::Max(x) => ("max", x),
+        };
+        format!("{}({})", function, column)
+    }
+
+    /// Aggregate of `rows`, empty when none of them has a numeric value
+    fn compute(&self, rows: &[&Row]) -> String {
+        let Some(column) = self.get_column() else {
+            return rows.len().to_string();
+        };
+        let mut numbers: Vec<f64> = rows
+            .iter()
+            .filter_map(|row| column_value(row, column)?.trim().parse::<f64>().ok())
+            .filter(|x| x.is_finite())
+            .collect();
+        if numbers.is_empty() {
+            return String::new();
+        }
+        numbers.sort_by(f64::total_cmp);
+        let n = numbers.len();
+        let value = match self {
+            Aggregate::Count => unreachable!("count reads no column"),
+            Aggregate::Sum(_) => numbers.iter().sum(),
+            Aggregate::Mean(_) => numbers.iter().sum::<f64>() / n as f64,
+            Aggregate::Median(_) if n % 2 == 0 => (numbers[n / 2 - 1] + numbers[n / 2]) / 2.0,
+            Aggregate::Median(_) => numbers[n / 2],
+            Aggregate::Min(_) => numbers[0],
+            Aggregate::Max(_) => numbers[n - 1],
+        };
+        format_number(value)
+    }
+}
+
+/// Format a number with at most two decimals, e.g. "4213.5" or "12"
+pub(crate) fn format_number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    format!("{}", rounded)
+}
+
+/// Value of a column of a row, ranks (e.g. "phylum") being read from its
+/// GTDB taxonomy
+pub(crate) fn column_value(row: &Row, column: &str) -> Option<String> {
+    match Rank::from_name(column) {
+        Some(rank) => parse_lineage(row.get("gtdb_taxonomy")?).remove(&rank),
+        None => row.get(column).cloned(),
+    }
+}
+
+/// Group rows by the value of `key` and aggregate each group, as rows of
+/// the key followed by the aggregates, sorted by key
+pub(crate) fn group_by(rows: &[Row], key: &str, aggregates: &[Aggregate]) -> Vec<Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<&Row>> = BTreeMap::new();
+    for row in rows {
+        let value = column_value(row, key).unwrap_or_default();
+        groups.entry(value).or_default().push(row);
+    }
+    groups
+        .into_iter()
+        .map(|(value, rows)| {
+            std::iter::once(value)
+                .chain(aggregates.iter().map(|x| x.compute(&rows)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parse the row and column keys of a pivot, e.g.
+/// "rows=phylum,cols=ncbi_assembly_level"
+pub(crate) fn parse_pivot(pivot: &str) -> Result<(String, String), String> {
+    let (mut rows, mut cols) = (None, None);
+    for part in pivot.split(',') {
+        match part.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+            Some(("rows", v)) if !v.is_empty() => rows = Some(v.to_string()),
+            Some(("cols", v)) if !v.is_empty() => cols = Some(v.to_string()),
+            _ => rows = None,
+        }
+    }
+    rows.zip(cols).ok_or_else(|| {
+        format!(
+            "Invalid pivot {}, expected rows=COLUMN,cols=COLUMN, e.g. rows=phylum,cols=ncbi_assembly_level",
+            pivot
+        )
+    })
+}
+
+/// Contingency table of the number of rows for each value of `row_key`
+/// and `col_key`, as its header and rows, with the totals of the rows and
+/// columns last
+pub(crate) fn pivot(rows: &[Row], row_key: &str, col_key: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    let mut col_totals: BTreeMap<String, usize> = BTreeMap::new();
+    for row in rows {
+        let row_value = column_value(row, row_key).unwrap_or_default();
+        let col_value = column_value(row, col_key).unwrap_or_default();
+        *counts
+            .entry(row_value)
+            .or_default()
+            .entry(col_value.clone())
+            .or_default() += 1;
+        *col_totals.entry(col_value).or_default() += 1;
+    }
+
+    let header = std::iter::once(row_key.to_string())
+        .chain(col_totals.keys().cloned())
+        .chain(std::iter::once("total".to_string()))
+        .collect();
+    let mut table: Vec<Vec<String>> = counts
+        .into_iter()
+        .map(|(row_value, counts)| {
+            std::iter::once(row_value)
+                .chain(
+                    col_totals
+                        .keys()
+                        .map(|x| counts.get(x).copied().unwrap_or(0).to_string()),
+                )
+                .chain(std::iter::once(counts.values().sum::<usize>().to_string()))
+                .collect()
+        })
+        .collect();
+    table.push(
+        std::iter::once("total".to_string())
+            .chain(col_totals.values().map(usize::to_string))
+            .chain(std::iter::once(rows.len().to_string()))
+            .collect(),
+    );
+    (header, table)
+}
+
+/// Number of bins of histograms
+const HISTOGRAM_BINS: usize = 10;
+
+/// Width of the longest bar of histograms, in characters
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Bin of a histogram: its bounds and number of values
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Bin {
+    column: String,
+    start: f64,
+    end: f64,
+    count: usize,
+}
+
+/// Histogram of the numeric values of `column` in `rows`, as equal-width
+/// bins from the lowest to the highest value, and the number of rows
+/// without a numeric value
+pub(crate) fn histogram(rows: &[Row], column: &str) -> (Vec<Bin>, usize) {
+    let values: Vec<f64> = rows
+        .iter()
+        .filter_map(|row| column_value(row, column)?.trim().parse::<f64>().ok())
+        .filter(|x| x.is_finite())
+        .collect();
+    let missing = rows.len() - values.len();
+    let (Some(min), Some(max)) = (
+        values.iter().copied().reduce(f64::min),
+        values.iter().copied().reduce(f64::max),
+    ) else {
+        return (vec![], missing);
+    };
+
+    let bins = if min == max { 1 } else { HISTOGRAM_BINS };
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0; bins];
+    for value in values {
+        let index = match width > 0.0 {
+            true => ((value - min) / width) as usize,
+            false => 0,
+        };
+        counts[index.min(bins - 1)] += 1;
+    }
+    let bins = counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| Bin {
+            column: column.to_string(),
+            start: min + width * i as f64,
+            end: min + width * (i + 1) as f64,
+            count,
+        })
+        .collect();
+    (bins, missing)
+}
+
+/// Render a histogram in the terminal: a title with a sparkline of the
+/// distribution, then a bar for each bin
+pub(crate) fn render_histogram(column: &str, bins: &[Bin], missing: usize) -> String {
+    let total: usize = bins.iter().map(|x| x.count).sum();
+    let max = bins.iter().map(|x| x.count).max().unwrap_or(0);
+    let mut output = format!("{}: {} values", column, total);
+    if missing > 0 {
+        output.push_str(&format!(", {} missing", missing));
+    }
+    if max == 0 {
+        output.push('\n');
+        return output;
+    }
+
+    const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let sparkline: String = bins
+        .iter()
+        .map(|x| SPARKS[x.count * (SPARKS.len() - 1) / max])
+        .collect();
+    output.push_str(&format!(" {}\n", sparkline));
+
+    let labels: Vec<String> = bins
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let close = if i + 1 == bins.len() { ']' } else { ')' };
+            format!(
+                "[{}, {}{}",
+                format_number(x.start),
+                format_number(x.end),
+                close
+            )
+        })
+        .collect();
+    let label_width = labels.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let count_width = max.to_string().len();
+    for (label, bin) in labels.iter().zip(bins) {
+        let bar = "█".repeat((bin.count * HISTOGRAM_WIDTH + max - 1) / max);
+        output.push_str(&format!(
+            "{:<label_width$}  {:>count_width$}  {}\n",
+            label, bin.count, bar
+        ));
+    }
+    output
+}
+
+/// Grouped rows as JSON records, aggregates being numbers or null
+pub(crate) fn to_json_records(header: &[&str], rows: &[Vec<String>]) -> Vec<Value> {
+    rows.iter()
+        .map(|row| {
+            let record: Map<String, Value> = header
+                .iter()
+                .zip(row)
+                .enumerate()
+                .map(|(i, (name, value))| {
+                    let value = match i {
+                        0 => Value::from(value.as_str()),
+                        _ => value
+                            .parse::<f64>()
+                            .ok()
+                            .and_then(serde_json::Number::from_f64)
+                            .map_or(Value::Null, Value::Number),
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+            Value::Object(record)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fields: &[(&str, &str)]) -> Row {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        assert_eq!(Aggregate::parse("count"), Ok(Aggregate::Count));
+        assert_eq!(
+            Aggregate::parse("mean(genome_size)"),
+            Ok(Aggregate::Mean("genome_size".to_string()))
+        );
+        assert_eq!(
+            Aggregate::parse("max( gc_percentage )").unwrap().name(),
+            "max(gc_percentage)"
+        );
+        assert!(Aggregate::parse("mode(genome_size)").is_err());
+        assert!(Aggregate::parse("mean()").is_err());
+        assert!(Aggregate::parse("genome_size").is_err());
+    }
+
+    #[test]
+    fn test_parse_pivot() {
+        assert_eq!(
+            parse_pivot("rows=phylum,cols=ncbi_assembly_level"),
+            Ok(("phylum".to_string(), "ncbi_assembly_level".to_string()))
+        );
+        assert_eq!(
+            parse_pivot("cols=genus, rows=family"),
+            Ok(("family".to_string(), "genus".to_string()))
+        );
+        assert!(parse_pivot("rows=phylum").is_err());
+        assert!(parse_pivot("rows=phylum,cols=genus,x=y").is_err());
+    }
+
+    #[test]
+    fn test_pivot() {
+        let rows = vec![
+            row(&[("gtdb_taxonomy", "d__Bacteria; p__A"), ("level", "Contig")]),
+            row(&[
+                ("gtdb_taxonomy", "d__Bacteria; p__A"),
+                ("level", "Complete Genome"),
+            ]),
+            row(&[("gtdb_taxonomy", "d__Bacteria; p__B"), ("level", "Contig")]),
+            row(&[("gtdb_taxonomy", "d__Bacteria; p__A"), ("level", "Contig")]),
+        ];
+        let (header, table) = pivot(&rows, "phylum", "level");
+        assert_eq!(header, ["phylum", "Complete Genome", "Contig", "total"]);
+        assert_eq!(
+            table,
+            vec![
+                vec!["p__A", "1", "2", "3"],
+                vec!["p__B", "0", "1", "1"],
+                vec!["total", "1", "3", "4"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let rows: Vec<Row> = ["1", "2", "2", "11", "none"]
+            .iter()
+            .map(|x| row(&[("genome_size", x)]))
+            .collect();
+        let (bins, missing) = histogram(&rows, "genome_size");
+        assert_eq!(missing, 1);
+        assert_eq!(bins.len(), HISTOGRAM_BINS);
+        assert_eq!((bins[0].start, bins[0].end), (1.0, 2.0));
+        let counts: Vec<usize> = bins.iter().map(|x| x.count).collect();
+        assert_eq!(counts, [1, 2, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let output = render_histogram("genome_size", &bins, missing);
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some("genome_size: 4 values, 1 missing ▄█▁▁▁▁▁▁▁▄")
+        );
+        assert_eq!(lines.next(), Some("[1, 2)    1  ████████████████████"));
+        assert_eq!(lines.last(), Some("[10, 11]  1  ████████████████████"));
+
+        // Identical values make a single bin
+        let rows = vec![row(&[("genome_size", "5")]); 2];
+        let (bins, _) = histogram(&rows, "genome_size");
+        assert_eq!(bins.len(), 1);
+        assert_eq!(bins[0].count, 2);
+        assert_eq!(histogram(&[], "genome_size").0, vec![]);
+    }
+
+    #[test]
+    fn test_group_by() {
+        let rows = vec![
+            row(&[
+                (
+                    "gtdb_taxonomy",
+                    "d__Bacteria; p__Bacillota; g__Lactobacillus",
+                ),
+                ("ncbi_assembly_level", "Complete Genome"),
+                ("genome_size", "2000000"),
+            ]),
+            row(&[
+                (
+                    "gtdb_taxonomy",
+                    "d__Bacteria; p__Bacillota; g__Lactobacillus",
+                ),
+                ("ncbi_assembly_level", "Contig"),
+                ("genome_size", "1000001"),
+            ]),
+            row(&[
+                (
+                    "gtdb_taxonomy",
+                    "d__Bacteria; p__Bacillota; g__Limosilactobacillus",
+                ),
+                ("ncbi_assembly_level", "Contig"),
+                ("genome_size", "none"),
+            ]),
+        ];
+        let aggregates = [
+            Aggregate::Count,
+            Aggregate::Mean("genome_size".to_string()),
+            Aggregate::Median("genome_size".to_string()),
+        ];
+        assert_eq!(
+            group_by(&rows, "ncbi_assembly_level", &aggregates),
+            vec![
+                vec!["Complete Genome", "1", "2000000", "2000000"],
+                vec!["Contig", "2", "1000001", "1000001"],
+            ]
+        );
+        assert_eq!(
+            group_by(&rows, "genus", &aggregates[..2]),
+            vec![
+                vec!["g__Lactobacillus", "2", "1500000.5"],
+                vec!["g__Limosilactobacillus", "1", ""],
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+use super::GTDB_API_URL;
+
+/// FastANI parameters of a GTDB API job
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FastAniParameters {
+    pub kmer: u32,
+    pub frag_len: u32,
+}
+
+impl Default for FastAniParameters {
+    fn default() -> Self {
+        FastAniParameters {
+            kmer: 16,
+            frag_len: 3000,
+        }
+    }
+}
+
+/// Body of a FastANI job submission to the GTDB API
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct FastAniJobRequest {
+    pub query: Vec<String>,
+    pub reference: Vec<String>,
+    pub parameters: FastAniParameters,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FastAniAPI;
+
+impl FastAniAPI {
+    /// Creates a new `FastAniAPI` instance.
+    pub fn new() -> Self {
+        FastAniAPI
+    }
+
+    /// Constructs a URL to submit a FastANI job.
+    pub fn get_submit_request(&self) -> String {
+        format!("{}/fastani", GTDB_API_URL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_submit_request() {
+        assert_eq!(
+            FastAniAPI::new().get_submit_request(),
+            "https://api.gtdb.ecogenomic.org/fastani"
+        );
+    }
+
+    #[test]
+    fn test_job_request_body() {
+        let request = FastAniJobRequest {
+            query: vec!["GCA_001512625.1".to_string()],
+            reference: vec!["GCF_018555685.1".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"query":["GCA_001512625.1"],"reference":["GCF_018555685.1"],"parameters":{"kmer":16,"frag_len":3000}}"#
+        );
+    }
+}
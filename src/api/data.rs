@@ -0,0 +1,173 @@
+use std::fmt;
+
+/// Domain of the GTDB release files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Domain {
+    // Bacteria, bac120 marker set
+    #[default]
+    Bacteria,
+    // Archaea, ar53 marker set (ar122 up to R95)
+    Archaea,
+}
+
+impl Domain {
+    /// Marker set prefixes of the release file names of the domain
+    pub fn markers(&self) -> &'static [&'static str] {
+        match self {
+            Domain::Bacteria => &["bac120"],
+            Domain::Archaea => &["ar53", "ar122"],
+        }
+    }
+}
+
+impl From<String> for Domain {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "ar" => Domain::Archaea,
+            _ => Domain::Bacteria,
+        }
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Domain::Bacteria => "bac",
+            Domain::Archaea => "ar",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Release files on the GTDB data server
+#[derive(Debug, Clone, Default)]
+pub struct GtdbDataAPI {
+    // Release number, None for the latest release
+    release: Option<u32>,
+}
+
+impl GtdbDataAPI {
+    /// Creates a new `GtdbDataAPI` instance for a release number,
+    /// or the latest release.
+    pub fn new(release: Option<u32>) -> Self {
+        GtdbDataAPI { release }
+    }
+
+    /// Constructs the URL of the release directory.
+    pub fn get_release_url(&self) -> String {
+        match self.release {
+            Some(n) => format!(
+                "https://data.gtdb.ecogenomic.org/releases/release{}/{}.0",
+                n, n
+            ),
+            None => "https://data.gtdb.ecogenomic.org/releases/latest".to_string(),
+        }
+    }
+
+    /// Constructs the URL of the md5 checksums of the release files.
+    pub fn get_checksums_request(&self) -> String {
+        format!("{}/MD5SUM.txt", self.get_release_url())
+    }
+
+    /// Constructs the URL of a release file.
+    pub fn get_file_request(&self, name: &str) -> String {
+        format!("{}/{}", self.get_release_url(), name)
+    }
+}
+
+/// Find the release file of `domain` named `<marker>_<kind>*<suffix>`
+/// among the file names of a release
+pub fn find_release_file<'a>(
+    names: impl IntoIterator<Item = &'a String>,
+    domain: Domain,
+    kind: &str,
+    suffix: &str,
+) -> Option<String> {
+    let prefixes: Vec<String> = domain
+        .markers()
+        .iter()
+        .map(|marker| format!("{}_{}", marker, kind))
+        .collect();
+    names
+        .into_iter()
+        .find(|name| {
+            name.ends_with(suffix) && prefixes.iter().any(|prefix| name.starts_with(prefix))
+        })
+        .cloned()
+}
+
+/// Find the reference tree of `domain`, named `<marker>_r<release>.tree`,
+/// possibly gzipped, among the file names of a release
+pub fn find_tree_file<'a>(
+    names: impl IntoIterator<Item = &'a String>,
+    domain: Domain,
+) -> Option<String> {
+    names
+        .into_iter()
+        .find(|name| {
+            domain.markers().iter().any(|marker| {
+                name.strip_prefix(marker)
+                    .and_then(|x| x.strip_prefix("_r"))
+                    .and_then(|x| x.strip_suffix(".gz").or(Some(x)))
+                    .and_then(|x| x.strip_suffix(".tree"))
+                    .is_some_and(|x| !x.is_empty() && x.chars().all(|c| c.is_ascii_digit()))
+            })
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_release_url() {
+        assert_eq!(
+            GtdbDataAPI::new(Some(220)).get_checksums_request(),
+            "https://data.gtdb.ecogenomic.org/releases/release220/220.0/MD5SUM.txt"
+        );
+        assert_eq!(
+            GtdbDataAPI::new(None).get_file_request("bac120_metadata.tsv.gz"),
+            "https://data.gtdb.ecogenomic.org/releases/latest/bac120_metadata.tsv.gz"
+        );
+    }
+
+    #[test]
+    fn test_find_release_file() {
+        let names = vec![
+            "VERSION.txt".to_string(),
+            "ar53_metadata_r220.tsv.gz".to_string(),
+            "bac120_metadata_r220.tsv.gz".to_string(),
+            "bac120_r220.tree.gz".to_string(),
+        ];
+        assert_eq!(
+            find_release_file(&names, Domain::Archaea, "metadata", ".tsv.gz"),
+            Some("ar53_metadata_r220.tsv.gz".to_string())
+        );
+        assert_eq!(
+            find_release_file(&names, Domain::Bacteria, "metadata", ".tsv.gz"),
+            Some("bac120_metadata_r220.tsv.gz".to_string())
+        );
+        assert_eq!(
+            find_release_file(&names, Domain::Bacteria, "taxonomy", ".tsv.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_tree_file() {
+        let names = vec![
+            "bac120_r220.sp_labels.tree".to_string(),
+            "bac120_r220.tree.gz".to_string(),
+            "ar53_r220.tree".to_string(),
+        ];
+        assert_eq!(
+            find_tree_file(&names, Domain::Bacteria),
+            Some("bac120_r220.tree.gz".to_string())
+        );
+        assert_eq!(
+            find_tree_file(&names, Domain::Archaea),
+            Some("ar53_r220.tree".to_string())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use super::GTDB_API_URL;
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum GenomeRequestType {
     Metadata,
@@ -32,8 +34,8 @@ impl From<String> for GenomeAPI {
 impl GenomeAPI {
     pub fn request(&self, request_type: GenomeRequestType) -> String {
         format!(
-            "https://api.gtdb.ecogenomic.org/genome/{}/{}",
-            self.accession, request_type
+            "{}/genome/{}/{}",
+            GTDB_API_URL, self.accession, request_type
         )
     }
 }
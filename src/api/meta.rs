@@ -0,0 +1,30 @@
+use super::GTDB_API_URL;
+
+#[derive(Debug, Clone, Default)]
+pub struct MetaAPI;
+
+impl MetaAPI {
+    /// Creates a new `MetaAPI` instance.
+    pub fn new() -> Self {
+        MetaAPI
+    }
+
+    /// Constructs a URL for the list of GTDB releases.
+    pub fn get_releases_request(&self) -> String {
+        format!("{}/meta/releases", GTDB_API_URL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_releases_request() {
+        let api = MetaAPI::new();
+        assert_eq!(
+            api.get_releases_request(),
+            "https://api.gtdb.ecogenomic.org/meta/releases"
+        );
+    }
+}
@@ -1,3 +1,63 @@
+//! Request builders of the GTDB API, sharing its base URL
+
+/// Base URL of the GTDB API
+pub(crate) const GTDB_API_URL: &str = "https://api.gtdb.ecogenomic.org";
+
+pub mod ani;
+pub mod data;
 pub mod genome;
+pub mod meta;
+pub mod ncbi;
+pub mod raw;
 pub mod search;
 pub mod taxon;
+
+/// Kind and name of the record queried by a GTDB API URL, e.g.
+/// `("accession", "GCA_001512625.1")` for a genome card, `("taxon", ...)`
+/// for a taxon or `("needle", ...)` for a search
+pub fn query_subject(url: &str) -> Option<(&'static str, String)> {
+    let path = url.strip_prefix(GTDB_API_URL)?.trim_start_matches('/');
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let mut segments = path.split('/');
+    let subject = match segments.next()? {
+        "genome" => ("accession", segments.next()?),
+        "taxon" => match segments.next()? {
+            "search" => ("taxon", segments.next()?),
+            name => ("taxon", name),
+        },
+        "search" => (
+            "needle",
+            query.split('&').find_map(|x| x.strip_prefix("search="))?,
+        ),
+        _ => return None,
+    };
+    Some((subject.0, subject.1.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_subject() {
+        let subject = |x: &str| query_subject(&format!("{}{}", GTDB_API_URL, x));
+        assert_eq!(
+            subject("/genome/GCA_001512625.1/card"),
+            Some(("accession", "GCA_001512625.1".to_string()))
+        );
+        assert_eq!(
+            subject("/taxon/g__Aminobacter/genomes?sp_reps_only=false"),
+            Some(("taxon", "g__Aminobacter".to_string()))
+        );
+        assert_eq!(
+            subject("/taxon/search/g__Amino?limit=1000000"),
+            Some(("taxon", "g__Amino".to_string()))
+        );
+        assert_eq!(
+            subject("/search/gtdb/csv?search=g__Aminobacter&page=1"),
+            Some(("needle", "g__Aminobacter".to_string()))
+        );
+        assert_eq!(subject("/meta/releases"), None);
+        assert_eq!(query_subject("https://example.org/genome/GCA_1/card"), None);
+    }
+}
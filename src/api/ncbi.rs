@@ -0,0 +1,218 @@
+use std::fmt;
+
+/// Files of an assembly directory on the NCBI FTP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssemblyFile {
+    // Genomic FASTA
+    #[default]
+    Genome,
+    // Protein FASTA
+    Protein,
+    // Genome annotation in GFF3
+    Gff,
+    // Coding sequences FASTA
+    Cds,
+}
+
+impl AssemblyFile {
+    /// Suffix appended to the assembly basename by NCBI
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            AssemblyFile::Genome => "_genomic.fna.gz",
+            AssemblyFile::Protein => "_protein.faa.gz",
+            AssemblyFile::Gff => "_genomic.gff.gz",
+            AssemblyFile::Cds => "_cds_from_genomic.fna.gz",
+        }
+    }
+}
+
+impl From<String> for AssemblyFile {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "protein" => AssemblyFile::Protein,
+            "gff" => AssemblyFile::Gff,
+            "cds" => AssemblyFile::Cds,
+            _ => AssemblyFile::Genome,
+        }
+    }
+}
+
+impl fmt::Display for AssemblyFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AssemblyFile::Genome => "genome",
+            AssemblyFile::Protein => "protein",
+            AssemblyFile::Gff => "gff",
+            AssemblyFile::Cds => "cds",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NcbiFtpAPI {
+    accession: String,
+    assembly_name: String,
+}
+
+/// Remove GTDB database prefix (RS_ or GB_) from an accession
+pub fn strip_gtdb_prefix(accession: &str) -> &str {
+    accession
+        .strip_prefix("RS_")
+        .or_else(|| accession.strip_prefix("GB_"))
+        .unwrap_or(accession)
+}
+
+/// Check that an accession is a NCBI assembly accession, e.g. GCA_001512625.1
+pub fn is_assembly_accession(accession: &str) -> bool {
+    let accession = strip_gtdb_prefix(accession);
+    let Some((number, version)) = accession
+        .strip_prefix("GCA_")
+        .or_else(|| accession.strip_prefix("GCF_"))
+        .and_then(|x| x.split_once('.'))
+    else {
+        return false;
+    };
+    number.len() == 9
+        && number.chars().all(|c| c.is_ascii_digit())
+        && !version.is_empty()
+        && version.chars().all(|c| c.is_ascii_digit())
+}
+
+impl NcbiFtpAPI {
+    /// Creates a new `NcbiFtpAPI` from an assembly accession and its NCBI assembly name.
+    pub fn new(accession: &str, assembly_name: &str) -> Self {
+        // NCBI replaces any character which is not alphanumeric, `.`, `-` or `_`
+        // by an underscore when building the assembly directory name
+        let assembly_name = assembly_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        NcbiFtpAPI {
+            accession: strip_gtdb_prefix(accession).to_string(),
+            assembly_name,
+        }
+    }
+
+    /// Assembly basename, e.g. "GCA_001512625.1_ASM151262v1"
+    pub fn basename(&self) -> String {
+        format!("{}_{}", self.accession, self.assembly_name)
+    }
+
+    /// Constructs the URL of the assembly directory.
+    pub fn directory(&self) -> String {
+        let (prefix, number) = self.accession.split_at(3);
+        let digits: String = number
+            .trim_start_matches('_')
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        format!(
+            "https://ftp.ncbi.nlm.nih.gov/genomes/all/{}/{}/{}/{}/{}",
+            prefix,
+            &digits[0..3],
+            &digits[3..6],
+            &digits[6..9],
+            self.basename()
+        )
+    }
+
+    /// Constructs the URL of the md5 checksums of the assembly directory files.
+    pub fn checksums(&self) -> String {
+        format!("{}/md5checksums.txt", self.directory())
+    }
+
+    /// Constructs the URL of a file of the assembly directory.
+    pub fn file(&self, file: AssemblyFile) -> String {
+        format!("{}/{}{}", self.directory(), self.basename(), file.suffix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_gtdb_prefix() {
+        assert_eq!(strip_gtdb_prefix("RS_GCF_018555685.1"), "GCF_018555685.1");
+        assert_eq!(strip_gtdb_prefix("GB_GCA_001512625.1"), "GCA_001512625.1");
+        assert_eq!(strip_gtdb_prefix("GCA_001512625.1"), "GCA_001512625.1");
+    }
+
+    #[test]
+    fn test_is_assembly_accession() {
+        assert!(is_assembly_accession("GCA_001512625.1"));
+        assert!(is_assembly_accession("RS_GCF_018555685.12"));
+        assert!(!is_assembly_accession("GCA_0015126.1"));
+        assert!(!is_assembly_accession("NC_000912.1"));
+        assert!(!is_assembly_accession("GCA_001512625"));
+    }
+
+    #[test]
+    fn test_ncbi_ftp_directory() {
+        let api = NcbiFtpAPI::new("GB_GCA_001512625.1", "ASM151262v1");
+        assert_eq!(
+            api.directory(),
+            "https://ftp.ncbi.nlm.nih.gov/genomes/all/GCA/001/512/625/GCA_001512625.1_ASM151262v1"
+        );
+    }
+
+    #[test]
+    fn test_ncbi_ftp_checksums() {
+        let api = NcbiFtpAPI::new("GCA_001512625.1", "ASM151262v1");
+        assert_eq!(
+            api.checksums(),
+            "https://ftp.ncbi.nlm.nih.gov/genomes/all/GCA/001/512/625/GCA_001512625.1_ASM151262v1/md5checksums.txt"
+        );
+    }
+
+    #[test]
+    fn test_ncbi_ftp_file() {
+        let api = NcbiFtpAPI::new("GCF_000005845.2", "ASM584v2");
+        assert_eq!(
+            api.file(AssemblyFile::Genome),
+            "https://ftp.ncbi.nlm.nih.gov/genomes/all/GCF/000/005/845/GCF_000005845.2_ASM584v2/GCF_000005845.2_ASM584v2_genomic.fna.gz"
+        );
+    }
+
+    #[test]
+    fn test_assembly_file_from_string() {
+        assert_eq!(
+            AssemblyFile::from("genome".to_string()),
+            AssemblyFile::Genome
+        );
+        assert_eq!(
+            AssemblyFile::from("protein".to_string()),
+            AssemblyFile::Protein
+        );
+        assert_eq!(AssemblyFile::from("gff".to_string()), AssemblyFile::Gff);
+        assert_eq!(AssemblyFile::from("cds".to_string()), AssemblyFile::Cds);
+        assert_eq!(AssemblyFile::Cds.to_string(), "cds");
+    }
+
+    #[test]
+    fn test_ncbi_ftp_annotation_files() {
+        let api = NcbiFtpAPI::new("GCF_000005845.2", "ASM584v2");
+        assert!(api
+            .file(AssemblyFile::Protein)
+            .ends_with("/GCF_000005845.2_ASM584v2_protein.faa.gz"));
+        assert!(api
+            .file(AssemblyFile::Gff)
+            .ends_with("/GCF_000005845.2_ASM584v2_genomic.gff.gz"));
+        assert!(api
+            .file(AssemblyFile::Cds)
+            .ends_with("/GCF_000005845.2_ASM584v2_cds_from_genomic.fna.gz"));
+    }
+
+    #[test]
+    fn test_ncbi_ftp_assembly_name_sanitized() {
+        let api = NcbiFtpAPI::new("GCA_000001405.28", "GRCh38 p13/alt");
+        assert_eq!(api.basename(), "GCA_000001405.28_GRCh38_p13_alt");
+    }
+}
@@ -0,0 +1,32 @@
+use super::GTDB_API_URL;
+
+/// A path of the GTDB API, for endpoints xgt does not wrap yet
+#[derive(Debug, Clone, Default)]
+pub struct RawAPI {
+    path: String,
+}
+
+impl RawAPI {
+    pub fn new(path: impl Into<String>) -> Self {
+        RawAPI { path: path.into() }
+    }
+
+    /// URL of the path, which can be given with or without the API base URL
+    pub fn request(&self) -> String {
+        let path = self.path.strip_prefix(GTDB_API_URL).unwrap_or(&self.path);
+        format!("{}/{}", GTDB_API_URL, path.trim_start_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_request() {
+        let url = "https://api.gtdb.ecogenomic.org/taxon/g__Aminobacter/genomes";
+        assert_eq!(RawAPI::new("/taxon/g__Aminobacter/genomes").request(), url);
+        assert_eq!(RawAPI::new("taxon/g__Aminobacter/genomes").request(), url);
+        assert_eq!(RawAPI::new(url).request(), url);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::cli::search::SearchArgs;
+use super::GTDB_API_URL;
 
 #[derive(Debug, Clone)]
 pub struct SearchAPI {
@@ -36,12 +36,16 @@ impl SearchAPI {
         SearchAPI::default()
     }
 
-    fn set_search(mut self, s: &str) -> Self {
+    pub fn set_search(mut self, s: &str) -> Self {
         self.search = s.to_string();
         self
     }
 
-    fn set_search_field(mut self, field: &str) -> Self {
+    pub fn get_search(&self) -> &str {
+        &self.search
+    }
+
+    pub fn set_search_field(mut self, field: &str) -> Self {
         self.search_field = field.to_string();
         self
     }
@@ -61,18 +65,20 @@ impl SearchAPI {
         self
     }
 
-    pub fn from(search: &str, args: &SearchArgs) -> Self {
-        SearchAPI::new()
-            .set_search(search)
-            .set_gtdb_species_rep_only(args.is_representative_species_only())
-            .set_ncbi_type_material_only(args.is_type_species_only())
-            .set_outfmt(&args.get_outfmt().to_string())
-            .set_search_field(&args.get_search_field().to_string())
+    pub fn set_page(mut self, page: u16) -> Self {
+        self.page = page;
+        self
+    }
+
+    pub fn set_items_per_page(mut self, items_per_page: u32) -> Self {
+        self.items_per_page = items_per_page;
+        self
     }
 
     pub fn request(&self) -> String {
         let url = format!(
-            "https://api.gtdb.ecogenomic.org/search/gtdb{}?",
+            "{}/search/gtdb{}?",
+            GTDB_API_URL,
             if self.outfmt == "json" {
                 String::from("")
             } else {
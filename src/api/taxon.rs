@@ -1,3 +1,5 @@
+use super::GTDB_API_URL;
+
 #[derive(Debug, Clone, Default)]
 pub struct TaxonAPI {
     name: String,
@@ -11,30 +13,32 @@ impl TaxonAPI {
 
     /// Constructs a URL for a name request.
     pub fn get_name_request(&self) -> String {
-        format!("https://api.gtdb.ecogenomic.org/taxon/{}", self.name)
+        format!("{}/taxon/{}", GTDB_API_URL, self.name)
     }
 
     /// Constructs a URL for a search request.
     pub fn get_search_request(&self) -> String {
-        format!(
-            "https://api.gtdb.ecogenomic.org/taxon/search/{}?limit=1000000",
-            self.name
-        )
+        format!("{}/taxon/search/{}?limit=1000000", GTDB_API_URL, self.name)
     }
 
     /// Constructs a URL for a search request across all releases.
     pub fn get_search_all_request(&self) -> String {
         format!(
-            "https://api.gtdb.ecogenomic.org/taxon/search/{}/all-releases?limit=10000000",
-            self.name
+            "{}/taxon/search/{}/all-releases?limit=10000000",
+            GTDB_API_URL, self.name
         )
     }
 
+    /// Constructs a URL for the releases in which the taxon name was seen.
+    pub fn get_previous_releases_request(&self) -> String {
+        format!("{}/taxon/{}/previous-releases", GTDB_API_URL, self.name)
+    }
+
     /// Constructs a URL for a genome request.
     pub fn get_genomes_request(&self, is_reps_only: bool) -> String {
         format!(
-            "https://api.gtdb.ecogenomic.org/taxon/{}/genomes?sp_reps_only={}",
-            self.name, is_reps_only
+            "{}/taxon/{}/genomes?sp_reps_only={}",
+            GTDB_API_URL, self.name, is_reps_only
         )
     }
 }
@@ -81,4 +85,13 @@ mod tests {
         assert_eq!(api.get_genomes_request(true), expected_url_reps);
         assert_eq!(api.get_genomes_request(false), expected_url_non_reps);
     }
+
+    #[test]
+    fn test_get_previous_releases_request() {
+        let api = TaxonAPI::new("g__Aminobacter");
+        assert_eq!(
+            api.get_previous_releases_request(),
+            "https://api.gtdb.ecogenomic.org/taxon/g__Aminobacter/previous-releases"
+        );
+    }
 }
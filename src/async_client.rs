@@ -0,0 +1,177 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::genome::{GenomeAPI, GenomeRequestType};
+use crate::api::taxon::TaxonAPI;
+use crate::cmd::genome::{GenomeCard, GenomeTaxonHistory, History};
+use crate::cmd::search::{SearchResult, SearchResults};
+use crate::cmd::taxon::{Taxon, TaxonGenomes, TaxonRelease, TaxonReleases, TaxonResult};
+use crate::drift;
+use crate::error::{self, XgtError};
+use crate::metrics;
+use crate::query::SearchQuery;
+
+/// Async client of the GTDB API, to be used from async code instead of
+/// spawning blocking threads for [`GtdbClient`](crate::GtdbClient)
+///
+/// ```no_run
+/// use xgt::query::SearchQuery;
+/// use xgt::AsyncGtdbClient;
+///
+/// # async fn run() -> Result<(), xgt::XgtError> {
+/// let client = AsyncGtdbClient::new()?;
+/// for genome in client.search(&SearchQuery::taxon("g__Aminobacter")).await? {
+///     println!("{:?}", genome.get_accession());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncGtdbClient {
+    client: reqwest::Client,
+}
+
+impl AsyncGtdbClient {
+    /// Client verifying the SSL certificate of the GTDB API
+    pub fn new() -> Result<Self, XgtError> {
+        Self::build(false)
+    }
+
+    /// Client accepting invalid SSL certificates, as with `--insecure`
+    pub fn insecure() -> Result<Self, XgtError> {
+        Self::build(true)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn build(disable_certificate_verification: bool) -> Result<Self, XgtError> {
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(disable_certificate_verification)
+            .build()
+            .map_err(|e| XgtError::Tls(e.to_string()))?;
+        Ok(AsyncGtdbClient { client })
+    }
+
+    /// In the browser, requests are sent with fetch, which always verifies
+    /// SSL certificates
+    #[cfg(target_arch = "wasm32")]
+    fn build(disable_certificate_verification: bool) -> Result<Self, XgtError> {
+        if disable_certificate_verification {
+            return Err(XgtError::InvalidInput(
+                "SSL certificate verification cannot be disabled in the browser".to_string(),
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| XgtError::Tls(e.to_string()))?;
+        Ok(AsyncGtdbClient { client })
+    }
+
+    /// Send a GET request and decode its JSON response
+    async fn get_json<T: DeserializeOwned + Serialize>(
+        &self,
+        url: &str,
+        not_found: String,
+    ) -> Result<T, XgtError> {
+        let decoded = self.send_json(url, not_found).await;
+        if decoded.is_err() {
+            error::record_failed_url(url);
+        }
+        decoded
+    }
+
+    async fn send_json<T: DeserializeOwned + Serialize>(
+        &self,
+        url: &str,
+        not_found: String,
+    ) -> Result<T, XgtError> {
+        let start = metrics::now();
+        let response = self.client.get(url).send().await;
+        let status = response.as_ref().ok().map(|x| x.status().as_u16());
+        metrics::record_request(url, status, start);
+        let response = response.map_err(|e| XgtError::from_reqwest(e, not_found.clone()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(XgtError::from_status(status.as_u16(), not_found));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| XgtError::from_reqwest(e, not_found))?;
+        metrics::record_bytes(body.len() as u64);
+        drift::decode(&body)
+    }
+
+    /// Genomes matching a search query
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, XgtError> {
+        let mut results: SearchResults = self
+            .get_json(
+                &query.url(),
+                format!("No match found for {}", query.get_needle()),
+            )
+            .await?;
+        results.apply_query(query);
+        Ok(results.into_rows())
+    }
+
+    /// Card of a genome: taxonomy, quality and assembly statistics
+    pub async fn genome_card(&self, accession: &str) -> Result<GenomeCard, XgtError> {
+        let url = GenomeAPI::from(accession.to_string()).request(GenomeRequestType::Card);
+        self.get_json(&url, format!("Genome {} not found", accession))
+            .await
+    }
+
+    /// GTDB taxonomy of a genome in each release
+    pub async fn genome_history(&self, accession: &str) -> Result<Vec<History>, XgtError> {
+        let url = GenomeAPI::from(accession.to_string()).request(GenomeRequestType::TaxonHistory);
+        let history: GenomeTaxonHistory = self
+            .get_json(&url, format!("Genome {} not found", accession))
+            .await?;
+        Ok(history.data)
+    }
+
+    /// Direct children of a taxon, which are genomes for a species
+    pub async fn taxon_children(&self, taxon: &str) -> Result<Vec<Taxon>, XgtError> {
+        let url = TaxonAPI::new(taxon).get_name_request();
+        let children: TaxonResult = self
+            .get_json(&url, format!("Taxon {} not found", taxon))
+            .await?;
+        Ok(children.data)
+    }
+
+    /// Accessions of the genomes of a taxon, only species representatives
+    /// when `reps_only` is set
+    pub async fn taxon_genomes(
+        &self,
+        taxon: &str,
+        reps_only: bool,
+    ) -> Result<Vec<String>, XgtError> {
+        let url = TaxonAPI::new(taxon).get_genomes_request(reps_only);
+        let genomes: TaxonGenomes = self
+            .get_json(&url, format!("No match found for {}", taxon))
+            .await?;
+        if genomes.data.is_empty() {
+            return Err(XgtError::NotFound(format!("No data found for {}", taxon)));
+        }
+        Ok(genomes.data)
+    }
+
+    /// Names given to a taxon across releases, oldest first
+    pub async fn taxon_history(&self, taxon: &str) -> Result<Vec<TaxonRelease>, XgtError> {
+        let url = TaxonAPI::new(taxon).get_previous_releases_request();
+        let releases: TaxonReleases = self
+            .get_json(&url, format!("Taxon {} not found", taxon))
+            .await?;
+        releases.into_history(taxon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_client_new() {
+        assert!(AsyncGtdbClient::new().is_ok());
+        assert!(AsyncGtdbClient::insecure().is_ok());
+    }
+}
@@ -0,0 +1,121 @@
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Ani subcmd arguments.
+pub struct AniArgs {
+    // Query genome accessions
+    pub(crate) query: Vec<String>,
+    // Reference genome accessions
+    pub(crate) reference: Vec<String>,
+    // FastANI k-mer size
+    pub(crate) kmer: u32,
+    // FastANI fragment length
+    pub(crate) frag_len: u32,
+    // Email notified when the job is done
+    pub(crate) email: Option<String>,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+/// Read accessions from the values of `arg`, or from the lines of `file_arg`
+fn accessions(arg_matches: &ArgMatches, arg: &str, file_arg: &str) -> Vec<String> {
+    match arg_matches.get_one::<String>(file_arg) {
+        Some(file_path) => {
+            let file = File::open(file_path)
+                .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+            BufReader::new(file)
+                .lines()
+                .map(|l| l.expect("Cannot parse line"))
+                .filter(|l| !l.trim().is_empty())
+                .collect()
+        }
+        None => arg_matches
+            .get_many::<String>(arg)
+            .map(|x| x.cloned().collect())
+            .unwrap_or_default(),
+    }
+}
+
+impl AniArgs {
+    pub fn get_query(&self) -> Vec<String> {
+        self.query.clone()
+    }
+
+    pub fn get_reference(&self) -> Vec<String> {
+        self.reference.clone()
+    }
+
+    pub fn get_kmer(&self) -> u32 {
+        self.kmer
+    }
+
+    pub fn get_frag_len(&self) -> u32 {
+        self.frag_len
+    }
+
+    pub fn get_email(&self) -> Option<String> {
+        self.email.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        AniArgs {
+            query: accessions(arg_matches, "query", "query-file"),
+            reference: accessions(arg_matches, "reference", "reference-file"),
+            kmer: *arg_matches
+                .get_one::<u32>("kmer")
+                .expect("kmer has a default value"),
+            frag_len: *arg_matches
+                .get_one::<u32>("frag-len")
+                .expect("frag-len has a default value"),
+            email: arg_matches.get_one::<String>("email").cloned(),
+            output: arg_matches.get_one::<String>("out").cloned(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_ani_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "ani",
+            "--query",
+            "GCA_001512625.1,GCF_018555685.1",
+            "--reference-file",
+            "test/acc.txt",
+            "--kmer",
+            "14",
+        ]);
+        let args = AniArgs::from_arg_matches(matches.subcommand_matches("ani").unwrap());
+        assert_eq!(
+            args.get_query(),
+            vec!["GCA_001512625.1".to_string(), "GCF_018555685.1".to_string()]
+        );
+        assert_eq!(
+            args.get_reference(),
+            vec!["GCF_018555685.1".to_string(), "GCF_900445235.1".to_string()]
+        );
+        assert_eq!(args.get_kmer(), 14);
+        assert_eq!(args.get_frag_len(), 3000);
+        assert_eq!(args.get_email(), None);
+    }
+}
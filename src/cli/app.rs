@@ -1,16 +1,105 @@
 use std::path::Path;
+use std::time::Duration;
 
 use clap::{Arg, ArgAction, Command};
 
+use crate::aggregate::{self, Aggregate};
+use crate::utils;
+use crate::values;
+
 pub fn build_app() -> Command {
     Command::new("xgt")
         .about("Query and parse GTDB data")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(
+            Arg::new("release")
+                .long("release")
+                .value_name("RELEASE")
+                .global(true)
+                .value_parser(is_valid_release)
+                .help("Pin queries to a GTDB release, e.g. R220"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .global(true)
+                .action(ArgAction::Count)
+                .help("Print details on stderr, such as the changes of the GTDB API responses"),
+        )
+        .arg(
+            Arg::new("errors")
+                .long("errors")
+                .value_name("FORMAT")
+                .global(true)
+                .default_value("text")
+                .value_parser(["text", "json"])
+                .help("Format of the errors printed on stderr, json giving their code, category, URL and subject"),
+        )
+        .arg(
+            Arg::new("na-string")
+                .long("na-string")
+                .value_name("STRING")
+                .global(true)
+                .help("Representation of missing values in CSV, TSV and table outputs [default: empty]"),
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("COLUMN[:desc]")
+                .global(true)
+                .value_parser(utils::parse_sort)
+                .help("Sort the rows of CSV, TSV and table outputs by COLUMN, numbers by value, in descending order with :desc"),
+        )
+        .arg(
+            Arg::new("provenance")
+                .long("provenance")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Start output files with the xgt version, GTDB release, command and time they were created with"),
+        )
+        .arg(
+            Arg::new("compact")
+                .long("compact")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Write JSON on a single line instead of pretty-printing it, the default when stdout is not a terminal"),
+        )
+        .arg(
+            Arg::new("request-stats")
+                .long("request-stats")
+                .global(true)
+                .value_name("FILE")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("-")
+                .help("Report the number of requests, cache hits, bytes received, latency and wallclock time on stderr, or as JSON in FILE"),
+        )
         .subcommand(
             // Search a taxon on GTDB
             Command::new("search")
                 .about("Search a taxon on GTDB")
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the API URLs of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .conflicts_with("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Report the requests and output files without sending or writing anything"),
+                )
+                .arg(
+                    Arg::new("as-curl")
+                        .long("as-curl")
+                        .conflicts_with_all(["url", "dry-run"])
+                        .action(ArgAction::SetTrue)
+                        .help("Print the equivalent curl commands of the requests instead of sending them"),
+                )
                 .arg(
                     Arg::new("NAME").conflicts_with("file").help(
                         "a value (typically a species or genus name/taxon) used for searching.",
@@ -26,54 +115,1298 @@ pub fn build_app() -> Command {
                         .help("search field"),
                 )
                 .arg(
-                    Arg::new("word")
-                        .short('w')
-                        .long("word")
-                        .action(ArgAction::SetTrue)
-                        .help("match only whole words"),
+                    Arg::new("word")
+                        .short('w')
+                        .long("word")
+                        .action(ArgAction::SetTrue)
+                        .help("match only whole words"),
+                )
+                .arg(
+                    Arg::new("rep")
+                        .long("rep")
+                        .short('r')
+                        .action(ArgAction::SetTrue)
+                        .help("search GTDB representative species only"),
+                )
+                .arg(
+                    Arg::new("type")
+                        .long("type")
+                        .short('t')
+                        .action(ArgAction::SetTrue)
+                        .help("search NCBI type species only"),
+                )
+                .arg(
+                    Arg::new("id")
+                        .short('i')
+                        .long("id")
+                        .action(ArgAction::SetTrue)
+                        .help("only print matched genomes ID"),
+                )
+                .arg(
+                    Arg::new("id-kind")
+                        .long("id-kind")
+                        .value_name("KIND")
+                        .default_value("gid")
+                        .value_parser(["gid", "accession", "ncbi_assembly"])
+                        .help("identifier printed by --id: GTDB genome ID, GTDB accession with its RS_/GB_ prefix or NCBI assembly accession"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .short('c')
+                        .long("count")
+                        .action(ArgAction::SetTrue)
+                        .help("only print a count of matched genomes"),
+                )
+                .arg(
+                    Arg::new("summarize")
+                        .long("summarize")
+                        .value_name("RANK")
+                        .value_parser([
+                            "domain", "phylum", "class", "order", "family", "genus", "species",
+                        ])
+                        .conflicts_with_all(["id", "count"])
+                        .help("group matched genomes by a GTDB taxonomy rank"),
+                )
+                .arg(
+                    Arg::new("local")
+                        .long("local")
+                        .action(ArgAction::SetTrue)
+                        .help("search the cached GTDB metadata, without network"),
+                )
+                .arg(
+                    Arg::new("lineage")
+                        .long("lineage")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["field", "word"])
+                        .help("take NAME as a full or partial GTDB lineage and print the genomes matching it at every rank"),
+                )
+                .arg(
+                    Arg::new("stable-sort")
+                        .long("stable-sort")
+                        .value_name("COLUMN")
+                        .num_args(0..=1)
+                        .require_equals(true)
+                        .default_missing_value("accession")
+                        .value_parser(["accession", "ncbi_organism_name", "ncbi_taxonomy", "gtdb_taxonomy"])
+                        .help("sort matched genomes by COLUMN, then by accession, for identical outputs across runs [default with --out: accession]"),
+                )
+                .arg(
+                    Arg::new("envelope")
+                        .long("envelope")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["id", "count", "summarize", "lineage"])
+                        .help("wrap JSON results with their query, count, retrieval time and GTDB release"),
+                )
+                .arg(
+                    Arg::new("group-by")
+                        .long("group-by")
+                        .value_name("COLUMN")
+                        .conflicts_with_all(["id", "count", "summarize", "lineage", "envelope"])
+                        .help("group matched genomes by a column or GTDB rank, reading columns missing from the results in the cached metadata"),
+                )
+                .arg(
+                    Arg::new("agg")
+                        .long("agg")
+                        .value_name("AGG")
+                        .value_delimiter(',')
+                        .default_value("count")
+                        .requires("group-by")
+                        .value_parser(is_valid_aggregate)
+                        .help("aggregates of the --group-by groups: count, or sum, mean, median, min or max of a column, e.g. count,mean(genome_size)"),
+                )
+                .arg(
+                    Arg::new("pivot")
+                        .long("pivot")
+                        .value_name("rows=COLUMN,cols=COLUMN")
+                        .value_parser(aggregate::parse_pivot)
+                        .conflicts_with_all(["id", "count", "summarize", "lineage", "envelope", "group-by"])
+                        .help("count matched genomes by the values of two columns or GTDB ranks, as a contingency table"),
+                )
+                .arg(
+                    Arg::new("hist")
+                        .long("hist")
+                        .value_name("COLUMNS")
+                        .value_delimiter(',')
+                        .conflicts_with_all(["id", "count", "summarize", "lineage", "envelope", "group-by", "pivot"])
+                        .help("print histograms of numeric columns of matched genomes, e.g. genome_size,gc_percentage"),
+                )
+                .arg(
+                    Arg::new("released-after")
+                        .long("released-after")
+                        .value_name("DATE")
+                        .value_parser(is_valid_date)
+                        .help("only keep genomes released by NCBI on or after DATE, e.g. 2020 or 2020-06-15"),
+                )
+                .arg(
+                    Arg::new("released-before")
+                        .long("released-before")
+                        .value_name("DATE")
+                        .value_parser(is_valid_date)
+                        .help("only keep genomes released by NCBI on or before DATE, e.g. 2021 or 2021-03"),
+                )
+                .arg(
+                    Arg::new("exclude-surveillance")
+                        .long("exclude-surveillance")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("local")
+                        .help("drop genomes of NCBI surveillance projects, from their metadata"),
+                )
+                .arg(
+                    Arg::new("only-surveillance")
+                        .long("only-surveillance")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["local", "exclude-surveillance"])
+                        .help("only keep genomes of NCBI surveillance projects, from their metadata"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("number of parallel genome metadata requests for --exclude-surveillance and --only-surveillance"),
+                )
+                .arg(
+                    Arg::new("distinct-species")
+                        .long("distinct-species")
+                        .action(ArgAction::SetTrue)
+                        .help("keep one genome per GTDB species, preferably its representative, with the number of genomes of the species"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("takes NAME from FILE"),
+                )
+                .arg(
+                    Arg::new("fail-fast")
+                        .long("fail-fast")
+                        .action(ArgAction::SetTrue)
+                        .help("stop at the first NAME of FILE which fails, rather than reporting it and going on"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("csv")
+                        .value_parser(["csv", "json", "tsv", "html"]),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("genome")
+                .about("Information about a genome")
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the API URLs of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .conflicts_with("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Report the requests and output files without sending or writing anything"),
+                )
+                .arg(
+                    Arg::new("as-curl")
+                        .long("as-curl")
+                        .conflicts_with_all(["url", "dry-run"])
+                        .action(ArgAction::SetTrue)
+                        .help("Print the equivalent curl commands of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("accession")
+                        .conflicts_with("file")
+                        .help("Genome accession"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Search from name in FILE"),
+                )
+                .arg(
+                    Arg::new("fail-fast")
+                        .long("fail-fast")
+                        .action(ArgAction::SetTrue)
+                        .help("Stop at the first accession of FILE which fails, rather than reporting it and going on"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel requests for --file accessions, written in the order of the file"),
+                )
+                .arg(
+                    Arg::new("history")
+                        .short('H')
+                        .long("history")
+                        .action(ArgAction::SetTrue)
+                        .help("Get genome taxon history"),
+                )
+                .arg(
+                    Arg::new("metadata")
+                        .short('m')
+                        .long("metadata")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("history")
+                        .help("Get genome metadata"),
+                )
+                .arg(
+                    Arg::new("links")
+                        .short('l')
+                        .long("links")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["history", "metadata"])
+                        .help("Get NCBI and ENA links of the genome"),
+                )
+                .arg(
+                    Arg::new("compare")
+                        .short('c')
+                        .long("compare")
+                        .value_name("ACCESSION")
+                        .conflicts_with_all(["history", "metadata", "links"])
+                        .help("Compare the genome card with the one of ACCESSION"),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("Genome card output format")
+                        .value_name("STR")
+                        .default_value("json")
+                        .value_parser(["json", "html"])
+                        .conflicts_with_all(["history", "metadata", "links", "compare"]),
+                )
+                .arg(
+                    Arg::new("full")
+                        .long("full")
+                        .value_name("LAYOUT")
+                        .num_args(0..=1)
+                        .require_equals(true)
+                        .default_missing_value("flat")
+                        .value_parser(["flat", "nested"])
+                        .conflicts_with_all(["history", "metadata", "links", "compare", "outfmt"])
+                        .help("Merge the genome card and metadata into one record, their fields flattened or nested under card and metadata [default: flat]"),
+                )
+                .arg(
+                    Arg::new("taxonomy-only")
+                        .short('T')
+                        .long("taxonomy-only")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["history", "metadata", "links", "compare", "full"])
+                        .help("Only output accession and GTDB taxonomy"),
+                )
+                .arg(
+                    Arg::new("ncbi")
+                        .long("ncbi")
+                        .action(ArgAction::SetTrue)
+                        .requires("taxonomy-only")
+                        .help("Add NCBI taxonomy to --taxonomy-only output"),
+                )
+                .arg(
+                    Arg::new("taxid")
+                        .long("taxid")
+                        .action(ArgAction::SetTrue)
+                        .requires("taxonomy-only")
+                        .help("Add NCBI taxid and species taxid to --taxonomy-only output"),
+                )
+                .arg(
+                    Arg::new("discrepancies")
+                        .long("discrepancies")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "history",
+                            "metadata",
+                            "links",
+                            "compare",
+                            "taxonomy-only",
+                            "full",
+                        ])
+                        .help("Report ranks where GTDB and NCBI taxonomy disagree"),
+                )
+                .arg(
+                    Arg::new("nomenclature")
+                        .long("nomenclature")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "history",
+                            "metadata",
+                            "links",
+                            "compare",
+                            "outfmt",
+                            "taxonomy-only",
+                            "discrepancies",
+                            "full",
+                        ])
+                        .help("Output the type designations and LPSN link of the genomes as a TSV table"),
+                )
+                .arg(
+                    Arg::new("diff-cache")
+                        .long("diff-cache")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "history",
+                            "metadata",
+                            "links",
+                            "compare",
+                            "outfmt",
+                            "taxonomy-only",
+                            "discrepancies",
+                            "full",
+                            "nomenclature",
+                        ])
+                        .help("Report the fields of the genome cards which changed since they were cached by the previous run, and cache them"),
+                )
+                .arg(
+                    Arg::new("local")
+                        .long("local")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "history",
+                            "links",
+                            "compare",
+                            "discrepancies",
+                            "full",
+                            "nomenclature",
+                            "diff-cache",
+                        ])
+                        .help("Answer from the cached GTDB metadata, without network"),
+                )
+                .arg(
+                    Arg::new("exclude-surveillance")
+                        .long("exclude-surveillance")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["local", "compare"])
+                        .help("Skip the genomes of NCBI surveillance projects, from their metadata"),
+                )
+                .arg(
+                    Arg::new("only-surveillance")
+                        .long("only-surveillance")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["local", "compare", "exclude-surveillance"])
+                        .help("Only keep the genomes of NCBI surveillance projects, from their metadata"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .action(ArgAction::SetTrue)
+                        .help("Output genome cards as sent by GTDB, with HTML links and numbers as strings"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Output raw JSON, each genome to its own file if FILE contains {accession}, e.g. cards/{accession}.json")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("skip-existing")
+                        .long("skip-existing")
+                        .requires("out")
+                        .action(ArgAction::SetTrue)
+                        .help("Skip the accessions whose --out {accession} file exists and is not empty"),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommands(cfg!(feature = "downloads").then(|| {
+            Command::new("download")
+                .about("Download genome assemblies from NCBI")
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Report the requests and output files without sending or writing anything"),
+                )
+                .arg(
+                    Arg::new("as-curl")
+                        .long("as-curl")
+                        .conflicts_with("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the equivalent curl commands of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("accession")
+                        .conflicts_with("file")
+                        .required_unless_present("file")
+                        .help("Genome accession"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Download accessions listed in FILE"),
+                )
+                .arg(
+                    Arg::new("what")
+                        .short('w')
+                        .long("what")
+                        .value_name("STR")
+                        .value_delimiter(',')
+                        .default_value("genome")
+                        .value_parser(["genome", "protein", "gff", "cds"])
+                        .help("Comma-separated list of files to download"),
+                )
+                .arg(
+                    Arg::new("outdir")
+                        .short('d')
+                        .long("outdir")
+                        .value_name("DIR")
+                        .default_value(".")
+                        .help("Output directory"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("INT")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel downloads"),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                )
+        }))
+        .subcommand(
+            Command::new("taxon")
+                .about("Information about a specific taxon")
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the API URLs of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .conflicts_with("url")
+                        .action(ArgAction::SetTrue)
+                        .help("Report the requests and output files without sending or writing anything"),
+                )
+                .arg(
+                    Arg::new("as-curl")
+                        .long("as-curl")
+                        .conflicts_with_all(["url", "dry-run"])
+                        .action(ArgAction::SetTrue)
+                        .help("Print the equivalent curl commands of the requests instead of sending them"),
+                )
+                .arg(
+                    Arg::new("NAME")
+                        .conflicts_with("file")
+                        .help("taxon name")
+                        .value_parser(is_valid_taxon),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Search from name in FILE"),
+                )
+                .arg(
+                    Arg::new("fail-fast")
+                        .long("fail-fast")
+                        .action(ArgAction::SetTrue)
+                        .help("Stop at the first name of FILE which fails, rather than reporting it and going on"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("word")
+                        .short('w')
+                        .long("word")
+                        .action(ArgAction::SetTrue)
+                        .help("match only whole words"),
+                )
+                .arg(
+                    Arg::new("search")
+                        .short('s')
+                        .long("search")
+                        .action(ArgAction::SetTrue)
+                        .help("Search for a taxon in current release"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .short('a')
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .help("Search for a taxon across all releases"),
+                )
+                .arg(
+                    Arg::new("genomes")
+                        .short('g')
+                        .long("genomes")
+                        .action(ArgAction::SetTrue)
+                        .help("Get V taxon genomes"),
+                )
+                .arg(
+                    Arg::new("reps")
+                        .short('r')
+                        .long("reps")
+                        .action(ArgAction::SetTrue)
+                        .help("Set taxon V genomes search to lookup reps seqs only"),
+                )
+                .arg(
+                    Arg::new("type-material")
+                        .short('t')
+                        .long("type-material")
+                        .action(ArgAction::SetTrue)
+                        .requires("genomes")
+                        .help("Only keep taxon genomes which are NCBI type material"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .short('c')
+                        .long("count")
+                        .action(ArgAction::SetTrue)
+                        .requires("genomes")
+                        .help("Only print the number of taxon genomes"),
+                )
+                .arg(
+                    Arg::new("detail")
+                        .long("detail")
+                        .action(ArgAction::SetTrue)
+                        .requires("genomes")
+                        .conflicts_with("count")
+                        .help("Add organism name, GTDB species, rep status and quality to taxon genomes"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("4")
+                        .help("Number of parallel requests for --file taxa, and genome requests for --detail, --type-summary, --sankey and --clusters"),
+                )
+                .arg(
+                    Arg::new("children")
+                        .long("children")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["search", "all", "genomes"])
+                        .help("List direct children of the taxon with their genome counts"),
+                )
+                .arg(
+                    Arg::new("lineage")
+                        .long("lineage")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["search", "all", "genomes", "children"])
+                        .help("Get the parent lineage of the taxon"),
+                )
+                .arg(
+                    Arg::new("ranks")
+                        .long("ranks")
+                        .action(ArgAction::SetTrue)
+                        .requires("lineage")
+                        .help("Output --lineage as one column per rank"),
+                )
+                .arg(
+                    Arg::new("history")
+                        .long("history")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["search", "all", "genomes", "children", "lineage"])
+                        .help("Get the releases in which the taxon name existed"),
+                )
+                .arg(
+                    Arg::new("stats")
+                        .long("stats")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history",
+                        ])
+                        .help("Summarize the composition of the taxon"),
+                )
+                .arg(
+                    Arg::new("type-summary")
+                        .long("type-summary")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history", "stats",
+                        ])
+                        .help("List the species of the taxon with their type material designations"),
+                )
+                .arg(
+                    Arg::new("sankey")
+                        .long("sankey")
+                        .value_name("FORMAT")
+                        .num_args(0..=1)
+                        .require_equals(true)
+                        .default_missing_value("json")
+                        .value_parser(["json", "mermaid"])
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history", "stats",
+                            "type-summary",
+                        ])
+                        .help("Write how the taxon genomes were redistributed among names across releases, as Sankey nodes and links in JSON or Mermaid"),
+                )
+                .arg(
+                    Arg::new("clusters")
+                        .long("clusters")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history", "stats",
+                            "type-summary", "sankey",
+                        ])
+                        .help("List the species clusters of the taxon with their representative and size, largest first"),
+                )
+                .arg(
+                    Arg::new("novel")
+                        .long("novel")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history", "stats",
+                            "type-summary", "sankey", "clusters",
+                        ])
+                        .help("Report the descendant taxa with placeholder names versus validly published ones"),
+                )
+                .arg(
+                    Arg::new("nomenclature")
+                        .long("nomenclature")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "search", "all", "genomes", "children", "lineage", "history", "stats",
+                            "type-summary", "sankey", "clusters", "novel",
+                        ])
+                        .help("List the LPSN, Bergey's Manual and SeqCode links and type material of the child taxa"),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("json")
+                        .value_parser(["csv", "json", "tsv", "table"]),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("tree")
+                .about("Print a taxon subtree with genome counts")
+                .arg(
+                    Arg::new("NAME")
+                        .required(true)
+                        .help("root taxon name")
+                        .value_parser(is_valid_taxon),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .short('d')
+                        .long("depth")
+                        .value_name("INT")
+                        .default_value("2")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of levels below the root taxon"),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("ascii")
+                        .value_parser(["ascii", "dot"]),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare a taxon between two GTDB releases")
+                .arg(
+                    Arg::new("NAME")
+                        .required(true)
+                        .help("taxon name")
+                        .value_parser(is_valid_taxon),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("RELEASE")
+                        .required(true)
+                        .help("Release to compare from, e.g. R207"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("RELEASE")
+                        .required(true)
+                        .help("Release to compare to, e.g. R220"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("INT")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel genome history requests"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("tsv")
+                        .value_parser(["csv", "tsv", "table"]),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("discordance")
+                .about("Find the genomes of a taxon whose GTDB and NCBI classifications disagree")
+                .arg(
+                    Arg::new("NAME")
+                        .required(true)
+                        .help("taxon name")
+                        .value_parser(is_valid_taxon),
+                )
+                .arg(
+                    Arg::new("rank")
+                        .long("rank")
+                        .value_name("RANK")
+                        .default_value("genus")
+                        .value_parser([
+                            "domain", "phylum", "class", "order", "family", "genus", "species",
+                        ])
+                        .help("Rank at which the classifications are compared"),
+                )
+                .arg(
+                    Arg::new("genomes")
+                        .long("genomes")
+                        .action(ArgAction::SetTrue)
+                        .help("List the discordant genomes rather than counts per conflicting pair of taxa"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("tsv")
+                        .value_parser(["csv", "tsv", "table", "json"]),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("releases")
+                .about("List GTDB releases")
+                .arg(
+                    Arg::new("latest")
+                        .short('l')
+                        .long("latest")
+                        .action(ArgAction::SetTrue)
+                        .help("Only print the current release"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("outfmt")
+                        .long("outfmt")
+                        .short('O')
+                        .help("output format")
+                        .value_name("STR")
+                        .default_value("table")
+                        .value_parser(["csv", "json", "tsv", "table"]),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the JSON Schemas of the JSON outputs")
+                .arg(
+                    Arg::new("NAME")
+                        .value_parser(["search-row", "genome-card", "metadata", "history", "taxon"])
+                        .help("Only print the schema of NAME, keyed by name otherwise"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                ),
+        )
+        .subcommands(cfg!(feature = "downloads").then(|| {
+            Command::new("fetch-metadata")
+                .about("Download the GTDB metadata files into the cache")
+                .arg(
+                    Arg::new("domain")
+                        .long("domain")
+                        .value_name("STR")
+                        .value_delimiter(',')
+                        .default_value("bac,ar")
+                        .value_parser(["bac", "ar"])
+                        .help("Comma-separated list of domains to download"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect the download report to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                )
+        }))
+        .subcommands(cfg!(feature = "downloads").then(|| {
+            Command::new("fetch-tree")
+                .about("Download the GTDB reference trees")
+                .arg(
+                    Arg::new("domain")
+                        .long("domain")
+                        .value_name("STR")
+                        .value_delimiter(',')
+                        .default_value("bac,ar")
+                        .value_parser(["bac", "ar"])
+                        .help("Comma-separated list of domains to download"),
+                )
+                .arg(
+                    Arg::new("outdir")
+                        .short('d')
+                        .long("outdir")
+                        .value_name("DIR")
+                        .default_value(".")
+                        .help("Output directory"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect the download report to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                )
+        }))
+        .subcommand(
+            Command::new("grep")
+                .about("Search a local GTDB taxonomy file")
+                .arg(
+                    Arg::new("NAME")
+                        .conflicts_with("file")
+                        .required_unless_present("file")
+                        .help("a value (typically a species or genus name/taxon) used for searching"),
+                )
+                .arg(
+                    Arg::new("taxonomy")
+                        .long("taxonomy")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("GTDB taxonomy file (accession and lineage, may be gzipped)"),
+                )
+                .arg(
+                    Arg::new("field")
+                        .long("field")
+                        .short('F')
+                        .value_name("STR")
+                        .default_value("all")
+                        .value_parser(["all", "acc", "gtdb"])
+                        .help("search field"),
+                )
+                .arg(
+                    Arg::new("word")
+                        .short('w')
+                        .long("word")
+                        .action(ArgAction::SetTrue)
+                        .help("match only whole words"),
+                )
+                .arg(
+                    Arg::new("level")
+                        .long("level")
+                        .value_name("RANK")
+                        .value_parser([
+                            "domain", "phylum", "class", "order", "family", "genus", "species",
+                        ])
+                        .help("only match the taxon at RANK"),
+                )
+                .arg(
+                    Arg::new("id")
+                        .short('i')
+                        .long("id")
+                        .action(ArgAction::SetTrue)
+                        .help("only print matched genomes ID"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .short('c')
+                        .long("count")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("id")
+                        .help("only print a count of matched genomes"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("takes NAME from FILE"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                ),
+        )
+        .subcommand(
+            Command::new("ani")
+                .about("Submit a FastANI job to the GTDB API")
+                .arg(
+                    Arg::new("query")
+                        .short('q')
+                        .long("query")
+                        .value_name("ACCESSION")
+                        .value_delimiter(',')
+                        .conflicts_with("query-file")
+                        .required_unless_present("query-file")
+                        .help("Comma-separated list of query genome accessions"),
+                )
+                .arg(
+                    Arg::new("query-file")
+                        .long("query-file")
+                        .value_name("FILE")
+                        .help("Takes query accessions from FILE"),
+                )
+                .arg(
+                    Arg::new("reference")
+                        .short('r')
+                        .long("reference")
+                        .value_name("ACCESSION")
+                        .value_delimiter(',')
+                        .conflicts_with("reference-file")
+                        .required_unless_present("reference-file")
+                        .help("Comma-separated list of reference genome accessions"),
+                )
+                .arg(
+                    Arg::new("reference-file")
+                        .long("reference-file")
+                        .value_name("FILE")
+                        .help("Takes reference accessions from FILE"),
+                )
+                .arg(
+                    Arg::new("kmer")
+                        .long("kmer")
+                        .value_name("INT")
+                        .default_value("16")
+                        .value_parser(clap::value_parser!(u32).range(1..=16))
+                        .help("FastANI k-mer size"),
+                )
+                .arg(
+                    Arg::new("frag-len")
+                        .long("frag-len")
+                        .value_name("INT")
+                        .default_value("3000")
+                        .value_parser(clap::value_parser!(u32))
+                        .help("FastANI fragment length"),
+                )
+                .arg(
+                    Arg::new("email")
+                        .long("email")
+                        .value_name("EMAIL")
+                        .help("Email notified when the job is done"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Check for a new GTDB release")
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("DURATION")
+                        .value_parser(is_valid_duration)
+                        .help("Check every DURATION (e.g. 30m, 24h, 7d) until a new release appears"),
+                )
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .value_name("FILE")
+                        .help("File storing the last release seen [default: in the cache directory]"),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommands(cfg!(feature = "exports").then(|| {
+            Command::new("export")
+                .about("Export GTDB lineages of genomes to the input format of other tools")
+                .arg(
+                    Arg::new("FORMAT")
+                        .required(true)
+                        .value_parser([
+                            "krona",
+                            "newick",
+                            "taxdump",
+                            "seqid2taxid",
+                            "qiime",
+                            "sourmash-lineages",
+                            "phyloseq",
+                            "anvio",
+                            "edges",
+                            "biom",
+                        ])
+                        .help("Export format"),
+                )
+                .arg(
+                    Arg::new("taxon")
+                        .long("taxon")
+                        .value_name("NAME")
+                        .value_parser(is_valid_taxon)
+                        .required_unless_present_any(["search", "file"])
+                        .conflicts_with_all(["search", "file"])
+                        .help("Export the genomes of a taxon"),
+                )
+                .arg(
+                    Arg::new("search")
+                        .long("search")
+                        .value_name("NAME")
+                        .conflicts_with("file")
+                        .help("Export the genomes matching a search"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Export the genomes of the accessions listed in FILE"),
                 )
                 .arg(
-                    Arg::new("rep")
-                        .long("rep")
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel requests for --file accessions"),
+                )
+                .arg(
+                    Arg::new("reps")
+                        .long("reps")
                         .short('r')
                         .action(ArgAction::SetTrue)
-                        .help("search GTDB representative species only"),
+                        .help("Only export GTDB species representative genomes"),
                 )
                 .arg(
-                    Arg::new("type")
-                        .long("type")
-                        .short('t')
+                    Arg::new("lineage")
+                        .long("lineage")
                         .action(ArgAction::SetTrue)
-                        .help("search NCBI type species only"),
+                        .help("Map accessions to their lineage instead of their taxid (seqid2taxid)"),
                 )
                 .arg(
-                    Arg::new("id")
-                        .short('i')
-                        .long("id")
-                        .action(ArgAction::SetTrue)
-                        .help("only print matched genomes ID"),
+                    Arg::new("html")
+                        .long("html")
+                        .value_name("FILE")
+                        .help("Also write a Krona HTML chart to FILE (requires KronaTools)"),
                 )
                 .arg(
-                    Arg::new("count")
-                        .short('c')
-                        .long("count")
-                        .action(ArgAction::SetTrue)
-                        .help("only print a count of matched genomes"),
+                    Arg::new("outdir")
+                        .short('d')
+                        .long("outdir")
+                        .value_name("DIR")
+                        .default_value(".")
+                        .help("Output directory of taxdump and edges files"),
                 )
                 .arg(
-                    Arg::new("file")
-                        .short('f')
-                        .long("file")
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
                         .value_name("FILE")
-                        .help("takes NAME from FILE"),
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                )
+        }))
+        .subcommand(
+            Command::new("convert")
+                .about("Convert lineages between taxonomy formats")
+                .arg(
+                    Arg::new("FILE")
+                        .help("Lineages to convert, one per line [default: stdin]"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("FORMAT")
+                        .required(true)
+                        .value_parser(["lineage", "ranks", "list"])
+                        .help("Input format"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("FORMAT")
+                        .required(true)
+                        .value_parser(["lineage", "ranks", "list"])
+                        .help("Output format"),
                 )
                 .arg(
                     Arg::new("out")
                         .short('o')
                         .long("out")
-                        .help("output to FILE")
+                        .help("Redirect output to FILE")
                         .value_name("FILE")
                         .value_parser(is_existing),
+                ),
+        )
+        .subcommand(
+            Command::new("translate")
+                .about("Translate a taxon name between the GTDB and NCBI taxonomies")
+                .arg(
+                    Arg::new("NAME")
+                        .required(true)
+                        .value_parser(is_valid_taxon)
+                        .help("Taxon name in greengenes format, e.g. g__Escherichia"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("TAXONOMY")
+                        .default_value("ncbi")
+                        .value_parser(["ncbi", "gtdb"])
+                        .help("Taxonomy to translate NAME to"),
                 )
                 .arg(
                     Arg::new("outfmt")
@@ -82,21 +1415,62 @@ pub fn build_app() -> Command {
                         .help("output format")
                         .value_name("STR")
                         .default_value("csv")
-                        .value_parser(["csv", "json", "tsv"]),
+                        .value_parser(["csv", "tsv", "table"]),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
                 )
                 .arg(
                     Arg::new("insecure")
                         .short('k')
                         .long("insecure")
-                        .help("disable SSL certificate verification")
+                        .help("Disable SSL certificate verification")
                         .action(ArgAction::SetTrue),
                 ),
         )
         .subcommand(
-            Command::new("genome")
-                .about("Information about a genome")
+            Command::new("taxid")
+                .about("NCBI taxids behind GTDB taxa and genomes")
                 .arg(
-                    Arg::new("accession")
+                    Arg::new("NAME")
+                        .required_unless_present("file")
+                        .conflicts_with("file")
+                        .help("Taxon name in greengenes format or genome accession"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Taxon names or genome accessions in FILE"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("lineage")
+                .about("GTDB or NCBI lineage of genomes")
+                .arg(
+                    Arg::new("ACCESSION")
+                        .required_unless_present("file")
                         .conflicts_with("file")
                         .help("Genome accession"),
                 )
@@ -105,28 +1479,34 @@ pub fn build_app() -> Command {
                         .short('f')
                         .long("file")
                         .value_name("FILE")
-                        .help("Search from name in FILE"),
+                        .help("Genome accessions in FILE"),
                 )
                 .arg(
-                    Arg::new("history")
-                        .short('H')
-                        .long("history")
+                    Arg::new("ncbi")
+                        .long("ncbi")
                         .action(ArgAction::SetTrue)
-                        .help("Get genome taxon history"),
+                        .help("Output the NCBI lineage instead of the GTDB one"),
                 )
                 .arg(
-                    Arg::new("metadata")
-                        .short('m')
-                        .long("metadata")
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel requests"),
+                )
+                .arg(
+                    Arg::new("refresh")
+                        .long("refresh")
                         .action(ArgAction::SetTrue)
-                        .conflicts_with("history")
-                        .help("Get genome metadata"),
+                        .help("Fetch lineages again instead of using cached ones"),
                 )
                 .arg(
                     Arg::new("out")
                         .short('o')
                         .long("out")
-                        .help("Output raw JSON")
+                        .help("Redirect output to FILE")
                         .value_name("FILE")
                         .value_parser(is_existing),
                 )
@@ -139,20 +1519,32 @@ pub fn build_app() -> Command {
                 ),
         )
         .subcommand(
-            Command::new("taxon")
-                .about("Information about a specific taxon")
+            Command::new("api")
+                .about("Send a request to any GTDB API path and print the raw response")
                 .arg(
-                    Arg::new("NAME")
-                        .conflicts_with("file")
-                        .help("taxon name")
-                        .value_parser(is_valid_taxon),
+                    Arg::new("as-curl")
+                        .long("as-curl")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the equivalent curl command of the request instead of sending it"),
                 )
                 .arg(
-                    Arg::new("file")
-                        .short('f')
-                        .long("file")
-                        .value_name("FILE")
-                        .help("Search from name in FILE"),
+                    Arg::new("METHOD")
+                        .required(true)
+                        .ignore_case(true)
+                        .value_parser(["GET", "POST", "PUT", "DELETE"])
+                        .help("HTTP method"),
+                )
+                .arg(
+                    Arg::new("PATH")
+                        .required(true)
+                        .help("GTDB API path, e.g. /taxon/g__Aminobacter/genomes"),
+                )
+                .arg(
+                    Arg::new("data")
+                        .short('d')
+                        .long("data")
+                        .value_name("JSON")
+                        .help("JSON request body"),
                 )
                 .arg(
                     Arg::new("out")
@@ -163,39 +1555,154 @@ pub fn build_app() -> Command {
                         .value_parser(is_existing),
                 )
                 .arg(
-                    Arg::new("word")
-                        .short('w')
-                        .long("word")
-                        .action(ArgAction::SetTrue)
-                        .help("match only whole words"),
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("open")
+                .about("Open the GTDB website page of a genome or taxon in the browser")
+                .arg(
+                    Arg::new("RECORD")
+                        .required(true)
+                        .help("Genome accession or taxon, e.g. GCF_000005845.2 or g__Escherichia"),
                 )
                 .arg(
-                    Arg::new("search")
-                        .short('s')
-                        .long("search")
+                    Arg::new("print")
+                        .long("print")
                         .action(ArgAction::SetTrue)
-                        .help("Search for a taxon in current release"),
+                        .help("Print the URL of the page instead of opening it"),
+                ),
+        )
+        .subcommands(cfg!(feature = "tui").then(|| {
+            Command::new("browse")
+                .about("Browse GTDB taxa and genomes interactively")
+                .arg(
+                    Arg::new("NAME")
+                        .help("taxon to start browsing from")
+                        .value_parser(is_valid_taxon),
                 )
                 .arg(
-                    Arg::new("all")
-                        .short('a')
-                        .long("all")
-                        .action(ArgAction::SetTrue)
-                        .help("Search for a taxon across all releases"),
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Export the marked genomes to FILE")
+                        .value_name("FILE")
+                        .default_value("selection.txt"),
                 )
                 .arg(
-                    Arg::new("genomes")
-                        .short('g')
-                        .long("genomes")
-                        .action(ArgAction::SetTrue)
-                        .help("Get V taxon genomes"),
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
                 )
+        }))
+        .subcommand(
+            Command::new("shell")
+                .about("Run queries from an interactive prompt sharing one connection")
                 .arg(
-                    Arg::new("reps")
-                        .short('r')
-                        .long("reps")
+                    Arg::new("no-history")
+                        .long("no-history")
                         .action(ArgAction::SetTrue)
-                        .help("Set taxon V genomes search to lookup reps seqs only"),
+                        .help("Do not save the commands to the history file"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serve a local caching proxy of the GTDB API")
+                .arg(
+                    Arg::new("host")
+                        .long("host")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1")
+                        .help("Address to listen on"),
+                )
+                .arg(
+                    Arg::new("port")
+                        .short('p')
+                        .long("port")
+                        .value_name("INT")
+                        .default_value("8080")
+                        .value_parser(clap::value_parser!(u16))
+                        .help("Port to listen on"),
+                )
+                .arg(
+                    Arg::new("rate")
+                        .long("rate")
+                        .value_name("FLOAT")
+                        .default_value("2")
+                        .value_parser(is_positive_rate)
+                        .help("Maximum number of requests per second sent to the GTDB API"),
+                )
+                .arg(
+                    Arg::new("cache-ttl")
+                        .long("cache-ttl")
+                        .value_name("DURATION")
+                        .default_value("24h")
+                        .value_parser(is_valid_duration)
+                        .help("Fetch responses cached for longer than DURATION (e.g. 30m, 24h, 7d) again"),
+                )
+                .arg(
+                    Arg::new("insecure")
+                        .short('k')
+                        .long("insecure")
+                        .help("Disable SSL certificate verification")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Run the jobs described in a TOML manifest")
+                .arg(
+                    Arg::new("MANIFEST")
+                        .required(true)
+                        .help("TOML file describing the jobs and their outputs"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("INT")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of jobs run in parallel [default: manifest jobs, or 4]"),
+                ),
+        )
+        .subcommand(
+            Command::new("exists")
+                .about("Check that genomes are in the current GTDB release")
+                .arg(
+                    Arg::new("ACCESSION")
+                        .required_unless_present("file")
+                        .conflicts_with("file")
+                        .help("Genome accession"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Genome accessions in FILE"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of parallel requests"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .help("Redirect output to FILE")
+                        .value_name("FILE")
+                        .value_parser(is_existing),
                 )
                 .arg(
                     Arg::new("insecure")
@@ -217,6 +1724,50 @@ fn is_valid_taxon(s: &str) -> Result<String, String> {
     Err("Taxon name must be in greengenes format, e.g. g__Foo".to_string())
 }
 
+fn is_valid_release(s: &str) -> Result<String, String> {
+    let number = s.strip_prefix('R').unwrap_or(s);
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        Ok(format!("R{}", number))
+    } else {
+        Err("Release must be a GTDB release number, e.g. R220".to_string())
+    }
+}
+
+fn is_valid_aggregate(s: &str) -> Result<String, String> {
+    Aggregate::parse(s).map(|_| s.trim().to_string())
+}
+
+fn is_valid_date(s: &str) -> Result<String, String> {
+    match values::parse_partial_date(s) {
+        Some(_) => Ok(s.to_string()),
+        None => Err(
+            "Date must be a year, a month or a day, e.g. 2020, 2020-06 or 2020-06-15".to_string(),
+        ),
+    }
+}
+
+fn is_valid_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = s.split_at(s.trim_end_matches(|c: char| c.is_ascii_alphabetic()).len());
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err("Duration unit must be one of s, m, h or d, e.g. 24h".to_string()),
+    };
+    match number.parse::<u64>() {
+        Ok(n) if n > 0 => Ok(Duration::from_secs(n * seconds)),
+        _ => Err("Duration must be a positive number, e.g. 24h".to_string()),
+    }
+}
+
+fn is_positive_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err("rate should be a positive number".to_string()),
+    }
+}
+
 fn is_existing(s: &str) -> Result<String, String> {
     if !Path::new(s).exists() {
         Ok(s.to_string())
@@ -229,6 +1780,24 @@ fn is_existing(s: &str) -> Result<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_valid_duration() {
+        assert_eq!(is_valid_duration("24h"), Ok(Duration::from_secs(86400)));
+        assert_eq!(is_valid_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(is_valid_duration("90"), Ok(Duration::from_secs(90)));
+        assert!(is_valid_duration("0h").is_err());
+        assert!(is_valid_duration("2w").is_err());
+        assert!(is_valid_duration("h").is_err());
+    }
+
+    #[test]
+    fn test_is_positive_rate() {
+        assert_eq!(is_positive_rate("0.5"), Ok(0.5));
+        assert!(is_positive_rate("0").is_err());
+        assert!(is_positive_rate("inf").is_err());
+        assert!(is_positive_rate("fast").is_err());
+    }
+
     #[test]
     fn test_is_existing() {
         // Test with a non-existing file
@@ -318,4 +1887,28 @@ mod tests {
             Err("Taxon name must be in greengenes format, e.g. g__Foo".to_string())
         );
     }
+
+    #[test]
+    fn test_is_valid_release() {
+        assert_eq!(is_valid_release("R220"), Ok("R220".to_string()));
+        assert_eq!(is_valid_release("207"), Ok("R207".to_string()));
+        assert!(is_valid_release("latest").is_err());
+        assert!(is_valid_release("R").is_err());
+    }
+
+    #[test]
+    fn test_global_release() {
+        let matches = build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_001512625.1",
+            "--release",
+            "220",
+        ]);
+        let sub_matches = matches.subcommand_matches("genome").unwrap();
+        assert_eq!(
+            sub_matches.get_one::<String>("release"),
+            Some(&"R220".to_string())
+        );
+    }
 }
@@ -0,0 +1,45 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Batch subcmd arguments.
+pub struct BatchArgs {
+    // TOML file describing the jobs
+    pub(crate) manifest: String,
+    // Number of jobs run in parallel, overriding the manifest
+    pub(crate) jobs: Option<usize>,
+}
+
+impl BatchArgs {
+    pub fn get_manifest(&self) -> String {
+        self.manifest.clone()
+    }
+
+    pub fn get_jobs(&self) -> Option<usize> {
+        self.jobs
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        BatchArgs {
+            manifest: arg_matches
+                .get_one::<String>("MANIFEST")
+                .expect("MANIFEST is required")
+                .to_string(),
+            jobs: arg_matches.get_one::<usize>("jobs").copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_batch_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "batch", "manifest.toml", "-j", "2"]);
+        let args = BatchArgs::from_arg_matches(matches.subcommand_matches("batch").unwrap());
+        assert_eq!(args.get_manifest(), "manifest.toml");
+        assert_eq!(args.get_jobs(), Some(2));
+    }
+}
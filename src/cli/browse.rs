@@ -0,0 +1,52 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Browse subcmd arguments.
+pub struct BrowseArgs {
+    // Taxon to start browsing from
+    pub(crate) name: Option<String>,
+    // File the marked genomes are exported to
+    pub(crate) output: String,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl BrowseArgs {
+    pub fn get_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn get_output(&self) -> String {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        BrowseArgs {
+            name: arg_matches.get_one::<String>("NAME").cloned(),
+            output: arg_matches
+                .get_one::<String>("out")
+                .expect("out has a default value")
+                .to_string(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_browse_from_args() {
+        let matches = app::build_app().get_matches_from(vec!["xgt", "browse", "g__Aminobacter"]);
+        let args = BrowseArgs::from_arg_matches(matches.subcommand_matches("browse").unwrap());
+        assert_eq!(args.get_name(), Some("g__Aminobacter".to_string()));
+        assert_eq!(args.get_output(), "selection.txt");
+        assert!(!args.get_disable_certificate_verification());
+    }
+}
@@ -0,0 +1,95 @@
+use clap::ArgMatches;
+
+/// Textual representations of lineages, one per line, optionally
+/// preceded by an ID column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineageFormat {
+    // Greengenes-style lineage strings, e.g. "d__Bacteria; p__Pseudomonadota"
+    #[default]
+    Lineage,
+    // Per-rank TSV columns of names without rank prefix, with a header
+    Ranks,
+    // Tab-separated lists of rank-prefixed taxa
+    List,
+}
+
+impl From<String> for LineageFormat {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "ranks" => LineageFormat::Ranks,
+            "list" => LineageFormat::List,
+            _ => LineageFormat::Lineage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Convert subcmd arguments.
+pub struct ConvertArgs {
+    // Input file, None to read stdin
+    pub(crate) input: Option<String>,
+    // Input format
+    pub(crate) from: LineageFormat,
+    // Output format
+    pub(crate) to: LineageFormat,
+    // Output file
+    pub(crate) output: Option<String>,
+}
+
+impl ConvertArgs {
+    pub fn get_input(&self) -> Option<String> {
+        self.input.clone()
+    }
+
+    pub fn get_from(&self) -> LineageFormat {
+        self.from
+    }
+
+    pub fn get_to(&self) -> LineageFormat {
+        self.to
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        ConvertArgs {
+            input: arg_matches
+                .get_one::<String>("FILE")
+                .filter(|x| x.as_str() != "-")
+                .map(String::from),
+            from: LineageFormat::from(
+                arg_matches
+                    .get_one::<String>("from")
+                    .expect("from is required")
+                    .to_string(),
+            ),
+            to: LineageFormat::from(
+                arg_matches
+                    .get_one::<String>("to")
+                    .expect("to is required")
+                    .to_string(),
+            ),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_convert_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt", "convert", "-", "--from", "ranks", "--to", "list",
+        ]);
+        let args = ConvertArgs::from_arg_matches(matches.subcommand_matches("convert").unwrap());
+        assert_eq!(args.get_input(), None);
+        assert_eq!(args.get_from(), LineageFormat::Ranks);
+        assert_eq!(args.get_to(), LineageFormat::List);
+        assert_eq!(args.get_output(), None);
+    }
+}
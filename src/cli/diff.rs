@@ -0,0 +1,105 @@
+use crate::utils::OutputFormat;
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Diff subcmd arguments.
+pub struct DiffArgs {
+    // Taxon to compare
+    pub(crate) name: String,
+    // Release to compare from, e.g. R207
+    pub(crate) from: String,
+    // Release to compare to, e.g. R220
+    pub(crate) to: String,
+    // Number of parallel genome history requests
+    pub(crate) jobs: usize,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Output format
+    pub(crate) outfmt: OutputFormat,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl DiffArgs {
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_from(&self) -> String {
+        self.from.clone()
+    }
+
+    pub fn get_to(&self) -> String {
+        self.to.clone()
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        DiffArgs {
+            name: arg_matches
+                .get_one::<String>("NAME")
+                .expect("NAME is required")
+                .to_string(),
+            from: arg_matches
+                .get_one::<String>("from")
+                .expect("from is required")
+                .to_string(),
+            to: arg_matches
+                .get_one::<String>("to")
+                .expect("to is required")
+                .to_string(),
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_diff_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "diff",
+            "g__Aminobacter",
+            "--from",
+            "R207",
+            "--to",
+            "R220",
+        ]);
+        let args = DiffArgs::from_arg_matches(matches.subcommand_matches("diff").unwrap());
+        assert_eq!(args.get_name(), "g__Aminobacter".to_string());
+        assert_eq!(args.get_from(), "R207".to_string());
+        assert_eq!(args.get_to(), "R220".to_string());
+        assert_eq!(args.get_jobs(), 4);
+        assert_eq!(args.get_outfmt(), OutputFormat::Tsv);
+        assert!(!args.get_disable_certificate_verification());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::taxonomy::Rank;
+use crate::utils::OutputFormat;
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Discordance subcmd arguments.
+pub struct DiscordanceArgs {
+    // Taxon whose genomes are compared
+    pub(crate) name: String,
+    // Rank at which the classifications are compared
+    pub(crate) rank: Rank,
+    // List the discordant genomes rather than counts per conflicting pair
+    pub(crate) genomes: bool,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Output format
+    pub(crate) outfmt: OutputFormat,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl DiscordanceArgs {
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_rank(&self) -> Rank {
+        self.rank
+    }
+
+    pub fn is_genomes(&self) -> bool {
+        self.genomes
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        DiscordanceArgs {
+            name: arg_matches
+                .get_one::<String>("NAME")
+                .expect("NAME is required")
+                .to_string(),
+            rank: arg_matches
+                .get_one::<String>("rank")
+                .and_then(|x| Rank::from_name(x))
+                .expect("rank has a default value"),
+            genomes: arg_matches.get_flag("genomes"),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_discordance_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "discordance",
+            "f__Enterobacteriaceae",
+            "--rank",
+            "genus",
+        ]);
+        let args =
+            DiscordanceArgs::from_arg_matches(matches.subcommand_matches("discordance").unwrap());
+        assert_eq!(args.get_name(), "f__Enterobacteriaceae".to_string());
+        assert_eq!(args.get_rank(), Rank::Genus);
+        assert!(!args.is_genomes());
+        assert_eq!(args.get_outfmt(), OutputFormat::Tsv);
+    }
+}
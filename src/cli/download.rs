@@ -0,0 +1,132 @@
+use crate::api::ncbi::AssemblyFile;
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Download subcmd arguments.
+pub struct DownloadArgs {
+    // Accessions to download
+    pub(crate) accession: Vec<String>,
+    // Files to download for each accession
+    pub(crate) files: Vec<AssemblyFile>,
+    // Output directory
+    pub(crate) outdir: String,
+    // Number of parallel downloads
+    pub(crate) jobs: usize,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl DownloadArgs {
+    pub fn get_accession(&self) -> Vec<String> {
+        self.accession.clone()
+    }
+
+    pub fn get_files(&self) -> Vec<AssemblyFile> {
+        self.files.clone()
+    }
+
+    pub fn get_outdir(&self) -> String {
+        self.outdir.clone()
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let accession = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .filter(|l| !l.trim().is_empty())
+                    .collect()
+            }
+            None => vec![arg_matches
+                .get_one::<String>("accession")
+                .expect("Missing accession value")
+                .to_string()],
+        };
+
+        DownloadArgs {
+            accession,
+            files: arg_matches
+                .get_many::<String>("what")
+                .expect("what has a default value")
+                .map(|x| AssemblyFile::from(x.to_string()))
+                .collect(),
+            outdir: arg_matches
+                .get_one::<String>("outdir")
+                .expect("outdir has a default value")
+                .to_string(),
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_download_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "download",
+            "GCA_001512625.1",
+            "-j",
+            "2",
+        ]);
+        let args = DownloadArgs::from_arg_matches(matches.subcommand_matches("download").unwrap());
+        assert_eq!(args.get_accession(), vec!["GCA_001512625.1".to_string()]);
+        assert_eq!(args.get_outdir(), ".".to_string());
+        assert_eq!(args.get_files(), vec![AssemblyFile::Genome]);
+        assert_eq!(args.get_jobs(), 2);
+        assert!(!args.get_disable_certificate_verification());
+    }
+
+    #[test]
+    fn test_download_from_file() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "download",
+            "--file",
+            "test/acc.txt",
+            "--outdir",
+            "genomes",
+            "--what",
+            "genome,protein,gff",
+            "-k",
+        ]);
+        let args = DownloadArgs::from_arg_matches(matches.subcommand_matches("download").unwrap());
+        assert_eq!(
+            args.get_accession(),
+            vec!["GCF_018555685.1".to_string(), "GCF_900445235.1".to_string()]
+        );
+        assert_eq!(args.get_outdir(), "genomes".to_string());
+        assert_eq!(
+            args.get_files(),
+            vec![
+                AssemblyFile::Genome,
+                AssemblyFile::Protein,
+                AssemblyFile::Gff
+            ]
+        );
+        assert_eq!(args.get_jobs(), 4);
+        assert!(args.get_disable_certificate_verification());
+    }
+}
@@ -0,0 +1,83 @@
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Exists subcmd arguments.
+pub struct ExistsArgs {
+    // Genome accessions
+    pub(crate) accessions: Vec<String>,
+    // Number of parallel requests
+    pub(crate) jobs: usize,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl ExistsArgs {
+    pub fn get_accessions(&self) -> Vec<String> {
+        self.accessions.clone()
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let accessions = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect()
+            }
+            None => vec![arg_matches
+                .get_one::<String>("ACCESSION")
+                .expect("Missing ACCESSION value")
+                .to_string()],
+        };
+
+        ExistsArgs {
+            accessions,
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_exists_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "exists", "-f", "test/acc.txt", "-k"]);
+        let args = ExistsArgs::from_arg_matches(matches.subcommand_matches("exists").unwrap());
+        assert_eq!(
+            args.get_accessions(),
+            vec!["GCF_018555685.1", "GCF_900445235.1"]
+        );
+        assert_eq!(args.get_jobs(), 4);
+        assert!(args.get_disable_certificate_verification());
+    }
+}
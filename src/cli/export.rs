@@ -0,0 +1,158 @@
+use crate::exporters::ExportFormat;
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Export subcmd arguments.
+pub struct ExportArgs {
+    // Export format
+    pub(crate) format: ExportFormat,
+    // Export the genomes of this taxon
+    pub(crate) taxon: Option<String>,
+    // Export the genomes matching this search
+    pub(crate) search: Option<String>,
+    // Export the genomes of these accessions
+    pub(crate) accessions: Vec<String>,
+    // Number of parallel requests for accessions
+    pub(crate) jobs: usize,
+    // Only export GTDB species representative genomes
+    pub(crate) reps_only: bool,
+    // Map accessions to their lineage instead of their taxid
+    pub(crate) with_lineage: bool,
+    // Write a Krona HTML chart to this file
+    pub(crate) html: Option<String>,
+    // Output directory of the formats written as several files
+    pub(crate) outdir: String,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl ExportArgs {
+    pub fn get_format(&self) -> ExportFormat {
+        self.format
+    }
+
+    pub fn get_taxon(&self) -> Option<String> {
+        self.taxon.clone()
+    }
+
+    pub fn get_search(&self) -> Option<String> {
+        self.search.clone()
+    }
+
+    pub fn get_accessions(&self) -> Vec<String> {
+        self.accessions.clone()
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn is_reps_only(&self) -> bool {
+        self.reps_only
+    }
+
+    pub fn is_with_lineage(&self) -> bool {
+        self.with_lineage
+    }
+
+    pub fn get_html(&self) -> Option<String> {
+        self.html.clone()
+    }
+
+    pub fn get_outdir(&self) -> String {
+        self.outdir.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let accessions = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .filter(|l| !l.trim().is_empty())
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        ExportArgs {
+            format: ExportFormat::from(
+                arg_matches
+                    .get_one::<String>("FORMAT")
+                    .expect("FORMAT is required")
+                    .to_string(),
+            ),
+            taxon: arg_matches.get_one::<String>("taxon").cloned(),
+            search: arg_matches.get_one::<String>("search").cloned(),
+            accessions,
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            reps_only: arg_matches.get_flag("reps"),
+            with_lineage: arg_matches.get_flag("lineage"),
+            html: arg_matches.get_one::<String>("html").cloned(),
+            outdir: arg_matches
+                .get_one::<String>("outdir")
+                .expect("outdir has a default value")
+                .to_string(),
+            output: arg_matches.get_one::<String>("out").cloned(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_export_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "export",
+            "krona",
+            "--taxon",
+            "g__Escherichia",
+            "--reps",
+            "--html",
+            "escherichia.html",
+        ]);
+        let args = ExportArgs::from_arg_matches(matches.subcommand_matches("export").unwrap());
+        assert_eq!(args.get_format(), ExportFormat::Krona);
+        assert_eq!(args.get_taxon(), Some("g__Escherichia".to_string()));
+        assert_eq!(args.get_search(), None);
+        assert!(args.is_reps_only());
+        assert_eq!(args.get_html(), Some("escherichia.html".to_string()));
+    }
+
+    #[test]
+    fn test_export_file_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "export", "qiime", "-f", "test/acc.txt"]);
+        let args = ExportArgs::from_arg_matches(matches.subcommand_matches("export").unwrap());
+        assert_eq!(args.get_format(), ExportFormat::Qiime);
+        assert_eq!(args.get_taxon(), None);
+        assert_eq!(
+            args.get_accessions(),
+            vec!["GCF_018555685.1".to_string(), "GCF_900445235.1".to_string()]
+        );
+        assert_eq!(args.get_jobs(), 4);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::api::data::Domain;
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Fetch-metadata subcmd arguments.
+pub struct FetchMetadataArgs {
+    // Domains of the metadata files to download
+    pub(crate) domains: Vec<Domain>,
+    // GTDB release, None for the latest release
+    pub(crate) release: Option<String>,
+    // Output file of the download report
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl FetchMetadataArgs {
+    pub fn get_domains(&self) -> Vec<Domain> {
+        self.domains.clone()
+    }
+
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        FetchMetadataArgs {
+            domains: arg_matches
+                .get_many::<String>("domain")
+                .expect("domain has a default value")
+                .map(|x| Domain::from(x.to_string()))
+                .collect(),
+            release: arg_matches.get_one::<String>("release").cloned(),
+            output: arg_matches.get_one::<String>("out").cloned(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Fetch-tree subcmd arguments.
+pub struct FetchTreeArgs {
+    // Domains of the trees to download
+    pub(crate) domains: Vec<Domain>,
+    // GTDB release, None for the latest release
+    pub(crate) release: Option<String>,
+    // Output directory
+    pub(crate) outdir: String,
+    // Output file of the download report
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl FetchTreeArgs {
+    pub fn get_domains(&self) -> Vec<Domain> {
+        self.domains.clone()
+    }
+
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    pub fn get_outdir(&self) -> String {
+        self.outdir.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        FetchTreeArgs {
+            domains: arg_matches
+                .get_many::<String>("domain")
+                .expect("domain has a default value")
+                .map(|x| Domain::from(x.to_string()))
+                .collect(),
+            release: arg_matches.get_one::<String>("release").cloned(),
+            outdir: arg_matches
+                .get_one::<String>("outdir")
+                .expect("outdir has a default value")
+                .to_string(),
+            output: arg_matches.get_one::<String>("out").cloned(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_fetch_metadata_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "fetch-metadata",
+            "--release",
+            "220",
+            "--domain",
+            "ar",
+        ]);
+        let args = FetchMetadataArgs::from_arg_matches(
+            matches.subcommand_matches("fetch-metadata").unwrap(),
+        );
+        assert_eq!(args.get_domains(), vec![Domain::Archaea]);
+        assert_eq!(args.get_release(), Some("R220".to_string()));
+
+        let matches = app::build_app().get_matches_from(vec!["xgt", "fetch-metadata"]);
+        let args = FetchMetadataArgs::from_arg_matches(
+            matches.subcommand_matches("fetch-metadata").unwrap(),
+        );
+        assert_eq!(args.get_domains(), vec![Domain::Bacteria, Domain::Archaea]);
+        assert_eq!(args.get_release(), None);
+    }
+
+    #[test]
+    fn test_fetch_tree_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "fetch-tree",
+            "--domain",
+            "bac",
+            "-d",
+            "trees",
+            "--release",
+            "R207",
+        ]);
+        let args =
+            FetchTreeArgs::from_arg_matches(matches.subcommand_matches("fetch-tree").unwrap());
+        assert_eq!(args.get_domains(), vec![Domain::Bacteria]);
+        assert_eq!(args.get_outdir(), "trees".to_string());
+        assert_eq!(args.get_release(), Some("R207".to_string()));
+    }
+}
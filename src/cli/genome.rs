@@ -1,10 +1,14 @@
+use crate::utils::OutputFormat;
 use clap::ArgMatches;
 use std::{
     fs::File,
     io::{BufRead, BufReader},
 };
 
-#[derive(Debug, Clone)]
+/// Placeholder of `--out` replaced by the accession of each genome
+pub const ACCESSION_PLACEHOLDER: &str = "{accession}";
+
+#[derive(Debug, Clone, Default)]
 /// Genome subcmd arguments.
 pub struct GenomeArgs {
     // Accession
@@ -13,6 +17,38 @@ pub struct GenomeArgs {
     pub(crate) output: Option<String>,
     // Check SSL peer verification
     pub(crate) disable_certificate_verification: bool,
+    // Genome card output format
+    pub(crate) outfmt: OutputFormat,
+    // Accession to compare the genome with
+    pub(crate) compare: Option<String>,
+    // Only output the taxonomy of the genome
+    pub(crate) taxonomy_only: bool,
+    // Add NCBI taxonomy to taxonomy only output
+    pub(crate) with_ncbi: bool,
+    // Add NCBI taxids to taxonomy only output
+    pub(crate) with_taxid: bool,
+    // Report GTDB and NCBI taxonomy discrepancies
+    pub(crate) discrepancies: bool,
+    // GTDB release the query is pinned to
+    pub(crate) release: Option<String>,
+    // Answer from the cached GTDB metadata
+    pub(crate) local: bool,
+    // Keep the HTML markup of the NCBI taxonomy links
+    pub(crate) raw: bool,
+    // Number of parallel requests for several accessions
+    pub(crate) jobs: usize,
+    // Skip the accessions whose output file already exists
+    pub(crate) skip_existing: bool,
+    // Stop at the first accession which fails
+    pub(crate) fail_fast: bool,
+    // Merge the genome card and metadata, flat or nested
+    pub(crate) full: Option<String>,
+    // Only keep (true) or skip (false) NCBI surveillance genomes
+    pub(crate) surveillance: Option<bool>,
+    // Output the nomenclature of the genomes
+    pub(crate) nomenclature: bool,
+    // Report the changes of the cards since the previous run
+    pub(crate) diff_cache: bool,
 }
 
 impl GenomeArgs {
@@ -28,6 +64,88 @@ impl GenomeArgs {
         self.disable_certificate_verification
     }
 
+    pub fn get_compare(&self) -> Option<String> {
+        self.compare.clone()
+    }
+
+    pub fn is_taxonomy_only(&self) -> bool {
+        self.taxonomy_only
+    }
+
+    pub fn is_with_ncbi(&self) -> bool {
+        self.with_ncbi
+    }
+
+    pub fn is_with_taxid(&self) -> bool {
+        self.with_taxid
+    }
+
+    pub fn is_discrepancies(&self) -> bool {
+        self.discrepancies
+    }
+
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn is_skip_existing(&self) -> bool {
+        self.skip_existing
+    }
+
+    pub fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Whether `--out` is a template writing each genome to its own file
+    pub fn is_per_accession(&self) -> bool {
+        self.output
+            .as_ref()
+            .is_some_and(|x| x.contains(ACCESSION_PLACEHOLDER))
+    }
+
+    /// Output file of `accession`, `--out` with `{accession}` replaced
+    pub fn get_accession_output(&self, accession: &str) -> Option<String> {
+        self.output
+            .as_ref()
+            .map(|x| x.replace(ACCESSION_PLACEHOLDER, accession))
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    /// Layout of the merged genome card and metadata, flat or nested, if
+    /// requested
+    pub fn get_full(&self) -> Option<String> {
+        self.full.clone()
+    }
+
+    /// Whether only (true) or no (false) NCBI surveillance genomes are
+    /// kept, none to keep every genome
+    pub fn get_surveillance(&self) -> Option<bool> {
+        self.surveillance
+    }
+
+    pub fn is_nomenclature(&self) -> bool {
+        self.nomenclature
+    }
+
+    pub fn is_diff_cache(&self) -> bool {
+        self.diff_cache
+    }
+
     pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
         let accession = match arg_matches.get_one::<String>("file") {
             Some(file_path) => {
@@ -47,10 +165,46 @@ impl GenomeArgs {
             accession,
             output: arg_matches.get_one::<String>("out").cloned(),
             disable_certificate_verification: arg_matches.get_flag("insecure"),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            compare: arg_matches.get_one::<String>("compare").cloned(),
+            taxonomy_only: arg_matches.get_flag("taxonomy-only"),
+            with_ncbi: arg_matches.get_flag("ncbi"),
+            with_taxid: arg_matches.get_flag("taxid"),
+            discrepancies: arg_matches.get_flag("discrepancies"),
+            release: arg_matches.get_one::<String>("release").cloned(),
+            local: arg_matches.get_flag("local"),
+            raw: arg_matches.get_flag("raw"),
+            jobs: arg_matches
+                .get_one::<usize>("jobs")
+                .copied()
+                .expect("jobs has a default value"),
+            skip_existing: arg_matches.get_flag("skip-existing"),
+            fail_fast: arg_matches.get_flag("fail-fast"),
+            full: arg_matches.get_one::<String>("full").cloned(),
+            surveillance: surveillance_filter(arg_matches),
+            nomenclature: arg_matches.get_flag("nomenclature"),
+            diff_cache: arg_matches.get_flag("diff-cache"),
         }
     }
 }
 
+/// Surveillance genomes kept by `--only-surveillance` (true) or skipped by
+/// `--exclude-surveillance` (false)
+pub(crate) fn surveillance_filter(arg_matches: &ArgMatches) -> Option<bool> {
+    if arg_matches.get_flag("only-surveillance") {
+        Some(true)
+    } else if arg_matches.get_flag("exclude-surveillance") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -64,6 +218,7 @@ mod tests {
             accession: vec![String::from("NC_000001.11")],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert_eq!(genome_args.get_accession(), vec!["NC_000001.11"]);
@@ -75,6 +230,7 @@ mod tests {
             accession: vec![String::from("NC_000001.11")],
             output: Some(String::from("output4.txt")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert_eq!(genome_args.get_output(), Some(String::from("output4.txt")));
@@ -94,6 +250,103 @@ mod tests {
 
         assert_eq!(args.get_accession(), name);
         assert_eq!(args.get_output(), None);
+        assert_eq!(args.get_compare(), None);
+        assert!(!args.is_raw());
+    }
+
+    #[test]
+    fn test_genome_skip_existing_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "--file",
+            "test/acc.txt",
+            "--out",
+            "cards/{accession}.json",
+            "--skip-existing",
+        ]);
+
+        let args = GenomeArgs::from_arg_matches(matches.subcommand_matches("genome").unwrap());
+
+        assert!(args.is_skip_existing());
+        assert!(args.is_per_accession());
+        assert!(!args.is_fail_fast());
+        assert_eq!(
+            args.get_accession_output("GCA_001512625.1"),
+            Some("cards/GCA_001512625.1.json".to_string())
+        );
+
+        // Without --out, there are no files to skip
+        let result = app::build_app().try_get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_1",
+            "--skip-existing",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_genome_surveillance_from_args() {
+        let args = |flags: &[&str]| {
+            let matches = app::build_app().try_get_matches_from(
+                ["xgt", "genome", "--file", "test/acc.txt"]
+                    .iter()
+                    .chain(flags),
+            )?;
+            Ok::<_, clap::Error>(GenomeArgs::from_arg_matches(
+                matches.subcommand_matches("genome").unwrap(),
+            ))
+        };
+
+        assert_eq!(args(&[]).unwrap().get_surveillance(), None);
+        assert_eq!(
+            args(&["--exclude-surveillance"])
+                .unwrap()
+                .get_surveillance(),
+            Some(false)
+        );
+        assert_eq!(
+            args(&["--only-surveillance"]).unwrap().get_surveillance(),
+            Some(true)
+        );
+        assert!(args(&["--only-surveillance", "--exclude-surveillance"]).is_err());
+        assert!(args(&["--only-surveillance", "--local"]).is_err());
+    }
+
+    #[test]
+    fn test_genome_compare_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_001512625.1",
+            "--compare",
+            "GCF_018555685.1",
+        ]);
+
+        let args = GenomeArgs::from_arg_matches(matches.subcommand_matches("genome").unwrap());
+
+        assert_eq!(args.get_accession(), vec!["GCA_001512625.1".to_string()]);
+        assert_eq!(args.get_compare(), Some("GCF_018555685.1".to_string()));
+    }
+
+    #[test]
+    fn test_genome_taxonomy_only_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_001512625.1",
+            "--taxonomy-only",
+            "--ncbi",
+            "--taxid",
+        ]);
+
+        let args = GenomeArgs::from_arg_matches(matches.subcommand_matches("genome").unwrap());
+
+        assert!(args.is_taxonomy_only());
+        assert!(args.is_with_ncbi());
+        assert!(args.is_with_taxid());
+        assert!(!args.is_discrepancies());
     }
 
     #[test]
@@ -107,11 +360,15 @@ mod tests {
             OsString::from("test/acc.txt"),
             OsString::from("-o"),
             OsString::from("out"),
+            OsString::from("-O"),
+            OsString::from("html"),
         ]);
 
         let args = GenomeArgs::from_arg_matches(matches.subcommand_matches("genome").unwrap());
 
         assert_eq!(args.get_accession(), name);
         assert_eq!(args.get_output(), Some("out".to_string()));
+        assert_eq!(args.get_outfmt(), OutputFormat::Html);
+        assert_eq!(args.get_jobs(), 4);
     }
 }
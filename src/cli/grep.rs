@@ -0,0 +1,132 @@
+use crate::taxonomy::Rank;
+use crate::utils::SearchField;
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Grep subcmd arguments.
+pub struct GrepArgs {
+    // Names to search
+    pub(crate) needles: Vec<String>,
+    // Local GTDB taxonomy file
+    pub(crate) taxonomy: String,
+    // Searched field: all, acc or gtdb
+    pub(crate) search_field: SearchField,
+    // Match only whole words
+    pub(crate) is_whole_words_matching: bool,
+    // Only match the taxon at this rank
+    pub(crate) level: Option<Rank>,
+    // Only print matched genomes ID
+    pub(crate) id: bool,
+    // Only print a count of matched genomes
+    pub(crate) count: bool,
+    // Output file
+    pub(crate) output: Option<String>,
+}
+
+impl GrepArgs {
+    pub fn get_needles(&self) -> Vec<String> {
+        self.needles.clone()
+    }
+
+    pub fn get_taxonomy(&self) -> String {
+        self.taxonomy.clone()
+    }
+
+    pub fn get_search_field(&self) -> SearchField {
+        self.search_field.clone()
+    }
+
+    pub fn is_whole_words_matching(&self) -> bool {
+        self.is_whole_words_matching
+    }
+
+    pub fn get_level(&self) -> Option<Rank> {
+        self.level
+    }
+
+    pub fn is_only_print_ids(&self) -> bool {
+        self.id
+    }
+
+    pub fn is_only_num_entries(&self) -> bool {
+        self.count
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let needles = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .filter(|l| !l.trim().is_empty())
+                    .collect()
+            }
+            None => vec![arg_matches
+                .get_one::<String>("NAME")
+                .expect("Missing NAME value")
+                .to_string()],
+        };
+
+        GrepArgs {
+            needles,
+            taxonomy: arg_matches
+                .get_one::<String>("taxonomy")
+                .expect("taxonomy is required")
+                .to_string(),
+            search_field: SearchField::from(
+                arg_matches
+                    .get_one::<String>("field")
+                    .expect("field has a default value")
+                    .to_string(),
+            ),
+            is_whole_words_matching: arg_matches.get_flag("word"),
+            level: arg_matches
+                .get_one::<String>("level")
+                .and_then(|x| Rank::from_name(x)),
+            id: arg_matches.get_flag("id"),
+            count: arg_matches.get_flag("count"),
+            output: arg_matches.get_one::<String>("out").cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_grep_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "grep",
+            "g__Escherichia",
+            "--taxonomy",
+            "bac120_taxonomy_r220.tsv.gz",
+            "--field",
+            "gtdb",
+            "-w",
+            "--level",
+            "genus",
+            "-c",
+        ]);
+        let args = GrepArgs::from_arg_matches(matches.subcommand_matches("grep").unwrap());
+        assert_eq!(args.get_needles(), vec!["g__Escherichia".to_string()]);
+        assert_eq!(args.get_taxonomy(), "bac120_taxonomy_r220.tsv.gz");
+        assert_eq!(args.get_search_field(), SearchField::Gtdb);
+        assert!(args.is_whole_words_matching());
+        assert_eq!(args.get_level(), Some(Rank::Genus));
+        assert!(args.is_only_num_entries());
+        assert!(!args.is_only_print_ids());
+    }
+}
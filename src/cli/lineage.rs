@@ -0,0 +1,106 @@
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Lineage subcmd arguments.
+pub struct LineageArgs {
+    // Genome accessions
+    pub(crate) accessions: Vec<String>,
+    // Output the NCBI lineage instead of the GTDB one
+    pub(crate) ncbi: bool,
+    // Number of parallel requests
+    pub(crate) jobs: usize,
+    // Fetch lineages again instead of using cached ones
+    pub(crate) refresh: bool,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl LineageArgs {
+    pub fn get_accessions(&self) -> Vec<String> {
+        self.accessions.clone()
+    }
+
+    pub fn is_ncbi(&self) -> bool {
+        self.ncbi
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn is_refresh(&self) -> bool {
+        self.refresh
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let accessions = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect()
+            }
+            None => vec![arg_matches
+                .get_one::<String>("ACCESSION")
+                .expect("Missing ACCESSION value")
+                .to_string()],
+        };
+
+        LineageArgs {
+            accessions,
+            ncbi: arg_matches.get_flag("ncbi"),
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            refresh: arg_matches.get_flag("refresh"),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_lineage_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "lineage",
+            "--file",
+            "test/acc.txt",
+            "--ncbi",
+            "-j",
+            "8",
+        ]);
+        let args = LineageArgs::from_arg_matches(matches.subcommand_matches("lineage").unwrap());
+        assert_eq!(
+            args.get_accessions(),
+            vec!["GCF_018555685.1".to_string(), "GCF_900445235.1".to_string()]
+        );
+        assert!(args.is_ncbi());
+        assert_eq!(args.get_jobs(), 8);
+        assert!(!args.is_refresh());
+        assert_eq!(args.get_output(), None);
+    }
+}
@@ -1,4 +1,30 @@
+pub mod ani;
 pub mod app;
+pub mod batch;
+#[cfg(feature = "tui")]
+pub mod browse;
+pub mod convert;
+pub mod diff;
+pub mod discordance;
+#[cfg(feature = "downloads")]
+pub mod download;
+pub mod exists;
+#[cfg(feature = "exports")]
+pub mod export;
+#[cfg(feature = "downloads")]
+pub mod fetch;
 pub mod genome;
+pub mod grep;
+pub mod lineage;
+pub mod open;
+pub mod raw;
+pub mod releases;
+pub mod schema;
 pub mod search;
+pub mod serve;
+pub mod shell;
+pub mod taxid;
 pub mod taxon;
+pub mod translate;
+pub mod tree;
+pub mod watch;
@@ -0,0 +1,45 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Open subcmd arguments.
+pub struct OpenArgs {
+    // Genome accession or taxon
+    pub(crate) record: String,
+    // Only print the URL
+    pub(crate) print: bool,
+}
+
+impl OpenArgs {
+    pub fn get_record(&self) -> String {
+        self.record.clone()
+    }
+
+    pub fn is_print(&self) -> bool {
+        self.print
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        OpenArgs {
+            record: arg_matches
+                .get_one::<String>("RECORD")
+                .expect("RECORD is required")
+                .to_string(),
+            print: arg_matches.get_flag("print"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_open_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "open", "g__Aminobacter", "--print"]);
+        let args = OpenArgs::from_arg_matches(matches.subcommand_matches("open").unwrap());
+        assert_eq!(args.get_record(), "g__Aminobacter");
+        assert!(args.is_print());
+    }
+}
@@ -0,0 +1,76 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Api subcmd arguments.
+pub struct RawArgs {
+    // HTTP method
+    pub(crate) method: String,
+    // GTDB API path
+    pub(crate) path: String,
+    // Request body
+    pub(crate) data: Option<String>,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl RawArgs {
+    pub fn get_method(&self) -> String {
+        self.method.clone()
+    }
+
+    pub fn get_path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn get_data(&self) -> Option<String> {
+        self.data.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        RawArgs {
+            method: arg_matches
+                .get_one::<String>("METHOD")
+                .expect("METHOD is required")
+                .to_uppercase(),
+            path: arg_matches
+                .get_one::<String>("PATH")
+                .expect("PATH is required")
+                .to_string(),
+            data: arg_matches.get_one::<String>("data").cloned(),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_raw_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "api",
+            "get",
+            "/taxon/g__Aminobacter/genomes",
+            "-k",
+        ]);
+        let args = RawArgs::from_arg_matches(matches.subcommand_matches("api").unwrap());
+        assert_eq!(args.get_method(), "GET");
+        assert_eq!(args.get_path(), "/taxon/g__Aminobacter/genomes");
+        assert_eq!(args.get_data(), None);
+        assert!(args.get_disable_certificate_verification());
+    }
+}
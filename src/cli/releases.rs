@@ -0,0 +1,63 @@
+use crate::utils::OutputFormat;
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Releases subcmd arguments.
+pub struct ReleasesArgs {
+    // Only print the current release
+    pub(crate) latest: bool,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Output format
+    pub(crate) outfmt: OutputFormat,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl ReleasesArgs {
+    pub fn is_latest(&self) -> bool {
+        self.latest
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        ReleasesArgs {
+            latest: arg_matches.get_flag("latest"),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_releases_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "releases", "--outfmt", "json", "-k"]);
+        let args = ReleasesArgs::from_arg_matches(matches.subcommand_matches("releases").unwrap());
+        assert!(!args.is_latest());
+        assert_eq!(args.get_outfmt(), OutputFormat::Json);
+        assert!(args.get_disable_certificate_verification());
+    }
+}
@@ -0,0 +1,41 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Schema subcmd arguments.
+pub struct SchemaArgs {
+    // Name of the output structure, or None for all of them
+    pub(crate) name: Option<String>,
+    // Output file
+    pub(crate) output: Option<String>,
+}
+
+impl SchemaArgs {
+    pub fn get_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        SchemaArgs {
+            name: arg_matches.get_one::<String>("NAME").cloned(),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_schema_from_args() {
+        let matches = app::build_app().get_matches_from(vec!["xgt", "schema", "genome-card"]);
+        let args = SchemaArgs::from_arg_matches(matches.subcommand_matches("schema").unwrap());
+        assert_eq!(args.get_name().as_deref(), Some("genome-card"));
+        assert_eq!(args.get_output(), None);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::cli::genome::surveillance_filter;
+use crate::query::SearchQuery;
+use crate::taxonomy::Rank;
 use crate::utils::{OutputFormat, SearchField};
 use clap::ArgMatches;
 use std::{
@@ -16,6 +19,8 @@ pub struct SearchArgs {
     pub(crate) is_whole_words_matching: bool,
     // returns entries' ids
     pub(crate) id: bool,
+    // identifier of the genomes printed by --id
+    pub(crate) id_kind: String,
     // count entries in result
     pub(crate) count: bool,
     // search representative species only
@@ -28,6 +33,38 @@ pub struct SearchArgs {
     pub(crate) outfmt: OutputFormat,
     // SSL certificate verification: true => disable, false => enable
     pub(crate) disable_certificate_verification: bool,
+    // summarize matched genomes by a GTDB taxonomy rank
+    pub(crate) summarize: Option<Rank>,
+    // search the cached GTDB metadata instead of the API
+    pub(crate) local: bool,
+    // GTDB release of the cached metadata to search
+    pub(crate) release: Option<String>,
+    // search genomes by a full or partial GTDB lineage
+    pub(crate) lineage: bool,
+    // wrap JSON results with the metadata of their query
+    pub(crate) envelope: bool,
+    // column to sort matched genomes by, or None to keep the API order
+    pub(crate) stable_sort: Option<String>,
+    // column or GTDB rank to group matched genomes by
+    pub(crate) group_by: Option<String>,
+    // aggregates of the groups, e.g. count or mean(genome_size)
+    pub(crate) aggregates: Vec<String>,
+    // columns or GTDB ranks of the rows and columns of a contingency table
+    pub(crate) pivot: Option<(String, String)>,
+    // numeric columns to print histograms of
+    pub(crate) hist: Vec<String>,
+    // earliest NCBI release date of matched genomes
+    pub(crate) released_after: Option<String>,
+    // latest NCBI release date of matched genomes
+    pub(crate) released_before: Option<String>,
+    // stop at the first needle which fails
+    pub(crate) fail_fast: bool,
+    // collapse matched genomes to one per GTDB species
+    pub(crate) distinct_species: bool,
+    // only keep (true) or drop (false) NCBI surveillance genomes
+    pub(crate) surveillance: Option<bool>,
+    // number of parallel genome metadata requests
+    pub(crate) jobs: usize,
 }
 
 impl SearchArgs {
@@ -71,6 +108,12 @@ impl SearchArgs {
         self.id
     }
 
+    /// Getter for the identifier of the genomes printed by --id: gid,
+    /// accession or ncbi_assembly
+    pub fn get_id_kind(&self) -> &str {
+        &self.id_kind
+    }
+
     /// Setter for count attribute
     pub(crate) fn set_count(&mut self, b: bool) {
         self.count = b;
@@ -127,10 +170,115 @@ impl SearchArgs {
         self.outfmt.clone()
     }
 
+    /// Getter for summarize attribute
+    pub fn get_summarize(&self) -> Option<Rank> {
+        self.summarize
+    }
+
+    /// Is search of the cached GTDB metadata enabled
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    /// Getter for release attribute
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    /// Is search by GTDB lineage enabled
+    pub fn is_lineage(&self) -> bool {
+        self.lineage
+    }
+
+    /// Is wrapping JSON results with their metadata enabled
+    pub fn is_envelope(&self) -> bool {
+        self.envelope
+    }
+
+    /// Getter for the column to sort matched genomes by
+    pub fn get_stable_sort(&self) -> Option<String> {
+        self.stable_sort.clone()
+    }
+
+    /// Getter for the column or rank to group matched genomes by
+    pub fn get_group_by(&self) -> Option<String> {
+        self.group_by.clone()
+    }
+
+    /// Getter for the aggregates of the groups
+    pub fn get_aggregates(&self) -> &[String] {
+        &self.aggregates
+    }
+
+    /// Getter for the row and column keys of the contingency table
+    pub fn get_pivot(&self) -> Option<(String, String)> {
+        self.pivot.clone()
+    }
+
+    /// Getter for the columns to print histograms of
+    pub fn get_hist(&self) -> &[String] {
+        &self.hist
+    }
+
+    /// Getter for the earliest NCBI release date of matched genomes
+    pub fn get_released_after(&self) -> Option<String> {
+        self.released_after.clone()
+    }
+
+    /// Getter for the latest NCBI release date of matched genomes
+    pub fn get_released_before(&self) -> Option<String> {
+        self.released_before.clone()
+    }
+
+    /// Does the search stop at the first needle which fails
+    pub fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Whether only (true) or no (false) NCBI surveillance genomes are
+    /// kept, none to keep every genome
+    pub fn get_surveillance(&self) -> Option<bool> {
+        self.surveillance
+    }
+
+    /// Get the number of parallel genome metadata requests
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Are matched genomes collapsed to one per GTDB species
+    pub fn is_distinct_species(&self) -> bool {
+        self.distinct_species
+    }
+
+    /// Are matched genomes summarized, by rank, groups, a contingency table
+    /// or histograms, rather than listed
+    pub fn is_summarized(&self) -> bool {
+        self.summarize.is_some()
+            || self.group_by.is_some()
+            || self.pivot.is_some()
+            || !self.hist.is_empty()
+    }
+
     pub fn new() -> Self {
         SearchArgs::default()
     }
 
+    /// Library query of `needle` with the search options of the arguments
+    pub fn to_query(&self, needle: &str) -> SearchQuery {
+        let mut query = SearchQuery::new(needle).field(self.get_search_field());
+        if self.is_whole_words_matching() {
+            query = query.whole_words();
+        }
+        if self.is_representative_species_only() {
+            query = query.reps_only();
+        }
+        if self.is_type_species_only() {
+            query = query.type_material_only();
+        }
+        query
+    }
+
     pub fn from_arg_matches(args: &ArgMatches) -> Self {
         let mut search_args = SearchArgs::new();
 
@@ -153,6 +301,10 @@ impl SearchArgs {
         search_args.set_matching_mode(args.get_flag("word"));
 
         search_args.set_id(args.get_flag("id"));
+        search_args.id_kind = args
+            .get_one::<String>("id-kind")
+            .cloned()
+            .unwrap_or_default();
 
         search_args.set_count(args.get_flag("count"));
 
@@ -179,6 +331,61 @@ impl SearchArgs {
 
         search_args.set_disable_certificate_verification(args.get_flag("insecure"));
 
+        search_args.summarize = args
+            .get_one::<String>("summarize")
+            .and_then(|x| Rank::from_name(x));
+
+        search_args.local = args.get_flag("local");
+
+        search_args.release = args.get_one::<String>("release").cloned();
+
+        search_args.lineage = args.get_flag("lineage");
+        if search_args.lineage {
+            // Lineages are matched against the GTDB taxonomy of the JSON
+            // results, and matched genomes are reported by their ID
+            search_args.set_search_field("gtdb");
+            search_args.set_id(true);
+            search_args.set_outfmt("json".to_string());
+        }
+
+        // Files are sorted by default, so that outputs of reruns can be diffed
+        search_args.stable_sort = args
+            .get_one::<String>("stable-sort")
+            .cloned()
+            .or_else(|| search_args.out.as_ref().map(|_| "accession".to_string()));
+
+        search_args.envelope = args.get_flag("envelope");
+        if search_args.envelope {
+            search_args.set_outfmt("json".to_string());
+        }
+
+        search_args.group_by = args.get_one::<String>("group-by").cloned();
+        if search_args.group_by.is_some() {
+            search_args.aggregates = args
+                .get_many::<String>("agg")
+                .map(|x| x.cloned().collect())
+                .unwrap_or_default();
+        }
+
+        search_args.pivot = args.get_one::<(String, String)>("pivot").cloned();
+
+        search_args.hist = args
+            .get_many::<String>("hist")
+            .map(|x| x.cloned().collect())
+            .unwrap_or_default();
+
+        search_args.released_after = args.get_one::<String>("released-after").cloned();
+        search_args.released_before = args.get_one::<String>("released-before").cloned();
+
+        search_args.fail_fast = args.get_flag("fail-fast");
+
+        search_args.distinct_species = args.get_flag("distinct-species");
+
+        search_args.surveillance = surveillance_filter(args);
+        search_args.jobs = *args
+            .get_one::<usize>("jobs")
+            .expect("jobs has a default value");
+
         search_args
     }
 }
@@ -204,6 +411,20 @@ mod tests {
         assert_eq!(search_args.get_search_field(), SearchField::Gtdb);
     }
 
+    #[test]
+    fn test_to_query() {
+        let mut search_args = SearchArgs::new();
+        search_args.set_search_field("gtdb");
+        search_args.set_matching_mode(true);
+        search_args.set_is_type_species_only(true);
+        assert_eq!(
+            search_args.to_query("g__Aminobacter"),
+            SearchQuery::taxon("g__Aminobacter")
+                .whole_words()
+                .type_material_only()
+        );
+    }
+
     #[test]
     fn test_set_matching_mode() {
         let mut search_args = SearchArgs::new();
@@ -0,0 +1,75 @@
+use clap::ArgMatches;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Serve subcmd arguments.
+pub struct ServeArgs {
+    // Address the server listens on
+    pub(crate) host: String,
+    // Port the server listens on
+    pub(crate) port: u16,
+    // Maximum number of requests per second sent to the GTDB API
+    pub(crate) rate: f64,
+    // Age after which cached responses are fetched again
+    pub(crate) cache_ttl: Duration,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl ServeArgs {
+    pub fn get_host(&self) -> String {
+        self.host.clone()
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn get_cache_ttl(&self) -> Duration {
+        self.cache_ttl
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        ServeArgs {
+            host: arg_matches
+                .get_one::<String>("host")
+                .expect("host has a default value")
+                .to_string(),
+            port: *arg_matches
+                .get_one::<u16>("port")
+                .expect("port has a default value"),
+            rate: *arg_matches
+                .get_one::<f64>("rate")
+                .expect("rate has a default value"),
+            cache_ttl: *arg_matches
+                .get_one::<Duration>("cache-ttl")
+                .expect("cache-ttl has a default value"),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_serve_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "serve", "-p", "9000", "--rate", "0.5"]);
+        let args = ServeArgs::from_arg_matches(matches.subcommand_matches("serve").unwrap());
+        assert_eq!(args.get_host(), "127.0.0.1");
+        assert_eq!(args.get_port(), 9000);
+        assert_eq!(args.get_rate(), 0.5);
+        assert_eq!(args.get_cache_ttl(), Duration::from_secs(24 * 3600));
+    }
+}
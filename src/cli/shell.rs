@@ -0,0 +1,33 @@
+use clap::ArgMatches;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Shell subcmd arguments.
+pub struct ShellArgs {
+    // Save the commands to the history file
+    pub(crate) save_history: bool,
+}
+
+impl ShellArgs {
+    pub fn is_save_history(&self) -> bool {
+        self.save_history
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        ShellArgs {
+            save_history: !arg_matches.get_flag("no-history"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_shell_from_args() {
+        let matches = app::build_app().get_matches_from(vec!["xgt", "shell", "--no-history"]);
+        let args = ShellArgs::from_arg_matches(matches.subcommand_matches("shell").unwrap());
+        assert!(!args.is_save_history());
+    }
+}
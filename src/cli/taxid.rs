@@ -0,0 +1,69 @@
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Taxid subcmd arguments.
+pub struct TaxidArgs {
+    // Taxon names or genome accessions
+    pub(crate) names: Vec<String>,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl TaxidArgs {
+    pub fn get_names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        let names = match arg_matches.get_one::<String>("file") {
+            Some(file_path) => {
+                let file = File::open(file_path)
+                    .unwrap_or_else(|_| panic!("Failed to open file: {}", file_path));
+                BufReader::new(file)
+                    .lines()
+                    .map(|l| l.expect("Cannot parse line"))
+                    .filter(|l| !l.trim().is_empty())
+                    .collect()
+            }
+            None => vec![arg_matches
+                .get_one::<String>("NAME")
+                .expect("Missing NAME value")
+                .to_string()],
+        };
+
+        TaxidArgs {
+            names,
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_taxid_from_args() {
+        let matches = app::build_app().get_matches_from(vec!["xgt", "taxid", "-f", "test/acc.txt"]);
+        let args = TaxidArgs::from_arg_matches(matches.subcommand_matches("taxid").unwrap());
+        assert!(!args.get_names().is_empty());
+        assert_eq!(args.get_output(), None);
+        assert!(!args.get_disable_certificate_verification());
+    }
+}
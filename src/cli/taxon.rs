@@ -1,10 +1,11 @@
+use crate::utils::OutputFormat;
 use clap::ArgMatches;
 use std::{
     fs::File,
     io::{BufRead, BufReader},
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct TaxonArgs {
     pub(crate) name: Vec<String>,
     pub(crate) output: Option<String>,
@@ -14,6 +15,23 @@ pub struct TaxonArgs {
     pub(crate) genomes: bool,
     pub(crate) reps_only: bool,
     pub(crate) disable_certificate_verification: bool,
+    pub(crate) count: bool,
+    pub(crate) outfmt: OutputFormat,
+    pub(crate) detail: bool,
+    pub(crate) jobs: usize,
+    pub(crate) children: bool,
+    pub(crate) lineage: bool,
+    pub(crate) ranks: bool,
+    pub(crate) history: bool,
+    pub(crate) stats: bool,
+    pub(crate) type_summary: bool,
+    pub(crate) sankey: Option<String>,
+    pub(crate) clusters: bool,
+    pub(crate) novel: bool,
+    pub(crate) nomenclature: bool,
+    pub(crate) type_material: bool,
+    pub(crate) release: Option<String>,
+    pub(crate) fail_fast: bool,
 }
 
 impl TaxonArgs {
@@ -49,6 +67,74 @@ impl TaxonArgs {
         self.reps_only
     }
 
+    pub fn is_count(&self) -> bool {
+        self.count
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    pub fn is_detail(&self) -> bool {
+        self.detail
+    }
+
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    pub fn is_children(&self) -> bool {
+        self.children
+    }
+
+    pub fn is_lineage(&self) -> bool {
+        self.lineage
+    }
+
+    pub fn is_ranks(&self) -> bool {
+        self.ranks
+    }
+
+    pub fn is_history(&self) -> bool {
+        self.history
+    }
+
+    pub fn is_stats(&self) -> bool {
+        self.stats
+    }
+
+    pub fn is_type_summary(&self) -> bool {
+        self.type_summary
+    }
+
+    pub fn is_clusters(&self) -> bool {
+        self.clusters
+    }
+
+    pub fn is_novel(&self) -> bool {
+        self.novel
+    }
+
+    pub fn is_nomenclature(&self) -> bool {
+        self.nomenclature
+    }
+
+    pub fn get_sankey(&self) -> Option<String> {
+        self.sankey.clone()
+    }
+
+    pub fn is_type_material(&self) -> bool {
+        self.type_material
+    }
+
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    pub fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
     pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
         let mut names = Vec::new();
 
@@ -77,6 +163,30 @@ impl TaxonArgs {
             genomes: arg_matches.get_flag("genomes"),
             reps_only: arg_matches.get_flag("reps"),
             disable_certificate_verification: arg_matches.get_flag("insecure"),
+            count: arg_matches.get_flag("count"),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            detail: arg_matches.get_flag("detail"),
+            jobs: *arg_matches
+                .get_one::<usize>("jobs")
+                .expect("jobs has a default value"),
+            children: arg_matches.get_flag("children"),
+            lineage: arg_matches.get_flag("lineage"),
+            ranks: arg_matches.get_flag("ranks"),
+            history: arg_matches.get_flag("history"),
+            stats: arg_matches.get_flag("stats"),
+            type_summary: arg_matches.get_flag("type-summary"),
+            sankey: arg_matches.get_one::<String>("sankey").cloned(),
+            clusters: arg_matches.get_flag("clusters"),
+            novel: arg_matches.get_flag("novel"),
+            nomenclature: arg_matches.get_flag("nomenclature"),
+            type_material: arg_matches.get_flag("type-material"),
+            release: arg_matches.get_one::<String>("release").cloned(),
+            fail_fast: arg_matches.get_flag("fail-fast"),
         }
     }
 }
@@ -98,6 +208,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert_eq!(args.get_name(), vec!["name1", "name2"]);
@@ -114,6 +225,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert_eq!(args.is_whole_words_matching(), true);
@@ -130,6 +242,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert_eq!(args.is_search(), true);
@@ -175,4 +288,179 @@ mod tests {
         assert!(args.is_search());
         assert_eq!(args.get_output(), Some("out".to_string()));
     }
+
+    #[test]
+    fn test_taxon_genomes_outfmt_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--genomes",
+            "--count",
+            "--outfmt",
+            "tsv",
+        ]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_genome());
+        assert!(args.is_count());
+        assert_eq!(args.get_outfmt(), OutputFormat::Tsv);
+        assert!(!args.is_detail());
+        assert_eq!(args.get_jobs(), 4);
+    }
+
+    #[test]
+    fn test_taxon_genomes_detail_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--genomes",
+            "--detail",
+            "-j",
+            "8",
+        ]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_detail());
+        assert_eq!(args.get_jobs(), 8);
+        assert!(!args.is_type_material());
+    }
+
+    #[test]
+    fn test_taxon_genomes_type_material_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--genomes",
+            "--reps",
+            "--type-material",
+        ]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_reps_only());
+        assert!(args.is_type_material());
+    }
+
+    #[test]
+    fn test_taxon_children_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "taxon", "g__Aminobacter", "--children"]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_children());
+        assert!(!args.is_genome());
+    }
+
+    #[test]
+    fn test_taxon_lineage_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--lineage",
+            "--ranks",
+        ]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_lineage());
+        assert!(args.is_ranks());
+        assert!(!args.is_history());
+        assert!(!args.is_stats());
+    }
+
+    #[test]
+    fn test_taxon_type_summary_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--type-summary",
+            "--outfmt",
+            "tsv",
+        ]);
+
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+
+        assert!(args.is_type_summary());
+        assert!(!args.is_stats());
+        assert_eq!(args.get_outfmt(), OutputFormat::Tsv);
+    }
+
+    #[test]
+    fn test_taxon_sankey_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--sankey=mermaid",
+        ]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert_eq!(args.get_sankey(), Some("mermaid".to_string()));
+
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "taxon", "g__Aminobacter", "--sankey"]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert_eq!(args.get_sankey(), Some("json".to_string()));
+    }
+
+    #[test]
+    fn test_taxon_clusters_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--clusters",
+            "--outfmt",
+            "table",
+        ]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert!(args.is_clusters());
+        assert!(!args.is_type_summary());
+        assert_eq!(args.get_outfmt(), OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_taxon_novel_from_args() {
+        let matches =
+            app::build_app().get_matches_from(vec!["xgt", "taxon", "f__Rhizobiaceae", "--novel"]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert!(args.is_novel());
+        assert!(!args.is_clusters());
+    }
+
+    #[test]
+    fn test_taxon_nomenclature_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Azorhizobium",
+            "--nomenclature",
+            "-O",
+            "tsv",
+        ]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert!(args.is_nomenclature());
+        assert!(!args.is_novel());
+        assert_eq!(args.get_outfmt(), OutputFormat::Tsv);
+    }
+
+    #[test]
+    fn test_taxon_fail_fast_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "--file",
+            "test/test.txt",
+            "--fail-fast",
+        ]);
+        let args = TaxonArgs::from_arg_matches(matches.subcommand_matches("taxon").unwrap());
+        assert!(args.is_fail_fast());
+    }
 }
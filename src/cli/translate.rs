@@ -0,0 +1,106 @@
+use crate::utils::OutputFormat;
+use clap::ArgMatches;
+
+/// Taxonomies a taxon name can be translated to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetTaxonomy {
+    // NCBI taxonomy
+    #[default]
+    Ncbi,
+    // GTDB taxonomy
+    Gtdb,
+}
+
+impl From<String> for TargetTaxonomy {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "gtdb" => TargetTaxonomy::Gtdb,
+            _ => TargetTaxonomy::Ncbi,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Translate subcmd arguments.
+pub struct TranslateArgs {
+    // Taxon name to translate
+    pub(crate) name: String,
+    // Taxonomy to translate the name to
+    pub(crate) to: TargetTaxonomy,
+    // Output format
+    pub(crate) outfmt: OutputFormat,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl TranslateArgs {
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_to(&self) -> TargetTaxonomy {
+        self.to
+    }
+
+    pub fn get_outfmt(&self) -> OutputFormat {
+        self.outfmt.clone()
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        TranslateArgs {
+            name: arg_matches
+                .get_one::<String>("NAME")
+                .expect("NAME is required")
+                .to_string(),
+            to: TargetTaxonomy::from(
+                arg_matches
+                    .get_one::<String>("to")
+                    .expect("to has a default value")
+                    .to_string(),
+            ),
+            outfmt: OutputFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_translate_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "translate",
+            "g__Shigella",
+            "--to",
+            "gtdb",
+            "-O",
+            "table",
+        ]);
+        let args =
+            TranslateArgs::from_arg_matches(matches.subcommand_matches("translate").unwrap());
+        assert_eq!(args.get_name(), "g__Shigella".to_string());
+        assert_eq!(args.get_to(), TargetTaxonomy::Gtdb);
+        assert_eq!(args.get_outfmt(), OutputFormat::Table);
+        assert!(!args.get_disable_certificate_verification());
+    }
+}
@@ -0,0 +1,103 @@
+use clap::ArgMatches;
+
+/// Output formats of a taxon subtree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeFormat {
+    // Indented ASCII art
+    #[default]
+    Ascii,
+    // Graphviz DOT graph
+    Dot,
+}
+
+impl From<String> for TreeFormat {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "dot" => TreeFormat::Dot,
+            _ => TreeFormat::Ascii,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Tree subcmd arguments.
+pub struct TreeArgs {
+    // Root taxon of the tree
+    pub(crate) name: String,
+    // Maximum number of levels below the root
+    pub(crate) depth: usize,
+    // Output format
+    pub(crate) outfmt: TreeFormat,
+    // Output file
+    pub(crate) output: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl TreeArgs {
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn get_outfmt(&self) -> TreeFormat {
+        self.outfmt
+    }
+
+    pub fn get_output(&self) -> Option<String> {
+        self.output.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        TreeArgs {
+            name: arg_matches
+                .get_one::<String>("NAME")
+                .expect("NAME is required")
+                .to_string(),
+            depth: *arg_matches
+                .get_one::<usize>("depth")
+                .expect("depth has a default value"),
+            outfmt: TreeFormat::from(
+                arg_matches
+                    .get_one::<String>("outfmt")
+                    .expect("outfmt has a default value")
+                    .to_string(),
+            ),
+            output: arg_matches.get_one::<String>("out").map(String::from),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_tree_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "tree",
+            "f__Rhizobiaceae",
+            "--depth",
+            "3",
+            "--outfmt",
+            "dot",
+            "-k",
+        ]);
+        let args = TreeArgs::from_arg_matches(matches.subcommand_matches("tree").unwrap());
+        assert_eq!(args.get_name(), "f__Rhizobiaceae".to_string());
+        assert_eq!(args.get_depth(), 3);
+        assert_eq!(args.get_outfmt(), TreeFormat::Dot);
+        assert_eq!(args.get_output(), None);
+        assert!(args.get_disable_certificate_verification());
+    }
+}
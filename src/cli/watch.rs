@@ -0,0 +1,60 @@
+use clap::ArgMatches;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Watch subcmd arguments.
+pub struct WatchArgs {
+    // Delay between two checks, None to check once
+    pub(crate) interval: Option<Duration>,
+    // File storing the last release seen
+    pub(crate) state: Option<String>,
+    // Check SSL peer verification
+    pub(crate) disable_certificate_verification: bool,
+}
+
+impl WatchArgs {
+    pub fn get_interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    pub fn get_state(&self) -> Option<String> {
+        self.state.clone()
+    }
+
+    pub fn get_disable_certificate_verification(&self) -> bool {
+        self.disable_certificate_verification
+    }
+
+    pub fn from_arg_matches(arg_matches: &ArgMatches) -> Self {
+        WatchArgs {
+            interval: arg_matches.get_one::<Duration>("interval").copied(),
+            state: arg_matches.get_one::<String>("state").cloned(),
+            disable_certificate_verification: arg_matches.get_flag("insecure"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::app;
+
+    #[test]
+    fn test_watch_from_args() {
+        let matches = app::build_app().get_matches_from(vec![
+            "xgt",
+            "watch",
+            "--interval",
+            "24h",
+            "--state",
+            "release.txt",
+        ]);
+        let args = WatchArgs::from_arg_matches(matches.subcommand_matches("watch").unwrap());
+        assert_eq!(args.get_interval(), Some(Duration::from_secs(24 * 3600)));
+        assert_eq!(args.get_state(), Some("release.txt".to_string()));
+
+        let matches = app::build_app().get_matches_from(vec!["xgt", "watch"]);
+        let args = WatchArgs::from_arg_matches(matches.subcommand_matches("watch").unwrap());
+        assert_eq!(args.get_interval(), None);
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::cmd::genome::{fetch_genome_card, fetch_genome_history, GenomeCard, History};
+use crate::cmd::search::{fetch_search_results, SearchResult};
+use crate::cmd::taxon::{
+    fetch_taxon_children, fetch_taxon_genomes, fetch_taxon_history, Taxon, TaxonRelease,
+};
+use crate::error::XgtError;
+use crate::query::SearchQuery;
+use crate::transport::Transport;
+use crate::utils;
+
+/// Client of the GTDB API, sharing its connections between requests
+///
+/// ```no_run
+/// use xgt::query::SearchQuery;
+/// use xgt::GtdbClient;
+///
+/// let client = GtdbClient::new()?;
+/// for genome in client.search(&SearchQuery::taxon("g__Aminobacter"))? {
+///     println!("{:?}", genome.get_accession());
+/// }
+/// # Ok::<(), xgt::XgtError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct GtdbClient {
+    transport: Arc<dyn Transport>,
+}
+
+impl GtdbClient {
+    /// Client verifying the SSL certificate of the GTDB API
+    pub fn new() -> Result<Self, XgtError> {
+        Ok(GtdbClient::with_transport(utils::get_agent(false)?))
+    }
+
+    /// Client accepting invalid SSL certificates, as with `--insecure`
+    pub fn insecure() -> Result<Self, XgtError> {
+        Ok(GtdbClient::with_transport(utils::get_agent(true)?))
+    }
+
+    /// Client sending its requests through `transport`, e.g. a
+    /// `reqwest::blocking::Client` or a fake transport in tests
+    pub fn with_transport(transport: impl Transport + 'static) -> Self {
+        GtdbClient {
+            transport: Arc::new(transport),
+        }
+    }
+
+    /// Genomes matching a search query
+    pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, XgtError> {
+        Ok(fetch_search_results(self.transport.as_ref(), query)?.into_rows())
+    }
+
+    /// Card of a genome: taxonomy, quality and assembly statistics
+    pub fn genome_card(&self, accession: &str) -> Result<GenomeCard, XgtError> {
+        fetch_genome_card(self.transport.as_ref(), accession)
+    }
+
+    /// GTDB taxonomy of a genome in each release
+    pub fn genome_history(&self, accession: &str) -> Result<Vec<History>, XgtError> {
+        Ok(fetch_genome_history(self.transport.as_ref(), accession)?.data)
+    }
+
+    /// Direct children of a taxon, which are genomes for a species
+    pub fn taxon_children(&self, taxon: &str) -> Result<Vec<Taxon>, XgtError> {
+        Ok(fetch_taxon_children(self.transport.as_ref(), taxon)?.data)
+    }
+
+    /// Accessions of the genomes of a taxon, only species representatives
+    /// when `reps_only` is set
+    pub fn taxon_genomes(&self, taxon: &str, reps_only: bool) -> Result<Vec<String>, XgtError> {
+        Ok(fetch_taxon_genomes(self.transport.as_ref(), taxon, reps_only)?.data)
+    }
+
+    /// Names given to a taxon across releases, oldest first
+    pub fn taxon_history(&self, taxon: &str) -> Result<Vec<TaxonRelease>, XgtError> {
+        fetch_taxon_history(self.transport.as_ref(), taxon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::FakeTransport;
+
+    #[test]
+    fn test_client_new() {
+        assert!(GtdbClient::new().is_ok());
+        assert!(GtdbClient::insecure().is_ok());
+    }
+
+    #[test]
+    fn test_client_with_transport() {
+        let url = crate::api::taxon::TaxonAPI::new("g__Aminobacter").get_genomes_request(true);
+        let transport = FakeTransport::default().with(&url, 200, r#"["GCA_001512625.1"]"#);
+        let client = GtdbClient::with_transport(transport);
+
+        assert_eq!(
+            client.taxon_genomes("g__Aminobacter", true).unwrap(),
+            vec!["GCA_001512625.1"]
+        );
+        assert!(matches!(
+            client.taxon_genomes("g__Unknown", true),
+            Err(XgtError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{ensure, Result};
+use serde::Deserialize;
+use ureq::Agent;
+
+use crate::api::ani::{FastAniAPI, FastAniJobRequest, FastAniParameters};
+use crate::cli::ani::AniArgs;
+use crate::error::XgtError;
+use crate::utils;
+
+/// Response of the GTDB API to a FastANI job submission
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct FastAniJob {
+    #[serde(alias = "jobId")]
+    job_id: String,
+}
+
+/// Submit a FastANI job comparing every query to every reference
+/// genome and print its job ID
+pub fn ani(args: AniArgs) -> Result<()> {
+    ensure!(
+        !args.get_query().is_empty() && !args.get_reference().is_empty(),
+        "At least one query and one reference genome are required"
+    );
+
+    let request = FastAniJobRequest {
+        query: args.get_query(),
+        reference: args.get_reference(),
+        parameters: FastAniParameters {
+            kmer: args.get_kmer(),
+            frag_len: args.get_frag_len(),
+        },
+        email: args.get_email(),
+    };
+
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let response = agent
+        .post(&FastAniAPI::new().get_submit_request())
+        .send_json(&request)
+        .map_err(|e| XgtError::from_request(e, "FastANI job could not be submitted"))?;
+
+    let job: FastAniJob = response.into_json()?;
+    utils::write_to_output(format!("{}\n", job.job_id).as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fastani_job_response() {
+        let job: FastAniJob =
+            serde_json::from_str(r#"{"job_id": "a1b2c3", "group_1": [], "group_2": []}"#).unwrap();
+        assert_eq!(job.job_id, "a1b2c3");
+    }
+
+    #[test]
+    fn test_ani_requires_genomes() {
+        let args = AniArgs {
+            query: vec!["GCA_001512625.1".to_string()],
+            ..Default::default()
+        };
+        assert!(ani(args).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::app;
+use crate::cli::batch::BatchArgs;
+use crate::error::{self, XgtError};
+use crate::interrupt;
+use crate::utils;
+
+/// Number of jobs run in parallel when neither the manifest nor the
+/// command line set it
+const DEFAULT_JOBS: usize = 4;
+
+/// Subcommands which are interactive, long-running, do not write to
+/// `--out` or exit with their own status code, and so cannot be batch jobs
+const NON_BATCH_SUBCOMMANDS: [&str; 7] = [
+    "batch", "browse", "exists", "open", "serve", "shell", "watch",
+];
+
+/// Global options which apply to the whole process, and so to every job
+/// running at the same time: they are only set on the batch command line
+const BATCH_OPTIONS: [&str; 7] = [
+    "verbose",
+    "errors",
+    "na-string",
+    "sort",
+    "provenance",
+    "compact",
+    "request-stats",
+];
+
+/// A TOML batch manifest
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct BatchManifest {
+    jobs: Option<usize>,
+    #[serde(rename = "job", default)]
+    job: Vec<Job>,
+}
+
+/// A xgt command line and the file its output is written to
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    name: String,
+    args: Vec<String>,
+    output: String,
+}
+
+/// Outcome of a job
+#[derive(Debug, Clone, PartialEq)]
+enum JobStatus {
+    Done,
+    // Output was already present, e.g. from a previous run
+    Skipped,
+    // Its partial output being removed, as for interrupted jobs
+    Failed(String),
+    // Not run or stopped by Ctrl-C, its partial output being removed
+    Interrupted,
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Done => write!(f, "done"),
+            JobStatus::Skipped => write!(f, "skipped"),
+            JobStatus::Failed(reason) => write!(f, "failed: {}", reason),
+            JobStatus::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+fn read_manifest(path: &str) -> Result<BatchManifest> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read manifest {}", path))?;
+    let manifest: BatchManifest =
+        toml::from_str(&content).with_context(|| format!("Invalid manifest {}", path))?;
+
+    let mut names = HashSet::new();
+    let mut outputs = HashSet::new();
+    for job in &manifest.job {
+        ensure!(names.insert(&job.name), "Duplicate job name {}", job.name);
+        ensure!(
+            outputs.insert(&job.output),
+            "Duplicate job output {}",
+            job.output
+        );
+    }
+    Ok(manifest)
+}
+
+/// Parse the command line of a job, its output added as `--out`
+fn job_matches(job: &Job) -> Result<ArgMatches> {
+    let subcommand = job.args.first().context("Empty args")?;
+    ensure!(
+        !NON_BATCH_SUBCOMMANDS.contains(&subcommand.as_str()),
+        "{} cannot be run as a batch job",
+        subcommand
+    );
+
+    let args = std::iter::once("xgt")
+        .chain(job.args.iter().map(String::as_str))
+        .chain(["--out", job.output.as_str()]);
+    let matches = app::build_app().try_get_matches_from(args).map_err(|e| {
+        let message = e.to_string();
+        let first_line = message.lines().next().unwrap_or_default();
+        anyhow!("{}", first_line.trim_start_matches("error: "))
+    })?;
+    if let Some(option) = BATCH_OPTIONS
+        .iter()
+        .find(|x| matches.value_source(x) == Some(ValueSource::CommandLine))
+    {
+        bail!(
+            "--{} applies to every job, set it on the batch command line",
+            option
+        );
+    }
+    Ok(matches)
+}
+
+/// Status of a job which was run
+fn job_status(result: Result<()>) -> JobStatus {
+    match result {
+        Ok(()) => JobStatus::Done,
+        Err(e) if error::is_interrupted(&e) => JobStatus::Interrupted,
+        Err(e) => JobStatus::Failed(format!("{:#}", e).replace('\n', " ")),
+    }
+}
+
+/// Path of the results manifest written next to the batch manifest
+fn results_path(manifest: &str) -> PathBuf {
+    Path::new(manifest).with_extension("results.tsv")
+}
+
+pub fn batch(args: BatchArgs, run: fn(&ArgMatches) -> Result<()>) -> Result<()> {
+    let manifest = read_manifest(&args.get_manifest())?;
+
+    // Every job is checked before any is run; a job whose output exists
+    // is skipped, making reruns idempotent
+    let mut planned = Vec::new();
+    let mut invalid = Vec::new();
+    for job in manifest.job {
+        if Path::new(&job.output).exists() {
+            planned.push((job, None));
+            continue;
+        }
+        match job_matches(&job) {
+            Ok(matches) => planned.push((job, Some(matches))),
+            Err(e) => invalid.push(format!("{}: {}", job.name, e)),
+        }
+    }
+    ensure!(
+        invalid.is_empty(),
+        "Invalid job(s) in {}:\n{}",
+        args.get_manifest(),
+        invalid.join("\n")
+    );
+
+    let jobs = args.get_jobs().or(manifest.jobs).unwrap_or(DEFAULT_JOBS);
+    // After Ctrl-C, jobs not started yet are not run. The partial output of
+    // the jobs which failed or were interrupted is removed, so that a rerun
+    // runs them again instead of skipping them.
+    let results = utils::parallel_map(planned, jobs, |(job, matches)| {
+        let status = match matches {
+            None => JobStatus::Skipped,
+            Some(_) if interrupt::is_interrupted() => JobStatus::Interrupted,
+            Some(matches) => job_status(run(&matches)),
+        };
+        if matches!(status, JobStatus::Failed(_) | JobStatus::Interrupted) {
+            let _ = fs::remove_file(&job.output);
+        }
+        (job, status)
+    });
+
+    let mut rows = vec!["name\tstatus\toutput".to_string()];
+    rows.extend(
+        results
+            .iter()
+            .map(|(job, status)| format!("{}\t{}\t{}", job.name, status, job.output)),
+    );
+    rows.push(String::new());
+    let path = results_path(&args.get_manifest());
+    fs::write(&path, rows.join("\n"))
+        .with_context(|| format!("Failed to write to {}", path.display()))?;
+
+    let interrupted = results
+        .iter()
+        .filter(|(_, status)| *status == JobStatus::Interrupted)
+        .count();
+    if interrupted > 0 {
+        return Err(XgtError::Interrupted).with_context(|| {
+            format!(
+                "{} job(s) interrupted, run the manifest again to complete them, see {}",
+                interrupted,
+                path.display()
+            )
+        });
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, status)| matches!(status, JobStatus::Failed(_)))
+        .count();
+    ensure!(
+        failed == 0,
+        "{} job(s) failed, see {}",
+        failed,
+        path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(name: &str, args: &[&str], output: &str) -> Job {
+        Job {
+            name: name.to_string(),
+            args: args.iter().map(|x| x.to_string()).collect(),
+            output: output.to_string(),
+        }
+    }
+
+    /// Write the subcommand name to the output, failing for taxon jobs
+    /// after a partial output
+    fn fake_run(matches: &ArgMatches) -> Result<()> {
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        fs::write(sub_matches.get_one::<String>("out").unwrap(), name)?;
+        ensure!(name != "taxon", "Taxon g__Unknown not found");
+        Ok(())
+    }
+
+    #[test]
+    fn test_job_matches() {
+        let matches = job_matches(&job("ecoli", &["genome", "GCA_001512625.1"], "ecoli.json"));
+        let matches = matches.unwrap();
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "genome");
+        assert_eq!(
+            sub_matches.get_one::<String>("out"),
+            Some(&"ecoli.json".to_string())
+        );
+
+        let err = job_matches(&job("shell", &["shell"], "out.txt")).unwrap_err();
+        assert_eq!(err.to_string(), "shell cannot be run as a batch job");
+        let err = job_matches(&job("bogus", &["genome", "--bogus"], "out.txt")).unwrap_err();
+        assert!(err.to_string().starts_with("unexpected argument '--bogus'"));
+        let err = job_matches(&job("sorted", &["search", "x", "--sort", "gid"], "out.txt"));
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "--sort applies to every job, set it on the batch command line"
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let dir = std::env::temp_dir().join(format!("xgt-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let out = |name: &str| dir.join(name).display().to_string();
+        fs::write(out("skipped.csv"), "").unwrap();
+        let manifest = out("manifest.toml");
+        fs::write(
+            &manifest,
+            format!(
+                r#"jobs = 2
+
+[[job]]
+name = "search"
+args = ["search", "g__Aminobacter"]
+output = "{}"
+
+[[job]]
+name = "skipped"
+args = ["search", "g__Escherichia"]
+output = "{}"
+
+[[job]]
+name = "taxon"
+args = ["taxon", "g__Unknown"]
+output = "{}"
+"#,
+                out("search.csv"),
+                out("skipped.csv"),
+                out("taxon.json")
+            ),
+        )
+        .unwrap();
+
+        let args = BatchArgs {
+            manifest: manifest.clone(),
+            jobs: None,
+        };
+        let err = batch(args, fake_run).unwrap_err();
+        assert!(err.to_string().starts_with("1 job(s) failed"));
+        assert_eq!(fs::read_to_string(out("search.csv")).unwrap(), "search");
+        assert!(!Path::new(&out("taxon.json")).exists());
+        assert_eq!(
+            fs::read_to_string(out("manifest.results.tsv")).unwrap(),
+            format!(
+                "name\tstatus\toutput\nsearch\tdone\t{}\nskipped\tskipped\t{}\ntaxon\tfailed: Taxon g__Unknown not found\t{}\n",
+                out("search.csv"),
+                out("skipped.csv"),
+                out("taxon.json")
+            )
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_job_status() {
+        assert_eq!(job_status(Ok(())), JobStatus::Done);
+        let interrupted = Err(XgtError::Interrupted).context("Failed to fetch genome cards");
+        assert_eq!(job_status(interrupted), JobStatus::Interrupted);
+        let failed = Err(anyhow!("Taxon g__Unknown\nnot found"));
+        assert_eq!(
+            job_status(failed),
+            JobStatus::Failed("Taxon g__Unknown not found".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read_manifest_duplicate_names() {
+        let path = std::env::temp_dir().join(format!("xgt-batch-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "[[job]]\nname = \"a\"\nargs = [\"search\", \"x\"]\noutput = \"a\"\n\
+             [[job]]\nname = \"a\"\nargs = [\"search\", \"y\"]\noutput = \"b\"\n",
+        )
+        .unwrap();
+        let err = read_manifest(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate job name a");
+
+        fs::write(
+            &path,
+            "[[job]]\nname = \"a\"\nargs = [\"search\", \"x\"]\noutput = \"a\"\n\
+             [[job]]\nname = \"b\"\nargs = [\"search\", \"y\"]\noutput = \"a\"\n",
+        )
+        .unwrap();
+        let err = read_manifest(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate job output a");
+        fs::remove_file(path).unwrap();
+    }
+}
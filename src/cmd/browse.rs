@@ -0,0 +1,497 @@
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::time::Duration;
+use ureq::Agent;
+
+use crate::api::taxon::TaxonAPI;
+use crate::cli::browse::BrowseArgs;
+use crate::cmd::genome::{card_summary, fetch_genome_card};
+use crate::cmd::taxon::{fetch_taxon_children, fetch_taxon_search};
+use crate::utils;
+
+/// Time without key press after which the typed query is searched
+const TYPE_AHEAD_DELAY: Duration = Duration::from_millis(300);
+
+/// Minimum number of characters of a query searched while typing
+const TYPE_AHEAD_MIN_LENGTH: usize = 3;
+
+const HELP: &str = "/ search  enter open  ← back  space mark  e export  q quit";
+
+/// A taxon or genome of the list pane
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    name: String,
+    // Unknown for search matches
+    genomes: Option<u64>,
+    is_genome: bool,
+}
+
+/// Pane receiving the key presses
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Focus {
+    #[default]
+    Search,
+    List,
+}
+
+/// Request to the GTDB API, or other side effect, triggered by a key
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Search(String),
+    Children(String),
+    Card(String),
+    Export,
+    Quit,
+}
+
+/// State of the browser, independent of the terminal and of the API
+#[derive(Debug, Clone, Default)]
+struct Browser {
+    focus: Focus,
+    query: String,
+    // Query edited since it was last searched
+    pending_search: bool,
+    // Taxa matching the last search
+    matches: Vec<Entry>,
+    // Taxa drilled into, from the search match
+    path: Vec<String>,
+    entries: Vec<Entry>,
+    selected: usize,
+    card: Option<(String, Vec<(&'static str, String)>)>,
+    marked: BTreeSet<String>,
+    status: String,
+}
+
+impl Browser {
+    fn selected_entry(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+        match self.focus {
+            Focus::Search => self.handle_search_key(key),
+            Focus::List => self.handle_list_key(key),
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.pending_search = true;
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.pending_search = true;
+            }
+            KeyCode::Enter => {
+                self.focus = Focus::List;
+                if self.pending_search && !self.query.is_empty() {
+                    self.pending_search = false;
+                    return Some(Action::Search(self.query.clone()));
+                }
+            }
+            KeyCode::Down | KeyCode::Tab => self.focus = Focus::List,
+            KeyCode::Esc => return Some(Action::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1))
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                let entry = self.selected_entry()?;
+                return Some(match entry.is_genome {
+                    true => Action::Card(entry.name.clone()),
+                    false => Action::Children(entry.name.clone()),
+                });
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => match self.path.len() {
+                0 => {}
+                1 => {
+                    self.path.clear();
+                    self.entries = self.matches.clone();
+                    self.selected = 0;
+                }
+                n => return Some(Action::Children(self.path[n - 2].clone())),
+            },
+            KeyCode::Char(' ') => {
+                let entry = self.selected_entry()?.clone();
+                if !entry.is_genome {
+                    self.status = "Only genomes can be marked".to_string();
+                } else if !self.marked.remove(&entry.name) {
+                    self.marked.insert(entry.name);
+                }
+            }
+            KeyCode::Char('e') => return Some(Action::Export),
+            KeyCode::Char('/') => self.focus = Focus::Search,
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// Search the query once typing paused
+    fn tick(&mut self) -> Option<Action> {
+        if self.focus == Focus::Search
+            && self.pending_search
+            && self.query.chars().count() >= TYPE_AHEAD_MIN_LENGTH
+        {
+            self.pending_search = false;
+            return Some(Action::Search(self.query.clone()));
+        }
+        None
+    }
+
+    fn show_matches(&mut self, names: Vec<String>) {
+        self.matches = names
+            .into_iter()
+            .map(|name| Entry {
+                name,
+                genomes: None,
+                is_genome: false,
+            })
+            .collect();
+        self.entries = self.matches.clone();
+        self.path.clear();
+        self.selected = 0;
+        self.status = format!("{} matching taxa", self.matches.len());
+    }
+
+    /// Show the children of `taxon`, either deeper in the current path or
+    /// one of its ancestors
+    fn show_children(&mut self, taxon: &str, entries: Vec<Entry>) {
+        match self.path.iter().position(|x| x == taxon) {
+            Some(i) => self.path.truncate(i + 1),
+            None => self.path.push(taxon.to_string()),
+        }
+        self.entries = entries;
+        self.selected = 0;
+        self.status.clear();
+    }
+
+    fn entry_label(&self, entry: &Entry) -> String {
+        match (entry.is_genome, entry.genomes) {
+            (true, _) => {
+                let mark = if self.marked.contains(&entry.name) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                format!("{} {}", mark, entry.name)
+            }
+            (false, Some(genomes)) => format!("{} ({})", entry.name, genomes),
+            (false, None) => entry.name.clone(),
+        }
+    }
+}
+
+fn focus_style(is_focused: bool) -> Style {
+    match is_focused {
+        true => Style::default().add_modifier(Modifier::BOLD),
+        false => Style::default().add_modifier(Modifier::DIM),
+    }
+}
+
+fn render(frame: &mut Frame, browser: &Browser) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    let search = Paragraph::new(browser.query.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Search taxon")
+            .border_style(focus_style(browser.focus == Focus::Search)),
+    );
+    frame.render_widget(search, rows[0]);
+    if browser.focus == Focus::Search {
+        frame.set_cursor(
+            rows[0].x + 1 + browser.query.chars().count() as u16,
+            rows[0].y + 1,
+        );
+    }
+
+    let title = match browser.path.is_empty() {
+        true => "Matches".to_string(),
+        false => browser.path.join(" > "),
+    };
+    let items: Vec<ListItem> = browser
+        .entries
+        .iter()
+        .map(|entry| ListItem::new(browser.entry_label(entry)))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(focus_style(browser.focus == Focus::List)),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    if !browser.entries.is_empty() {
+        state.select(Some(browser.selected));
+    }
+    frame.render_stateful_widget(list, panes[0], &mut state);
+
+    let (card_title, fields) = match &browser.card {
+        Some((accession, fields)) => (accession.as_str(), fields.as_slice()),
+        None => ("Genome card", [].as_slice()),
+    };
+    let lines: Vec<Line> = fields
+        .iter()
+        .map(|(name, value)| {
+            Line::from(vec![
+                Span::styled(*name, Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(": "),
+                Span::raw(value.as_str()),
+            ])
+        })
+        .collect();
+    let card = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(card_title));
+    frame.render_widget(card, panes[1]);
+
+    let status = format!(
+        "{} marked  {}  {}",
+        browser.marked.len(),
+        HELP,
+        browser.status
+    );
+    frame.render_widget(Paragraph::new(status), rows[2]);
+}
+
+/// Perform the API request or side effect of an action on the browser
+fn perform(browser: &mut Browser, agent: &Agent, action: Action, output: &str) -> Result<()> {
+    match action {
+        Action::Search(query) => {
+            let request_url = TaxonAPI::new(query.as_str()).get_search_request();
+            let result = fetch_taxon_search(agent, &request_url, &query)?;
+            browser.show_matches(result.matches);
+        }
+        Action::Children(taxon) => {
+            let entries = fetch_taxon_children(agent, &taxon)?
+                .data
+                .into_iter()
+                .map(|child| Entry {
+                    name: child.get_taxon(),
+                    genomes: Some(child.get_genome_count()),
+                    is_genome: child.is_genome(),
+                })
+                .collect();
+            browser.show_children(&taxon, entries);
+        }
+        Action::Card(accession) => {
+            let card = fetch_genome_card(agent, &accession)?;
+            browser.card = Some((accession, card_summary(&card)));
+        }
+        Action::Export => {
+            let mut content: Vec<&str> = browser.marked.iter().map(String::as_str).collect();
+            content.push("");
+            fs::write(output, content.join("\n"))?;
+            browser.status = format!("Exported {} genomes to {}", browser.marked.len(), output);
+        }
+        Action::Quit => {}
+    }
+    Ok(())
+}
+
+fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    browser: &mut Browser,
+    agent: &Agent,
+    output: &str,
+) -> Result<()> {
+    loop {
+        terminal.draw(|frame| render(frame, browser))?;
+        let action = if event::poll(TYPE_AHEAD_DELAY)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => browser.handle_key(key),
+                _ => None,
+            }
+        } else {
+            browser.tick()
+        };
+        match action {
+            Some(Action::Quit) => return Ok(()),
+            Some(action) => {
+                if let Err(e) = perform(browser, agent, action, output) {
+                    browser.status = e.to_string();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+pub fn browse(args: BrowseArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let output = args.get_output();
+
+    let mut browser = Browser::default();
+    if let Some(name) = args.get_name() {
+        perform(&mut browser, &agent, Action::Children(name), &output)?;
+        browser.focus = Focus::List;
+    }
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result = run(&mut terminal, &mut browser, &agent, &output);
+
+    // Restore the terminal even when browsing failed
+    terminal::disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn entry(name: &str, genomes: u64, is_genome: bool) -> Entry {
+        Entry {
+            name: name.to_string(),
+            genomes: Some(genomes),
+            is_genome,
+        }
+    }
+
+    #[test]
+    fn test_browser_type_ahead() {
+        let mut browser = Browser::default();
+        browser.handle_key(key(KeyCode::Char('g')));
+        browser.handle_key(key(KeyCode::Char('_')));
+        assert_eq!(browser.tick(), None);
+
+        browser.handle_key(key(KeyCode::Char('_')));
+        assert_eq!(browser.tick(), Some(Action::Search("g__".to_string())));
+        assert_eq!(browser.tick(), None);
+
+        browser.handle_key(key(KeyCode::Char('A')));
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Enter)),
+            Some(Action::Search("g__A".to_string()))
+        );
+        assert_eq!(browser.focus, Focus::List);
+    }
+
+    #[test]
+    fn test_browser_navigation() {
+        let mut browser = Browser {
+            focus: Focus::List,
+            ..Default::default()
+        };
+        browser.show_matches(vec!["g__Aminobacter".to_string()]);
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Enter)),
+            Some(Action::Children("g__Aminobacter".to_string()))
+        );
+
+        browser.show_children(
+            "g__Aminobacter",
+            vec![entry("s__Aminobacter aminovorans", 9, false)],
+        );
+        browser.show_children(
+            "s__Aminobacter aminovorans",
+            vec![
+                entry("GCF_000005845.2", 1, true),
+                entry("GCA_001512625.1", 1, true),
+            ],
+        );
+        assert_eq!(
+            browser.path,
+            vec!["g__Aminobacter", "s__Aminobacter aminovorans"]
+        );
+
+        browser.handle_key(key(KeyCode::Down));
+        browser.handle_key(key(KeyCode::Down));
+        browser.handle_key(key(KeyCode::Char(' ')));
+        assert_eq!(
+            browser.entry_label(&browser.entries[1]),
+            "[x] GCA_001512625.1"
+        );
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Enter)),
+            Some(Action::Card("GCA_001512625.1".to_string()))
+        );
+
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Left)),
+            Some(Action::Children("g__Aminobacter".to_string()))
+        );
+        browser.show_children(
+            "g__Aminobacter",
+            vec![entry("s__Aminobacter aminovorans", 9, false)],
+        );
+        assert_eq!(browser.path, vec!["g__Aminobacter"]);
+        assert_eq!(
+            browser.entry_label(&browser.entries[0]),
+            "s__Aminobacter aminovorans (9)"
+        );
+
+        assert_eq!(browser.handle_key(key(KeyCode::Left)), None);
+        assert!(browser.path.is_empty());
+        assert_eq!(browser.entry_label(&browser.entries[0]), "g__Aminobacter");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut browser = Browser::default();
+        browser.show_children("g__Aminobacter", vec![entry("GCA_001512625.1", 1, true)]);
+        browser.card = Some((
+            "GCA_001512625.1".to_string(),
+            vec![("GTDB taxonomy", "g__Aminobacter".to_string())],
+        ));
+
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|frame| render(frame, &browser)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("g__Aminobacter"));
+        assert!(screen.contains("[ ] GCA_001512625.1"));
+        assert!(screen.contains("GTDB taxonomy: g__Aminobacter"));
+    }
+}
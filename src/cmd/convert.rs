@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use crate::cli::convert::{ConvertArgs, LineageFormat};
+use crate::taxonomy::{format_lineage, lineage_from_names, parse_lineage, strip_rank_prefix, Rank};
+use crate::utils;
+
+/// A lineage read from a line of the input, with its optional ID
+#[derive(Debug, Clone, PartialEq)]
+struct LineageRecord {
+    id: Option<String>,
+    lineage: BTreeMap<Rank, String>,
+}
+
+/// Parse a line in `format`, None for the header of per-rank columns
+fn parse_record(line: &str, format: LineageFormat) -> Option<LineageRecord> {
+    let mut fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+    let id = match format {
+        LineageFormat::Lineage if fields.len() > 1 => Some(fields.remove(0)),
+        LineageFormat::Ranks if fields.len() > Rank::ALL.len() => Some(fields.remove(0)),
+        LineageFormat::List if Rank::from_taxon(fields[0]).is_none() => Some(fields.remove(0)),
+        _ => None,
+    };
+    let lineage = match format {
+        LineageFormat::Ranks => {
+            if fields.last().map(|x| x.to_lowercase()) == Some("species".to_string()) {
+                return None;
+            }
+            lineage_from_names(&fields)
+        }
+        LineageFormat::Lineage | LineageFormat::List => parse_lineage(&fields.join(";")),
+    };
+    Some(LineageRecord {
+        id: id.map(String::from),
+        lineage,
+    })
+}
+
+/// Format a record in `format`
+fn format_record(record: &LineageRecord, format: LineageFormat) -> String {
+    let lineage = match format {
+        LineageFormat::Lineage => format_lineage(&record.lineage),
+        LineageFormat::Ranks => Rank::ALL
+            .iter()
+            .map(|rank| {
+                record
+                    .lineage
+                    .get(rank)
+                    .map_or("", |x| strip_rank_prefix(x))
+            })
+            .collect::<Vec<&str>>()
+            .join("\t"),
+        LineageFormat::List => record
+            .lineage
+            .values()
+            .map(String::as_str)
+            .collect::<Vec<&str>>()
+            .join("\t"),
+    };
+    match &record.id {
+        Some(id) => format!("{}\t{}\n", id, lineage),
+        None => format!("{}\n", lineage),
+    }
+}
+
+/// Convert lines of lineages from one format to another
+fn convert_lines(lines: &[String], from: LineageFormat, to: LineageFormat) -> String {
+    let records: Vec<LineageRecord> = lines
+        .iter()
+        .filter(|x| !x.trim().is_empty())
+        .filter_map(|x| parse_record(x, from))
+        .collect();
+
+    let mut output = String::new();
+    if to == LineageFormat::Ranks {
+        let ranks: Vec<String> = Rank::ALL.iter().map(Rank::to_string).collect();
+        if records.iter().any(|x| x.id.is_some()) {
+            output.push_str("id\t");
+        }
+        output.push_str(&format!("{}\n", ranks.join("\t")));
+    }
+    for record in &records {
+        output.push_str(&format_record(record, to));
+    }
+    output
+}
+
+/// Convert lineages between greengenes-style strings, per-rank columns
+/// and lists of rank-prefixed taxa
+pub fn convert(args: ConvertArgs) -> Result<()> {
+    let lines = match args.get_input() {
+        Some(path) => {
+            let file = File::open(&path).with_context(|| format!("Failed to open {}", path))?;
+            BufReader::new(file)
+                .lines()
+                .collect::<io::Result<Vec<String>>>()
+        }
+        None => io::stdin().lock().lines().collect(),
+    }
+    .context("Failed to read input lineages")?;
+
+    let output = convert_lines(&lines, args.get_from(), args.get_to());
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let record = parse_record(
+            "GCF_1\td__Bacteria;p__Pseudomonadota",
+            LineageFormat::Lineage,
+        )
+        .unwrap();
+        assert_eq!(record.id, Some("GCF_1".to_string()));
+        assert_eq!(record.lineage.len(), 2);
+
+        let record = parse_record("Bacteria\t\tNA", LineageFormat::Ranks).unwrap();
+        assert_eq!(record.id, None);
+        assert_eq!(format_lineage(&record.lineage), "d__Bacteria");
+        assert_eq!(
+            parse_record(
+                "id\tdomain\tphylum\tclass\torder\tfamily\tgenus\tspecies",
+                LineageFormat::Ranks
+            ),
+            None
+        );
+
+        let record = parse_record("d__Bacteria\tp__Pseudomonadota", LineageFormat::List).unwrap();
+        assert_eq!(record.id, None);
+        assert_eq!(
+            record.lineage.get(&Rank::Phylum),
+            Some(&"p__Pseudomonadota".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_lines_round_trip() {
+        let lineages = "GCF_1\td__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__Enterobacterales; f__Enterobacteriaceae; g__Escherichia; s__Escherichia coli\n\
+                        GCF_2\td__Archaea; p__Thermoproteota\n";
+
+        let ranks = convert_lines(
+            &lines(lineages),
+            LineageFormat::Lineage,
+            LineageFormat::Ranks,
+        );
+        assert_eq!(
+            ranks,
+            "id\tdomain\tphylum\tclass\torder\tfamily\tgenus\tspecies\n\
+             GCF_1\tBacteria\tPseudomonadota\tGammaproteobacteria\tEnterobacterales\tEnterobacteriaceae\tEscherichia\tEscherichia coli\n\
+             GCF_2\tArchaea\tThermoproteota\t\t\t\t\t\n"
+        );
+
+        let list = convert_lines(&lines(&ranks), LineageFormat::Ranks, LineageFormat::List);
+        assert_eq!(
+            list.lines().nth(1),
+            Some("GCF_2\td__Archaea\tp__Thermoproteota")
+        );
+
+        let back = convert_lines(&lines(&list), LineageFormat::List, LineageFormat::Lineage);
+        assert_eq!(back, lineages);
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use ureq::Agent;
+
+use crate::cli::diff::DiffArgs;
+use crate::cmd::genome::{fetch_genome_histories, GenomeTaxonHistory, History};
+use crate::cmd::taxon::{fetch_taxon_genomes, fetch_taxon_history};
+use crate::error::XgtError;
+use crate::taxonomy::{release_number, Rank};
+use crate::utils;
+
+/// Placement of a genome in two releases: its child taxon under the
+/// compared taxon, or None when the genome was not in the taxon
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Placement {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// Get the taxonomy of a genome in `release`
+fn entry_in_release<'a>(history: &'a GenomeTaxonHistory, release: &str) -> Option<&'a History> {
+    let release = release_number(release);
+    history
+        .data
+        .iter()
+        .find(|x| x.get_release().as_deref().map(release_number) == Some(release))
+}
+
+/// Get the taxon of rank `rank` a genome belonged to in `release`
+fn taxon_in_release(history: &GenomeTaxonHistory, rank: Rank, release: &str) -> Option<String> {
+    entry_in_release(history, release)?.get_taxon(rank)
+}
+
+/// Get the child taxon of `name` a genome belonged to in `release`.
+/// Species have no child rank, so the species itself is used.
+fn child_in_release(
+    history: &GenomeTaxonHistory,
+    name: &str,
+    rank: Rank,
+    release: &str,
+) -> Option<String> {
+    let entry = entry_in_release(history, release)?;
+    if entry.get_taxon(rank).as_deref() != Some(name) {
+        return None;
+    }
+    match rank.child() {
+        Some(child) => entry.get_taxon(child),
+        None => Some(name.to_string()),
+    }
+}
+
+/// Name of the taxon in `release`: the taxon of rank `rank` most of its
+/// current genomes belonged to, so that renamed taxa are followed
+fn name_in_release<'a>(
+    histories: impl IntoIterator<Item = &'a GenomeTaxonHistory>,
+    rank: Rank,
+    release: &str,
+) -> Option<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for history in histories {
+        if let Some(taxon) = taxon_in_release(history, rank, release) {
+            *counts.entry(taxon).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(taxon, _)| taxon)
+}
+
+/// Report the renaming of the taxon, genomes added to and removed from it,
+/// and child taxa renamed between the two releases, as rows of change,
+/// name, from, to, genomes
+fn diff_rows(
+    name: &str,
+    names: (&str, &str),
+    placements: &BTreeMap<String, Placement>,
+) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    if names.0 != names.1 {
+        let genomes = placements
+            .values()
+            .filter(|x| x.from.is_some() && x.to.is_some())
+            .count();
+        rows.push(vec![
+            "taxon_renamed".to_string(),
+            name.to_string(),
+            names.0.to_string(),
+            names.1.to_string(),
+            genomes.to_string(),
+        ]);
+    }
+    let mut renamed: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+    for (accession, placement) in placements {
+        match (&placement.from, &placement.to) {
+            (None, Some(to)) => rows.push(vec![
+                "genome_added".to_string(),
+                accession.clone(),
+                String::new(),
+                to.clone(),
+                "1".to_string(),
+            ]),
+            (Some(from), None) => rows.push(vec![
+                "genome_removed".to_string(),
+                accession.clone(),
+                from.clone(),
+                String::new(),
+                "1".to_string(),
+            ]),
+            (Some(from), Some(to)) if from != to => {
+                *renamed.entry((from.clone(), to.clone())).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+
+    for ((from, to), genomes) in renamed {
+        rows.push(vec![
+            "child_renamed".to_string(),
+            name.to_string(),
+            from,
+            to,
+            genomes.to_string(),
+        ]);
+    }
+    rows
+}
+
+/// Accessions of the genomes of `name`, none if it is not a taxon of the
+/// current release
+fn current_genomes(agent: &Agent, name: &str) -> Result<Vec<String>> {
+    match fetch_taxon_genomes(agent, name, false) {
+        Ok(genomes) => Ok(genomes.data),
+        Err(XgtError::NotFound(_)) => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn diff(args: DiffArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let name = args.get_name();
+    let rank = Rank::from_taxon(&name).context("Taxon name must be in greengenes format")?;
+
+    // Release-specific membership is only available through the taxon
+    // history of each genome. Genomes which left the taxon are now in the
+    // taxa given the same genomes, listed by the history of the taxon name.
+    let members = fetch_taxon_genomes(&agent, &name, false)?.data;
+    let others: BTreeSet<String> = match fetch_taxon_history(&agent, &name) {
+        Ok(releases) => releases.iter().map(|x| x.get_taxon()).collect(),
+        Err(XgtError::NotFound(_)) => BTreeSet::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut candidates: BTreeSet<String> = members.iter().cloned().collect();
+    for other in others.iter().filter(|x| **x != name) {
+        candidates.extend(current_genomes(&agent, other)?);
+    }
+    let candidates: Vec<String> = candidates.into_iter().collect();
+    let histories = fetch_genome_histories(
+        candidates.clone(),
+        args.get_jobs(),
+        args.get_disable_certificate_verification(),
+    )?;
+    let histories = candidates
+        .into_iter()
+        .zip(histories)
+        .map(|(accession, history)| Ok((accession, history?)))
+        .collect::<Result<BTreeMap<String, GenomeTaxonHistory>>>()?;
+
+    let current = members.iter().filter_map(|x| histories.get(x));
+    let from_name =
+        name_in_release(current.clone(), rank, &args.get_from()).unwrap_or_else(|| name.clone());
+    let to_name = name_in_release(current, rank, &args.get_to()).unwrap_or_else(|| name.clone());
+
+    let placements: BTreeMap<String, Placement> = histories
+        .iter()
+        .map(|(accession, history)| {
+            let placement = Placement {
+                from: child_in_release(history, &from_name, rank, &args.get_from()),
+                to: child_in_release(history, &to_name, rank, &args.get_to()),
+            };
+            (accession.clone(), placement)
+        })
+        .filter(|(_, x)| x.from.is_some() || x.to.is_some())
+        .collect();
+
+    let header = ["change", "name", "from", "to", "genomes"];
+    let rows = diff_rows(&name, (&from_name, &to_name), &placements);
+    let table = utils::render_table(&header, &rows, &args.get_outfmt());
+    utils::write_to_output(table.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> GenomeTaxonHistory {
+        serde_json::from_str(
+            r#"[
+                {"release": "R220", "d": "d__Bacteria", "p": "p__Pseudomonadota",
+                 "c": "c__Alphaproteobacteria", "o": "o__Rhizobiales", "f": "f__Rhizobiaceae",
+                 "g": "g__Aminobacter", "s": "s__Aminobacter niigataensis"},
+                {"release": "R207", "d": "d__Bacteria", "p": "p__Proteobacteria",
+                 "c": "c__Alphaproteobacteria", "o": "o__Rhizobiales", "f": "f__Rhizobiaceae",
+                 "g": "g__Aminobacter", "s": "s__Aminobacter sp000000001"},
+                {"release": "R95", "d": "d__Bacteria", "p": "p__Proteobacteria",
+                 "c": "c__Alphaproteobacteria", "o": "o__Rhizobiales", "f": "f__Rhizobiaceae",
+                 "g": "g__Mesorhizobium", "s": "s__Mesorhizobium sp000000001"}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_child_in_release() {
+        let history = history();
+        assert_eq!(
+            child_in_release(&history, "g__Aminobacter", Rank::Genus, "R220"),
+            Some("s__Aminobacter niigataensis".to_string())
+        );
+        assert_eq!(
+            child_in_release(&history, "g__Aminobacter", Rank::Genus, "207"),
+            Some("s__Aminobacter sp000000001".to_string())
+        );
+        assert_eq!(
+            child_in_release(&history, "g__Aminobacter", Rank::Genus, "R95"),
+            None
+        );
+        assert_eq!(
+            child_in_release(
+                &history,
+                "s__Aminobacter niigataensis",
+                Rank::Species,
+                "R220"
+            ),
+            Some("s__Aminobacter niigataensis".to_string())
+        );
+    }
+
+    #[test]
+    fn test_name_in_release() {
+        let history = history();
+        let mut moved = history.clone();
+        moved.data[1] = serde_json::from_str(
+            r#"{"release": "R207", "p": "p__Firmicutes", "g": "g__Bacillus"}"#,
+        )
+        .unwrap();
+        let histories = [&history, &history, &moved];
+        assert_eq!(
+            name_in_release(histories, Rank::Phylum, "R207"),
+            Some("p__Proteobacteria".to_string())
+        );
+        assert_eq!(
+            name_in_release(histories, Rank::Phylum, "R220"),
+            Some("p__Pseudomonadota".to_string())
+        );
+        assert_eq!(name_in_release(histories, Rank::Phylum, "R80"), None);
+    }
+
+    #[test]
+    fn test_diff_rows() {
+        let mut placements = BTreeMap::new();
+        placements.insert(
+            "GCA_000000001.1".to_string(),
+            Placement {
+                from: None,
+                to: Some("s__A".to_string()),
+            },
+        );
+        placements.insert(
+            "GCA_000000002.1".to_string(),
+            Placement {
+                from: Some("s__B".to_string()),
+                to: Some("s__C".to_string()),
+            },
+        );
+        placements.insert(
+            "GCA_000000003.1".to_string(),
+            Placement {
+                from: Some("s__B".to_string()),
+                to: Some("s__C".to_string()),
+            },
+        );
+        placements.insert(
+            "GCA_000000004.1".to_string(),
+            Placement {
+                from: Some("s__D".to_string()),
+                to: None,
+            },
+        );
+        placements.insert(
+            "GCA_000000005.1".to_string(),
+            Placement {
+                from: Some("s__A".to_string()),
+                to: Some("s__A".to_string()),
+            },
+        );
+
+        assert_eq!(
+            diff_rows("g__A", ("g__A", "g__A"), &placements),
+            vec![
+                vec!["genome_added", "GCA_000000001.1", "", "s__A", "1"],
+                vec!["genome_removed", "GCA_000000004.1", "s__D", "", "1"],
+                vec!["child_renamed", "g__A", "s__B", "s__C", "2"],
+            ]
+        );
+        assert_eq!(
+            diff_rows("g__A", ("g__B", "g__A"), &placements)[0],
+            vec!["taxon_renamed", "g__A", "g__B", "g__A", "3"]
+        );
+    }
+}
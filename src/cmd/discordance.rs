@@ -0,0 +1,196 @@
+use anyhow::{ensure, Result};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use ureq::Agent;
+
+use crate::cli::discordance::DiscordanceArgs;
+use crate::cmd::search::{fetch_search_results, SearchResult};
+use crate::query::SearchQuery;
+use crate::taxonomy::{parse_lineage, Rank};
+use crate::utils::{self, OutputFormat};
+
+/// Genome classified in different GTDB and NCBI taxa at a rank, the NCBI
+/// taxon being empty when NCBI does not classify the genome at this rank
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Discordance {
+    accession: String,
+    gtdb_taxon: String,
+    ncbi_taxon: String,
+}
+
+impl Discordance {
+    const HEADER: [&'static str; 3] = ["accession", "gtdb_taxon", "ncbi_taxon"];
+
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.accession.clone(),
+            self.gtdb_taxon.clone(),
+            self.ncbi_taxon.clone(),
+        ]
+    }
+}
+
+/// Number of genomes classified in a GTDB taxon and a different NCBI taxon
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ConflictingPair {
+    gtdb_taxon: String,
+    ncbi_taxon: String,
+    genomes: usize,
+}
+
+impl ConflictingPair {
+    const HEADER: [&'static str; 3] = ["gtdb_taxon", "ncbi_taxon", "genomes"];
+
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.gtdb_taxon.clone(),
+            self.ncbi_taxon.clone(),
+            self.genomes.to_string(),
+        ]
+    }
+}
+
+/// Genomes whose GTDB and NCBI taxa differ at `rank`. Genomes without a
+/// GTDB taxon at this rank cannot be compared and are skipped.
+fn discordances(genomes: &[SearchResult], rank: Rank) -> Vec<Discordance> {
+    let taxon =
+        |taxonomy: Option<String>| parse_lineage(&taxonomy.unwrap_or_default()).remove(&rank);
+    genomes
+        .iter()
+        .filter_map(|genome| {
+            let gtdb_taxon = taxon(genome.get_gtdb_taxonomy())?;
+            let ncbi_taxon = taxon(genome.get_ncbi_taxonomy()).unwrap_or_default();
+            (gtdb_taxon != ncbi_taxon).then(|| Discordance {
+                accession: genome.gid.clone(),
+                gtdb_taxon,
+                ncbi_taxon,
+            })
+        })
+        .collect()
+}
+
+/// Count discordant genomes by pair of GTDB and NCBI taxa, the most
+/// frequent pairs first
+fn conflicting_pairs(discordances: &[Discordance]) -> Vec<ConflictingPair> {
+    let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for discordance in discordances {
+        *counts
+            .entry((&discordance.gtdb_taxon, &discordance.ncbi_taxon))
+            .or_default() += 1;
+    }
+    let mut pairs: Vec<ConflictingPair> = counts
+        .into_iter()
+        .map(|((gtdb_taxon, ncbi_taxon), genomes)| ConflictingPair {
+            gtdb_taxon: gtdb_taxon.to_string(),
+            ncbi_taxon: ncbi_taxon.to_string(),
+            genomes,
+        })
+        .collect();
+    pairs.sort_by_key(|x| Reverse(x.genomes));
+    pairs
+}
+
+/// Report the genomes of a taxon whose GTDB and NCBI classifications
+/// disagree at a rank, as counts per conflicting pair of taxa or as a list
+/// of genomes
+pub fn discordance(args: DiscordanceArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let name = args.get_name();
+    let rank = args.get_rank();
+
+    let genomes =
+        fetch_search_results(&agent, &SearchQuery::taxon(&name).whole_words())?.into_rows();
+    ensure!(!genomes.is_empty(), "No data found for {}", name);
+    let discordances = discordances(&genomes, rank);
+    if utils::verbosity() > 0 {
+        eprintln!(
+            "{} of {} genomes of {} are classified in different GTDB and NCBI taxa at the {} rank",
+            discordances.len(),
+            genomes.len(),
+            name,
+            rank
+        );
+    }
+
+    let outfmt = args.get_outfmt();
+    let output = match (args.is_genomes(), &outfmt) {
+        (true, OutputFormat::Json) => utils::to_json(&discordances)?,
+        (true, outfmt) => {
+            let rows: Vec<Vec<String>> = discordances.iter().map(Discordance::to_fields).collect();
+            utils::render_table(&Discordance::HEADER, &rows, outfmt)
+        }
+        (false, OutputFormat::Json) => utils::to_json(&conflicting_pairs(&discordances))?,
+        (false, outfmt) => {
+            let rows: Vec<Vec<String>> = conflicting_pairs(&discordances)
+                .iter()
+                .map(ConflictingPair::to_fields)
+                .collect();
+            utils::render_table(&ConflictingPair::HEADER, &rows, outfmt)
+        }
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genome(gid: &str, gtdb: &str, ncbi: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "gid": gid,
+            "gtdbTaxonomy": gtdb,
+            "ncbiTaxonomy": ncbi,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_discordances() {
+        let genomes = [
+            genome(
+                "GCA_1",
+                "d__Bacteria; f__Enterobacteriaceae; g__Escherichia",
+                "d__Bacteria; f__Enterobacteriaceae; g__Escherichia",
+            ),
+            genome(
+                "GCA_2",
+                "d__Bacteria; f__Enterobacteriaceae; g__Escherichia",
+                "d__Bacteria; f__Enterobacteriaceae; g__Shigella",
+            ),
+            genome(
+                "GCA_3",
+                "d__Bacteria; f__Enterobacteriaceae; g__Escherichia",
+                "d__Bacteria; f__Enterobacteriaceae; g__Shigella",
+            ),
+            genome(
+                "GCA_4",
+                "d__Bacteria; f__Enterobacteriaceae; g__Klebsiella",
+                "d__Bacteria; f__Enterobacteriaceae; g__",
+            ),
+            genome("GCA_5", "d__Bacteria; f__Enterobacteriaceae", ""),
+        ];
+        let discordances = discordances(&genomes, Rank::Genus);
+        let accessions: Vec<&str> = discordances.iter().map(|x| x.accession.as_str()).collect();
+        assert_eq!(accessions, ["GCA_2", "GCA_3", "GCA_4"]);
+        assert_eq!(discordances[2].ncbi_taxon, "");
+
+        assert_eq!(
+            conflicting_pairs(&discordances),
+            [
+                ConflictingPair {
+                    gtdb_taxon: "g__Escherichia".into(),
+                    ncbi_taxon: "g__Shigella".into(),
+                    genomes: 2,
+                },
+                ConflictingPair {
+                    gtdb_taxon: "g__Klebsiella".into(),
+                    ncbi_taxon: "".into(),
+                    genomes: 1,
+                },
+            ]
+        );
+    }
+}
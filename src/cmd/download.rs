@@ -0,0 +1,592 @@
+use crate::api::genome::{GenomeAPI, GenomeRequestType};
+use crate::api::ncbi::{self, AssemblyFile, NcbiFtpAPI};
+use crate::cli::download::DownloadArgs;
+use crate::cmd::genome::fetch_genome_card;
+use crate::error::XgtError;
+use crate::utils;
+
+use anyhow::{bail, ensure, Context, Result};
+use md5::{Digest, Md5};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use ureq::Agent;
+
+/// Name of the manifest written in the output directory
+const MANIFEST: &str = "manifest.tsv";
+
+/// Outcome of the download of a file
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DownloadStatus {
+    Downloaded,
+    // File was already present and verified in the output directory
+    Skipped,
+    // No checksum was published to verify the file against
+    Unverified,
+    Failed(String),
+}
+
+impl fmt::Display for DownloadStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadStatus::Downloaded => write!(f, "downloaded"),
+            DownloadStatus::Skipped => write!(f, "skipped"),
+            DownloadStatus::Unverified => write!(f, "unverified"),
+            DownloadStatus::Failed(reason) => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
+/// A line of the download manifest
+#[derive(Debug, Clone, PartialEq)]
+struct ManifestEntry {
+    accession: String,
+    // None when the accession could not be resolved
+    file: Option<AssemblyFile>,
+    status: DownloadStatus,
+    path: Option<PathBuf>,
+    size: Option<u64>,
+    md5: Option<String>,
+    url: Option<String>,
+}
+
+impl ManifestEntry {
+    const HEADER: &'static str = "accession\tfile\tstatus\tpath\tsize\tmd5\turl";
+
+    fn failed(accession: &str, error: anyhow::Error) -> Self {
+        ManifestEntry {
+            accession: accession.to_string(),
+            file: None,
+            status: DownloadStatus::Failed(error.to_string()),
+            path: None,
+            size: None,
+            md5: None,
+            url: None,
+        }
+    }
+
+    fn to_tsv_row(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.accession,
+            self.file.map(|f| f.to_string()).unwrap_or_default(),
+            self.status,
+            self.path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            self.size.map(|s| s.to_string()).unwrap_or_default(),
+            self.md5.clone().unwrap_or_default(),
+            self.url.clone().unwrap_or_default()
+        )
+    }
+}
+
+/// Size and checksum of the files verified by a previous run, indexed by path
+type VerifiedFiles = HashMap<PathBuf, (u64, String)>;
+
+/// Read the verified files recorded in a previous manifest, if any
+fn read_manifest(path: &Path) -> VerifiedFiles {
+    let Ok(file) = File::open(path) else {
+        return VerifiedFiles::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(|l| l.ok())
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields[..] {
+                [_, _, status, path, size, md5, _]
+                    if (status == "downloaded" || status == "skipped") && !md5.is_empty() =>
+                {
+                    Some((PathBuf::from(path), (size.parse().ok()?, md5.to_string())))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Key of a manifest row: the path of its file, or its accession when it
+/// could not be resolved
+fn manifest_key<'a>(accession: &'a str, path: &'a str) -> &'a str {
+    if path.is_empty() {
+        accession
+    } else {
+        path
+    }
+}
+
+/// Merge the rows of this run into the `previous` manifest, replacing the
+/// rows of the same files, so that files downloaded by earlier runs of
+/// other accessions stay recorded
+fn merge_manifest(previous: &str, entries: &[ManifestEntry]) -> String {
+    let rows: Vec<String> = entries.iter().map(|e| e.to_tsv_row()).collect();
+    let key = |row: &str| {
+        let fields: Vec<&str> = row.split('\t').collect();
+        match fields[..] {
+            [accession, _, _, path, ..] => manifest_key(accession, path).to_string(),
+            _ => row.to_string(),
+        }
+    };
+    let replaced: HashSet<String> = rows.iter().map(|row| key(row)).collect();
+
+    let mut manifest = vec![ManifestEntry::HEADER.to_string()];
+    manifest.extend(
+        previous
+            .lines()
+            .skip(1)
+            .filter(|row| !row.is_empty() && !replaced.contains(&key(row)))
+            .map(|row| row.to_string()),
+    );
+    manifest.extend(rows);
+    manifest.push(String::new());
+    manifest.join("\n")
+}
+
+/// Parse a NCBI md5checksums.txt file into a map of file name to checksum
+pub(crate) fn parse_checksums(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (md5, name) = line.trim().split_once(char::is_whitespace)?;
+            Some((
+                name.trim().trim_start_matches("./").to_string(),
+                md5.to_lowercase(),
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn fetch_checksums(agent: &Agent, url: &str) -> Result<HashMap<String, String>> {
+    let response = agent
+        .get(url)
+        .call()
+        .map_err(|e| XgtError::from_request(e, format!("No checksums found at {}", url)))?;
+    Ok(parse_checksums(&response.into_string()?))
+}
+
+/// Compute the md5 checksum of a file as a lowercase hexadecimal string
+fn md5_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file {}", path.display()))?;
+    let mut hasher = Md5::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Path of the temporary file used while `path` is being downloaded
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    PathBuf::from(partial)
+}
+
+/// Download `url` into `path`, resuming an interrupted download from
+/// its partial file.
+fn download_file(agent: &Agent, url: &str, path: &Path) -> Result<()> {
+    let partial = partial_path(path);
+    let offset = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = agent.get(url);
+    if offset > 0 {
+        request = request.set("Range", &format!("bytes={}-", offset));
+    }
+
+    let response = match request.call() {
+        Ok(r) => r,
+        // Range not satisfiable: the partial file is already complete
+        Err(ureq::Error::Status(416, _)) if offset > 0 => {
+            fs::rename(&partial, path)?;
+            return Ok(());
+        }
+        Err(e) => return Err(XgtError::from_request(e, format!("File {} not found", url)).into()),
+    };
+
+    let mut file = if response.status() == 206 {
+        OpenOptions::new().append(true).open(&partial)?
+    } else {
+        File::create(&partial)?
+    };
+    io::copy(&mut response.into_reader(), &mut file)
+        .with_context(|| format!("Failed to write to {}", partial.display()))?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+/// Make sure `path` holds a verified copy of `url`.
+/// A file already verified by a previous run is skipped without being
+/// hashed again; a file failing verification is downloaded again.
+pub(crate) fn fetch_file(
+    agent: &Agent,
+    url: &str,
+    path: &Path,
+    expected: Option<&String>,
+    previous: Option<&(u64, String)>,
+) -> Result<(DownloadStatus, u64, String)> {
+    let Some(expected) = expected else {
+        return fetch_unverified_file(agent, url, path, previous);
+    };
+    let matches = |md5: &String| md5 == expected;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if let Some((size, md5)) = previous {
+            if *size == metadata.len() && matches(md5) {
+                return Ok((DownloadStatus::Skipped, *size, md5.clone()));
+            }
+        }
+        let md5 = md5_file(path)?;
+        if matches(&md5) {
+            return Ok((DownloadStatus::Skipped, metadata.len(), md5));
+        }
+        fs::remove_file(path)?;
+    }
+
+    download_file(agent, url, path)?;
+
+    let md5 = md5_file(path)?;
+    if !matches(&md5) {
+        fs::remove_file(path)?;
+        bail!("checksum mismatch for {}", path.display());
+    }
+
+    Ok((DownloadStatus::Downloaded, fs::metadata(path)?.len(), md5))
+}
+
+/// Make sure `path` holds a copy of `url`, which has no published
+/// checksum. A file verified by a previous run is skipped, other files are
+/// kept or downloaded and recorded as unverified.
+fn fetch_unverified_file(
+    agent: &Agent,
+    url: &str,
+    path: &Path,
+    previous: Option<&(u64, String)>,
+) -> Result<(DownloadStatus, u64, String)> {
+    match fs::metadata(path) {
+        Ok(metadata) => {
+            if let Some((size, md5)) = previous {
+                if *size == metadata.len() {
+                    return Ok((DownloadStatus::Skipped, *size, md5.clone()));
+                }
+            }
+        }
+        Err(_) => download_file(agent, url, path)?,
+    }
+    Ok((
+        DownloadStatus::Unverified,
+        fs::metadata(path)?.len(),
+        md5_file(path)?,
+    ))
+}
+
+/// Resolve `accession` on the NCBI FTP server and download the requested
+/// `files` of its assembly into a directory named after the accession
+fn download_accession(
+    agent: &Agent,
+    accession: &str,
+    files: &[AssemblyFile],
+    outdir: &Path,
+    verified: &VerifiedFiles,
+) -> Result<Vec<ManifestEntry>> {
+    ensure!(
+        ncbi::is_assembly_accession(accession),
+        "{} is not a NCBI assembly accession",
+        accession
+    );
+
+    let card = fetch_genome_card(agent, accession)?;
+    let assembly_name = card
+        .get_assembly_name()
+        .with_context(|| format!("No NCBI assembly name found for {}", accession))?;
+
+    let ftp = NcbiFtpAPI::new(accession, &assembly_name);
+    let checksums = fetch_checksums(agent, &ftp.checksums())?;
+    let accession_dir = outdir.join(ncbi::strip_gtdb_prefix(accession));
+    fs::create_dir_all(&accession_dir)
+        .with_context(|| format!("Failed to create directory {}", accession_dir.display()))?;
+
+    Ok(files
+        .iter()
+        .map(|file| {
+            let url = ftp.file(*file);
+            let name = format!("{}{}", ftp.basename(), file.suffix());
+            let path = accession_dir.join(&name);
+            let mut entry = ManifestEntry {
+                accession: accession.to_string(),
+                file: Some(*file),
+                status: DownloadStatus::Downloaded,
+                path: Some(path.clone()),
+                size: None,
+                md5: None,
+                url: Some(url.clone()),
+            };
+            match fetch_file(
+                agent,
+                &url,
+                &path,
+                checksums.get(&name),
+                verified.get(&path),
+            ) {
+                Ok((status, size, md5)) => {
+                    entry.status = status;
+                    entry.size = Some(size);
+                    entry.md5 = Some(md5);
+                }
+                Err(e) => entry.status = DownloadStatus::Failed(e.to_string()),
+            }
+            entry
+        })
+        .collect())
+}
+
+/// URLs of the genome card requests resolving the assembly of each accession
+pub fn request_urls(args: &DownloadArgs) -> Result<Vec<String>> {
+    args.get_accession()
+        .into_iter()
+        .map(|accession| {
+            ensure!(
+                ncbi::is_assembly_accession(&accession),
+                "{} is not a NCBI assembly accession",
+                accession
+            );
+            Ok(GenomeAPI::from(accession).request(GenomeRequestType::Card))
+        })
+        .collect()
+}
+
+/// Files a download would write, the assembly name of each accession
+/// being only known once its genome card is fetched
+pub fn target_files(args: &DownloadArgs) -> Vec<String> {
+    let outdir = PathBuf::from(args.get_outdir());
+    let mut targets = vec![outdir.join(MANIFEST).display().to_string()];
+    for accession in args.get_accession() {
+        let accession = ncbi::strip_gtdb_prefix(&accession);
+        for file in args.get_files() {
+            let name = format!("{}_*{}", accession, file.suffix());
+            targets.push(outdir.join(accession).join(name).display().to_string());
+        }
+    }
+    targets
+}
+
+pub fn download(args: DownloadArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outdir = PathBuf::from(args.get_outdir());
+    fs::create_dir_all(&outdir)
+        .with_context(|| format!("Failed to create directory {}", outdir.display()))?;
+    let manifest_path = outdir.join(MANIFEST);
+    let verified = read_manifest(&manifest_path);
+
+    let files = args.get_files();
+    let entries: Vec<ManifestEntry> =
+        utils::parallel_map(args.get_accession(), args.get_jobs(), |accession| {
+            download_accession(&agent, &accession, &files, &outdir, &verified)
+                .unwrap_or_else(|e| vec![ManifestEntry::failed(&accession, e)])
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let previous = fs::read_to_string(&manifest_path).unwrap_or_default();
+    let partial = partial_path(&manifest_path);
+    fs::write(&partial, merge_manifest(&previous, &entries))
+        .with_context(|| format!("Failed to write to {}", partial.display()))?;
+    fs::rename(&partial, &manifest_path)
+        .with_context(|| format!("Failed to write to {}", manifest_path.display()))?;
+
+    let failed = entries
+        .iter()
+        .filter(|e| matches!(e.status, DownloadStatus::Failed(_)))
+        .count();
+    ensure!(
+        failed == 0,
+        "{} download(s) failed, see {}",
+        failed,
+        manifest_path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path(Path::new("out/GCA_001512625.1_ASM151262v1_genomic.fna.gz")),
+            PathBuf::from("out/GCA_001512625.1_ASM151262v1_genomic.fna.gz.part")
+        );
+    }
+
+    #[test]
+    fn test_manifest_entry_row() {
+        let entry = ManifestEntry {
+            accession: "GCA_001512625.1".to_string(),
+            file: Some(AssemblyFile::Gff),
+            status: DownloadStatus::Skipped,
+            path: Some(PathBuf::from("out/a.fna.gz")),
+            size: Some(42),
+            md5: Some("d41d8cd98f00b204e9800998ecf8427e".to_string()),
+            url: Some("https://ftp.ncbi.nlm.nih.gov/a.fna.gz".to_string()),
+        };
+        assert_eq!(
+            entry.to_tsv_row(),
+            "GCA_001512625.1\tgff\tskipped\tout/a.fna.gz\t42\td41d8cd98f00b204e9800998ecf8427e\thttps://ftp.ncbi.nlm.nih.gov/a.fna.gz"
+        );
+
+        let entry = ManifestEntry::failed("foo", anyhow::anyhow!("bad accession"));
+        assert_eq!(entry.to_tsv_row(), "foo\t\tfailed: bad accession\t\t\t\t");
+    }
+
+    #[test]
+    fn test_parse_checksums() {
+        let content = "4c5a0d8e06c0b02a0e2b6bc2d7e5e1b1  ./GCA_001512625.1_ASM151262v1_genomic.fna.gz\nA1B2  ./README.txt\n\n";
+        let checksums = parse_checksums(content);
+        assert_eq!(checksums.len(), 2);
+        assert_eq!(
+            checksums.get("GCA_001512625.1_ASM151262v1_genomic.fna.gz"),
+            Some(&"4c5a0d8e06c0b02a0e2b6bc2d7e5e1b1".to_string())
+        );
+        assert_eq!(checksums.get("README.txt"), Some(&"a1b2".to_string()));
+    }
+
+    #[test]
+    fn test_md5_file() {
+        assert_eq!(
+            md5_file(Path::new("test/test2.txt")).unwrap(),
+            format!("{:x}", Md5::digest(fs::read("test/test2.txt").unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_read_manifest() {
+        let path = &std::env::temp_dir()
+            .join(format!("xgt-test_read_manifest-{}.tsv", std::process::id()));
+        fs::write(
+            path,
+            format!(
+                "{}\nA\tgenome\tdownloaded\tout/a.fna.gz\t10\tabc\turl\nB\tgenome\tfailed: oops\tout/b.fna.gz\t\t\turl\n",
+                ManifestEntry::HEADER
+            ),
+        )
+        .unwrap();
+        let verified = read_manifest(path);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(verified.len(), 1);
+        assert_eq!(
+            verified.get(Path::new("out/a.fna.gz")),
+            Some(&(10, "abc".to_string()))
+        );
+        assert!(read_manifest(Path::new("non_existing_manifest.tsv")).is_empty());
+    }
+
+    #[test]
+    fn test_merge_manifest() {
+        let previous = format!(
+            "{}\nA\tgenome\tdownloaded\tout/a.fna.gz\t10\tabc\turl\nB\tgenome\tdownloaded\tout/b.fna.gz\t20\tdef\turl\nC\t\tfailed: oops\t\t\t\t\n",
+            ManifestEntry::HEADER
+        );
+        let entries = vec![
+            ManifestEntry {
+                accession: "B".to_string(),
+                file: Some(AssemblyFile::Genome),
+                status: DownloadStatus::Skipped,
+                path: Some(PathBuf::from("out/b.fna.gz")),
+                size: Some(20),
+                md5: Some("def".to_string()),
+                url: Some("url".to_string()),
+            },
+            ManifestEntry::failed("C", anyhow::anyhow!("still bad")),
+        ];
+        assert_eq!(
+            merge_manifest(&previous, &entries),
+            format!(
+                "{}\nA\tgenome\tdownloaded\tout/a.fna.gz\t10\tabc\turl\nB\tgenome\tskipped\tout/b.fna.gz\t20\tdef\turl\nC\t\tfailed: still bad\t\t\t\t\n",
+                ManifestEntry::HEADER
+            )
+        );
+        assert_eq!(
+            merge_manifest("", &[]),
+            format!("{}\n", ManifestEntry::HEADER)
+        );
+    }
+
+    #[test]
+    fn test_download_invalid_accession() {
+        let agent = utils::get_agent(false).unwrap();
+        let result = download_accession(
+            &agent,
+            "NC_000912.1",
+            &[AssemblyFile::Genome],
+            Path::new("."),
+            &VerifiedFiles::new(),
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "NC_000912.1 is not a NCBI assembly accession"
+        );
+    }
+
+    #[test]
+    fn test_fetch_file_skips_verified() {
+        let agent = utils::get_agent(false).unwrap();
+        let path = Path::new("test/acc.txt");
+        let md5 = md5_file(path).unwrap();
+
+        let (status, size, checksum) =
+            fetch_file(&agent, "https://invalid-url", path, Some(&md5), None).unwrap();
+        assert_eq!(status, DownloadStatus::Skipped);
+        assert_eq!(size, fs::metadata(path).unwrap().len());
+        assert_eq!(checksum, md5);
+
+        let previous = (size, md5.clone());
+        let (status, _, _) =
+            fetch_file(&agent, "https://invalid-url", path, None, Some(&previous)).unwrap();
+        assert_eq!(status, DownloadStatus::Skipped);
+
+        // Files without a checksum are kept but not verified
+        let (status, _, checksum) =
+            fetch_file(&agent, "https://invalid-url", path, None, None).unwrap();
+        assert_eq!(status, DownloadStatus::Unverified);
+        assert_eq!(checksum, md5);
+    }
+
+    #[test]
+    fn test_dry_run_plan() {
+        let args = DownloadArgs {
+            accession: vec!["RS_GCF_000005845.2".to_string()],
+            files: vec![AssemblyFile::Genome, AssemblyFile::Gff],
+            outdir: "out".to_string(),
+            jobs: 1,
+            disable_certificate_verification: false,
+        };
+
+        assert_eq!(
+            request_urls(&args).unwrap(),
+            vec!["https://api.gtdb.ecogenomic.org/genome/RS_GCF_000005845.2/card"]
+        );
+        assert_eq!(
+            target_files(&args),
+            vec![
+                "out/manifest.tsv",
+                "out/GCF_000005845.2/GCF_000005845.2_*_genomic.fna.gz",
+                "out/GCF_000005845.2/GCF_000005845.2_*_genomic.gff.gz",
+            ]
+        );
+
+        let args = DownloadArgs {
+            accession: vec!["NC_000912.1".to_string()],
+            ..args
+        };
+        assert!(request_urls(&args).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+
+use crate::cli::exists::ExistsArgs;
+use crate::cmd::genome::{fetch_genome_cards, GenomeCard};
+use crate::error::XgtError;
+use crate::utils;
+
+/// Presence of a genome in the current GTDB release
+#[derive(Debug, Clone, PartialEq)]
+enum Presence {
+    // Whether the genome is a GTDB species representative
+    Present { representative: bool },
+    Missing,
+}
+
+impl Presence {
+    fn to_tsv_row(&self, accession: &str) -> String {
+        match self {
+            Presence::Present { representative } => {
+                format!("{}\tok\t{}", accession, representative)
+            }
+            Presence::Missing => format!("{}\tmissing\t", accession),
+        }
+    }
+}
+
+/// Presence of a genome in the current GTDB release from its genome card.
+/// A genome is missing when GTDB does not know it or has not classified it.
+fn presence(card: Result<GenomeCard, XgtError>) -> Result<Presence, XgtError> {
+    let card = match card {
+        Ok(card) => card,
+        Err(XgtError::NotFound(_)) => return Ok(Presence::Missing),
+        Err(e) => return Err(e),
+    };
+
+    Ok(match card.get_gtdb_taxonomy().is_empty() {
+        true => Presence::Missing,
+        false => Presence::Present {
+            representative: card.is_gtdb_representative(),
+        },
+    })
+}
+
+/// Report whether each accession is in the current GTDB release, and
+/// return whether all of them are
+pub fn exists(args: ExistsArgs) -> Result<bool> {
+    let accessions = args.get_accessions();
+    let presences = fetch_genome_cards(
+        accessions.clone(),
+        args.get_jobs(),
+        args.get_disable_certificate_verification(),
+    )?
+    .into_iter()
+    .map(presence)
+    .collect::<Result<Vec<Presence>, XgtError>>()?;
+
+    let mut output = String::from("accession\tstatus\trepresentative\n");
+    for (accession, presence) in accessions.iter().zip(&presences) {
+        output.push_str(&presence.to_tsv_row(accession));
+        output.push('\n');
+    }
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(presences.iter().all(|x| *x != Presence::Missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence() {
+        let not_found = XgtError::NotFound("Genome GCA_000000000.1 not found".to_string());
+        assert_eq!(presence(Err(not_found)).unwrap(), Presence::Missing);
+        assert!(presence(Err(XgtError::RateLimited)).is_err());
+    }
+
+    #[test]
+    fn test_presence_to_tsv_row() {
+        let present = Presence::Present {
+            representative: true,
+        };
+        assert_eq!(
+            present.to_tsv_row("GCA_001512625.1"),
+            "GCA_001512625.1\tok\ttrue"
+        );
+        assert_eq!(
+            Presence::Missing.to_tsv_row("GCA_000000000.1"),
+            "GCA_000000000.1\tmissing\t"
+        );
+    }
+}
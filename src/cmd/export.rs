@@ -0,0 +1,131 @@
+use anyhow::{bail, ensure, Context, Result};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use ureq::Agent;
+
+use crate::cli::export::ExportArgs;
+use crate::cmd::genome::fetch_genome_card;
+use crate::cmd::search::fetch_search_results;
+use crate::exporters::{
+    anvio, biom, krona, network, newick, phyloseq, qiime, seqid2taxid, sourmash, taxdump,
+    ExportFormat, Genome,
+};
+use crate::query::SearchQuery;
+use crate::taxonomy::{parse_lineage, Rank};
+use crate::utils::{self, SearchField};
+
+/// Fetch the GTDB lineage of the genomes of a list of accessions
+fn fetch_accession_genomes(agent: &Agent, args: &ExportArgs) -> Result<Vec<Genome>> {
+    utils::parallel_map(args.get_accessions(), args.get_jobs(), |accession| {
+        let card = fetch_genome_card(agent, &accession)?;
+        Ok(Genome::new(&accession, &card.get_gtdb_taxonomy()))
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Fetch the genomes of a taxon, the genomes matching a search or the
+/// genomes of a list of accessions, with their GTDB lineage
+fn fetch_genomes(agent: &Agent, args: &ExportArgs) -> Result<Vec<Genome>> {
+    let (needle, search_field) = match (args.get_taxon(), args.get_search()) {
+        (Some(taxon), _) => (taxon, SearchField::Gtdb),
+        (None, Some(search)) => (search, SearchField::All),
+        (None, None) if !args.get_accessions().is_empty() => {
+            return fetch_accession_genomes(agent, args);
+        }
+        (None, None) => bail!("Either --taxon, --search or --file is required"),
+    };
+    let rank = Rank::from_taxon(&needle);
+
+    let genomes: Vec<Genome> = fetch_search_results(
+        agent,
+        &SearchQuery::new(&needle).field(search_field.clone()),
+    )?
+    .into_rows()
+    .into_iter()
+    .filter(|x| !args.is_reps_only() || x.is_gtdb_species_rep() == Some(true))
+    .filter_map(|x| {
+        let lineage = x.get_gtdb_taxonomy()?;
+        // The search is partial, only keep the genomes of the taxon itself
+        if let (SearchField::Gtdb, Some(rank)) = (&search_field, rank) {
+            if parse_lineage(&lineage).get(&rank) != Some(&needle) {
+                return None;
+            }
+        }
+        Some(Genome::new(&x.gid, &lineage))
+    })
+    .collect();
+    ensure!(!genomes.is_empty(), "No matching data found in GTDB");
+
+    Ok(genomes)
+}
+
+/// Write a Krona HTML chart with ktImportText from KronaTools
+fn write_krona_html(krona_text: &str, html: &str) -> Result<()> {
+    let input = env::temp_dir().join(format!("xgt-krona-{}.txt", std::process::id()));
+    fs::write(&input, krona_text)
+        .with_context(|| format!("Failed to write to {}", input.display()))?;
+    let status = Command::new("ktImportText")
+        .arg("-o")
+        .arg(html)
+        .arg(&input)
+        .status();
+    fs::remove_file(&input)?;
+
+    match status {
+        Ok(status) => ensure!(status.success(), "ktImportText failed with {}", status),
+        Err(_) => bail!("ktImportText not found, please install KronaTools to write HTML charts"),
+    }
+    Ok(())
+}
+
+/// Write named files into `outdir`
+fn write_files(outdir: &Path, files: [(&str, String); 2]) -> Result<()> {
+    fs::create_dir_all(outdir)
+        .with_context(|| format!("Failed to create directory {}", outdir.display()))?;
+    for (name, content) in files {
+        let path = outdir.join(name);
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write to {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Export the GTDB lineages of a set of genomes to the input format of another tool
+pub fn export(args: ExportArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let genomes = fetch_genomes(&agent, &args)?;
+
+    let output = match args.get_format() {
+        ExportFormat::Krona => krona::to_krona(&genomes),
+        ExportFormat::Newick => newick::to_newick(&genomes),
+        ExportFormat::Seqid2taxid => seqid2taxid::to_seqid2taxid(&genomes, args.is_with_lineage()),
+        ExportFormat::Qiime => qiime::to_qiime(&genomes),
+        ExportFormat::SourmashLineages => sourmash::to_sourmash_lineages(&genomes),
+        ExportFormat::Phyloseq => phyloseq::to_phyloseq(&genomes),
+        ExportFormat::Anvio => anvio::to_anvio(&genomes),
+        ExportFormat::Biom => biom::to_biom(&genomes),
+        ExportFormat::Taxdump => {
+            let (nodes_dmp, names_dmp) = taxdump::to_taxdump(&genomes);
+            let files = [("nodes.dmp", nodes_dmp), ("names.dmp", names_dmp)];
+            return write_files(Path::new(&args.get_outdir()), files);
+        }
+        ExportFormat::Edges => {
+            let (nodes, edges) = network::to_network(&genomes);
+            let files = [("nodes.csv", nodes), ("edges.csv", edges)];
+            return write_files(Path::new(&args.get_outdir()), files);
+        }
+    };
+    if let Some(html) = args.get_html() {
+        ensure!(
+            args.get_format() == ExportFormat::Krona,
+            "--html is only supported by the krona format"
+        );
+        write_krona_html(&output, &html)?;
+    }
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use ureq::Agent;
+
+use crate::api::data::{find_release_file, find_tree_file, GtdbDataAPI};
+use crate::cli::fetch::{FetchMetadataArgs, FetchTreeArgs};
+use crate::cmd::download::{fetch_checksums, fetch_file};
+use crate::cmd::releases::fetch_releases;
+use crate::local;
+use crate::taxonomy::release_number;
+use crate::utils::{self, OutputFormat};
+
+/// Header of the report of the fetched release files
+const REPORT_HEADER: [&str; 5] = ["domain", "file", "status", "md5", "path"];
+
+/// Download the GTDB metadata files of a release into the cache,
+/// verified against the release md5 checksums
+pub fn fetch_metadata(args: FetchMetadataArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    // The latest release is resolved to its number, which names the cached
+    // files so that local queries find them
+    let release = match args.get_release() {
+        Some(release) => release_number(&release),
+        None => release_number(
+            &fetch_releases(&agent)?
+                .last()
+                .expect("releases is not empty")
+                .get_release(),
+        ),
+    };
+    let api = GtdbDataAPI::new(Some(release));
+    let checksums = fetch_checksums(&agent, &api.get_checksums_request())?;
+    let mut names: Vec<&String> = checksums.keys().collect();
+    names.sort();
+
+    let cache_dir = local::metadata_dir()?;
+    fs::create_dir_all(&cache_dir)
+        .with_context(|| format!("Failed to create directory {}", cache_dir.display()))?;
+
+    let mut rows = Vec::new();
+    for domain in args.get_domains() {
+        let name = find_release_file(names.iter().copied(), domain, "metadata", ".tsv.gz")
+            .with_context(|| {
+                format!(
+                    "No {} metadata file found in {}",
+                    domain,
+                    api.get_release_url()
+                )
+            })?;
+        let path = cache_dir.join(cached_metadata_name(&name, release));
+        let (status, _, md5) = fetch_file(
+            &agent,
+            &api.get_file_request(&name),
+            &path,
+            checksums.get(&name),
+            None,
+        )?;
+        rows.push(vec![
+            domain.to_string(),
+            name,
+            status.to_string(),
+            md5,
+            path.display().to_string(),
+        ]);
+    }
+
+    let report = utils::render_table(&REPORT_HEADER, &rows, &OutputFormat::Tsv);
+    utils::write_to_output(report.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+/// Name of a metadata file in the cache, suffixed with its release when
+/// the release file is not, e.g. "bac120_metadata_r220.tsv.gz" for
+/// "bac120_metadata.tsv.gz" of R220
+fn cached_metadata_name(name: &str, release: u32) -> String {
+    match local::metadata_release(name) {
+        Some(_) => name.to_string(),
+        None => {
+            let stem = name.strip_suffix(".tsv.gz").unwrap_or(name);
+            format!("{}_r{}.tsv.gz", stem, release)
+        }
+    }
+}
+
+/// Decompress the gzipped file `path` next to it, without its .gz extension
+fn gunzip_file(path: &Path) -> Result<PathBuf> {
+    let target = path.with_extension("");
+    let mut partial = target.as_os_str().to_owned();
+    partial.push(".part");
+
+    let file =
+        File::open(path).with_context(|| format!("Failed to open file {}", path.display()))?;
+    let mut output = File::create(&partial)?;
+    io::copy(&mut MultiGzDecoder::new(file), &mut output)
+        .with_context(|| format!("Failed to decompress {}", path.display()))?;
+    fs::rename(&partial, &target)?;
+
+    Ok(target)
+}
+
+/// Download the GTDB reference trees of a release into the output
+/// directory, verified against the release md5 checksums and decompressed
+pub fn fetch_tree(args: FetchTreeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let api = GtdbDataAPI::new(args.get_release().map(|x| release_number(&x)));
+    let checksums = fetch_checksums(&agent, &api.get_checksums_request())?;
+    let mut names: Vec<&String> = checksums.keys().collect();
+    names.sort();
+
+    let outdir = PathBuf::from(args.get_outdir());
+    fs::create_dir_all(&outdir)
+        .with_context(|| format!("Failed to create directory {}", outdir.display()))?;
+
+    let mut rows = Vec::new();
+    for domain in args.get_domains() {
+        let name = find_tree_file(names.iter().copied(), domain)
+            .with_context(|| format!("No {} tree found in {}", domain, api.get_release_url()))?;
+        let path = outdir.join(&name);
+        let (status, _, md5) = fetch_file(
+            &agent,
+            &api.get_file_request(&name),
+            &path,
+            checksums.get(&name),
+            None,
+        )?;
+        let tree = if name.ends_with(".gz") {
+            gunzip_file(&path)?
+        } else {
+            path
+        };
+        rows.push(vec![
+            domain.to_string(),
+            name,
+            status.to_string(),
+            md5,
+            tree.display().to_string(),
+        ]);
+    }
+
+    let report = utils::render_table(&REPORT_HEADER, &rows, &OutputFormat::Tsv);
+    utils::write_to_output(report.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_cached_metadata_name() {
+        assert_eq!(
+            cached_metadata_name("bac120_metadata_r220.tsv.gz", 220),
+            "bac120_metadata_r220.tsv.gz"
+        );
+        let name = cached_metadata_name("ar53_metadata.tsv.gz", 226);
+        assert_eq!(name, "ar53_metadata_r226.tsv.gz");
+        assert_eq!(local::metadata_release(&name), Some(226));
+    }
+
+    #[test]
+    fn test_gunzip_file() {
+        let path = Path::new("test_gunzip.tree.gz");
+        let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        encoder.write_all(b"((A:0.1,B:0.2):0.3,C:0.4);\n").unwrap();
+        encoder.finish().unwrap();
+
+        let tree = gunzip_file(path).unwrap();
+        assert_eq!(tree, PathBuf::from("test_gunzip.tree"));
+        assert_eq!(
+            fs::read_to_string(&tree).unwrap(),
+            "((A:0.1,B:0.2):0.3,C:0.4);\n"
+        );
+        fs::remove_file(path).unwrap();
+        fs::remove_file(tree).unwrap();
+    }
+}
@@ -1,18 +1,34 @@
 use crate::api::genome::GenomeAPI;
 use crate::api::genome::GenomeRequestType;
-use crate::cli::genome::GenomeArgs;
-use crate::utils;
+use crate::api::ncbi::strip_gtdb_prefix;
+#[cfg(feature = "async")]
+use crate::async_client::AsyncGtdbClient;
+use crate::cli::genome::{GenomeArgs, ACCESSION_PLACEHOLDER};
+use crate::cmd::search::lookup_accessions;
+use crate::cmd::taxon::Nomenclature;
+use crate::drift;
+use crate::error::XgtError;
+use crate::interrupt;
+use crate::local::{self, MetadataRecord};
+use crate::report::Report;
+use crate::taxonomy::{self, release_number, Rank};
+use crate::transport::{get_json, Transport};
+use crate::utils::{self, ItemErrors, OutputFormat};
+use crate::values;
 
-use anyhow::anyhow;
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
+use std::path::Path;
 
 use ureq::Agent;
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 /// GenomeCard API query result struct
+#[non_exhaustive]
 pub struct GenomeCard {
     // Genome struct
     genome: Genome,
@@ -48,6 +64,12 @@ pub struct GenomeCard {
     // Raw link to NCBI Taxonomy of Genome if any
     // for example: "<a target=\"_blank\" href=\"https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/2/\">d__Bacteria</a>; <a target=\"_blank\" href=\"https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/1224/\">p__Pseudomonadota</a>; <a target=\"_blank\" href=\"https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/81684/\">x__unclassified Pseudomonadota</a>; <a target=\"_blank\" href=\"https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/1977087/\">s__Pseudomonadota bacterium</a>"
     link_ncbi_taxonomy_unfiltered: Option<String>,
+    // Taxa and URLs of the NCBI Taxonomy links, set when their markup is
+    // stripped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ncbi_taxonomy_links: Option<Vec<TaxonLink>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ncbi_taxonomy_unfiltered_links: Option<Vec<TaxonLink>>,
     // Parsed NCBI taxonomy as a Vec of Taxon struct
     #[serde(alias = "ncbiTaxonomyFiltered")]
     ncbi_taxonomy_filtered: Vec<Taxon>,
@@ -56,14 +78,16 @@ pub struct GenomeCard {
     ncbi_taxonomy_unfiltered: Vec<Taxon>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
 pub struct Genome {
     accession: String,
     name: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename = "metadata_nucleotide")]
+#[non_exhaustive]
 pub struct MetadataNucleotide {
     trna_aa_count: Option<i32>,
     contig_count: Option<i32>,
@@ -77,21 +101,33 @@ pub struct MetadataNucleotide {
     ambiguous_bases: Option<i32>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename = "metadata_gene")]
+#[non_exhaustive]
+// Numbers sent as strings by GTDB are output as numbers, and described
+// as such in the JSON Schema of genome cards
 pub struct MetadataGene {
+    #[schemars(with = "Option<f64>")]
     checkm_completeness: Option<String>,
+    #[schemars(with = "Option<f64>")]
     checkm_contamination: Option<String>,
+    #[schemars(with = "Option<f64>")]
     checkm_strain_heterogeneity: Option<String>,
+    #[schemars(with = "Option<i64>")]
     lsu_5s_count: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ssu_count: Option<String>,
+    #[schemars(with = "Option<i64>")]
     lsu_23s_count: Option<String>,
+    #[schemars(with = "Option<i64>")]
     protein_count: Option<String>,
+    #[schemars(with = "Option<f64>")]
     coding_density: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename = "metadata_ncbi")]
+#[non_exhaustive]
 pub struct MetadataNCBI {
     ncbi_genbank_assembly_accession: Option<String>,
     ncbi_strain_identifiers: Option<String>,
@@ -106,25 +142,34 @@ pub struct MetadataNCBI {
     ncbi_isolate: Option<String>,
     ncbi_isolation_source: Option<String>,
     ncbi_lat_lon: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_molecule_count: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_cds_count: Option<String>,
     ncbi_refseq_category: Option<String>,
     ncbi_seq_rel_date: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_spanned_gaps: Option<String>,
     ncbi_species_taxid: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_ssu_count: Option<String>,
     ncbi_submitter: Option<String>,
     ncbi_taxid: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_total_gap_length: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_translation_table: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_trna_count: Option<String>,
+    #[schemars(with = "Option<i64>")]
     ncbi_unspanned_gaps: Option<String>,
     ncbi_version_status: Option<String>,
     ncbi_wgs_master: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase", rename = "metadata_type_material")]
+#[non_exhaustive]
 pub struct MetadataTypeMaterial {
     gtdb_type_designation: Option<String>,
     gtdb_type_designation_sources: Option<String>,
@@ -134,8 +179,9 @@ pub struct MetadataTypeMaterial {
     gtdb_type_species_of_genus: Option<bool>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename = "metadataTaxonomy")]
+#[non_exhaustive]
 pub struct MetadataTaxonomy {
     ncbi_taxonomy: Option<String>,
     ncbi_taxonomy_unfiltered: Option<String>,
@@ -158,23 +204,628 @@ pub struct MetadataTaxonomy {
     gtdb_species: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Taxon {
     taxon: Option<String>,
     taxon_id: Option<String>,
 }
 
+impl GenomeCard {
+    /// Get the NCBI assembly name, e.g. "ASM151262v1"
+    pub fn get_assembly_name(&self) -> Option<String> {
+        ncbi_value(&self.metadata_ncbi.ncbi_assembly_name)
+    }
+
+    /// Get the NCBI taxid of the genome
+    pub fn get_ncbi_taxid(&self) -> Option<String> {
+        ncbi_value(&self.metadata_ncbi.ncbi_taxid)
+    }
+
+    /// Get the NCBI taxid of the species of the genome
+    pub fn get_ncbi_species_taxid(&self) -> Option<String> {
+        ncbi_value(&self.metadata_ncbi.ncbi_species_taxid)
+    }
+
+    /// Get GTDB taxonomy as a greengenes-style string,
+    /// e.g. "d__Bacteria; p__Pseudomonadota; ...; s__UBA6186 sp001512625"
+    pub fn get_gtdb_taxonomy(&self) -> String {
+        let taxonomy = &self.metadata_taxonomy;
+        [
+            &taxonomy.gtdb_domain,
+            &taxonomy.gtdb_phylum,
+            &taxonomy.gtdb_class,
+            &taxonomy.gtdb_order,
+            &taxonomy.gtdb_family,
+            &taxonomy.gtdb_genus,
+            &taxonomy.gtdb_species,
+        ]
+        .iter()
+        .filter_map(|x| (*x).clone())
+        .collect::<Vec<String>>()
+        .join("; ")
+    }
+
+    /// Get NCBI taxonomy as a greengenes-style string
+    pub fn get_ncbi_taxonomy(&self) -> Option<String> {
+        self.metadata_taxonomy.ncbi_taxonomy.clone()
+    }
+
+    /// Get GTDB species, e.g. "s__UBA6186 sp001512625"
+    pub fn get_gtdb_species(&self) -> Option<String> {
+        self.metadata_taxonomy.gtdb_species.clone()
+    }
+
+    /// Check if genome is a GTDB species representative
+    pub fn is_gtdb_representative(&self) -> bool {
+        self.metadata_taxonomy.gtdb_representative
+    }
+
+    /// Get CheckM completeness
+    pub fn get_checkm_completeness(&self) -> Option<String> {
+        self.metadata_gene.checkm_completeness.clone()
+    }
+
+    /// Get CheckM contamination
+    pub fn get_checkm_contamination(&self) -> Option<String> {
+        self.metadata_gene.checkm_contamination.clone()
+    }
+
+    /// Get genome size in base pairs
+    pub fn get_genome_size(&self) -> Option<i64> {
+        self.metadata_nucleotide.genome_size
+    }
+
+    /// Get number of contigs
+    pub fn get_contig_count(&self) -> Option<i32> {
+        self.metadata_nucleotide.contig_count
+    }
+
+    /// Get accession and name of the genome
+    pub fn get_genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    /// Get nucleotide statistics of the assembly
+    pub fn get_metadata_nucleotide(&self) -> &MetadataNucleotide {
+        &self.metadata_nucleotide
+    }
+
+    /// Get gene statistics and CheckM estimates
+    pub fn get_metadata_gene(&self) -> &MetadataGene {
+        &self.metadata_gene
+    }
+
+    /// Get NCBI assembly metadata
+    pub fn get_metadata_ncbi(&self) -> &MetadataNCBI {
+        &self.metadata_ncbi
+    }
+
+    /// Get type material designations
+    pub fn get_metadata_type_material(&self) -> &MetadataTypeMaterial {
+        &self.metadata_type_material
+    }
+
+    /// Get GTDB and NCBI taxonomy
+    pub fn get_metadata_taxonomy(&self) -> &MetadataTaxonomy {
+        &self.metadata_taxonomy
+    }
+
+    /// Get GTDB type designation, e.g. "not type material"
+    pub fn get_gtdb_type_designation(&self) -> Option<String> {
+        self.gtdb_type_designation.clone()
+    }
+
+    /// Get summary of the 16S and 23S rRNA subunits
+    pub fn get_subunit_summary(&self) -> Option<String> {
+        self.subunit_summary.clone()
+    }
+
+    /// Get accession of the representative of the species cluster
+    pub fn get_species_rep_name(&self) -> Option<String> {
+        self.species_rep_name.clone()
+    }
+
+    /// Get number of genomes in the species cluster
+    pub fn get_species_cluster_count(&self) -> Option<i32> {
+        self.species_cluster_count
+    }
+
+    /// Get link to the LPSN page of the species
+    pub fn get_lpsn_url(&self) -> Option<String> {
+        self.lpsn_url.clone()
+    }
+
+    /// Get NCBI taxonomy as HTML links to the NCBI Taxonomy pages
+    pub fn get_link_ncbi_taxonomy(&self) -> Option<String> {
+        self.link_ncbi_taxonomy.clone()
+    }
+
+    /// Get unfiltered NCBI taxonomy as HTML links
+    pub fn get_link_ncbi_taxonomy_unfiltered(&self) -> Option<String> {
+        self.link_ncbi_taxonomy_unfiltered.clone()
+    }
+
+    /// Get the taxa and URLs of the NCBI taxonomy links
+    pub fn get_ncbi_taxonomy_links(&self) -> Vec<TaxonLink> {
+        self.ncbi_taxonomy_links
+            .clone()
+            .or_else(|| self.link_ncbi_taxonomy.as_deref().map(parse_taxon_links))
+            .unwrap_or_default()
+    }
+
+    /// Get the taxa and URLs of the unfiltered NCBI taxonomy links
+    pub fn get_ncbi_taxonomy_unfiltered_links(&self) -> Vec<TaxonLink> {
+        self.ncbi_taxonomy_unfiltered_links
+            .clone()
+            .or_else(|| {
+                self.link_ncbi_taxonomy_unfiltered
+                    .as_deref()
+                    .map(parse_taxon_links)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Replace the HTML links of the NCBI taxonomy by the plain lineage,
+    /// moving their taxa and URLs to separate fields
+    pub fn strip_link_markup(&mut self) {
+        if let Some(html) = self.link_ncbi_taxonomy.take() {
+            let links = parse_taxon_links(&html);
+            self.link_ncbi_taxonomy = Some(plain_lineage(&links));
+            self.ncbi_taxonomy_links = Some(links);
+        }
+        if let Some(html) = self.link_ncbi_taxonomy_unfiltered.take() {
+            let links = parse_taxon_links(&html);
+            self.link_ncbi_taxonomy_unfiltered = Some(plain_lineage(&links));
+            self.ncbi_taxonomy_unfiltered_links = Some(links);
+        }
+    }
+
+    /// Get NCBI taxonomy filtered to the ranks used by GTDB
+    pub fn get_ncbi_taxonomy_filtered(&self) -> &[Taxon] {
+        &self.ncbi_taxonomy_filtered
+    }
+
+    /// Get unfiltered NCBI taxonomy
+    pub fn get_ncbi_taxonomy_unfiltered(&self) -> &[Taxon] {
+        &self.ncbi_taxonomy_unfiltered
+    }
+}
+
+impl Genome {
+    /// Get `accession`
+    pub fn get_accession(&self) -> String {
+        self.accession.clone()
+    }
+
+    /// Get `name`
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl MetadataNucleotide {
+    /// Get `trna_aa_count`
+    pub fn get_trna_aa_count(&self) -> Option<i32> {
+        self.trna_aa_count
+    }
+
+    /// Get `contig_count`
+    pub fn get_contig_count(&self) -> Option<i32> {
+        self.contig_count
+    }
+
+    /// Get `n50_contigs`
+    pub fn get_n50_contigs(&self) -> Option<i32> {
+        self.n50_contigs
+    }
+
+    /// Get `longest_contig`
+    pub fn get_longest_contig(&self) -> Option<i32> {
+        self.longest_contig
+    }
+
+    /// Get `scaffold_count`
+    pub fn get_scaffold_count(&self) -> Option<i32> {
+        self.scaffold_count
+    }
+
+    /// Get `n50_scaffolds`
+    pub fn get_n50_scaffolds(&self) -> Option<i32> {
+        self.n50_scaffolds
+    }
+
+    /// Get `longest_scaffold`
+    pub fn get_longest_scaffold(&self) -> Option<i64> {
+        self.longest_scaffold
+    }
+
+    /// Get `genome_size`
+    pub fn get_genome_size(&self) -> Option<i64> {
+        self.genome_size
+    }
+
+    /// Get `gc_percentage`
+    pub fn get_gc_percentage(&self) -> Option<f64> {
+        self.gc_percentage
+    }
+
+    /// Get `ambiguous_bases`
+    pub fn get_ambiguous_bases(&self) -> Option<i32> {
+        self.ambiguous_bases
+    }
+}
+
+impl MetadataGene {
+    /// Get `checkm_completeness`
+    pub fn get_checkm_completeness(&self) -> Option<String> {
+        self.checkm_completeness.clone()
+    }
+
+    /// Get `checkm_contamination`
+    pub fn get_checkm_contamination(&self) -> Option<String> {
+        self.checkm_contamination.clone()
+    }
+
+    /// Get `checkm_strain_heterogeneity`
+    pub fn get_checkm_strain_heterogeneity(&self) -> Option<String> {
+        self.checkm_strain_heterogeneity.clone()
+    }
+
+    /// Get `lsu_5s_count`
+    pub fn get_lsu_5s_count(&self) -> Option<String> {
+        self.lsu_5s_count.clone()
+    }
+
+    /// Get `ssu_count`
+    pub fn get_ssu_count(&self) -> Option<String> {
+        self.ssu_count.clone()
+    }
+
+    /// Get `lsu_23s_count`
+    pub fn get_lsu_23s_count(&self) -> Option<String> {
+        self.lsu_23s_count.clone()
+    }
+
+    /// Get `protein_count`
+    pub fn get_protein_count(&self) -> Option<String> {
+        self.protein_count.clone()
+    }
+
+    /// Get `coding_density`
+    pub fn get_coding_density(&self) -> Option<String> {
+        self.coding_density.clone()
+    }
+}
+
+impl MetadataNCBI {
+    /// Get `ncbi_genbank_assembly_accession`
+    pub fn get_ncbi_genbank_assembly_accession(&self) -> Option<String> {
+        self.ncbi_genbank_assembly_accession.clone()
+    }
+
+    /// Get `ncbi_strain_identifiers`
+    pub fn get_ncbi_strain_identifiers(&self) -> Option<String> {
+        self.ncbi_strain_identifiers.clone()
+    }
+
+    /// Get `ncbi_assembly_level`
+    pub fn get_ncbi_assembly_level(&self) -> Option<String> {
+        self.ncbi_assembly_level.clone()
+    }
+
+    /// Get `ncbi_assembly_name`
+    pub fn get_ncbi_assembly_name(&self) -> Option<String> {
+        self.ncbi_assembly_name.clone()
+    }
+
+    /// Get `ncbi_assembly_type`
+    pub fn get_ncbi_assembly_type(&self) -> Option<String> {
+        self.ncbi_assembly_type.clone()
+    }
+
+    /// Get `ncbi_bioproject`
+    pub fn get_ncbi_bioproject(&self) -> Option<String> {
+        self.ncbi_bioproject.clone()
+    }
+
+    /// Get `ncbi_biosample`
+    pub fn get_ncbi_biosample(&self) -> Option<String> {
+        self.ncbi_biosample.clone()
+    }
+
+    /// Get `ncbi_country`
+    pub fn get_ncbi_country(&self) -> Option<String> {
+        self.ncbi_country.clone()
+    }
+
+    /// Get `ncbi_date`
+    pub fn get_ncbi_date(&self) -> Option<String> {
+        self.ncbi_date.clone()
+    }
+
+    /// Get `ncbi_genome_category`
+    pub fn get_ncbi_genome_category(&self) -> Option<String> {
+        self.ncbi_genome_category.clone()
+    }
+
+    /// Get `ncbi_isolate`
+    pub fn get_ncbi_isolate(&self) -> Option<String> {
+        self.ncbi_isolate.clone()
+    }
+
+    /// Get `ncbi_isolation_source`
+    pub fn get_ncbi_isolation_source(&self) -> Option<String> {
+        self.ncbi_isolation_source.clone()
+    }
+
+    /// Get `ncbi_lat_lon`
+    pub fn get_ncbi_lat_lon(&self) -> Option<String> {
+        self.ncbi_lat_lon.clone()
+    }
+
+    /// Get `ncbi_molecule_count`
+    pub fn get_ncbi_molecule_count(&self) -> Option<String> {
+        self.ncbi_molecule_count.clone()
+    }
+
+    /// Get `ncbi_cds_count`
+    pub fn get_ncbi_cds_count(&self) -> Option<String> {
+        self.ncbi_cds_count.clone()
+    }
+
+    /// Get `ncbi_refseq_category`
+    pub fn get_ncbi_refseq_category(&self) -> Option<String> {
+        self.ncbi_refseq_category.clone()
+    }
+
+    /// Get `ncbi_seq_rel_date`
+    pub fn get_ncbi_seq_rel_date(&self) -> Option<String> {
+        self.ncbi_seq_rel_date.clone()
+    }
+
+    /// Get `ncbi_spanned_gaps`
+    pub fn get_ncbi_spanned_gaps(&self) -> Option<String> {
+        self.ncbi_spanned_gaps.clone()
+    }
+
+    /// Get `ncbi_species_taxid`
+    pub fn get_ncbi_species_taxid(&self) -> Option<String> {
+        self.ncbi_species_taxid.clone()
+    }
+
+    /// Get `ncbi_ssu_count`
+    pub fn get_ncbi_ssu_count(&self) -> Option<String> {
+        self.ncbi_ssu_count.clone()
+    }
+
+    /// Get `ncbi_submitter`
+    pub fn get_ncbi_submitter(&self) -> Option<String> {
+        self.ncbi_submitter.clone()
+    }
+
+    /// Get `ncbi_taxid`
+    pub fn get_ncbi_taxid(&self) -> Option<String> {
+        self.ncbi_taxid.clone()
+    }
+
+    /// Get `ncbi_total_gap_length`
+    pub fn get_ncbi_total_gap_length(&self) -> Option<String> {
+        self.ncbi_total_gap_length.clone()
+    }
+
+    /// Get `ncbi_translation_table`
+    pub fn get_ncbi_translation_table(&self) -> Option<String> {
+        self.ncbi_translation_table.clone()
+    }
+
+    /// Get `ncbi_trna_count`
+    pub fn get_ncbi_trna_count(&self) -> Option<String> {
+        self.ncbi_trna_count.clone()
+    }
+
+    /// Get `ncbi_unspanned_gaps`
+    pub fn get_ncbi_unspanned_gaps(&self) -> Option<String> {
+        self.ncbi_unspanned_gaps.clone()
+    }
+
+    /// Get `ncbi_version_status`
+    pub fn get_ncbi_version_status(&self) -> Option<String> {
+        self.ncbi_version_status.clone()
+    }
+
+    /// Get `ncbi_wgs_master`
+    pub fn get_ncbi_wgs_master(&self) -> Option<String> {
+        self.ncbi_wgs_master.clone()
+    }
+}
+
+impl MetadataTypeMaterial {
+    /// Get `gtdb_type_designation`
+    pub fn get_gtdb_type_designation(&self) -> Option<String> {
+        self.gtdb_type_designation.clone()
+    }
+
+    /// Get `gtdb_type_designation_sources`
+    pub fn get_gtdb_type_designation_sources(&self) -> Option<String> {
+        self.gtdb_type_designation_sources.clone()
+    }
+
+    /// Get `lpsn_type_designation`
+    pub fn get_lpsn_type_designation(&self) -> Option<String> {
+        self.lpsn_type_designation.clone()
+    }
+
+    /// Get `dsmz_type_designation`
+    pub fn get_dsmz_type_designation(&self) -> Option<String> {
+        self.dsmz_type_designation.clone()
+    }
+
+    /// Get `lpsn_priority_year`
+    pub fn get_lpsn_priority_year(&self) -> Option<i32> {
+        self.lpsn_priority_year
+    }
+
+    /// Get `gtdb_type_species_of_genus`
+    pub fn is_gtdb_type_species_of_genus(&self) -> Option<bool> {
+        self.gtdb_type_species_of_genus
+    }
+}
+
+impl MetadataTaxonomy {
+    /// Get `ncbi_taxonomy`
+    pub fn get_ncbi_taxonomy(&self) -> Option<String> {
+        self.ncbi_taxonomy.clone()
+    }
+
+    /// Get `ncbi_taxonomy_unfiltered`
+    pub fn get_ncbi_taxonomy_unfiltered(&self) -> Option<String> {
+        self.ncbi_taxonomy_unfiltered.clone()
+    }
+
+    /// Get `gtdb_representative`
+    pub fn is_gtdb_representative(&self) -> bool {
+        self.gtdb_representative
+    }
+
+    /// Get `gtdb_genome_representative`
+    pub fn get_gtdb_genome_representative(&self) -> Option<String> {
+        self.gtdb_genome_representative.clone()
+    }
+
+    /// Get `ncbi_type_material_designation`
+    pub fn get_ncbi_type_material_designation(&self) -> Option<String> {
+        self.ncbi_type_material_designation.clone()
+    }
+
+    /// Get `gtdb_domain`
+    pub fn get_gtdb_domain(&self) -> Option<String> {
+        self.gtdb_domain.clone()
+    }
+
+    /// Get `gtdb_phylum`
+    pub fn get_gtdb_phylum(&self) -> Option<String> {
+        self.gtdb_phylum.clone()
+    }
+
+    /// Get `gtdb_class`
+    pub fn get_gtdb_class(&self) -> Option<String> {
+        self.gtdb_class.clone()
+    }
+
+    /// Get `gtdb_order`
+    pub fn get_gtdb_order(&self) -> Option<String> {
+        self.gtdb_order.clone()
+    }
+
+    /// Get `gtdb_family`
+    pub fn get_gtdb_family(&self) -> Option<String> {
+        self.gtdb_family.clone()
+    }
+
+    /// Get `gtdb_genus`
+    pub fn get_gtdb_genus(&self) -> Option<String> {
+        self.gtdb_genus.clone()
+    }
+
+    /// Get `gtdb_species`
+    pub fn get_gtdb_species(&self) -> Option<String> {
+        self.gtdb_species.clone()
+    }
+}
+
+impl Taxon {
+    /// Get `taxon`
+    pub fn get_taxon(&self) -> Option<String> {
+        self.taxon.clone()
+    }
+
+    /// Get `taxon_id`
+    pub fn get_taxon_id(&self) -> Option<String> {
+        self.taxon_id.clone()
+    }
+}
+
+/// A taxon of a lineage and the URL of its NCBI Taxonomy page, if linked
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
+pub struct TaxonLink {
+    taxon: String,
+    url: Option<String>,
+}
+
+impl TaxonLink {
+    /// Get the taxon, e.g. "p__Pseudomonadota"
+    pub fn get_taxon(&self) -> &str {
+        &self.taxon
+    }
+
+    /// Get the URL of the NCBI Taxonomy page of the taxon
+    pub fn get_url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+/// Parse a lineage whose taxa may be HTML links, e.g.
+/// `<a target="_blank" href="https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/2/">d__Bacteria</a>; c__`
+fn parse_taxon_links(html: &str) -> Vec<TaxonLink> {
+    html.split(';')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            let url = x
+                .split_once("href=\"")
+                .and_then(|(_, rest)| rest.split_once('"'))
+                .map(|(url, _)| url.to_string());
+            let taxon = match x.strip_prefix("<a") {
+                Some(tag) => tag
+                    .split_once('>')
+                    .map_or(tag, |(_, rest)| rest)
+                    .trim_end_matches("</a>"),
+                None => x,
+            };
+            TaxonLink {
+                taxon: taxon.trim().to_string(),
+                url,
+            }
+        })
+        .collect()
+}
+
+/// Lineage of the taxa of links, e.g. "d__Bacteria; p__Pseudomonadota; c__"
+fn plain_lineage(links: &[TaxonLink]) -> String {
+    links
+        .iter()
+        .map(|x| x.taxon.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 // GTDB Genome metadata API Struct
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
 pub struct GenomeMetadata {
     accession: Option<String>,
     #[serde(alias = "isNcbiSurveillance")]
     is_ncbi_surveillance: Option<bool>,
 }
 
+impl GenomeMetadata {
+    /// Get genome accession
+    pub fn get_accession(&self) -> Option<String> {
+        self.accession.clone()
+    }
+
+    /// Check if the genome is a NCBI surveillance record
+    pub fn is_ncbi_surveillance(&self) -> Option<bool> {
+        self.is_ncbi_surveillance
+    }
+}
+
 // GTDB Genome history API structs
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
 pub struct History {
     release: Option<String>,
     d: Option<String>,
@@ -186,137 +837,1292 @@ pub struct History {
     s: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+impl History {
+    /// Get release name, e.g. "R207"
+    pub fn get_release(&self) -> Option<String> {
+        self.release.clone()
+    }
+
+    /// Get taxon of the genome at `rank` in this release
+    pub fn get_taxon(&self, rank: Rank) -> Option<String> {
+        let taxon = match rank {
+            Rank::Domain => &self.d,
+            Rank::Phylum => &self.p,
+            Rank::Class => &self.c,
+            Rank::Order => &self.o,
+            Rank::Family => &self.f,
+            Rank::Genus => &self.g,
+            Rank::Species => &self.s,
+        };
+        taxon.clone()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(transparent)]
+#[non_exhaustive]
 pub struct GenomeTaxonHistory {
-    data: Vec<History>,
+    pub(crate) data: Vec<History>,
 }
 
-pub fn get_genome_metadata(args: GenomeArgs) -> Result<()> {
-    let genome_api: Vec<GenomeAPI> = args
-        .get_accession()
-        .iter()
-        .map(|x| GenomeAPI::from(x.to_string()))
-        .collect();
+impl GenomeTaxonHistory {
+    /// Get the taxonomy of the genome in each release
+    pub fn get_data(&self) -> &[History] {
+        &self.data
+    }
 
-    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    /// Only keep the taxonomy of the genome in `release`
+    fn retain_release(&mut self, release: &str) {
+        let release = release_number(release);
+        self.data
+            .retain(|x| x.get_release().as_deref().map(release_number) == Some(release));
+    }
+}
 
-    for accession in genome_api {
-        let request_url = accession.request(GenomeRequestType::Metadata);
+/// Resource links of a genome on NCBI and ENA
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GenomeLinks {
+    // Genome accession as known by GTDB
+    accession: String,
+    // NCBI Assembly page
+    ncbi_assembly: Option<String>,
+    // NCBI BioSample page
+    ncbi_biosample: Option<String>,
+    // NCBI BioProject page
+    ncbi_bioproject: Option<String>,
+    // ENA browser page of the GenBank assembly
+    ena: Option<String>,
+}
 
-        let response = agent.get(&request_url).call().map_err(|e| match e {
-            ureq::Error::Status(code, _) => {
-                anyhow!("The server returned an unexpected status code ({})", code)
-            }
-            _ => anyhow!("There was an error making the request or receiving the response."),
-        })?;
+impl GenomeLinks {
+    const HEADER: &'static str = "accession\tncbi_assembly\tncbi_biosample\tncbi_bioproject\tena";
 
-        let genome_card: GenomeMetadata = response.into_json()?;
+    /// Tab-separated representation of the links, missing links are left empty
+    fn to_tsv_row(&self) -> String {
+        [
+            Some(self.accession.clone()),
+            self.ncbi_assembly.clone(),
+            self.ncbi_biosample.clone(),
+            self.ncbi_bioproject.clone(),
+            self.ena.clone(),
+        ]
+        .iter()
+        .map(|x| x.clone().unwrap_or_default())
+        .collect::<Vec<String>>()
+        .join("\t")
+    }
+}
 
-        let genome_string = serde_json::to_string_pretty(&genome_card)?;
+/// NCBI fields use "none" or "na" for missing values
+fn ncbi_value(value: &Option<String>) -> Option<String> {
+    value
+        .as_ref()
+        .filter(|v| !v.is_empty() && *v != "none" && *v != "na")
+        .cloned()
+}
 
-        let output = args.get_output();
-        if let Some(path) = output {
-            let mut file = OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(&path)
-                .with_context(|| format!("Failed to create file {}", path))?;
-            writeln!(file, "{}", genome_string)
-                .with_context(|| format!("Failed to write to {}", path))?;
-        } else {
-            writeln!(io::stdout(), "{}", genome_string)?;
+impl From<&GenomeCard> for GenomeLinks {
+    fn from(card: &GenomeCard) -> Self {
+        let ncbi = &card.metadata_ncbi;
+        let genbank = ncbi_value(&ncbi.ncbi_genbank_assembly_accession);
+        GenomeLinks {
+            accession: card.genome.accession.clone(),
+            ncbi_assembly: Some(format!(
+                "https://www.ncbi.nlm.nih.gov/datasets/genome/{}/",
+                genbank.clone().unwrap_or(card.genome.accession.clone())
+            )),
+            ncbi_biosample: ncbi_value(&ncbi.ncbi_biosample)
+                .map(|x| format!("https://www.ncbi.nlm.nih.gov/biosample/{}", x)),
+            ncbi_bioproject: ncbi_value(&ncbi.ncbi_bioproject)
+                .map(|x| format!("https://www.ncbi.nlm.nih.gov/bioproject/{}", x)),
+            ena: genbank.map(|x| format!("https://www.ebi.ac.uk/ena/browser/view/{}", x)),
         }
     }
+}
 
+/// Write a line of output to the file `output`, appending to it, or to
+/// stdout
+fn write_line(line: &str, output: Option<String>) -> Result<()> {
+    if let Some(path) = output {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create file {}", path))?;
+        writeln!(file, "{}", line).with_context(|| format!("Failed to write to {}", path))?;
+    } else {
+        writeln!(io::stdout(), "{}", line)?;
+    }
     Ok(())
 }
 
-pub fn get_genome_card(args: GenomeArgs) -> Result<()> {
-    let genome_api: Vec<GenomeAPI> = args
-        .get_accession()
+/// Write the line of a genome to its own file, replacing it
+fn write_accession_file(line: &str, path: &str) -> Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::write(path, format!("{}\n", line)).with_context(|| format!("Failed to write to {}", path))
+}
+
+/// Accessions to fetch, without those whose `--out` file already exists
+/// and is not empty with `--skip-existing`
+fn pending_accessions(args: &GenomeArgs) -> Vec<String> {
+    let accessions = args.get_accession();
+    if !(args.is_skip_existing() && args.is_per_accession()) {
+        return accessions;
+    }
+    accessions
+        .into_iter()
+        .filter(|x| {
+            args.get_accession_output(x)
+                .and_then(|path| fs::metadata(path).ok())
+                .map_or(true, |x| x.len() == 0)
+        })
+        .collect()
+}
+
+/// Files a genome query would write, one per accession if `--out` is a
+/// template
+pub fn target_files(args: &GenomeArgs) -> Vec<String> {
+    match args.is_per_accession() {
+        true => pending_accessions(args)
+            .iter()
+            .filter_map(|x| args.get_accession_output(x))
+            .collect(),
+        false => args.get_output().into_iter().collect(),
+    }
+}
+
+/// Fetch the JSON of every accession with `fetch`, `jobs` at a time, and
+/// write them line by line in the order of the accessions, or each to its
+/// own file if `--out` is a template
+fn write_genome_lines<F>(args: &GenomeArgs, fetch: F) -> Result<()>
+where
+    F: Fn(&str) -> Result<String> + Sync,
+{
+    ensure!(
+        !args.is_skip_existing() || args.is_per_accession(),
+        "--skip-existing needs an --out path containing {}, e.g. cards/{}.json",
+        ACCESSION_PLACEHOLDER,
+        ACCESSION_PLACEHOLDER
+    );
+    let accessions = pending_accessions(args);
+    let skipped = args.get_accession().len() - accessions.len();
+    if skipped > 0 && utils::verbosity() > 0 {
+        eprintln!(
+            "Skipped {} accession(s) whose output file already exists",
+            skipped
+        );
+    }
+
+    let mut errors = ItemErrors::new(accessions.len(), args.is_fail_fast());
+    utils::parallel_for_each_ordered(
+        accessions,
+        args.get_jobs(),
+        |accession| {
+            let line = interrupt::check()
+                .map_err(anyhow::Error::from)
+                .and_then(|_| fetch(&accession));
+            (accession, line)
+        },
+        |(accession, line): (String, Result<String>)| {
+            let Some(line) = errors.check(&accession, line)? else {
+                return Ok(());
+            };
+            match args.get_accession_output(&accession) {
+                Some(path) if args.is_per_accession() => write_accession_file(&line, &path),
+                _ => write_line(&line, args.get_output()),
+            }
+        },
+    )?;
+    errors.finish()
+}
+
+pub fn get_genome_metadata(args: GenomeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    write_genome_lines(&args, |accession| {
+        let request_url =
+            GenomeAPI::from(accession.to_string()).request(GenomeRequestType::Metadata);
+
+        let response = agent
+            .get(&request_url)
+            .call()
+            .map_err(|e| XgtError::from_request(e, format!("Genome {} not found", accession)))?;
+
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body)?;
+        let genome_card: GenomeMetadata = drift::decode(&body)?;
+
+        Ok(utils::to_json(&genome_card)?)
+    })
+}
+
+/// URLs of the requests a genome query of type `request_type` would send,
+/// the cards and metadata with `--full` and the metadata checked by
+/// surveillance filters, none for local queries
+pub fn request_urls(args: &GenomeArgs, request_type: GenomeRequestType) -> Vec<String> {
+    if args.is_local() {
+        return vec![];
+    }
+    // --full also requests the metadata of every genome, and surveillance
+    // filters request it first
+    let mut request_types = match args.get_full() {
+        Some(_) => vec![GenomeRequestType::Card, GenomeRequestType::Metadata],
+        None => vec![request_type],
+    };
+    if args.get_surveillance().is_some() && !request_types.contains(&GenomeRequestType::Metadata) {
+        request_types.insert(0, GenomeRequestType::Metadata);
+    }
+    pending_accessions(args)
+        .into_iter()
+        .chain(args.get_compare())
+        .flat_map(|x| {
+            request_types
+                .iter()
+                .map(|request_type| GenomeAPI::from(x.clone()).request(*request_type))
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// Fields of a genome summary card
+pub(crate) fn card_summary(card: &GenomeCard) -> Vec<(&'static str, String)> {
+    let to_string = |x: Option<String>| x.unwrap_or_default();
+    vec![
+        ("GTDB taxonomy", card.get_gtdb_taxonomy()),
+        ("NCBI taxonomy", to_string(card.get_ncbi_taxonomy())),
+        (
+            "GTDB species representative",
+            card.is_gtdb_representative().to_string(),
+        ),
+        (
+            "CheckM completeness",
+            to_string(card.get_checkm_completeness()),
+        ),
+        (
+            "CheckM contamination",
+            to_string(card.get_checkm_contamination()),
+        ),
+        (
+            "Genome size",
+            to_string(card.get_genome_size().map(|x| x.to_string())),
+        ),
+        (
+            "Contigs",
+            to_string(card.get_contig_count().map(|x| x.to_string())),
+        ),
+        ("NCBI taxid", to_string(card.get_ncbi_taxid())),
+    ]
+}
+
+/// Render genome cards as an HTML report of summary cards
+fn format_cards_report(cards: &[(String, GenomeCard)]) -> String {
+    cards
         .iter()
-        .map(|x| GenomeAPI::from(x.to_string()))
-        .collect();
+        .fold(
+            Report::new("xgt genome cards"),
+            |report, (accession, card)| report.card(accession, &card_summary(card)),
+        )
+        .render()
+}
 
+pub fn get_genome_card(args: GenomeArgs) -> Result<()> {
     let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
 
-    for accession in genome_api {
-        let request_url = accession.request(GenomeRequestType::Card);
+    if args.get_outfmt() == OutputFormat::Html {
+        let mut cards = Vec::new();
+        let result = utils::for_each_item(args.get_accession(), args.is_fail_fast(), |accession| {
+            cards.push((accession.to_string(), fetch_genome_card(&agent, accession)?));
+            Ok(())
+        });
+        let report = format_cards_report(&cards);
+        utils::write_to_output(report.as_bytes(), args.get_output())?;
+        return result;
+    }
 
-        let response = agent.get(&request_url).call().map_err(|e| match e {
-            ureq::Error::Status(code, _) => {
-                anyhow!("The server returned an unexpected status code ({})", code)
-            }
-            _ => anyhow!("There was an error making the request or receiving the response."),
-        })?;
+    write_genome_lines(&args, |accession| {
+        let genome_card = fetch_genome_card(&agent, accession)?;
+        Ok(utils::to_json(&card_json(genome_card, args.is_raw())?)?)
+    })
+}
+
+/// JSON of a genome card, as sent by GTDB if `raw` and otherwise with
+/// plain NCBI lineages and typed numbers and booleans
+fn card_json(mut genome_card: GenomeCard, raw: bool) -> serde_json::Result<serde_json::Value> {
+    if raw {
+        return serde_json::to_value(&genome_card);
+    }
+    genome_card.strip_link_markup();
+    let mut value = serde_json::to_value(&genome_card)?;
+    values::normalize_json(&mut value);
+    Ok(value)
+}
 
-        let genome_card: GenomeCard = response.into_json()?;
+/// Fetch the metadata of a genome
+pub(crate) fn fetch_genome_metadata(
+    transport: &dyn Transport,
+    accession: &str,
+) -> Result<GenomeMetadata, XgtError> {
+    let request_url = GenomeAPI::from(accession.to_string()).request(GenomeRequestType::Metadata);
+    get_json(
+        transport,
+        &request_url,
+        format!("Genome {} not found", accession),
+    )
+}
 
-        let genome_string = serde_json::to_string_pretty(&genome_card)?;
+/// Whether each genome of `accessions` is from an NCBI surveillance
+/// project, from the `is_ncbi_surveillance` flag of its metadata fetched
+/// with `metadata`, `jobs` at a time, or the error of its metadata
+pub(crate) fn surveillance_flags<F>(
+    accessions: Vec<String>,
+    jobs: usize,
+    metadata: F,
+) -> Result<Vec<Result<bool, XgtError>>, XgtError>
+where
+    F: Fn(&str) -> Result<GenomeMetadata, XgtError> + Sync,
+{
+    utils::parallel_map(accessions, jobs, |accession| {
+        interrupt::check()?;
+        Ok(metadata(&accession).map(|x| x.is_ncbi_surveillance().unwrap_or(false)))
+    })
+    .into_iter()
+    .collect()
+}
 
-        let output = args.get_output();
-        if let Some(path) = output {
-            let mut file = OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(&path)
-                .with_context(|| format!("Failed to create file {}", path))?;
-            writeln!(file, "{}", genome_string)
-                .with_context(|| format!("Failed to write to {}", path))?;
-        } else {
-            writeln!(io::stdout(), "{}", genome_string)?;
+/// Genomes of `accessions` kept by `--only-surveillance` (`only`) or
+/// `--exclude-surveillance`, from their surveillance flag fetched with
+/// `metadata`. Genomes whose metadata could not be fetched are kept, so
+/// that their error is reported by the query.
+fn select_surveillance<F>(
+    accessions: Vec<String>,
+    only: bool,
+    jobs: usize,
+    metadata: F,
+) -> Result<Vec<String>, XgtError>
+where
+    F: Fn(&str) -> Result<GenomeMetadata, XgtError> + Sync,
+{
+    let flags = surveillance_flags(accessions.clone(), jobs, metadata)?;
+    Ok(accessions
+        .into_iter()
+        .zip(flags)
+        .filter(|(_, flag)| !matches!(flag, Ok(x) if *x != only))
+        .map(|(accession, _)| accession)
+        .collect())
+}
+
+/// Drop the accessions of the genomes skipped by `--exclude-surveillance`
+/// or `--only-surveillance` from the arguments, fetching the metadata of
+/// every genome `--jobs` at a time
+pub fn filter_surveillance(mut args: GenomeArgs) -> Result<GenomeArgs> {
+    let Some(only) = args.get_surveillance() else {
+        return Ok(args);
+    };
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let total = args.accession.len();
+    args.accession =
+        select_surveillance(args.get_accession(), only, args.get_jobs(), |accession| {
+            fetch_genome_metadata(&agent, accession)
+        })?;
+    if total > args.accession.len() && utils::verbosity() > 0 {
+        eprintln!(
+            "Skipped {} of {} genome(s) {} NCBI surveillance projects",
+            total - args.accession.len(),
+            total,
+            if only { "not from" } else { "from" }
+        );
+    }
+    Ok(args)
+}
+
+/// Merge the JSON of a genome card and of its metadata into one record:
+/// `{"card": ..., "metadata": ...}` if `nested`, else a single object of
+/// the fields of the card sections and of the metadata. Fields found in
+/// several sections with different values are named after their section,
+/// e.g. `metadata.accession`.
+fn merge_card_metadata(
+    card: serde_json::Value,
+    metadata: serde_json::Value,
+    nested: bool,
+) -> serde_json::Value {
+    if nested {
+        return serde_json::json!({ "card": card, "metadata": metadata });
+    }
+    let mut record = serde_json::Map::new();
+    let mut insert =
+        |section: &str, field: String, value: serde_json::Value| match record.get(&field) {
+            None => {
+                record.insert(field, value);
+            }
+            Some(x) if *x == value => (),
+            Some(_) => {
+                record.insert(format!("{}.{}", section, field), value);
+            }
+        };
+    let sections = [("card", card), ("metadata", metadata)];
+    for (name, section) in sections {
+        let serde_json::Value::Object(fields) = section else {
+            continue;
+        };
+        for (field, value) in fields {
+            match value {
+                serde_json::Value::Object(subfields) if name == "card" => {
+                    for (subfield, value) in subfields {
+                        insert(&field, subfield, value);
+                    }
+                }
+                value => insert(name, field, value),
+            }
         }
     }
+    serde_json::Value::Object(record)
+}
 
-    Ok(())
+/// Fetch both the card and the metadata of genomes and write them merged
+/// into one record per genome, see `merge_card_metadata`
+pub fn get_genome_full(args: GenomeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let nested = args.get_full().as_deref() == Some("nested");
+
+    write_genome_lines(&args, |accession| {
+        let card = card_json(fetch_genome_card(&agent, accession)?, args.is_raw())?;
+        let mut metadata = serde_json::to_value(fetch_genome_metadata(&agent, accession)?)?;
+        if !args.is_raw() {
+            values::normalize_json(&mut metadata);
+        }
+        Ok(utils::to_json(&merge_card_metadata(
+            card, metadata, nested,
+        ))?)
+    })
+}
+
+/// Fetch the taxonomy of a genome in each GTDB release
+pub(crate) fn fetch_genome_history(
+    transport: &dyn Transport,
+    accession: &str,
+) -> Result<GenomeTaxonHistory, XgtError> {
+    let request_url =
+        GenomeAPI::from(accession.to_string()).request(GenomeRequestType::TaxonHistory);
+    get_json(
+        transport,
+        &request_url,
+        format!("Genome {} not found", accession),
+    )
 }
 
 pub fn get_genome_taxon_history(args: GenomeArgs) -> Result<()> {
-    let genome_api: Vec<GenomeAPI> = args
-        .get_accession()
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    write_genome_lines(&args, |accession| {
+        let mut genome = fetch_genome_history(&agent, accession)?;
+        if let Some(release) = args.get_release() {
+            genome.retain_release(&release);
+            ensure!(
+                !genome.data.is_empty(),
+                "Genome {} is not in GTDB {}",
+                accession,
+                release
+            );
+        }
+
+        Ok(utils::to_json(&genome)?)
+    })
+}
+
+/// Fetch and parse the GTDB card of a single genome
+pub(crate) fn fetch_genome_card(
+    transport: &dyn Transport,
+    accession: &str,
+) -> Result<GenomeCard, XgtError> {
+    let request_url = GenomeAPI::from(accession.to_string()).request(GenomeRequestType::Card);
+    get_json(
+        transport,
+        &request_url,
+        format!("Genome {} not found", accession),
+    )
+}
+
+/// Fetch the genome cards of `accessions`, `jobs` at a time, in the order
+/// of the accessions
+#[cfg(feature = "async")]
+pub(crate) fn fetch_genome_cards(
+    accessions: Vec<String>,
+    jobs: usize,
+    disable_certificate_verification: bool,
+) -> Result<Vec<Result<GenomeCard, XgtError>>, XgtError> {
+    let client = async_client(disable_certificate_verification)?;
+    Ok(utils::concurrent_map(accessions, jobs, |accession| {
+        let client = &client;
+        async move {
+            interrupt::check()?;
+            client.genome_card(&accession).await
+        }
+    }))
+}
+
+#[cfg(not(feature = "async"))]
+pub(crate) fn fetch_genome_cards(
+    accessions: Vec<String>,
+    jobs: usize,
+    disable_certificate_verification: bool,
+) -> Result<Vec<Result<GenomeCard, XgtError>>, XgtError> {
+    let agent = utils::get_agent(disable_certificate_verification)?;
+    Ok(utils::parallel_map(accessions, jobs, |accession| {
+        interrupt::check()?;
+        fetch_genome_card(&agent, &accession)
+    }))
+}
+
+/// Fetch the taxon history of the genomes `accessions`, `jobs` at a time,
+/// in the order of the accessions
+#[cfg(feature = "async")]
+pub(crate) fn fetch_genome_histories(
+    accessions: Vec<String>,
+    jobs: usize,
+    disable_certificate_verification: bool,
+) -> Result<Vec<Result<GenomeTaxonHistory, XgtError>>, XgtError> {
+    let client = async_client(disable_certificate_verification)?;
+    Ok(utils::concurrent_map(accessions, jobs, |accession| {
+        let client = &client;
+        async move {
+            interrupt::check()?;
+            let data = client.genome_history(&accession).await?;
+            Ok(GenomeTaxonHistory { data })
+        }
+    }))
+}
+
+#[cfg(not(feature = "async"))]
+pub(crate) fn fetch_genome_histories(
+    accessions: Vec<String>,
+    jobs: usize,
+    disable_certificate_verification: bool,
+) -> Result<Vec<Result<GenomeTaxonHistory, XgtError>>, XgtError> {
+    let agent = utils::get_agent(disable_certificate_verification)?;
+    Ok(utils::parallel_map(accessions, jobs, |accession| {
+        interrupt::check()?;
+        fetch_genome_history(&agent, &accession)
+    }))
+}
+
+#[cfg(feature = "async")]
+fn async_client(disable_certificate_verification: bool) -> Result<AsyncGtdbClient, XgtError> {
+    match disable_certificate_verification {
+        true => AsyncGtdbClient::insecure(),
+        false => AsyncGtdbClient::new(),
+    }
+}
+
+pub fn get_genome_links(args: GenomeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    let mut table = vec![GenomeLinks::HEADER.to_string()];
+    let result = utils::for_each_item(args.get_accession(), args.is_fail_fast(), |accession| {
+        let genome_card = fetch_genome_card(&agent, accession)?;
+        table.push(GenomeLinks::from(&genome_card).to_tsv_row());
+        Ok(())
+    });
+    table.push(String::new());
+
+    utils::write_to_output(table.join("\n").as_bytes(), args.get_output())?;
+    result
+}
+
+/// Write a TSV table of the type designations and LPSN link of each genome
+/// with its GTDB species
+pub fn get_genome_nomenclature(args: GenomeArgs) -> Result<()> {
+    let mut errors = ItemErrors::new(args.get_accession().len(), args.is_fail_fast());
+    let cards = fetch_genome_cards(
+        args.get_accession(),
+        args.get_jobs(),
+        args.get_disable_certificate_verification(),
+    )?;
+
+    let mut rows = Vec::new();
+    for (accession, card) in args.get_accession().iter().zip(cards) {
+        if let Some(card) = errors.check(accession, card.map_err(anyhow::Error::from))? {
+            rows.push(Nomenclature::from_card(accession, &card).to_fields());
+        }
+    }
+    let table = utils::render_table(&Nomenclature::HEADER, &rows, &OutputFormat::Tsv);
+    utils::write_to_output(table.as_bytes(), args.get_output())?;
+
+    errors.finish()
+}
+
+/// Card sections compared by `--compare`: assembly statistics,
+/// quality metrics and taxonomy
+const COMPARED_SECTIONS: [&str; 3] = ["metadata_nucleotide", "metadata_gene", "metadata_taxonomy"];
+
+/// Flatten the compared sections of a card into (field, value) pairs
+fn comparable_fields(card: &GenomeCard) -> Result<Vec<(String, String)>> {
+    let value = serde_json::to_value(card)?;
+    Ok(COMPARED_SECTIONS
         .iter()
-        .map(|x| GenomeAPI::from(x.to_string()))
-        .collect();
+        .filter_map(|section| value.get(section).and_then(|v| v.as_object()))
+        .flatten()
+        .map(|(field, v)| {
+            let v = match v {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(x) => x.clone(),
+                x => x.to_string(),
+            };
+            (field.clone(), v)
+        })
+        .collect())
+}
+
+/// Side-by-side table of two genome cards, fields which differ are
+/// marked with a `*` in the last column
+fn compare_cards(first: &GenomeCard, second: &GenomeCard) -> Result<String> {
+    let mut table = vec![format!(
+        "field\t{}\t{}\tdiffers",
+        first.genome.accession, second.genome.accession
+    )];
+    for ((field, a), (_, b)) in comparable_fields(first)?
+        .into_iter()
+        .zip(comparable_fields(second)?)
+    {
+        let marker = if a != b { "*" } else { "" };
+        table.push(format!("{}\t{}\t{}\t{}", field, a, b, marker));
+    }
+    table.push(String::new());
+    Ok(table.join("\n"))
+}
+
+/// Read the card of a genome cached by `--diff-cache`, none if the genome
+/// was never cached
+fn read_cached_card(path: &Path) -> Result<Option<GenomeCard>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let card = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse cached card {}", path.display()))?;
+    Ok(Some(card))
+}
 
+fn write_cached_card(path: &Path, card: &GenomeCard) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::write(path, utils::to_json(card)?)
+        .with_context(|| format!("Failed to write to {}", path.display()))
+}
+
+/// Fields of the compared sections whose value changed between the cached
+/// and the current card, as (field, cached, current)
+fn changed_fields(cached: &GenomeCard, current: &GenomeCard) -> Result<Vec<[String; 3]>> {
+    let cached: HashMap<String, String> = comparable_fields(cached)?.into_iter().collect();
+    Ok(comparable_fields(current)?
+        .into_iter()
+        .filter_map(|(field, value)| {
+            let previous = cached.get(&field).cloned().unwrap_or_default();
+            (previous != value).then_some([field, previous, value])
+        })
+        .collect())
+}
+
+/// Compare the cards of the genomes with their copies cached by the
+/// previous run and write a TSV table of the fields which changed, then
+/// cache the current cards. Genomes seen for the first time are only
+/// cached.
+pub fn diff_cached_cards(args: GenomeArgs) -> Result<()> {
+    let cache = utils::cache_dir()?.join("cards");
+    let mut errors = ItemErrors::new(args.get_accession().len(), args.is_fail_fast());
+    let cards = fetch_genome_cards(
+        args.get_accession(),
+        args.get_jobs(),
+        args.get_disable_certificate_verification(),
+    )?;
+
+    let mut rows = Vec::new();
+    let mut fresh = Vec::new();
+    for (accession, card) in args.get_accession().iter().zip(cards) {
+        let compared = card.map_err(anyhow::Error::from).and_then(|card| {
+            let path = cache.join(format!("{}.json", strip_gtdb_prefix(accession)));
+            let changes = match read_cached_card(&path)? {
+                Some(cached) => changed_fields(&cached, &card)?,
+                None => {
+                    if utils::verbosity() > 0 {
+                        eprintln!("{}: no cached card, caching it", accession);
+                    }
+                    Vec::new()
+                }
+            };
+            Ok((path, card, changes))
+        });
+        let Some((path, card, changes)) = errors.check(accession, compared)? else {
+            continue;
+        };
+        for [field, previous, value] in changes {
+            rows.push(vec![accession.clone(), field, previous, value]);
+        }
+        fresh.push((path, card));
+    }
+    let table = utils::render_table(
+        &["accession", "field", "cached", "current"],
+        &rows,
+        &OutputFormat::Tsv,
+    );
+    utils::write_to_output(table.as_bytes(), args.get_output())?;
+
+    // Only cache the cards once their changes are reported, so that an
+    // aborted run reports them again
+    for (path, card) in fresh {
+        write_cached_card(&path, &card)?;
+    }
+
+    errors.finish()
+}
+
+pub fn compare_genomes(args: GenomeArgs) -> Result<()> {
     let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let other = args
+        .get_compare()
+        .context("Missing accession to compare with")?;
+    let other_card = fetch_genome_card(&agent, &other)?;
+
+    utils::for_each_item(args.get_accession(), args.is_fail_fast(), |accession| {
+        let genome_card = fetch_genome_card(&agent, accession)?;
+        let table = compare_cards(&genome_card, &other_card)?;
+        utils::write_to_output(table.as_bytes(), args.get_output())
+    })
+}
 
-    for accession in genome_api {
-        let request_url = accession.request(GenomeRequestType::TaxonHistory);
+/// Print the taxonomy of genomes, looked up with a few bulk searches
+/// unless their NCBI taxids are requested
+pub fn get_genome_taxonomy(args: GenomeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
 
-        let response = agent.get(&request_url).call().map_err(|e| match e {
-            ureq::Error::Status(code, _) => {
-                anyhow!("The server returned an unexpected status code ({})", code)
+    // NCBI taxids are only on genome cards
+    let found = match args.is_with_taxid() {
+        true => HashMap::new(),
+        false => lookup_accessions(&agent, &args.get_accession(), args.get_jobs()),
+    };
+    let mut errors = ItemErrors::new(args.get_accession().len(), args.is_fail_fast());
+    utils::parallel_for_each_ordered(
+        args.get_accession(),
+        args.get_jobs(),
+        |accession| {
+            let line = interrupt::check().and_then(|_| match found.get(&accession) {
+                Some(genome) => Ok(vec![
+                    genome.get_gtdb_taxonomy().unwrap_or_default(),
+                    genome.get_ncbi_taxonomy().unwrap_or_default(),
+                ]),
+                None => fetch_genome_card(&agent, &accession).map(|card| {
+                    vec![
+                        card.get_gtdb_taxonomy(),
+                        card.get_ncbi_taxonomy().unwrap_or_default(),
+                        card.get_ncbi_taxid().unwrap_or_default(),
+                        card.get_ncbi_species_taxid().unwrap_or_default(),
+                    ]
+                }),
+            });
+            (accession, line)
+        },
+        |(accession, fields): (String, Result<Vec<String>, XgtError>)| {
+            let Some(fields) = errors.check(&accession, fields.map_err(anyhow::Error::from))?
+            else {
+                return Ok(());
+            };
+            let mut line = format!("{}\t{}", accession, fields[0]);
+            if args.is_with_ncbi() {
+                line.push('\t');
+                line.push_str(&fields[1]);
             }
-            _ => anyhow!("There was an error making the request or receiving the response."),
-        })?;
+            if args.is_with_taxid() {
+                line.push('\t');
+                line.push_str(&fields[2]);
+                line.push('\t');
+                line.push_str(&fields[3]);
+            }
+            line.push('\n');
+            utils::write_to_output(line.as_bytes(), args.get_output())
+        },
+    )?;
+    errors.finish()
+}
+
+/// Value of the column `name` of a metadata record
+fn record_value(record: &MetadataRecord, name: &str) -> String {
+    record
+        .iter()
+        .find(|(column, _)| column == name)
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
 
-        let genome: GenomeTaxonHistory = response.into_json()?;
+/// Answer genome queries from the cached GTDB metadata, without network
+pub fn get_genome_local(args: GenomeArgs) -> Result<()> {
+    let files = local::metadata_files(
+        &local::metadata_dir()?,
+        args.get_release().map(|x| release_number(&x)),
+    )?;
+    let accessions: HashSet<String> = args
+        .get_accession()
+        .iter()
+        .map(|x| strip_gtdb_prefix(x).to_string())
+        .collect();
+    let records = local::find_records_in_files(&files, &accessions)?;
 
-        let genome_string = serde_json::to_string_pretty(&genome)?;
+    utils::for_each_item(args.get_accession(), args.is_fail_fast(), |accession| {
+        let record = records
+            .get(strip_gtdb_prefix(accession))
+            .with_context(|| format!("{} not found in the cached GTDB metadata", accession))?;
 
-        let output = args.get_output();
-        if let Some(path) = output {
-            let mut file = OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(&path)
-                .with_context(|| format!("Failed to create file {}", path))?;
-            writeln!(file, "{}", genome_string)
-                .with_context(|| format!("Failed to write to {}", path))?;
+        let output = if args.is_taxonomy_only() {
+            let mut line = format!("{}\t{}", accession, record_value(record, "gtdb_taxonomy"));
+            if args.is_with_ncbi() {
+                line.push('\t');
+                line.push_str(&record_value(record, "ncbi_taxonomy"));
+            }
+            line
         } else {
-            writeln!(io::stdout(), "{}", genome_string)?;
+            let record: serde_json::Map<String, serde_json::Value> = record
+                .iter()
+                .map(|(column, value)| (column.clone(), values::typed_value(column, value)))
+                .collect();
+            utils::to_json(&record)?
+        };
+        utils::write_to_output(format!("{}\n", output).as_bytes(), args.get_output())
+    })
+}
+
+/// A rank at which the GTDB and NCBI lineages of a genome diverge
+#[derive(Debug, Clone, PartialEq)]
+struct Discrepancy {
+    rank: Rank,
+    gtdb: String,
+    // None when NCBI does not assign a taxon at this rank
+    ncbi: Option<String>,
+}
+
+impl Discrepancy {
+    fn status(&self) -> &'static str {
+        match self.ncbi {
+            Some(_) => "differs",
+            None => "unassigned",
         }
     }
+}
 
-    Ok(())
+/// Compare GTDB and NCBI lineages rank-by-rank
+fn lineage_discrepancies(gtdb: &str, ncbi: &str) -> Vec<Discrepancy> {
+    let gtdb = taxonomy::parse_lineage(gtdb);
+    let ncbi = taxonomy::parse_lineage(ncbi);
+
+    Rank::ALL
+        .iter()
+        .filter_map(|rank| {
+            let gtdb_taxon = gtdb.get(rank)?;
+            let ncbi_taxon = ncbi.get(rank);
+            (ncbi_taxon != Some(gtdb_taxon)).then(|| Discrepancy {
+                rank: *rank,
+                gtdb: gtdb_taxon.clone(),
+                ncbi: ncbi_taxon.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Summarize how often each rank diverges, most divergent ranks first
+fn discrepancies_summary(discrepancies: &[Discrepancy]) -> String {
+    let mut counts: HashMap<Rank, (usize, usize)> = HashMap::new();
+    for discrepancy in discrepancies {
+        let count = counts.entry(discrepancy.rank).or_default();
+        match discrepancy.ncbi {
+            Some(_) => count.0 += 1,
+            None => count.1 += 1,
+        }
+    }
+
+    let mut ranks: Vec<(Rank, (usize, usize))> = Rank::ALL
+        .iter()
+        .map(|rank| (*rank, counts.get(rank).copied().unwrap_or_default()))
+        .collect();
+    ranks.sort_by_key(|(_, (differs, _))| std::cmp::Reverse(*differs));
+
+    let mut summary = String::from("rank\tdiffers\tunassigned\n");
+    for (rank, (differs, unassigned)) in ranks {
+        summary.push_str(&format!("{}\t{}\t{}\n", rank, differs, unassigned));
+    }
+    summary
+}
+
+pub fn get_genome_discrepancies(args: GenomeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let mut all_discrepancies = Vec::new();
+
+    utils::write_to_output(b"accession\trank\tgtdb\tncbi\tstatus\n", args.get_output())?;
+    let result = utils::for_each_item(args.get_accession(), args.is_fail_fast(), |accession| {
+        let genome_card = fetch_genome_card(&agent, accession)?;
+        let discrepancies = lineage_discrepancies(
+            &genome_card.get_gtdb_taxonomy(),
+            &genome_card.get_ncbi_taxonomy().unwrap_or_default(),
+        );
+
+        let mut rows = String::new();
+        for discrepancy in &discrepancies {
+            rows.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                accession,
+                discrepancy.rank,
+                discrepancy.gtdb,
+                discrepancy.ncbi.clone().unwrap_or_default(),
+                discrepancy.status()
+            ));
+        }
+        utils::write_to_output(rows.as_bytes(), args.get_output())?;
+        all_discrepancies.extend(discrepancies);
+        Ok(())
+    });
+
+    // The summary has its own columns, keep the output a single table
+    eprint!("{}", discrepancies_summary(&all_discrepancies));
+
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cli::genome;
-    use std::path::Path;
+
+    #[test]
+    fn test_pending_accessions() {
+        let dir = std::env::temp_dir().join(format!("xgt-genome-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("GCA_1.json"), "{}\n").unwrap();
+        fs::write(dir.join("GCA_2.json"), "").unwrap();
+        let mut args = genome::GenomeArgs {
+            accession: ["GCA_1", "GCA_2", "GCA_3"].map(String::from).to_vec(),
+            output: Some(dir.join("{accession}.json").display().to_string()),
+            skip_existing: true,
+            ..Default::default()
+        };
+
+        // Empty files were interrupted, so are fetched again
+        assert_eq!(pending_accessions(&args), ["GCA_2", "GCA_3"]);
+        assert_eq!(
+            target_files(&args),
+            [dir.join("GCA_2.json"), dir.join("GCA_3.json")].map(|x| x.display().to_string())
+        );
+        assert_eq!(request_urls(&args, GenomeRequestType::Card).len(), 2);
+
+        args.skip_existing = false;
+        assert_eq!(pending_accessions(&args).len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn card_fixture() -> GenomeCard {
+        let json = std::fs::read_to_string("test/card.json").unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_genome_links_from_card() {
+        let links = GenomeLinks::from(&card_fixture());
+        assert_eq!(links.accession, "GCA_001512625.1");
+        assert_eq!(
+            links.ncbi_assembly,
+            Some("https://www.ncbi.nlm.nih.gov/datasets/genome/GCA_001512625.1/".to_string())
+        );
+        assert_eq!(
+            links.ncbi_biosample,
+            Some("https://www.ncbi.nlm.nih.gov/biosample/SAMN03839937".to_string())
+        );
+        assert_eq!(
+            links.ncbi_bioproject,
+            Some("https://www.ncbi.nlm.nih.gov/bioproject/PRJNA288027".to_string())
+        );
+        assert_eq!(
+            links.ena,
+            Some("https://www.ebi.ac.uk/ena/browser/view/GCA_001512625.1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_link_markup() {
+        let mut card = card_fixture();
+        assert!(card.get_link_ncbi_taxonomy().unwrap().contains("<a "));
+        let links = card.get_ncbi_taxonomy_links();
+
+        card.strip_link_markup();
+        assert_eq!(
+            card.get_link_ncbi_taxonomy(),
+            Some("d__Bacteria; p__Pseudomonadota; c__; o__; f__; g__; s__".to_string())
+        );
+        assert_eq!(card.get_ncbi_taxonomy_links(), links);
+        assert_eq!(links.len(), 7);
+        assert_eq!(links[1].get_taxon(), "p__Pseudomonadota");
+        assert_eq!(
+            links[1].get_url(),
+            Some("https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/1224/")
+        );
+        assert_eq!(links[2].get_url(), None);
+        assert!(!card
+            .get_link_ncbi_taxonomy_unfiltered()
+            .unwrap()
+            .contains('<'));
+
+        let json = serde_json::to_value(&card).unwrap();
+        assert_eq!(
+            json["ncbi_taxonomy_links"][0],
+            serde_json::json!({"taxon": "d__Bacteria", "url": "https://www.ncbi.nlm.nih.gov/data-hub/taxonomy/2/"})
+        );
+        assert!(serde_json::to_value(card_fixture())
+            .unwrap()
+            .get("ncbi_taxonomy_links")
+            .is_none());
+    }
+
+    #[test]
+    fn test_genome_card_taxids() {
+        let card = card_fixture();
+        assert_eq!(card.get_ncbi_taxid(), Some("1977087".to_string()));
+        assert_eq!(card.get_ncbi_species_taxid(), Some("1977087".to_string()));
+    }
+
+    #[test]
+    fn test_format_cards_report() {
+        let cards = vec![("GCA_001512625.1".to_string(), card_fixture())];
+        let html = format_cards_report(&cards);
+        assert!(html.contains("<h2>GCA_001512625.1</h2>"));
+        assert!(html.contains("<dt>NCBI taxid</dt><dd>1977087</dd>"));
+    }
+
+    #[test]
+    fn test_request_urls() {
+        let args = GenomeArgs {
+            accession: vec!["GCA_001512625.1".to_string()],
+            compare: Some("GCF_018555685.1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            request_urls(&args, GenomeRequestType::Card),
+            vec![
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/card",
+                "https://api.gtdb.ecogenomic.org/genome/GCF_018555685.1/card"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_card_metadata() -> Result<()> {
+        let card = card_json(card_fixture(), false)?;
+        let metadata = serde_json::json!({
+            "accession": "GCA_001512625.1",
+            "is_ncbi_surveillance": false,
+        });
+
+        let record = merge_card_metadata(card.clone(), metadata.clone(), false);
+        assert_eq!(record["accession"], "GCA_001512625.1");
+        assert_eq!(record["genome_size"], 2105384);
+        assert_eq!(record["checkm_completeness"], 96.55);
+        assert_eq!(record["is_ncbi_surveillance"], false);
+        assert!(record.get("metadata.accession").is_none());
+        assert!(record.get("metadata_nucleotide").is_none());
+
+        // Fields differing between sections are named after their section
+        let other = serde_json::json!({ "accession": "GCF_001512625.1" });
+        let record = merge_card_metadata(card.clone(), other, false);
+        assert_eq!(record["accession"], "GCA_001512625.1");
+        assert_eq!(record["metadata.accession"], "GCF_001512625.1");
+
+        let record = merge_card_metadata(card.clone(), metadata.clone(), true);
+        assert_eq!(record["card"], card);
+        assert_eq!(record["metadata"], metadata);
+
+        let args = GenomeArgs {
+            accession: vec!["GCA_001512625.1".to_string()],
+            full: Some("flat".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            request_urls(&args, GenomeRequestType::Card),
+            vec![
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/card",
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/metadata"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_surveillance() -> Result<()> {
+        let accessions: Vec<String> = ["GCA_1", "GCA_2", "GCA_3", "GCA_4"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let metadata = |accession: &str| match accession {
+            "GCA_4" => Err(XgtError::NotFound(accession.to_string())),
+            _ => Ok(GenomeMetadata {
+                accession: Some(accession.to_string()),
+                is_ncbi_surveillance: match accession {
+                    "GCA_1" => Some(true),
+                    "GCA_2" => Some(false),
+                    _ => None,
+                },
+            }),
+        };
+        assert_eq!(
+            select_surveillance(accessions.clone(), false, 2, metadata)?,
+            ["GCA_2", "GCA_3", "GCA_4"]
+        );
+        assert_eq!(
+            select_surveillance(accessions, true, 2, metadata)?,
+            ["GCA_1", "GCA_4"]
+        );
+
+        let args = GenomeArgs {
+            accession: vec!["GCA_001512625.1".to_string()],
+            surveillance: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            request_urls(&args, GenomeRequestType::Card),
+            vec![
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/metadata",
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/card"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_genome_links_missing_values() {
+        let mut card = card_fixture();
+        card.metadata_ncbi.ncbi_biosample = Some("none".to_string());
+        card.metadata_ncbi.ncbi_bioproject = None;
+        let links = GenomeLinks::from(&card);
+        assert_eq!(links.ncbi_biosample, None);
+        assert_eq!(links.ncbi_bioproject, None);
+        assert_eq!(
+            links.to_tsv_row(),
+            "GCA_001512625.1\thttps://www.ncbi.nlm.nih.gov/datasets/genome/GCA_001512625.1/\t\t\thttps://www.ebi.ac.uk/ena/browser/view/GCA_001512625.1"
+        );
+    }
+
+    #[test]
+    fn test_genome_card_taxonomy() {
+        let card = card_fixture();
+        assert_eq!(
+            card.get_gtdb_taxonomy(),
+            "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__UBA6186; f__UBA6186; g__UBA6186; s__UBA6186 sp001512625"
+        );
+        assert_eq!(
+            card.get_ncbi_taxonomy(),
+            Some("d__Bacteria; p__Pseudomonadota; c__; o__; f__; g__; s__".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lineage_discrepancies() {
+        let card = card_fixture();
+        let discrepancies = lineage_discrepancies(
+            &card.get_gtdb_taxonomy(),
+            &card.get_ncbi_taxonomy().unwrap(),
+        );
+        assert_eq!(discrepancies.len(), 5);
+        assert_eq!(discrepancies[0].rank, Rank::Class);
+        assert_eq!(discrepancies[0].status(), "unassigned");
+
+        let discrepancies = lineage_discrepancies(
+            "d__Bacteria; p__Bacillota; c__Bacilli; g__Escherichia",
+            "d__Bacteria; p__Firmicutes; c__Bacilli; g__Escherichia",
+        );
+        assert_eq!(
+            discrepancies,
+            vec![Discrepancy {
+                rank: Rank::Phylum,
+                gtdb: "p__Bacillota".to_string(),
+                ncbi: Some("p__Firmicutes".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_discrepancies_summary() {
+        let mut discrepancies = lineage_discrepancies(
+            "d__Bacteria; p__Bacillota; g__Clostridium_A",
+            "d__Bacteria; p__Firmicutes; g__Clostridium",
+        );
+        discrepancies.extend(lineage_discrepancies(
+            "d__Bacteria; g__Clostridium_A",
+            "d__Bacteria; g__Clostridium",
+        ));
+        let summary = discrepancies_summary(&discrepancies);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "rank\tdiffers\tunassigned");
+        assert_eq!(lines[1], "genus\t2\t0");
+        assert_eq!(lines[2], "phylum\t1\t0");
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn test_genome_history_retain_release() {
+        let mut history: GenomeTaxonHistory = serde_json::from_str(
+            r#"[{"release": "R220", "g": "g__Aminobacter"}, {"release": "R207", "g": "g__Aminobacter"}]"#,
+        )
+        .unwrap();
+        history.retain_release("R207");
+        assert_eq!(history.data.len(), 1);
+        assert_eq!(history.data[0].get_release(), Some("R207".to_string()));
+    }
+
+    #[test]
+    fn test_compare_cards() {
+        let first = card_fixture();
+        let mut second = card_fixture();
+        second.genome.accession = "GCF_018555685.1".to_string();
+        second.metadata_nucleotide.contig_count = Some(1);
+        second.metadata_taxonomy.gtdb_species = Some("s__UBA6186 sp018555685".to_string());
+
+        let table = compare_cards(&first, &second).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "field\tGCA_001512625.1\tGCF_018555685.1\tdiffers");
+        assert!(lines.contains(&"contig_count\t123\t1\t*"));
+        assert!(lines.contains(&"genome_size\t2105384\t2105384\t"));
+        assert!(lines.contains(&"checkm_completeness\t96.55\t96.55\t"));
+        assert!(lines.contains(&"gtdb_species\ts__UBA6186 sp001512625\ts__UBA6186 sp018555685\t*"));
+        assert_eq!(lines.iter().filter(|l| l.ends_with('*')).count(), 2);
+    }
+
+    #[test]
+    fn test_changed_fields() -> Result<()> {
+        let path = std::env::temp_dir()
+            .join(format!("xgt-cards-{}", std::process::id()))
+            .join("GCA_001512625.1.json");
+        assert_eq!(read_cached_card(&path)?, None);
+        write_cached_card(&path, &card_fixture())?;
+        let cached = read_cached_card(&path)?.unwrap();
+        assert_eq!(cached, card_fixture());
+        fs::remove_dir_all(path.parent().unwrap())?;
+
+        let mut current = card_fixture();
+        assert!(changed_fields(&cached, &current)?.is_empty());
+        current.metadata_taxonomy.gtdb_representative = false;
+        current.metadata_taxonomy.gtdb_species = Some("s__UBA6186 sp018555685".to_string());
+        assert_eq!(
+            changed_fields(&cached, &current)?,
+            [
+                ["gtdb_representative", "true", "false"],
+                [
+                    "gtdb_species",
+                    "s__UBA6186 sp001512625",
+                    "s__UBA6186 sp018555685"
+                ],
+            ]
+            .map(|x| x.map(String::from))
+        );
+        Ok(())
+    }
 
     #[test]
     fn test_genome_gtdb_card_1() {
@@ -324,6 +2130,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         println!("{:?}", get_genome_card(args.clone()));
         assert!(get_genome_card(args.clone()).is_ok());
@@ -335,6 +2142,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_card(args).is_ok());
     }
@@ -345,6 +2153,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_metadata(args).is_ok());
     }
@@ -355,6 +2164,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_metadata(args).is_ok());
         std::fs::remove_file(Path::new("genome")).unwrap();
@@ -366,6 +2176,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome1")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_metadata(args).is_ok());
         std::fs::remove_file(Path::new("genome1")).unwrap();
@@ -377,6 +2188,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome2")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_card(args).is_ok());
         std::fs::remove_file(Path::new("genome2")).unwrap();
@@ -388,6 +2200,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome3")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_card(args).is_ok());
         std::fs::remove_file(Path::new("genome3")).unwrap();
@@ -399,6 +2212,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome4")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_taxon_history(args).is_ok());
         std::fs::remove_file(Path::new("genome4")).unwrap();
@@ -410,6 +2224,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: Some(String::from("genome5")),
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_taxon_history(args).is_ok());
         std::fs::remove_file(Path::new("genome5")).unwrap();
@@ -421,6 +2236,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_metadata(args).is_ok());
     }
@@ -431,6 +2247,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_taxon_history(args).is_ok());
     }
@@ -441,6 +2258,7 @@ mod tests {
             accession: vec!["GCA_001512625.1".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(get_genome_taxon_history(args).is_ok());
     }
@@ -451,6 +2269,7 @@ mod tests {
             accession: vec!["".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         assert!(get_genome_card(args).is_err())
@@ -462,6 +2281,7 @@ mod tests {
             accession: vec!["&&&&^^^^^||||".to_owned()],
             output: None,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         assert!(
             get_genome_card(args).is_err(),
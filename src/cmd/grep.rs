@@ -0,0 +1,152 @@
+use anyhow::{ensure, Result};
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::api::ncbi::strip_gtdb_prefix;
+use crate::cli::grep::GrepArgs;
+use crate::cmd::search::local_field_match;
+use crate::local;
+use crate::taxonomy::parse_lineage;
+use crate::utils::{self, SearchField};
+
+/// A line of a GTDB taxonomy file
+#[derive(Debug, Clone, PartialEq)]
+struct TaxonomyEntry {
+    // Accession with its GTDB database prefix, e.g. RS_GCF_000010525.1
+    accession: String,
+    // Lineage as written in the file, e.g. "d__Bacteria;p__Pseudomonadota;..."
+    lineage: String,
+}
+
+impl TaxonomyEntry {
+    /// Parse a `accession<TAB>lineage` line, None for malformed lines
+    fn from_line(line: &str) -> Option<Self> {
+        let (accession, lineage) = line.split_once('\t')?;
+        Some(TaxonomyEntry {
+            accession: accession.trim().to_string(),
+            lineage: lineage.trim().to_string(),
+        })
+    }
+
+    /// Lineage with ranks separated by "; ", as in the GTDB API
+    fn normalized_lineage(&self) -> String {
+        self.lineage
+            .split(';')
+            .map(str::trim)
+            .collect::<Vec<&str>>()
+            .join("; ")
+    }
+
+    /// Check if the entry matches `needle` with the same semantics as
+    /// the online search
+    fn is_match(&self, needle: &str, args: &GrepArgs) -> bool {
+        let whole_words = args.is_whole_words_matching();
+        let accession_match = || {
+            local_field_match(
+                strip_gtdb_prefix(&self.accession),
+                needle,
+                false,
+                whole_words,
+            )
+        };
+        let lineage_match = || match args.get_level() {
+            Some(level) => parse_lineage(&self.lineage)
+                .get(&level)
+                .is_some_and(|taxon| local_field_match(taxon, needle, false, whole_words)),
+            None => local_field_match(&self.normalized_lineage(), needle, true, whole_words),
+        };
+
+        match args.get_search_field() {
+            SearchField::Acc => accession_match(),
+            SearchField::Gtdb => lineage_match(),
+            _ => accession_match() || lineage_match(),
+        }
+    }
+}
+
+/// Read the entries of a GTDB taxonomy file, possibly gzipped
+fn read_taxonomy(path: &Path) -> Result<Vec<TaxonomyEntry>> {
+    let mut entries = Vec::new();
+    for line in local::open_release_file(path)?.lines() {
+        if let Some(entry) = TaxonomyEntry::from_line(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Search a local GTDB taxonomy file, without network
+pub fn grep(args: GrepArgs) -> Result<()> {
+    let entries = read_taxonomy(Path::new(&args.get_taxonomy()))?;
+
+    for needle in args.get_needles() {
+        let matched: Vec<&TaxonomyEntry> = entries
+            .iter()
+            .filter(|entry| entry.is_match(&needle, &args))
+            .collect();
+        ensure!(
+            !matched.is_empty(),
+            "No matching data found in {}",
+            args.get_taxonomy()
+        );
+
+        let output = if args.is_only_num_entries() {
+            format!("{}\n", matched.len())
+        } else {
+            let mut output = String::new();
+            for entry in matched {
+                if args.is_only_print_ids() {
+                    output.push_str(&entry.accession);
+                } else {
+                    output.push_str(&format!("{}\t{}", entry.accession, entry.lineage));
+                }
+                output.push('\n');
+            }
+            output
+        };
+        utils::write_to_output(output.as_bytes(), args.get_output())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taxonomy::Rank;
+
+    fn entries() -> Vec<TaxonomyEntry> {
+        [
+            "RS_GCF_000005845.2\td__Bacteria;p__Pseudomonadota;c__Gammaproteobacteria;o__Enterobacterales;f__Enterobacteriaceae;g__Escherichia;s__Escherichia coli",
+            "GB_GCA_000000001.1\td__Bacteria;p__Pseudomonadota;c__Gammaproteobacteria;o__Enterobacterales;f__Enterobacteriaceae;g__Escherichia_A;s__Escherichia_A sp1",
+            "malformed line",
+        ]
+        .iter()
+        .filter_map(|line| TaxonomyEntry::from_line(line))
+        .collect()
+    }
+
+    #[test]
+    fn test_taxonomy_entry_is_match() {
+        let entries = entries();
+        assert_eq!(entries.len(), 2);
+
+        let mut args = GrepArgs::default();
+        let count = |args: &GrepArgs, needle: &str| {
+            entries.iter().filter(|x| x.is_match(needle, args)).count()
+        };
+        assert_eq!(count(&args, "escherichia"), 2);
+        assert_eq!(count(&args, "GCF_000005845"), 1);
+
+        args.is_whole_words_matching = true;
+        assert_eq!(count(&args, "g__Escherichia"), 1);
+        assert_eq!(count(&args, "GCA_000000001.1"), 1);
+
+        args.search_field = SearchField::Gtdb;
+        args.is_whole_words_matching = false;
+        args.level = Some(Rank::Species);
+        assert_eq!(count(&args, "Escherichia_A"), 1);
+        args.level = Some(Rank::Family);
+        assert_eq!(count(&args, "Escherichia"), 0);
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use ureq::Agent;
+
+use crate::cli::lineage::LineageArgs;
+use crate::cmd::genome::fetch_genome_card;
+use crate::cmd::releases::fetch_releases;
+use crate::cmd::search::lookup_accessions;
+use crate::interrupt;
+use crate::metrics;
+use crate::taxonomy::release_number;
+use crate::utils;
+
+/// GTDB and NCBI lineages of a genome
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Lineages {
+    gtdb: String,
+    ncbi: String,
+}
+
+/// Read cached `accession<TAB>gtdb<TAB>ncbi` lineages, none if the cache
+/// does not exist yet. Lines appended later win over earlier ones.
+fn read_cache(path: &Path) -> Result<HashMap<String, Lineages>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let accession = fields.next()?.to_string();
+            let gtdb = fields.next()?.to_string();
+            let ncbi = fields.next().unwrap_or_default().to_string();
+            Some((accession, Lineages { gtdb, ncbi }))
+        })
+        .collect())
+}
+
+/// Append newly fetched lineages to the cache
+fn append_cache(path: &Path, lineages: &[(String, Lineages)]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    for (accession, lineage) in lineages {
+        writeln!(file, "{}\t{}\t{}", accession, lineage.gtdb, lineage.ncbi)
+            .with_context(|| format!("Failed to write to {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Cache of the lineages of the current GTDB release, e.g.
+/// `lineages_r220.tsv`, so that lineages are fetched again after a release
+fn cache_path(agent: &Agent) -> Result<PathBuf> {
+    let releases = fetch_releases(agent)?;
+    let release = releases
+        .last()
+        .expect("releases is not empty")
+        .get_release();
+    Ok(utils::cache_dir()?.join(format!("lineages_r{}.tsv", release_number(&release))))
+}
+
+/// Fetch the lineages of a genome from its card
+fn fetch_lineages(agent: &Agent, accession: &str) -> Result<Lineages> {
+    let card = fetch_genome_card(agent, accession)?;
+    Ok(Lineages {
+        gtdb: card.get_gtdb_taxonomy(),
+        ncbi: card.get_ncbi_taxonomy().unwrap_or_default(),
+    })
+}
+
+/// Print the GTDB or NCBI lineage of a batch of genomes. Lineages are
+/// cached for the current release, so that only genomes never seen before
+/// are fetched, most of them with a few bulk searches and the others from
+/// their card. Lineages fetched before a failure or Ctrl-C are cached all
+/// the same.
+pub fn lineage(args: LineageArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let cache = cache_path(&agent)?;
+
+    let mut lineages = if args.is_refresh() {
+        HashMap::new()
+    } else {
+        read_cache(&cache)?
+    };
+    let (cached, mut missing): (Vec<String>, Vec<String>) = args
+        .get_accessions()
+        .into_iter()
+        .partition(|x| lineages.contains_key(x));
+    metrics::record_cache_hits(cached.len() as u64);
+    missing.sort();
+    missing.dedup();
+
+    let found = lookup_accessions(&agent, &missing, args.get_jobs());
+    let results = utils::parallel_map(missing, args.get_jobs(), |accession| {
+        interrupt::check()?;
+        let lineage = match found.get(&accession) {
+            Some(genome) => Lineages {
+                gtdb: genome.get_gtdb_taxonomy().unwrap_or_default(),
+                ncbi: genome.get_ncbi_taxonomy().unwrap_or_default(),
+            },
+            None => fetch_lineages(&agent, &accession)?,
+        };
+        Ok((accession, lineage))
+    });
+    let mut fetched = Vec::new();
+    let mut error = None;
+    for result in results {
+        match result {
+            Ok(lineage) => fetched.push(lineage),
+            Err(e) => {
+                error.get_or_insert(e);
+            }
+        }
+    }
+    append_cache(&cache, &fetched)?;
+    if let Some(e) = error {
+        return Err(e);
+    }
+    lineages.extend(fetched);
+
+    let mut output = String::new();
+    for accession in args.get_accessions() {
+        let lineage = &lineages[&accession];
+        let taxonomy = if args.is_ncbi() {
+            &lineage.ncbi
+        } else {
+            &lineage.gtdb
+        };
+        output.push_str(&format!("{}\t{}\n", accession, taxonomy));
+    }
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lineage_cache() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("xgt-lineage-{}", std::process::id()));
+        let path = dir.join("lineages.tsv");
+        assert!(read_cache(&path)?.is_empty());
+
+        let lineage = Lineages {
+            gtdb: "d__Bacteria; p__Pseudomonadota".to_string(),
+            ncbi: "d__Bacteria; p__Proteobacteria".to_string(),
+        };
+        append_cache(&path, &[("GCF_000005845.2".to_string(), lineage.clone())])?;
+        append_cache(
+            &path,
+            &[("GCA_001512625.1".to_string(), Lineages::default())],
+        )?;
+
+        let cache = read_cache(&path)?;
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache["GCF_000005845.2"], lineage);
+        assert_eq!(cache["GCA_001512625.1"].ncbi, "");
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{ensure, Context, Result};
+use std::process::Command;
+
+use crate::api::ncbi;
+use crate::cli::open::OpenArgs;
+
+/// Base URL of the GTDB website
+const GTDB_WEB_URL: &str = "https://gtdb.ecogenomic.org";
+
+/// URL of the GTDB website page of a taxon, in the tree browser, or of a
+/// genome
+fn web_url(record: &str) -> String {
+    let is_taxon = ["d__", "p__", "c__", "o__", "f__", "g__", "s__"]
+        .iter()
+        .any(|prefix| record.starts_with(prefix));
+    if is_taxon {
+        format!("{}/tree?r={}", GTDB_WEB_URL, record.replace(' ', "%20"))
+    } else {
+        format!(
+            "{}/genome?gid={}",
+            GTDB_WEB_URL,
+            ncbi::strip_gtdb_prefix(record)
+        )
+    }
+}
+
+/// Command opening a URL in the default browser of the platform
+fn browser_command(url: &str) -> Command {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url);
+    command
+}
+
+pub fn open(args: OpenArgs) -> Result<()> {
+    let url = web_url(&args.get_record());
+    if args.is_print() {
+        println!("{}", url);
+        return Ok(());
+    }
+
+    let status = browser_command(&url)
+        .status()
+        .with_context(|| format!("Failed to open a browser, the page is at {}", url))?;
+    ensure!(
+        status.success(),
+        "Failed to open a browser, the page is at {}",
+        url
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_url() {
+        assert_eq!(
+            web_url("RS_GCF_000005845.2"),
+            "https://gtdb.ecogenomic.org/genome?gid=GCF_000005845.2"
+        );
+        assert_eq!(
+            web_url("g__Aminobacter"),
+            "https://gtdb.ecogenomic.org/tree?r=g__Aminobacter"
+        );
+        assert_eq!(
+            web_url("s__Escherichia coli"),
+            "https://gtdb.ecogenomic.org/tree?r=s__Escherichia%20coli"
+        );
+    }
+}
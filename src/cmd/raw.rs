@@ -0,0 +1,35 @@
+use anyhow::Result;
+use std::io::Read;
+use ureq::Agent;
+
+use crate::api::raw::RawAPI;
+use crate::cli::raw::RawArgs;
+use crate::error::XgtError;
+use crate::utils;
+
+/// URL of the request of the API path
+pub fn request_url(args: &RawArgs) -> String {
+    RawAPI::new(args.get_path()).request()
+}
+
+/// Send a request to an arbitrary GTDB API path and print the raw response
+pub fn raw(args: RawArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let request = agent.request(&args.get_method(), &request_url(&args));
+
+    let result = match args.get_data() {
+        Some(data) => request
+            .set("Content-Type", "application/json")
+            .send_string(&data),
+        None => request.call(),
+    };
+    let response = result.map_err(|e| {
+        XgtError::from_request(e, format!("API path {} not found", args.get_path()))
+    })?;
+
+    let mut body = Vec::new();
+    response.into_reader().read_to_end(&mut body)?;
+    utils::write_to_output(&body, args.get_output())?;
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use ureq::Agent;
+
+use crate::api::meta::MetaAPI;
+use crate::cli::releases::ReleasesArgs;
+use crate::drift;
+use crate::error::XgtError;
+use crate::taxonomy::release_number;
+use crate::utils::{self, OutputFormat};
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Default)]
+/// A GTDB release and its content
+pub struct Release {
+    // Release name, e.g. "R220"
+    release: String,
+    // Release date, e.g. "2024-04-24"
+    date: Option<String>,
+    genomes: Option<u64>,
+    species: Option<u64>,
+    taxa: Option<u64>,
+}
+
+impl Release {
+    const HEADER: [&'static str; 5] = ["release", "date", "genomes", "species", "taxa"];
+
+    /// Get the release name, e.g. "R220"
+    pub(crate) fn get_release(&self) -> String {
+        self.release.clone()
+    }
+
+    fn to_fields(&self) -> Vec<String> {
+        let to_string = |x: Option<u64>| x.map(|x| x.to_string()).unwrap_or_default();
+        vec![
+            self.release.clone(),
+            self.date.clone().unwrap_or_default(),
+            to_string(self.genomes),
+            to_string(self.species),
+            to_string(self.taxa),
+        ]
+    }
+}
+
+/// Fetch GTDB releases, sorted from the oldest to the current one
+pub(crate) fn fetch_releases(agent: &Agent) -> Result<Vec<Release>> {
+    let request_url = MetaAPI::new().get_releases_request();
+    let response = agent
+        .get(&request_url)
+        .call()
+        .map_err(|e| XgtError::from_request(e, "No release found"))?;
+
+    let mut body = Vec::new();
+    response.into_reader().read_to_end(&mut body)?;
+    let mut releases: Vec<Release> = drift::decode(&body)?;
+    ensure!(!releases.is_empty(), "No release found");
+    releases.sort_by_key(|x| release_number(&x.release));
+
+    Ok(releases)
+}
+
+/// Latest GTDB release, e.g. "R220", or None if it cannot be fetched
+pub fn latest_release(agent: &Agent) -> Option<String> {
+    let releases = fetch_releases(agent).ok()?;
+    releases.last().map(Release::get_release)
+}
+
+/// Render releases in the requested output format
+fn format_releases(releases: &[Release], outfmt: &OutputFormat) -> Result<String> {
+    if *outfmt == OutputFormat::Json {
+        return Ok(utils::to_json(releases)?);
+    }
+
+    let rows: Vec<Vec<String>> = releases.iter().map(Release::to_fields).collect();
+    Ok(utils::render_table(&Release::HEADER, &rows, outfmt))
+}
+
+pub fn releases(args: ReleasesArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let releases = fetch_releases(&agent)?;
+
+    let output = if args.is_latest() {
+        let current = releases.last().expect("releases is not empty");
+        format!("{}\n", current.release)
+    } else {
+        format_releases(&releases, &args.get_outfmt())?
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_releases() -> Result<()> {
+        let mut releases: Vec<Release> = serde_json::from_str(
+            r#"[
+                {"release": "R220", "date": "2024-04-24", "genomes": 596859, "species": 113104},
+                {"release": "R95", "date": "2020-07-17", "genomes": 194600, "species": 31910}
+            ]"#,
+        )?;
+        releases.sort_by_key(|x| release_number(&x.release));
+
+        let table = format_releases(&releases, &OutputFormat::Tsv)?;
+        assert_eq!(
+            table,
+            "release\tdate\tgenomes\tspecies\ttaxa\nR95\t2020-07-17\t194600\t31910\t\nR220\t2024-04-24\t596859\t113104\t\n"
+        );
+
+        let json = format_releases(&releases, &OutputFormat::Json)?;
+        let parsed: Vec<Release> = serde_json::from_str(&json)?;
+        assert_eq!(parsed, releases);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use std::collections::BTreeMap;
+
+use crate::cli::schema::SchemaArgs;
+use crate::cmd::genome::{GenomeCard, GenomeMetadata, GenomeTaxonHistory};
+use crate::cmd::search::SearchResult;
+use crate::cmd::taxon::Taxon;
+use crate::utils;
+
+/// Names of the output structures described by a JSON Schema
+const SCHEMA_NAMES: [&str; 5] = ["search-row", "genome-card", "metadata", "history", "taxon"];
+
+/// JSON Schema of an output structure, as written by `--outfmt json`
+fn root_schema(name: &str) -> Option<RootSchema> {
+    let schema = match name {
+        "search-row" => schema_for!(SearchResult),
+        "genome-card" => schema_for!(GenomeCard),
+        "metadata" => schema_for!(GenomeMetadata),
+        "history" => schema_for!(GenomeTaxonHistory),
+        "taxon" => schema_for!(Taxon),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Print the JSON Schema of an output structure, or of all of them by name
+pub fn schema(args: SchemaArgs) -> Result<()> {
+    let output = match args.get_name() {
+        Some(name) => utils::to_json(&root_schema(&name).expect("NAME is checked by clap"))?,
+        None => {
+            let schemas: BTreeMap<&str, RootSchema> = SCHEMA_NAMES
+                .iter()
+                .filter_map(|&name| Some((name, root_schema(name)?)))
+                .collect();
+            utils::to_json(&schemas)?
+        }
+    };
+    utils::write_to_output(format!("{}\n", output).as_bytes(), args.get_output())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_root_schema() {
+        for name in SCHEMA_NAMES {
+            assert!(root_schema(name).is_some(), "{}", name);
+        }
+        assert_eq!(root_schema("genome"), None);
+
+        let schema = serde_json::to_value(root_schema("search-row").unwrap()).unwrap();
+        assert_eq!(schema["properties"]["gid"]["type"], "string");
+        assert!(schema["properties"]["isGtdbSpeciesRep"].is_object());
+
+        // Numbers sent as strings by GTDB are output and described as numbers
+        let schema = serde_json::to_value(root_schema("genome-card").unwrap()).unwrap();
+        let completeness =
+            &schema["definitions"]["metadata_gene"]["properties"]["checkm_completeness"];
+        assert_eq!(completeness["type"], serde_json::json!(["number", "null"]));
+
+        let schema = serde_json::to_value(root_schema("history").unwrap()).unwrap();
+        assert_eq!(schema["type"], Value::from("array"));
+    }
+}
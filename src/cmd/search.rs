@@ -1,19 +1,42 @@
 use anyhow::{anyhow, ensure, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::io::Read;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::aggregate::{self, Aggregate};
+use crate::api::ncbi::strip_gtdb_prefix;
 use crate::api::search::SearchAPI;
 use crate::cli;
+use crate::cmd::genome;
+use crate::cmd::releases;
+use crate::cmd::taxon::fetch_taxon_children;
+use crate::drift;
+use crate::error::{self, XgtError};
+use crate::interrupt;
+use crate::local;
+use crate::query::SearchQuery;
+use crate::report::Report;
+use crate::taxonomy::{lineage_matches, parse_lineage, release_number, Rank};
+use crate::transport::Transport;
 use crate::utils::{self, is_taxonomy_field, OutputFormat, SearchField};
+use crate::values;
 
 const INTO_STRING_LIMIT: usize = 20 * 1_024 * 1_024;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+/// Size of the first page requested when all the results at once time out
+/// or are too large, halved after each page which fails the same way
+const MAX_PAGE_SIZE: u32 = 8192;
+
+/// Smallest page size requested before giving up
+const MIN_PAGE_SIZE: u32 = 128;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 /// API search result struct
-struct SearchResult {
+#[non_exhaustive]
+pub struct SearchResult {
     // Genome accession used as table ID
-    gid: String,
+    pub(crate) gid: String,
     // Genome accession number
     accession: Option<String>,
     // NCBI organism name
@@ -28,16 +51,25 @@ struct SearchResult {
     // Boolean value indicating if species is a NCBI
     // type material
     is_ncbi_type_material: Option<bool>,
+    // Number of matched genomes of the GTDB species of the genome, when
+    // collapsed to one genome per species
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    species_genomes: Option<u32>,
 }
 
 impl SearchResult {
+    /// Get genome accession as used by GTDB, e.g. "GCA_001512625.1"
+    pub fn get_gid(&self) -> String {
+        self.gid.clone()
+    }
+
     /// Get genome accession number
     /// # Example
     /// ```
     /// let search_result = SearchResult::default();
     /// assert_eq!(search_result.get_accession(), None);
     /// ```
-    fn get_accession(&self) -> Option<String> {
+    pub fn get_accession(&self) -> Option<String> {
         self.accession.clone()
     }
 
@@ -47,7 +79,7 @@ impl SearchResult {
     /// let search_result = SearchResult::default();
     /// assert_eq!(search_result.get_ncbi_org_name(), None);
     /// ```
-    fn get_ncbi_org_name(&self) -> Option<String> {
+    pub fn get_ncbi_org_name(&self) -> Option<String> {
         self.ncbi_org_name.clone()
     }
 
@@ -57,7 +89,7 @@ impl SearchResult {
     /// let search_result = SearchResult::default();
     /// assert_eq!(search_result.get_ncbi_taxonomy(), None);
     /// ```
-    fn get_ncbi_taxonomy(&self) -> Option<String> {
+    pub fn get_ncbi_taxonomy(&self) -> Option<String> {
         self.ncbi_taxonomy.clone()
     }
 
@@ -67,15 +99,32 @@ impl SearchResult {
     /// let search_result = SearchResult::default();
     /// assert_eq!(search_result.get_gtdb_taxonomy(), None);
     /// ```
-    fn get_gtdb_taxonomy(&self) -> Option<String> {
+    pub fn get_gtdb_taxonomy(&self) -> Option<String> {
         self.gtdb_taxonomy.clone()
     }
+
+    /// Check if genome is a GTDB species representative
+    pub fn is_gtdb_species_rep(&self) -> Option<bool> {
+        self.is_gtdb_species_rep
+    }
+
+    /// Check if genome is a NCBI type material
+    pub fn is_ncbi_type_material(&self) -> Option<bool> {
+        self.is_ncbi_type_material
+    }
+
+    /// Get the number of matched genomes of the GTDB species of the genome,
+    /// for results collapsed to one genome per species
+    pub fn get_species_genomes(&self) -> Option<u32> {
+        self.species_genomes
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
-// JSON API search result struct
-struct SearchResults {
+/// JSON API search results struct
+#[non_exhaustive]
+pub struct SearchResults {
     // A list of SearchResult struct
     rows: Vec<SearchResult>,
     // A count of number of entries
@@ -106,15 +155,464 @@ impl SearchResults {
         self.total_rows = self.rows.len() as u32;
     }
 
+    /// Keep only whole words matches of the query if requested
+    pub(crate) fn apply_query(&mut self, query: &SearchQuery) {
+        if query.is_whole_words() {
+            self.filter_json(query.get_needle().to_string(), query.get_field());
+        }
+    }
+
+    /// Sort the genomes by a column of the CSV/TSV output, then by
+    /// accession, so that their order does not depend on the API
+    fn sort_by_column(&mut self, column: &str) {
+        self.rows.sort_by_cached_key(|x| {
+            let value = match column {
+                "ncbi_organism_name" => x.get_ncbi_org_name(),
+                "ncbi_taxonomy" => x.get_ncbi_taxonomy(),
+                "gtdb_taxonomy" => x.get_gtdb_taxonomy(),
+                _ => None,
+            };
+            (value, x.gid.clone())
+        });
+    }
+
+    /// Keep only the genomes whose GTDB taxonomy has every taxon of
+    /// `lineage` at the same rank
+    fn filter_lineage(&mut self, lineage: &str) {
+        let query = parse_lineage(lineage);
+        self.rows.retain(|result| {
+            result
+                .get_gtdb_taxonomy()
+                .is_some_and(|x| lineage_matches(&query, &x))
+        });
+        self.total_rows = self.rows.len() as u32;
+    }
+
+    /// Keep one genome per GTDB species, in the order of their first
+    /// genome: the species representative if matched, else a NCBI type
+    /// material genome, else the first genome. The number of genomes of
+    /// each species is kept in `species_genomes`. Genomes without a
+    /// species name are kept as species of their own.
+    fn distinct_species(&mut self) {
+        let species = |x: &SearchResult| {
+            x.get_gtdb_taxonomy()
+                .and_then(|lineage| parse_lineage(&lineage).remove(&Rank::Species))
+                .unwrap_or_else(|| x.gid.clone())
+        };
+        let preference = |x: &SearchResult| {
+            (
+                x.is_gtdb_species_rep() == Some(true),
+                x.is_ncbi_type_material() == Some(true),
+            )
+        };
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut rows: Vec<SearchResult> = Vec::new();
+        for row in self.rows.drain(..) {
+            match index.get(&species(&row)) {
+                Some(&i) => {
+                    let count = rows[i].species_genomes.unwrap_or(1) + 1;
+                    if preference(&row) > preference(&rows[i]) {
+                        rows[i] = row;
+                    }
+                    rows[i].species_genomes = Some(count);
+                }
+                None => {
+                    index.insert(species(&row), rows.len());
+                    rows.push(SearchResult {
+                        species_genomes: Some(1),
+                        ..row
+                    });
+                }
+            }
+        }
+        self.total_rows = rows.len() as u32;
+        self.rows = rows;
+    }
+
     /// Get total rows
     /// # Example
     /// ```
     /// let search_results = SearchResults::default();
     /// assert_eq!(search_results.get_total_rows(), 0_u32);
     /// ```
-    fn get_total_rows(&self) -> u32 {
+    pub fn get_total_rows(&self) -> u32 {
         self.total_rows
     }
+
+    /// Get the matched genomes
+    pub fn get_rows(&self) -> &[SearchResult] {
+        &self.rows
+    }
+
+    /// Consume the results and return the rows
+    pub fn into_rows(self) -> Vec<SearchResult> {
+        self.rows
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+/// Number of matched genomes in a taxon of the summarized rank
+struct RankSummary {
+    taxon: String,
+    genomes: usize,
+    percent: f64,
+}
+
+impl RankSummary {
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.taxon.clone(),
+            self.genomes.to_string(),
+            format!("{:.2}", self.percent),
+        ]
+    }
+}
+
+/// Group search results by their GTDB taxon at `rank`, most frequent first.
+/// Genomes without a name at that rank are grouped as "unclassified".
+fn summarize_by_rank(rows: &[SearchResult], rank: Rank) -> Vec<RankSummary> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let taxon = row
+            .get_gtdb_taxonomy()
+            .and_then(|lineage| parse_lineage(&lineage).remove(&rank))
+            .unwrap_or_else(|| "unclassified".to_string());
+        *counts.entry(taxon).or_insert(0) += 1;
+    }
+
+    let mut summary: Vec<RankSummary> = counts
+        .into_iter()
+        .map(|(taxon, genomes)| RankSummary {
+            taxon,
+            genomes,
+            percent: 100.0 * genomes as f64 / rows.len() as f64,
+        })
+        .collect();
+    summary.sort_by(|a, b| b.genomes.cmp(&a.genomes).then(a.taxon.cmp(&b.taxon)));
+    summary
+}
+
+/// Taxon searched to find the genomes of a lineage: its lowest rank taxon,
+/// which the GTDB taxonomy of all its genomes contains
+fn lineage_search_taxon(lineage: &str) -> Result<String> {
+    parse_lineage(lineage)
+        .into_values()
+        .next_back()
+        .ok_or_else(|| anyhow!("No taxon in greengenes format found in lineage {}", lineage))
+}
+
+/// Fetch JSON search results of a query
+pub(crate) fn fetch_search_results(
+    transport: &dyn Transport,
+    query: &SearchQuery,
+) -> Result<SearchResults, XgtError> {
+    let mut results = fetch_json_pages(
+        transport,
+        &query.api(),
+        &format!("No match found for {}", query.get_needle()),
+    )?;
+    results.apply_query(query);
+    Ok(results)
+}
+
+/// Could a smaller page avoid the error of a search request, which timed
+/// out, failed on the server or was too large
+fn is_page_too_large(error: &XgtError) -> bool {
+    matches!(error, XgtError::TooLarge(_) | XgtError::Network(_))
+}
+
+/// Children of a taxon whose search can be split into searches of its
+/// children, none for other needles, species and taxa whose children
+/// could not be fetched
+fn split_taxon(transport: &dyn Transport, needle: &str) -> Vec<String> {
+    match Rank::from_taxon(needle) {
+        Some(rank) if rank != Rank::Species => fetch_taxon_children(transport, needle)
+            .map(|x| x.data.iter().map(|x| x.get_taxon()).collect())
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+/// Send the search request `api`, first for all the results at once. If
+/// it times out or is too large, the search of a taxon is split into
+/// searches of its children, e.g. the classes of a phylum, and other
+/// searches are sent in pages of `itemsPerPage` results shrinking until
+/// they succeed. Pages are parsed by `parse`, `rows` being their number of
+/// results.
+fn fetch_pages<P>(
+    transport: &dyn Transport,
+    api: &SearchAPI,
+    not_found: &str,
+    parse: &dyn Fn(&[u8]) -> Result<P, XgtError>,
+    rows: &dyn Fn(&P) -> usize,
+) -> Result<Vec<P>, XgtError> {
+    let get = |api: &SearchAPI| {
+        let url = api.request();
+        let page = transport.get(&url).and_then(|response| {
+            if !(200..300).contains(&response.get_status()) {
+                return Err(XgtError::from_status(response.get_status(), not_found));
+            }
+            parse(response.get_body())
+        });
+        if page.is_err() {
+            error::record_failed_url(&url);
+        }
+        page
+    };
+
+    match get(api) {
+        Err(e) if is_page_too_large(&e) => {
+            let children = split_taxon(transport, api.get_search());
+            if !children.is_empty() {
+                if utils::verbosity() > 0 {
+                    eprintln!(
+                        "Warning: {}, splitting the search of {} into {} searches of its children",
+                        e,
+                        api.get_search(),
+                        children.len()
+                    );
+                }
+                let mut pages = Vec::new();
+                for child in children {
+                    interrupt::check()?;
+                    let child_api = api.clone().set_search(&child);
+                    pages.extend(fetch_pages(transport, &child_api, not_found, parse, rows)?);
+                }
+                return Ok(pages);
+            }
+            if utils::verbosity() > 0 {
+                eprintln!(
+                    "Warning: {}, retrying in pages of {} results",
+                    e, MAX_PAGE_SIZE
+                );
+            }
+        }
+        result => return result.map(|x| vec![x]),
+    }
+
+    // Pages are numbered from 1 in units of the page size, which stays a
+    // divisor of the number of results already fetched as it is halved
+    let mut pages = Vec::new();
+    let mut fetched = 0;
+    let mut size = MAX_PAGE_SIZE;
+    loop {
+        let page_api = api
+            .clone()
+            .set_items_per_page(size)
+            .set_page((fetched / size + 1) as u16);
+        match get(&page_api) {
+            Ok(page) => {
+                let count = rows(&page);
+                pages.push(page);
+                fetched += size;
+                if count < size as usize {
+                    return Ok(pages);
+                }
+            }
+            Err(e) if is_page_too_large(&e) && size > MIN_PAGE_SIZE => {
+                size /= 2;
+                if utils::verbosity() > 0 {
+                    eprintln!("Warning: {}, retrying in pages of {} results", e, size);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Fetch JSON search results, in pages or searches of child taxa if
+/// needed, stitched back together. Genomes matched by the searches of
+/// several children are only kept once.
+fn fetch_json_pages(
+    transport: &dyn Transport,
+    api: &SearchAPI,
+    not_found: &str,
+) -> Result<SearchResults, XgtError> {
+    let pages = fetch_pages(
+        transport,
+        &api.clone().set_outfmt("json"),
+        not_found,
+        &drift::decode::<SearchResults>,
+        &|x| x.rows.len(),
+    )?;
+    let mut seen = HashSet::new();
+    let rows: Vec<SearchResult> = pages
+        .into_iter()
+        .flat_map(|x| x.rows)
+        .filter(|x| seen.insert(x.gid.clone()))
+        .collect();
+    Ok(SearchResults {
+        total_rows: rows.len() as u32,
+        rows,
+    })
+}
+
+/// Fetch CSV or TSV search results, in pages or searches of child taxa if
+/// needed, stitched back together under the header of the first page.
+/// Responses larger than 20 MB are split the same way, and records
+/// matched by several searches are only kept once.
+fn fetch_xsv_pages(
+    transport: &dyn Transport,
+    api: &SearchAPI,
+    not_found: &str,
+) -> Result<String, XgtError> {
+    let parse = |body: &[u8]| match body.len() > INTO_STRING_LIMIT {
+        true => Err(XgtError::TooLarge("larger than 20 MB".to_string())),
+        false => Ok(String::from_utf8_lossy(body).into_owned()),
+    };
+    let records = |page: &String| page.lines().skip(1).filter(|x| !x.is_empty()).count();
+    let pages = fetch_pages(transport, api, not_found, &parse, &records)?;
+    if pages.len() == 1 {
+        return Ok(pages.into_iter().next().unwrap_or_default());
+    }
+
+    let mut text = String::new();
+    let mut seen = HashSet::new();
+    for (i, page) in pages.iter().enumerate() {
+        let mut lines = page.split_inclusive('\n');
+        let header = lines.next().unwrap_or_default();
+        if i == 0 {
+            text.push_str(header);
+        }
+        for line in lines.filter(|x| seen.insert(x.trim_end())) {
+            text.push_str(line);
+        }
+    }
+    Ok(text)
+}
+
+/// Number of leading characters of the accessions looked up together by
+/// one search, e.g. "GCA_0015" for a range of 100,000 accession numbers
+const BULK_GROUP_LEN: usize = 8;
+
+/// Searches looking up `accessions` in bulk, with the accessions of their
+/// group which they match. Accessions are grouped by their first
+/// characters and each group of several accessions is searched by the
+/// longest prefix they share, accessions alone in their group being left
+/// to their genome card.
+fn bulk_searches(accessions: &[String]) -> Vec<(String, Vec<String>)> {
+    let mut groups: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+    for accession in accessions {
+        let key = strip_gtdb_prefix(accession);
+        groups
+            .entry(key.get(..BULK_GROUP_LEN).unwrap_or(key))
+            .or_default()
+            .push(accession);
+    }
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let first = strip_gtdb_prefix(group[0]);
+            let len = group[1..].iter().fold(first.len(), |len, x| {
+                first
+                    .bytes()
+                    .zip(strip_gtdb_prefix(x).bytes())
+                    .take(len)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            (
+                first[..len].to_string(),
+                group.into_iter().cloned().collect(),
+            )
+        })
+        .collect()
+}
+
+/// Search results of the records of a CSV response of the GTDB API
+fn parse_csv_results(text: &str) -> Vec<SearchResult> {
+    let unquote = |x: &str| x.trim().trim_matches('"').replace("\"\"", "\"");
+    let mut lines = text.lines();
+    let header: Vec<String> = lines
+        .next()
+        .map(|x| {
+            values::split_xsv_line(x, ',')
+                .into_iter()
+                .map(unquote)
+                .collect()
+        })
+        .unwrap_or_default();
+    let index = |name: &str| header.iter().position(|x| x == name);
+    let (accession, org_name, ncbi_taxonomy, gtdb_taxonomy, representative, type_material) = (
+        index(XSV_HEADER[0]),
+        index(XSV_HEADER[1]),
+        index(XSV_HEADER[2]),
+        index(XSV_HEADER[3]),
+        index(XSV_HEADER[4]),
+        index(XSV_HEADER[5]),
+    );
+    lines
+        .filter(|x| !x.is_empty())
+        .filter_map(|line| {
+            let fields: Vec<String> = values::split_xsv_line(line, ',')
+                .into_iter()
+                .map(unquote)
+                .collect();
+            let field = |i: Option<usize>| i.and_then(|i| fields.get(i)).cloned();
+            let gid = strip_gtdb_prefix(&field(accession)?).to_string();
+            Some(SearchResult {
+                gid: gid.clone(),
+                accession: Some(gid),
+                ncbi_org_name: field(org_name),
+                ncbi_taxonomy: field(ncbi_taxonomy),
+                gtdb_taxonomy: field(gtdb_taxonomy),
+                is_gtdb_species_rep: field(representative).and_then(|x| values::parse_bool(&x)),
+                is_ncbi_type_material: field(type_material).and_then(|x| values::parse_bool(&x)),
+                species_genomes: None,
+            })
+        })
+        .collect()
+}
+
+/// Look up the taxonomy, representative and type material status of many
+/// genomes with a few bulk CSV searches of their accessions instead of a
+/// genome card request each, `jobs` searches at a time. Results are keyed
+/// by the accessions as given, an accession without version matching any
+/// version. Genomes which could not be looked up this way, e.g. alone in
+/// their group or because their search failed, are missing from the
+/// results, to be fetched from their card.
+pub(crate) fn lookup_accessions<T: Transport + Sync>(
+    transport: &T,
+    accessions: &[String],
+    jobs: usize,
+) -> HashMap<String, SearchResult> {
+    let searches = bulk_searches(accessions);
+    let count = searches.len();
+    let results = utils::parallel_map(searches, jobs, |(needle, group)| {
+        if interrupt::is_interrupted() {
+            return vec![];
+        }
+        let api = SearchQuery::accession(&needle).api().set_outfmt("csv");
+        let rows = match fetch_xsv_pages(transport, &api, "") {
+            Ok(text) => parse_csv_results(&text),
+            Err(e) => {
+                if utils::verbosity() > 0 {
+                    eprintln!("Warning: bulk search of {} failed: {}", needle, e);
+                }
+                vec![]
+            }
+        };
+        group
+            .into_iter()
+            .filter_map(|accession| {
+                let key = strip_gtdb_prefix(&accession);
+                let row = rows.iter().find(|x| {
+                    x.gid == key || (!key.contains('.') && x.gid.split('.').next() == Some(key))
+                })?;
+                Some((accession, row.clone()))
+            })
+            .collect()
+    });
+    let found: HashMap<String, SearchResult> = results.into_iter().flatten().collect();
+    if utils::verbosity() > 0 && count > 0 {
+        eprintln!(
+            "Looked up {} genomes with {} bulk searches",
+            found.len(),
+            count
+        );
+    }
+    found
 }
 
 /// Perform whole word exact matching
@@ -224,50 +722,385 @@ fn filter_xsv(
 }
 
 /// Search GTDB data from `SearchArgs`
+/// URL of the GTDB API search request of `needle`
+fn search_request(needle: &str, args: &cli::search::SearchArgs) -> Result<String> {
+    Ok(search_api(needle, args)?.request())
+}
+
+/// GTDB API search request of `needle`
+fn search_api(needle: &str, args: &cli::search::SearchArgs) -> Result<SearchAPI> {
+    let query = if args.is_lineage() {
+        args.to_query(&lineage_search_taxon(needle)?)
+    } else {
+        args.to_query(needle)
+    };
+    let mut search_api = query.api().set_outfmt(&args.get_outfmt().to_string());
+    if args.is_summarized() || is_filtered_by_metadata(args) || args.is_distinct_species() {
+        search_api = search_api.set_outfmt("json");
+    }
+    Ok(search_api)
+}
+
+/// Are matched genomes filtered by their metadata: their NCBI release date
+/// in the cached GTDB metadata or their NCBI surveillance flag
+fn is_filtered_by_metadata(args: &cli::search::SearchArgs) -> bool {
+    args.get_released_after().is_some()
+        || args.get_released_before().is_some()
+        || args.get_surveillance().is_some()
+}
+
+/// Keep only the genomes of NCBI surveillance projects with
+/// `--only-surveillance`, or drop them with `--exclude-surveillance`, from
+/// the `is_ncbi_surveillance` flag of their metadata as `genome` does. A
+/// genome whose metadata cannot be fetched fails the search rather than
+/// being guessed.
+fn filter_surveillance(
+    transport: &dyn Transport,
+    search_result: &mut SearchResults,
+    args: &cli::search::SearchArgs,
+) -> Result<()> {
+    let Some(only) = args.get_surveillance() else {
+        return Ok(());
+    };
+    let accessions: Vec<String> = search_result.rows.iter().map(|x| x.get_gid()).collect();
+    let flags = genome::surveillance_flags(accessions.clone(), args.get_jobs(), |accession| {
+        genome::fetch_genome_metadata(transport, accession)
+    })?;
+    let mut keep = Vec::with_capacity(flags.len());
+    for (accession, flag) in accessions.iter().zip(flags) {
+        let is_surveillance = flag.map_err(|e| {
+            anyhow!(
+                "Failed to fetch the surveillance flag of {}: {}",
+                accession,
+                e
+            )
+        })?;
+        keep.push(is_surveillance == only);
+    }
+    let mut keep = keep.into_iter();
+    search_result.rows.retain(|_| keep.next().unwrap_or(false));
+    search_result.total_rows = search_result.rows.len() as u32;
+    Ok(())
+}
+
+/// Keep the genomes released by NCBI within the `--released-after` and
+/// `--released-before` dates, partial dates spanning their whole year or
+/// month. Release dates are read from the cached GTDB metadata, and
+/// genomes without a release date are dropped.
+fn filter_release_date(
+    search_result: &mut SearchResults,
+    args: &cli::search::SearchArgs,
+) -> Result<()> {
+    let bound = |date: Option<String>| date.as_deref().and_then(values::parse_partial_date);
+    let after =
+        bound(args.get_released_after()).map(|(y, m, d)| (y, m.unwrap_or(1), d.unwrap_or(1)));
+    let before =
+        bound(args.get_released_before()).map(|(y, m, d)| (y, m.unwrap_or(12), d.unwrap_or(31)));
+    if after.is_none() && before.is_none() {
+        return Ok(());
+    }
+
+    let rows = result_rows(
+        search_result,
+        &["ncbi_seq_rel_date", "ncbi_date"],
+        args.get_release(),
+    )?;
+    let mut keep = rows.iter().map(|row| {
+        let date = ["ncbi_seq_rel_date", "ncbi_date"]
+            .iter()
+            .find_map(|x| values::parse_date(row.get(*x)?));
+        date.is_some_and(|date| {
+            after.map_or(true, |x| date >= x) && before.map_or(true, |x| date <= x)
+        })
+    });
+    search_result.rows.retain(|_| keep.next().unwrap_or(false));
+    search_result.total_rows = search_result.rows.len() as u32;
+    Ok(())
+}
+
+/// URLs of the requests a search would send, none for local searches
+pub fn request_urls(args: &cli::search::SearchArgs) -> Result<Vec<String>> {
+    if args.is_local() {
+        return Ok(vec![]);
+    }
+    args.get_needles()
+        .iter()
+        .map(|needle| search_request(needle, args))
+        .collect()
+}
+
 pub fn search(args: cli::search::SearchArgs) -> Result<()> {
+    if args.is_local() {
+        return search_local(&args);
+    }
+
     let agent = utils::get_agent(args.disable_certificate_verification())?;
+    let release = match args.is_envelope() {
+        true => releases::latest_release(&agent),
+        false => None,
+    };
 
-    for needle in args.get_needles() {
-        let search_api = SearchAPI::from(needle, &args);
-        let request_url = search_api.request();
+    utils::for_each_item(args.get_needles().clone(), args.is_fail_fast(), |needle| {
+        let search_api = search_api(needle, &args)?;
+        let not_found = format!("No match found for {}", needle);
 
-        let response = agent.get(&request_url).call().map_err(|e| match e {
-            ureq::Error::Status(code, _) => {
-                anyhow::anyhow!("The server returned an unexpected status code ({})", code)
-            }
-            _ => {
-                anyhow::anyhow!("There was an error making the request or receiving the response.")
+        // If -c, -i or --summarize just use JSON output format to count entries or
+        // return ids list as converting using into_string can
+        // throw an error of too big to convert to string especially
+        // when querying data related to large genus like Escherichia
+        // See cli/search.rs#L166-L178
+        let output_result = if args.is_summarized()
+            || is_filtered_by_metadata(&args)
+            || args.is_distinct_species()
+            || args.is_only_print_ids()
+            || args.is_only_num_entries()
+            || args.get_outfmt() == OutputFormat::Json
+            || args.get_outfmt() == OutputFormat::Html
+        {
+            fetch_json_pages(&agent, &search_api, &not_found)
+                .map_err(anyhow::Error::from)
+                .and_then(|search_result| parse_json_response(&agent, search_result, needle, &args))
+                .and_then(|search_result| {
+                    format_search_results(&search_result, needle, &args, release.as_deref())
+                })
+        } else {
+            fetch_xsv_pages(&agent, &search_api, &not_found)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| handle_xsv_response(text, needle, &args))
+        };
+
+        utils::write_to_output(output_result?.as_bytes(), args.get_output().clone())
+    })
+}
+
+/// Search results with the metadata of their query, written by `--envelope`
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    query: &'a str,
+    total_rows: u32,
+    retrieved_at: String,
+    gtdb_api_version: Option<&'a str>,
+    rows: &'a [SearchResult],
+}
+
+/// Columns of the GTDB metadata needed to answer a search
+const LOCAL_SEARCH_COLUMNS: [&str; 6] = [
+    "accession",
+    "ncbi_organism_name",
+    "ncbi_taxonomy",
+    "gtdb_taxonomy",
+    "gtdb_representative",
+    "ncbi_type_material_designation",
+];
+
+/// Check if a field value matches `needle`, either as a case-insensitive
+/// substring or, with whole words matching, as a whole word or taxon
+pub(crate) fn local_field_match(
+    value: &str,
+    needle: &str,
+    is_taxonomy: bool,
+    whole_words: bool,
+) -> bool {
+    match (whole_words, is_taxonomy) {
+        (true, true) => whole_taxon_match(value, needle),
+        (true, false) => whole_word_match(value, needle),
+        (false, _) => value.to_lowercase().contains(&needle.to_lowercase()),
+    }
+}
+
+/// Search `needle` in the columns of the cached GTDB metadata
+fn search_metadata(
+    table: &local::MetadataTable,
+    needle: &str,
+    args: &cli::search::SearchArgs,
+) -> SearchResults {
+    let accession = table.column("accession");
+    let org_name = table.column("ncbi_organism_name");
+    let ncbi_taxonomy = table.column("ncbi_taxonomy");
+    let gtdb_taxonomy = table.column("gtdb_taxonomy");
+    let representative = table.column("gtdb_representative");
+    let type_material = table.column("ncbi_type_material_designation");
+
+    let whole_words = args.is_whole_words_matching();
+    let query = parse_lineage(needle);
+    let matches = |i: usize| match args.get_search_field() {
+        _ if args.is_lineage() => lineage_matches(&query, &gtdb_taxonomy[i]),
+        SearchField::All => {
+            local_field_match(&accession[i], needle, false, whole_words)
+                || local_field_match(&org_name[i], needle, false, whole_words)
+                || local_field_match(&ncbi_taxonomy[i], needle, true, whole_words)
+                || local_field_match(&gtdb_taxonomy[i], needle, true, whole_words)
+        }
+        SearchField::Acc => local_field_match(&accession[i], needle, false, whole_words),
+        SearchField::Org => local_field_match(&org_name[i], needle, false, whole_words),
+        SearchField::Ncbi => local_field_match(&ncbi_taxonomy[i], needle, true, whole_words),
+        SearchField::Gtdb => local_field_match(&gtdb_taxonomy[i], needle, true, whole_words),
+    };
+    let is_representative = |i: usize| representative[i] == "t";
+    let is_type_material = |i: usize| !matches!(type_material[i].as_str(), "" | "none");
+
+    let rows: Vec<SearchResult> = (0..table.len())
+        .filter(|&i| {
+            matches(i)
+                && (!args.is_representative_species_only() || is_representative(i))
+                && (!args.is_type_species_only() || is_type_material(i))
+        })
+        .map(|i| {
+            let gid = strip_gtdb_prefix(&accession[i]).to_string();
+            SearchResult {
+                gid: gid.clone(),
+                accession: Some(gid),
+                ncbi_org_name: Some(org_name[i].clone()),
+                ncbi_taxonomy: Some(ncbi_taxonomy[i].clone()),
+                gtdb_taxonomy: Some(gtdb_taxonomy[i].clone()),
+                is_gtdb_species_rep: Some(is_representative(i)),
+                is_ncbi_type_material: Some(is_type_material(i)),
+                species_genomes: None,
             }
-        })?;
+        })
+        .collect();
 
-        let output_result = if args.is_only_print_ids() || args.is_only_num_entries() {
-            handle_id_or_count_response(response, needle, &args)
-        } else {
-            match args.get_outfmt() {
-                OutputFormat::Json => handle_json_response(response, needle, &args),
-                _ => handle_xsv_response(response, needle, &args),
+    SearchResults {
+        total_rows: rows.len() as u32,
+        rows,
+    }
+}
+
+/// Search the cached GTDB metadata, without network
+fn search_local(args: &cli::search::SearchArgs) -> Result<()> {
+    let files = local::metadata_files(
+        &local::metadata_dir()?,
+        args.get_release().map(|x| release_number(&x)),
+    )?;
+    let table = local::MetadataTable::read_files(&files, &LOCAL_SEARCH_COLUMNS)?;
+    let release = files
+        .first()
+        .and_then(|x| x.file_name()?.to_str())
+        .and_then(local::metadata_release)
+        .map(|x| format!("R{}", x));
+
+    utils::for_each_item(args.get_needles().clone(), args.is_fail_fast(), |needle| {
+        let mut search_result = search_metadata(&table, needle, args);
+        filter_release_date(&mut search_result, args)?;
+        if args.is_distinct_species() {
+            search_result.distinct_species();
+        }
+        if let Some(column) = args.get_stable_sort() {
+            search_result.sort_by_column(&column);
+        }
+        ensure!(
+            search_result.get_total_rows() != 0,
+            "No matching data found in GTDB"
+        );
+
+        let output = match args.get_outfmt() {
+            OutputFormat::Csv | OutputFormat::Tsv
+                if !args.is_summarized()
+                    && !args.is_only_print_ids()
+                    && !args.is_only_num_entries() =>
+            {
+                format_xsv(&search_result, &args.get_outfmt())
             }
+            _ => format_search_results(&search_result, needle, args, release.as_deref())?,
         };
+        utils::write_to_output(output.as_bytes(), args.get_output())
+    })
+}
 
-        utils::write_to_output(output_result?.as_bytes(), args.get_output().clone())?;
+/// Columns of the GTDB API CSV/TSV output
+const XSV_HEADER: [&str; 6] = [
+    "accession",
+    "ncbi_organism_name",
+    "ncbi_taxonomy",
+    "gtdb_taxonomy",
+    "gtdb_species_representative",
+    "ncbi_type_material",
+];
+
+/// Columns of search results rendered as CSV/TSV: those of the GTDB API,
+/// and the number of genomes of each species when collapsed to species
+fn xsv_header(search_result: &SearchResults) -> Vec<&'static str> {
+    let mut header = XSV_HEADER.to_vec();
+    if search_result
+        .rows
+        .iter()
+        .any(|x| x.species_genomes.is_some())
+    {
+        header.push("species_genomes");
     }
+    header
+}
 
-    Ok(())
+/// Render search results with the columns of the GTDB API CSV/TSV output
+fn format_xsv(search_result: &SearchResults, outfmt: &OutputFormat) -> String {
+    utils::render_table(&xsv_header(search_result), &xsv_rows(search_result), outfmt)
+}
+
+/// Search results as rows of the GTDB API CSV/TSV output, followed by
+/// their number of species genomes if collapsed to species
+fn xsv_rows(search_result: &SearchResults) -> Vec<Vec<String>> {
+    let to_string = |x: Option<bool>| (x == Some(true)).to_string();
+    search_result
+        .rows
+        .iter()
+        .map(|x| {
+            let mut row = vec![
+                x.gid.clone(),
+                x.get_ncbi_org_name().unwrap_or_default(),
+                x.get_ncbi_taxonomy().unwrap_or_default(),
+                x.get_gtdb_taxonomy().unwrap_or_default(),
+                to_string(x.is_gtdb_species_rep()),
+                to_string(x.is_ncbi_type_material()),
+            ];
+            row.extend(x.species_genomes.map(|n| n.to_string()));
+            row
+        })
+        .collect()
+}
+
+/// Render search results as an HTML report: a sortable table of the
+/// genomes and the composition of the genomes from phylum to genus
+fn format_html_report(search_result: &SearchResults, needle: &str) -> String {
+    let mut report = Report::new(&format!("xgt search: {}", needle)).table(
+        &format!("{} genomes", search_result.get_total_rows()),
+        &xsv_header(search_result),
+        &xsv_rows(search_result),
+    );
+    for rank in [
+        Rank::Phylum,
+        Rank::Class,
+        Rank::Order,
+        Rank::Family,
+        Rank::Genus,
+    ] {
+        let counts: Vec<(String, usize)> = summarize_by_rank(&search_result.rows, rank)
+            .into_iter()
+            .map(|x| (x.taxon, x.genomes))
+            .collect();
+        report = report.composition(&format!("Composition by {}", rank), &counts);
+    }
+    report.render()
 }
 
-// If -c or -i just use JSON output format to count entries or
-// return ids list as converting using into_string can
-// throw an error of too big to convert to string especially
-// when querying data related to large genus like Escherichia
-// See cli/search.rs#L166-L178
-fn handle_id_or_count_response(
-    response: ureq::Response,
+/// Filter JSON search results, keeping only whole words matches if
+/// requested
+fn parse_json_response(
+    transport: &dyn Transport,
+    mut search_result: SearchResults,
     needle: &str,
     args: &cli::search::SearchArgs,
-) -> Result<String> {
-    let mut search_result: SearchResults = response.into_json()?;
-    if args.is_whole_words_matching() {
-        search_result.filter_json(needle.to_string(), args.get_search_field());
+) -> Result<SearchResults> {
+    search_result.apply_query(&args.to_query(needle));
+    if args.is_lineage() {
+        search_result.filter_lineage(needle);
+    }
+    filter_release_date(&mut search_result, args)?;
+    filter_surveillance(transport, &mut search_result, args)?;
+    if args.is_distinct_species() {
+        search_result.distinct_species();
+    }
+    if let Some(column) = args.get_stable_sort() {
+        search_result.sort_by_column(&column);
     }
 
     ensure!(
@@ -275,13 +1108,72 @@ fn handle_id_or_count_response(
         "No matching data found in GTDB"
     );
 
-    let result_str = if args.is_only_num_entries() {
+    Ok(search_result)
+}
+
+/// Identifier of a genome printed by `--id`: the NCBI assembly accession
+/// for `ncbi_assembly`, e.g. "GCF_000005845.2", the GTDB accession with
+/// its RefSeq or GenBank prefix for `accession`, e.g. "RS_GCF_000005845.2",
+/// and the GTDB genome ID otherwise
+fn genome_id(genome: &SearchResult, kind: &str) -> String {
+    let assembly = strip_gtdb_prefix(&genome.gid);
+    match kind {
+        "ncbi_assembly" => assembly.to_string(),
+        "accession" if assembly.starts_with("GCF_") => format!("RS_{}", assembly),
+        "accession" if assembly.starts_with("GCA_") => format!("GB_{}", assembly),
+        _ => genome.gid.clone(),
+    }
+}
+
+/// Render search results as a summary, groups, a contingency table,
+/// histograms, a count, a list of ids, an HTML report, CSV/TSV or JSON,
+/// wrapped in an envelope reporting `release` if requested
+fn format_search_results(
+    search_result: &SearchResults,
+    needle: &str,
+    args: &cli::search::SearchArgs,
+    release: Option<&str>,
+) -> Result<String> {
+    let result_str = if let Some(rank) = args.get_summarize() {
+        format_summary(search_result, rank, &args.get_outfmt())?
+    } else if let Some(key) = args.get_group_by() {
+        format_groups(search_result, &key, args)?
+    } else if let Some((row_key, col_key)) = args.get_pivot() {
+        let rows = result_rows(search_result, &[&row_key, &col_key], args.get_release())?;
+        let (header, table) = aggregate::pivot(&rows, &row_key, &col_key);
+        let header: Vec<&str> = header.iter().map(String::as_str).collect();
+        match args.get_outfmt() {
+            OutputFormat::Json => utils::to_json(&aggregate::to_json_records(&header, &table))?,
+            outfmt => utils::render_table(&header, &table, &outfmt),
+        }
+    } else if !args.get_hist().is_empty() {
+        format_histograms(search_result, args)?
+    } else if args.is_only_num_entries() {
         search_result.get_total_rows().to_string()
+    } else if args.is_only_print_ids() {
+        search_result
+            .rows
+            .iter()
+            .map(|x| genome_id(x, args.get_id_kind()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    } else if args.get_outfmt() == OutputFormat::Html {
+        format_html_report(search_result, needle)
+    } else if matches!(args.get_outfmt(), OutputFormat::Csv | OutputFormat::Tsv) {
+        format_xsv(search_result, &args.get_outfmt())
+    } else if args.is_envelope() {
+        utils::to_json(&Envelope {
+            query: needle,
+            total_rows: search_result.get_total_rows(),
+            retrieved_at: utils::utc_now(),
+            gtdb_api_version: release,
+            rows: &search_result.rows,
+        })?
     } else {
         search_result
             .rows
             .iter()
-            .map(|x| x.gid.clone())
+            .map(|x| utils::to_json(x).unwrap())
             .collect::<Vec<String>>()
             .join("\n")
     };
@@ -289,45 +1181,124 @@ fn handle_id_or_count_response(
     Ok(result_str)
 }
 
-fn handle_json_response(
-    response: ureq::Response,
-    needle: &str,
-    args: &cli::search::SearchArgs,
+fn format_summary(
+    search_result: &SearchResults,
+    rank: Rank,
+    outfmt: &OutputFormat,
 ) -> Result<String> {
-    let mut search_result: SearchResults = response.into_json()?;
-    if args.is_whole_words_matching() {
-        search_result.filter_json(needle.to_string(), args.get_search_field());
+    let summary = summarize_by_rank(&search_result.rows, rank);
+    let result_str = match outfmt {
+        OutputFormat::Json => utils::to_json(&summary)?,
+        outfmt => {
+            let header = [rank.to_string(), "genomes".into(), "percent".into()];
+            let header: Vec<&str> = header.iter().map(String::as_str).collect();
+            let rows: Vec<Vec<String>> = summary.iter().map(RankSummary::to_fields).collect();
+            utils::render_table(&header, &rows, outfmt)
+        }
+    };
+
+    Ok(result_str)
+}
+
+/// Search results as rows of named columns, joined with the cached GTDB
+/// metadata of `release` when some of `columns` are not in the results
+fn result_rows(
+    search_result: &SearchResults,
+    columns: &[&str],
+    release: Option<String>,
+) -> Result<Vec<aggregate::Row>> {
+    let mut rows: Vec<aggregate::Row> = xsv_rows(search_result)
+        .into_iter()
+        .map(|row| XSV_HEADER.iter().map(|x| x.to_string()).zip(row).collect())
+        .collect();
+    let is_known = |x: &&str| XSV_HEADER.contains(x) || Rank::from_name(x).is_some();
+    if columns.iter().all(is_known) {
+        return Ok(rows);
     }
 
-    ensure!(
-        search_result.get_total_rows() != 0,
-        "No matching data found in GTDB"
-    );
+    let files =
+        local::metadata_files(&local::metadata_dir()?, release.map(|x| release_number(&x)))?;
+    let accessions = rows
+        .iter()
+        .map(|x| strip_gtdb_prefix(&x["accession"]).to_string())
+        .collect();
+    let mut records = local::find_records_in_files(&files, &accessions)?;
+    for row in rows.iter_mut() {
+        if let Some(record) = records.remove(strip_gtdb_prefix(&row["accession"])) {
+            for (column, value) in record {
+                row.entry(column).or_insert(value);
+            }
+        }
+    }
+    Ok(rows)
+}
 
-    let result_str = search_result
-        .rows
+/// Group search results by `key` and render the aggregates of the groups
+fn format_groups(
+    search_result: &SearchResults,
+    key: &str,
+    args: &cli::search::SearchArgs,
+) -> Result<String> {
+    let aggregates = args
+        .get_aggregates()
         .iter()
-        .map(|x| serde_json::to_string_pretty(x).unwrap())
-        .collect::<Vec<String>>()
-        .join("\n");
+        .map(|x| Aggregate::parse(x).map_err(|e| anyhow!(e)))
+        .collect::<Result<Vec<Aggregate>>>()?;
+    let columns: Vec<&str> = std::iter::once(key)
+        .chain(aggregates.iter().filter_map(Aggregate::get_column))
+        .collect();
+    let rows = result_rows(search_result, &columns, args.get_release())?;
+    let groups = aggregate::group_by(&rows, key, &aggregates);
+
+    let names: Vec<String> = aggregates.iter().map(Aggregate::name).collect();
+    let header: Vec<&str> = std::iter::once(key)
+        .chain(names.iter().map(String::as_str))
+        .collect();
+    let result_str = match args.get_outfmt() {
+        OutputFormat::Json => utils::to_json(&aggregate::to_json_records(&header, &groups))?,
+        outfmt => utils::render_table(&header, &groups, &outfmt),
+    };
+
+    Ok(result_str)
+}
+
+/// Render histograms of the numeric columns of search results, as bins
+/// with JSON output and as text otherwise
+fn format_histograms(
+    search_result: &SearchResults,
+    args: &cli::search::SearchArgs,
+) -> Result<String> {
+    let columns: Vec<&str> = args.get_hist().iter().map(String::as_str).collect();
+    let rows = result_rows(search_result, &columns, args.get_release())?;
+    let histograms: Vec<(&str, Vec<aggregate::Bin>, usize)> = columns
+        .iter()
+        .map(|&column| {
+            let (bins, missing) = aggregate::histogram(&rows, column);
+            (column, bins, missing)
+        })
+        .collect();
+
+    let result_str = match args.get_outfmt() {
+        OutputFormat::Json => {
+            let bins: Vec<&aggregate::Bin> = histograms.iter().flat_map(|x| &x.1).collect();
+            utils::to_json(&bins)?
+        }
+        _ => histograms
+            .iter()
+            .map(|(column, bins, missing)| aggregate::render_histogram(column, bins, *missing))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    };
 
     Ok(result_str)
 }
 
 fn handle_xsv_response(
-    response: ureq::Response,
+    text: String,
     needle: &str,
     args: &cli::search::SearchArgs,
 ) -> Result<String> {
-    let mut buf: Vec<u8> = vec![];
-    response
-        .into_reader()
-        .take((INTO_STRING_LIMIT + 1) as u64)
-        .read_to_end(&mut buf)?;
-    if buf.len() > INTO_STRING_LIMIT {
-        return Err(anyhow!("GTDB response is too big (> 20 MB) to convert to string. Please use JSON output format (-O json)"));
-    }
-    let result = String::from_utf8_lossy(&buf).to_string();
+    let mut result = values::normalize_xsv(&text, &args.get_outfmt(), &utils::na_string());
     if args.is_whole_words_matching() {
         filter_xsv(
             result.clone(),
@@ -336,12 +1307,145 @@ fn handle_xsv_response(
             args.get_outfmt(),
         );
     }
-    Ok(result)
+    let separator = match args.get_outfmt() {
+        OutputFormat::Csv => ',',
+        _ => '\t',
+    };
+    if let Some(column) = args.get_stable_sort() {
+        result = utils::sort_xsv_by(&result, separator, &column)?;
+    }
+    Ok(utils::sort_xsv(&result, separator))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::taxon::TaxonAPI;
+    use crate::transport::tests::FakeTransport;
+
+    #[test]
+    fn test_fetch_xsv_pages() {
+        let api = SearchQuery::taxon("g__Escherichia").api().set_outfmt("tsv");
+        let page = |size: u32, page: u16| {
+            api.clone()
+                .set_items_per_page(size)
+                .set_page(page)
+                .request()
+        };
+        let records = |start: usize, n: usize| {
+            let mut text = "accession\tgtdb_taxonomy\n".to_string();
+            for i in start..start + n {
+                text.push_str(&format!("GCA_{}\tg__Escherichia\n", i));
+            }
+            text
+        };
+        // All the results at once time out, then the first page is too large
+        let transport = FakeTransport::default()
+            .with(&api.request(), 504, "")
+            .with(&page(8192, 1), 413, "")
+            .with(&page(4096, 1), 200, &records(0, 4096))
+            .with(&page(4096, 2), 200, &records(4096, 2));
+
+        let text = fetch_xsv_pages(&transport, &api, "").unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4099);
+        assert_eq!(lines[0], "accession\tgtdb_taxonomy");
+        assert_eq!(lines[4097], "GCA_4096\tg__Escherichia");
+        assert_eq!(text.matches("accession").count(), 1);
+
+        // Other errors are not retried in pages
+        let error = fetch_xsv_pages(&FakeTransport::default(), &api, "No match found").unwrap_err();
+        assert!(matches!(error, XgtError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_lookup_accessions() {
+        let accessions: Vec<String> = [
+            "GCA_001512625.1",
+            "RS_GCF_001512635.1",
+            "GCA_001512699",
+            "GCA_900000001.1",
+        ]
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+        assert_eq!(
+            bulk_searches(&accessions),
+            vec![(
+                "GCA_0015126".to_string(),
+                vec![accessions[0].clone(), accessions[2].clone()]
+            )]
+        );
+
+        let api = SearchQuery::accession("GCA_0015126")
+            .api()
+            .set_outfmt("csv");
+        let csv = "accession,ncbi_organism_name,ncbi_taxonomy,gtdb_taxonomy,\
+            gtdb_species_representative,ncbi_type_material\n\
+            GCA_001512625.1,\"Bacillus sp., strain X\",d__Bacteria,d__Bacteria; g__Bacillus,True,False\n\
+            GCA_001512699.2,Escherichia coli,d__Bacteria,d__Bacteria; g__Escherichia,False,True\n";
+        let transport = FakeTransport::default().with(&api.request(), 200, csv);
+
+        let found = lookup_accessions(&transport, &accessions, 2);
+        assert_eq!(found.len(), 2);
+        let genome = &found["GCA_001512625.1"];
+        assert_eq!(
+            genome.get_ncbi_org_name().as_deref(),
+            Some("Bacillus sp., strain X")
+        );
+        assert_eq!(
+            genome.get_gtdb_taxonomy().as_deref(),
+            Some("d__Bacteria; g__Bacillus")
+        );
+        assert_eq!(genome.is_gtdb_species_rep(), Some(true));
+        // An accession without version matches any version
+        assert_eq!(found["GCA_001512699"].get_gid(), "GCA_001512699.2");
+        assert_eq!(found["GCA_001512699"].is_ncbi_type_material(), Some(true));
+
+        // Accessions of failed searches are left to their card
+        assert!(lookup_accessions(&FakeTransport::default(), &accessions, 2).is_empty());
+    }
+
+    #[test]
+    fn test_fetch_pages_split_taxon() {
+        let api = SearchQuery::taxon("o__Enterobacterales").api();
+        let child = |name: &str| api.clone().set_search(name).request();
+        let rows = |gids: &[&str]| {
+            let rows: Vec<String> = gids
+                .iter()
+                .map(|x| {
+                    format!(
+                        r#"{{"gid": "{}", "gtdbTaxonomy": "o__Enterobacterales"}}"#,
+                        x
+                    )
+                })
+                .collect();
+            format!(
+                r#"{{"rows": [{}], "totalRows": {}}}"#,
+                rows.join(","),
+                gids.len()
+            )
+        };
+        // The order times out, so is searched family by family
+        let transport = FakeTransport::default()
+            .with(&api.request(), 504, "")
+            .with(
+                &TaxonAPI::new("o__Enterobacterales").get_name_request(),
+                200,
+                r#"[{"taxon": "f__Enterobacteriaceae"}, {"taxon": "f__Yersiniaceae"}]"#,
+            )
+            .with(
+                &child("f__Enterobacteriaceae"),
+                200,
+                &rows(&["GCA_1", "GCA_2"]),
+            )
+            .with(&child("f__Yersiniaceae"), 200, &rows(&["GCA_2", "GCA_3"]));
+
+        let results = fetch_json_pages(&transport, &api, "").unwrap();
+        let gids: Vec<&str> = results.rows.iter().map(|x| x.gid.as_str()).collect();
+        assert_eq!(gids, ["GCA_1", "GCA_2", "GCA_3"]);
+        assert_eq!(results.total_rows, 3);
+    }
 
     #[test]
     fn test_filter_xsv_csv_accession_field() {
@@ -408,6 +1512,268 @@ mod tests {
         assert_eq!(results.rows.len(), 3);
     }
 
+    #[test]
+    fn test_sort_by_column() {
+        let mut results = SearchResults {
+            rows: [("GCA_2", "s__B"), ("GCA_3", "s__A"), ("GCA_1", "s__B")]
+                .into_iter()
+                .map(|(gid, taxonomy)| SearchResult {
+                    gid: gid.into(),
+                    gtdb_taxonomy: Some(taxonomy.into()),
+                    ..Default::default()
+                })
+                .collect(),
+            total_rows: 3,
+        };
+        results.sort_by_column("gtdb_taxonomy");
+        let gids: Vec<&str> = results.rows.iter().map(|x| x.gid.as_str()).collect();
+        assert_eq!(gids, ["GCA_3", "GCA_1", "GCA_2"]);
+        results.sort_by_column("accession");
+        let gids: Vec<&str> = results.rows.iter().map(|x| x.gid.as_str()).collect();
+        assert_eq!(gids, ["GCA_1", "GCA_2", "GCA_3"]);
+    }
+
+    #[test]
+    fn test_genome_id() {
+        let genome = |gid: &str| SearchResult {
+            gid: gid.into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            genome_id(&genome("GCF_000005845.2"), "gid"),
+            "GCF_000005845.2"
+        );
+        assert_eq!(
+            genome_id(&genome("GCF_000005845.2"), "accession"),
+            "RS_GCF_000005845.2"
+        );
+        assert_eq!(
+            genome_id(&genome("GB_GCA_001512625.1"), "accession"),
+            "GB_GCA_001512625.1"
+        );
+        assert_eq!(
+            genome_id(&genome("RS_GCF_000005845.2"), "ncbi_assembly"),
+            "GCF_000005845.2"
+        );
+    }
+
+    #[test]
+    fn test_distinct_species() {
+        let mut results = SearchResults {
+            rows: [
+                ("GCA_1", "g__Escherichia; s__Escherichia coli", false, true),
+                (
+                    "GCA_2",
+                    "g__Escherichia; s__Escherichia fergusonii",
+                    false,
+                    false,
+                ),
+                ("GCA_3", "g__Escherichia; s__Escherichia coli", true, false),
+                ("GCA_4", "g__Escherichia; s__Escherichia coli", false, false),
+                ("GCA_5", "g__Escherichia", false, false),
+            ]
+            .into_iter()
+            .map(|(gid, taxonomy, rep, type_material)| SearchResult {
+                gid: gid.into(),
+                gtdb_taxonomy: Some(taxonomy.into()),
+                is_gtdb_species_rep: Some(rep),
+                is_ncbi_type_material: Some(type_material),
+                ..Default::default()
+            })
+            .collect(),
+            total_rows: 5,
+        };
+        results.distinct_species();
+        let species: Vec<(&str, Option<u32>)> = results
+            .rows
+            .iter()
+            .map(|x| (x.gid.as_str(), x.get_species_genomes()))
+            .collect();
+        assert_eq!(
+            species,
+            [("GCA_3", Some(3)), ("GCA_2", Some(1)), ("GCA_5", Some(1))]
+        );
+        assert_eq!(results.get_total_rows(), 3);
+
+        let csv = format_xsv(&results, &OutputFormat::Csv);
+        assert!(csv.starts_with("accession,"));
+        assert!(csv.lines().next().unwrap().ends_with(",species_genomes"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",true,false,3"));
+        assert!(utils::to_json(&results.rows[0])
+            .unwrap()
+            .contains("\"speciesGenomes\": 3"));
+    }
+
+    #[test]
+    fn test_format_search_results_envelope() {
+        let results = SearchResults {
+            rows: vec![SearchResult {
+                gid: "GCA_001512625.1".into(),
+                ..Default::default()
+            }],
+            total_rows: 1,
+        };
+        let args = cli::search::SearchArgs {
+            envelope: true,
+            outfmt: OutputFormat::Json,
+            ..Default::default()
+        };
+        let output = format_search_results(&results, "g__Aminobacter", &args, Some("R220"));
+        let value: serde_json::Value = serde_json::from_str(&output.unwrap()).unwrap();
+        assert_eq!(value["query"], "g__Aminobacter");
+        assert_eq!(value["total_rows"], 1);
+        assert_eq!(value["gtdb_api_version"], "R220");
+        assert!(value["retrieved_at"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(value["rows"][0]["gid"], "GCA_001512625.1");
+    }
+
+    #[test]
+    fn test_format_groups() {
+        let result = |gid: &str, taxonomy: &str, rep: bool| SearchResult {
+            gid: gid.into(),
+            gtdb_taxonomy: Some(taxonomy.into()),
+            is_gtdb_species_rep: Some(rep),
+            ..Default::default()
+        };
+        let results = SearchResults {
+            rows: vec![
+                result(
+                    "GCA_1",
+                    "d__Bacteria; p__Bacillota; f__Lactobacillaceae",
+                    true,
+                ),
+                result("GCA_2", "d__Bacteria; p__Bacillota; f__Bacillaceae", false),
+                result(
+                    "GCA_3",
+                    "d__Bacteria; p__Bacillota; f__Lactobacillaceae",
+                    false,
+                ),
+            ],
+            total_rows: 3,
+        };
+        let args = cli::search::SearchArgs {
+            group_by: Some("family".into()),
+            aggregates: vec!["count".into()],
+            outfmt: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_search_results(&results, "p__Bacillota", &args, None).unwrap(),
+            "family,count\nf__Bacillaceae,1\nf__Lactobacillaceae,2\n"
+        );
+
+        let args = cli::search::SearchArgs {
+            group_by: Some("gtdb_species_representative".into()),
+            aggregates: vec!["count".into()],
+            outfmt: OutputFormat::Json,
+            ..Default::default()
+        };
+        let output = format_search_results(&results, "p__Bacillota", &args, None).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {"gtdb_species_representative": "false", "count": 2.0},
+                {"gtdb_species_representative": "true", "count": 1.0},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_filter_lineage() {
+        let mut results = SearchResults {
+            rows: [
+                (
+                    "GCF_1",
+                    "d__Bacteria; f__Enterobacteriaceae; g__Escherichia; s__Escherichia coli",
+                ),
+                (
+                    "GCF_2",
+                    "d__Bacteria; f__Enterobacteriaceae; g__Escherichia_A; s__Escherichia_A sp.",
+                ),
+                (
+                    "GCF_3",
+                    "d__Bacteria; f__Enterobacteriaceae; g__Escherichia; s__Escherichia fergusonii",
+                ),
+            ]
+            .iter()
+            .map(|(gid, lineage)| SearchResult {
+                gid: gid.to_string(),
+                gtdb_taxonomy: Some(lineage.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+            total_rows: 3,
+        };
+
+        results.filter_lineage("d__Bacteria;f__Enterobacteriaceae;g__Escherichia");
+        assert_eq!(results.get_total_rows(), 2);
+        assert_eq!(results.rows[1].gid, "GCF_3");
+        assert_eq!(
+            lineage_search_taxon("d__Bacteria; f__Enterobacteriaceae; g__Escherichia").unwrap(),
+            "g__Escherichia"
+        );
+        assert!(lineage_search_taxon("Bacteria").is_err());
+    }
+
+    #[test]
+    fn test_summarize_by_rank() {
+        let rows: Vec<SearchResult> = [
+            "d__Bacteria; p__Pseudomonadota; c__Alphaproteobacteria; o__Rhizobiales; f__Rhizobiaceae; g__Rhizobium; s__Rhizobium etli",
+            "d__Bacteria; p__Pseudomonadota; c__Alphaproteobacteria; o__Rhizobiales; f__Rhizobiaceae; g__Agrobacterium; s__Agrobacterium tumefaciens",
+            "d__Bacteria; p__Pseudomonadota; c__Alphaproteobacteria; o__Rhizobiales; f__Rhizobiaceae; g__Rhizobium; s__Rhizobium phaseoli",
+            "d__Bacteria; p__Pseudomonadota; c__Alphaproteobacteria; o__Rhizobiales; f__Rhizobiaceae; g__; s__",
+        ]
+        .iter()
+        .map(|lineage| SearchResult {
+            gtdb_taxonomy: Some(lineage.to_string()),
+            ..Default::default()
+        })
+        .collect();
+
+        let summary = summarize_by_rank(&rows, Rank::Genus);
+        assert_eq!(summary.len(), 3);
+        assert_eq!(
+            summary[0].to_fields(),
+            vec!["g__Rhizobium".to_string(), "2".into(), "50.00".into()]
+        );
+        assert_eq!(summary[1].taxon, "g__Agrobacterium");
+        assert_eq!(summary[2].taxon, "unclassified");
+
+        let summary = summarize_by_rank(&rows, Rank::Family);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].percent, 100.0);
+    }
+
+    #[test]
+    fn test_search_metadata() {
+        let metadata = "accession\tncbi_organism_name\tncbi_taxonomy\tgtdb_taxonomy\tgtdb_representative\tncbi_type_material_designation\n\
+            RS_GCF_000010525.1\tAzorhizobium caulinodans ORS 571\td__Bacteria; g__Azorhizobium\td__Bacteria; g__Azorhizobium\tt\ttype strain of species\n\
+            GB_GCA_002279595.1\tAzorhizobium sp. 35-67-15\td__Bacteria; g__Azorhizobium\td__Bacteria; g__Azorhizobium_A\tf\tnone\n";
+        let table = local::MetadataTable::read(metadata.as_bytes(), &LOCAL_SEARCH_COLUMNS).unwrap();
+
+        let mut args = cli::search::SearchArgs::new();
+        let results = search_metadata(&table, "azorhizobium", &args);
+        assert_eq!(results.get_total_rows(), 2);
+        assert_eq!(results.rows[0].gid, "GCF_000010525.1");
+        assert_eq!(results.rows[0].is_ncbi_type_material(), Some(true));
+
+        args.set_search_field("gtdb");
+        args.set_matching_mode(true);
+        let results = search_metadata(&table, "g__Azorhizobium", &args);
+        assert_eq!(results.get_total_rows(), 1);
+
+        args.set_search_field("all");
+        args.set_matching_mode(false);
+        args.is_representative_species_only = true;
+        let results = search_metadata(&table, "Azorhizobium", &args);
+        assert_eq!(
+            format_xsv(&results, &OutputFormat::Tsv),
+            "accession\tncbi_organism_name\tncbi_taxonomy\tgtdb_taxonomy\tgtdb_species_representative\tncbi_type_material\n\
+             GCF_000010525.1\tAzorhizobium caulinodans ORS 571\td__Bacteria; g__Azorhizobium\td__Bacteria; g__Azorhizobium\ttrue\ttrue\n"
+        );
+    }
+
     #[test]
     fn test_search_id() {
         let mut args = cli::search::SearchArgs::new();
@@ -0,0 +1,373 @@
+use anyhow::{bail, Context, Result};
+use md5::{Digest, Md5};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use ureq::Agent;
+
+use crate::api::GTDB_API_URL;
+use crate::cli::serve::ServeArgs;
+use crate::error::XgtError;
+use crate::interrupt;
+use crate::metrics;
+use crate::utils;
+
+/// Directory of the cached responses in the cache directory
+const RESPONSES_DIR: &str = "responses";
+
+/// Endpoints of the GTDB API served by the proxy
+const PROXIED_PATHS: [&str; 3] = ["/search/", "/genome/", "/taxon/"];
+
+/// Space out requests so that at most `rate` are sent per second
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(rate: f64) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs_f64(1.0 / rate),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Block until a request can be sent
+    fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().expect("rate limiter lock poisoned");
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Successful responses of the GTDB API stored on disk, indexed by URL,
+/// fetched again after `ttl` so that a new release is served
+#[derive(Debug, Clone)]
+struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:x}", Md5::digest(url.as_bytes())))
+    }
+
+    /// Content type and body of a cached response, none if it expired
+    fn get(&self, url: &str) -> Option<(String, Vec<u8>)> {
+        let path = self.path(url);
+        let age = fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age > self.ttl {
+            return None;
+        }
+        let content = fs::read(path).ok()?;
+        let newline = content.iter().position(|&b| b == b'\n')?;
+        let content_type = String::from_utf8(content[..newline].to_vec()).ok()?;
+        Some((content_type, content[newline + 1..].to_vec()))
+    }
+
+    fn put(&self, url: &str, content_type: &str, body: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Write then rename so that concurrent readers never see a partial file
+        let path = self.path(url);
+        let partial = path.with_extension("part");
+        let mut content = format!("{}\n", content_type).into_bytes();
+        content.extend_from_slice(body);
+        fs::write(&partial, content)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
+/// A response sent back to the client
+#[derive(Debug, Clone, PartialEq)]
+struct Response {
+    status: u16,
+    content_type: String,
+    body: Vec<u8>,
+    // Whether the response was served from the cache
+    cached: bool,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "text/plain".to_string(),
+            body: format!("{}\n", message).into_bytes(),
+            cached: false,
+        }
+    }
+
+    fn write_to(&self, stream: &mut impl Write) -> Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            502 => "Bad Gateway",
+            _ => "",
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nX-Cache: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len(),
+            if self.cached { "HIT" } else { "MISS" }
+        )?;
+        stream.write_all(&self.body)?;
+        Ok(stream.flush()?)
+    }
+}
+
+/// Method and target of the request line of a HTTP request
+fn parse_request_line(line: &str) -> Result<(String, String)> {
+    match line.split_whitespace().collect::<Vec<&str>>()[..] {
+        [method, target, version] if version.starts_with("HTTP/") => {
+            Ok((method.to_string(), target.to_string()))
+        }
+        _ => bail!("Invalid request line: {}", line.trim()),
+    }
+}
+
+/// State shared by the connections of the proxy
+struct Proxy {
+    agent: Agent,
+    upstream: String,
+    cache: ResponseCache,
+    limiter: RateLimiter,
+}
+
+impl Proxy {
+    /// Answer a request from the cache, or from the GTDB API once the rate
+    /// limiter allows it
+    fn respond(&self, method: &str, target: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, "Only GET requests are supported");
+        }
+        if !PROXIED_PATHS.iter().any(|path| target.starts_with(path)) {
+            return Response::error(404, "Only search, genome and taxon requests are served");
+        }
+
+        let url = format!("{}{}", self.upstream, target);
+        if let Some((content_type, body)) = self.cache.get(&url) {
+            metrics::record_cache_hits(1);
+            return Response {
+                status: 200,
+                content_type,
+                body,
+                cached: true,
+            };
+        }
+
+        self.limiter.wait();
+        let (status, response) = match self.agent.get(&url).call() {
+            Ok(response) => (200, response),
+            // Errors of the GTDB API are forwarded but not cached
+            Err(ureq::Error::Status(code, response)) => (code, response),
+            Err(_) => return Response::error(502, "Error making the request to the GTDB API"),
+        };
+        let content_type = response.content_type().to_string();
+        let mut body = Vec::new();
+        if response.into_reader().read_to_end(&mut body).is_err() {
+            return Response::error(502, "Error receiving the response of the GTDB API");
+        }
+        if status == 200 {
+            if let Err(e) = self.cache.put(&url, &content_type, &body) {
+                eprintln!("Failed to cache {}: {}", url, e);
+            }
+        }
+
+        Response {
+            status,
+            content_type,
+            body,
+            cached: false,
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are not used but must be read before answering
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let response = match parse_request_line(&request_line) {
+            Ok((method, target)) => self.respond(&method, &target),
+            Err(e) => Response::error(400, &e.to_string()),
+        };
+        response.write_to(&mut &stream)
+    }
+}
+
+/// Answer the connections of `listener`, each in its own thread, until
+/// Ctrl-C, then wait for the requests in progress
+fn run(listener: TcpListener, proxy: Proxy) -> Result<()> {
+    let proxy = Arc::new(proxy);
+    // Connections are polled so that Ctrl-C is noticed between them
+    listener.set_nonblocking(true)?;
+    let mut handlers: Vec<thread::JoinHandle<()>> = Vec::new();
+    while !interrupt::is_interrupted() {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(interrupt::POLL_INTERVAL);
+                continue;
+            }
+            // Failed connections, e.g. aborted or beyond the open files
+            // limit, do not stop the proxy
+            Err(e) => {
+                eprintln!("Error: Failed to accept a connection: {}", e);
+                thread::sleep(interrupt::POLL_INTERVAL);
+                continue;
+            }
+        };
+        if let Err(e) = stream.set_nonblocking(false) {
+            eprintln!("Error: {}", e);
+            continue;
+        }
+        let proxy = Arc::clone(&proxy);
+        handlers.retain(|x| !x.is_finished());
+        handlers.push(thread::spawn(move || {
+            if let Err(e) = proxy.handle(stream) {
+                eprintln!("Error: {:#}", e);
+            }
+        }));
+    }
+    for handler in handlers {
+        let _ = handler.join();
+    }
+    Err(XgtError::Interrupted.into())
+}
+
+pub fn serve(args: ServeArgs) -> Result<()> {
+    let address = format!("{}:{}", args.get_host(), args.get_port());
+    let listener =
+        TcpListener::bind(&address).with_context(|| format!("Failed to listen on {}", address))?;
+    let proxy = Proxy {
+        agent: utils::get_agent(args.get_disable_certificate_verification())?,
+        upstream: GTDB_API_URL.to_string(),
+        cache: ResponseCache {
+            dir: utils::cache_dir()?.join(RESPONSES_DIR),
+            ttl: args.get_cache_ttl(),
+        },
+        limiter: RateLimiter::new(args.get_rate()),
+    };
+
+    eprintln!("Serving the GTDB API on http://{}", address);
+    run(listener, proxy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(
+            parse_request_line("GET /taxon/g__Aminobacter HTTP/1.1\r\n").unwrap(),
+            ("GET".to_string(), "/taxon/g__Aminobacter".to_string())
+        );
+        assert!(parse_request_line("GET /taxon/g__Aminobacter").is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(20.0);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.wait();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_response_cache_ttl() {
+        let dir = std::env::temp_dir().join(format!("xgt-serve-ttl-{}", std::process::id()));
+        let mut cache = ResponseCache {
+            dir: dir.clone(),
+            ttl: Duration::from_secs(3600),
+        };
+        cache.put("/taxon/g__A", "application/json", b"[]").unwrap();
+        assert_eq!(
+            cache.get("/taxon/g__A"),
+            Some(("application/json".to_string(), b"[]".to_vec()))
+        );
+
+        thread::sleep(Duration::from_millis(10));
+        cache.ttl = Duration::ZERO;
+        assert_eq!(cache.get("/taxon/g__A"), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_proxy() {
+        let mut server = Server::new();
+        let mock = server
+            .mock("GET", "/taxon/g__Aminobacter")
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"taxon": "s__Aminobacter aminovorans"}]"#)
+            .expect(1)
+            .create();
+        server
+            .mock("GET", "/genome/unknown/card")
+            .with_status(400)
+            .create();
+
+        let dir = std::env::temp_dir().join(format!("xgt-serve-{}", std::process::id()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let proxy = Proxy {
+            agent: utils::get_agent(false).unwrap(),
+            upstream: server.url(),
+            cache: ResponseCache {
+                dir: dir.clone(),
+                ttl: Duration::from_secs(3600),
+            },
+            limiter: RateLimiter::new(100.0),
+        };
+        thread::spawn(move || run(listener, proxy));
+
+        let agent = utils::get_agent(false).unwrap();
+        let url = format!("http://{}/taxon/g__Aminobacter", address);
+        for cache in ["MISS", "HIT"] {
+            let response = agent.get(&url).call().unwrap();
+            assert_eq!(response.header("x-cache"), Some(cache));
+            assert_eq!(response.content_type(), "application/json");
+            assert_eq!(
+                response.into_string().unwrap(),
+                r#"[{"taxon": "s__Aminobacter aminovorans"}]"#
+            );
+        }
+        mock.assert();
+
+        let url = format!("http://{}/genome/unknown/card", address);
+        assert!(matches!(
+            agent.get(&url).call(),
+            Err(ureq::Error::Status(400, _))
+        ));
+        let url = format!("http://{}/releases", address);
+        assert!(matches!(
+            agent.get(&url).call(),
+            Err(ureq::Error::Status(404, _))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
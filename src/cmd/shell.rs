@@ -0,0 +1,187 @@
+use anyhow::{bail, ensure, Context, Result};
+use clap::ArgMatches;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::app;
+use crate::cli::shell::ShellArgs;
+use crate::interrupt;
+use crate::utils;
+
+/// Name of the history file in the cache directory
+const HISTORY_FILE: &str = "shell_history";
+
+const PROMPT: &str = "xgt> ";
+
+/// Split a command line into words, honouring quotes and backslash escapes
+fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                let escaped = chars.next().context("Unfinished escape at end of line")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    ensure!(quote.is_none(), "Unmatched quote in command line");
+    words.extend(word);
+
+    Ok(words)
+}
+
+/// Replace a `!!` or `!N` command line by the last or Nth command of the
+/// history
+fn expand_history(line: &str, history: &[String]) -> Result<String> {
+    let Some(reference) = line.strip_prefix('!') else {
+        return Ok(line.to_string());
+    };
+    let command = match reference {
+        "!" => history.last(),
+        n => match n.parse::<usize>() {
+            Ok(n) if n > 0 => history.get(n - 1),
+            _ => bail!("Invalid history reference {}", line),
+        },
+    };
+    command
+        .cloned()
+        .with_context(|| format!("{}: event not found", line))
+}
+
+fn read_history(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| content.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn append_history(path: &Path, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Parse a command line as xgt arguments and run it
+fn run_line(line: &str, run: fn(&ArgMatches) -> Result<()>) -> Result<()> {
+    let mut words = split_words(line)?;
+    if words.first().map(String::as_str) == Some("xgt") {
+        words.remove(0);
+    }
+    ensure!(
+        words.first().map(String::as_str) != Some("shell"),
+        "Already running a shell"
+    );
+
+    match app::build_app().try_get_matches_from(std::iter::once("xgt".to_string()).chain(words)) {
+        Ok(matches) => run(&matches),
+        // Also prints help and version requests
+        Err(e) => Ok(e.print()?),
+    }
+}
+
+pub fn shell(args: ShellArgs, run: fn(&ArgMatches) -> Result<()>) -> Result<()> {
+    let history_path: Option<PathBuf> = match args.is_save_history() {
+        true => Some(utils::cache_dir()?.join(HISTORY_FILE)),
+        false => None,
+    };
+    let mut history = history_path
+        .as_deref()
+        .map(read_history)
+        .unwrap_or_default();
+
+    let stdin = io::stdin();
+    let mut input = String::new();
+    loop {
+        print!("{}", PROMPT);
+        io::stdout().flush()?;
+        input.clear();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        let line = match expand_history(input.trim(), &history) {
+            Ok(line) if line.is_empty() => continue,
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                continue;
+            }
+        };
+        if line != input.trim() {
+            println!("{}", line);
+        }
+        history.push(line.clone());
+        if let Some(path) = &history_path {
+            append_history(path, &line)?;
+        }
+
+        match line.as_str() {
+            "exit" | "quit" => return Ok(()),
+            "history" => {
+                for (i, command) in history.iter().enumerate() {
+                    println!("{:5}  {}", i + 1, command);
+                }
+            }
+            _ => {
+                if let Err(e) = run_line(&line, run) {
+                    eprintln!("Error: {:#}", e);
+                }
+                // A Ctrl-C only stops the query it interrupted
+                interrupt::reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"search -w "s__Escherichia coli" --id"#).unwrap(),
+            vec!["search", "-w", "s__Escherichia coli", "--id"]
+        );
+        assert_eq!(
+            split_words(r"taxon s__Escherichia\ coli ''").unwrap(),
+            vec!["taxon", "s__Escherichia coli", ""]
+        );
+        assert!(split_words("search \"g__Escherichia").is_err());
+    }
+
+    #[test]
+    fn test_expand_history() {
+        let history = vec![
+            "search g__Aminobacter".to_string(),
+            "genome GCA_001512625.1".to_string(),
+        ];
+        assert_eq!(
+            expand_history("!!", &history).unwrap(),
+            "genome GCA_001512625.1"
+        );
+        assert_eq!(
+            expand_history("!1", &history).unwrap(),
+            "search g__Aminobacter"
+        );
+        assert_eq!(expand_history("taxon", &history).unwrap(), "taxon");
+        assert!(expand_history("!3", &history).is_err());
+        assert!(expand_history("!x", &history).is_err());
+    }
+}
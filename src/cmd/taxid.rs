@@ -0,0 +1,94 @@
+use anyhow::{bail, Context, Result};
+use ureq::Agent;
+
+use crate::cli::taxid::TaxidArgs;
+use crate::cmd::genome::fetch_genome_card;
+use crate::cmd::taxon::{fetch_taxon_children, fetch_taxon_lineage};
+use crate::taxonomy::Rank;
+use crate::utils::{self, OutputFormat};
+
+const HEADER: [&str; 3] = ["name", "ncbi_taxid", "ncbi_species_taxid"];
+
+/// NCBI taxids behind a GTDB taxon or genome
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Taxids {
+    name: String,
+    ncbi_taxid: Option<String>,
+    ncbi_species_taxid: Option<String>,
+}
+
+impl Taxids {
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.ncbi_taxid.clone().unwrap_or_default(),
+            self.ncbi_species_taxid.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// NCBI taxid and species taxid of a genome, from its card
+fn genome_taxids(agent: &Agent, accession: &str) -> Result<Taxids> {
+    let card = fetch_genome_card(agent, accession)?;
+    Ok(Taxids {
+        name: accession.to_string(),
+        ncbi_taxid: card.get_ncbi_taxid(),
+        ncbi_species_taxid: card.get_ncbi_species_taxid(),
+    })
+}
+
+/// NCBI taxid of a taxon, as listed among the children of its parent
+fn taxon_taxids(agent: &Agent, name: &str) -> Result<Taxids> {
+    let lineage = fetch_taxon_lineage(agent, name)?;
+    let rank = Rank::from_taxon(name).context("Taxon name must be in greengenes format")?;
+    let Some((_, parent)) = lineage.range(..rank).next_back() else {
+        bail!(
+            "The GTDB API does not list the NCBI taxid of domain {}",
+            name
+        );
+    };
+    let taxon = fetch_taxon_children(agent, parent)?
+        .data
+        .into_iter()
+        .find(|x| x.get_taxon() == name)
+        .with_context(|| format!("Taxon {} not found among the children of {}", name, parent))?;
+    Ok(Taxids {
+        name: name.to_string(),
+        ncbi_taxid: taxon.get_ncbi_tax_id().map(|x| x.to_string()),
+        ncbi_species_taxid: None,
+    })
+}
+
+/// Print the NCBI taxids behind GTDB taxa and genomes
+pub fn taxid(args: TaxidArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    let rows = args
+        .get_names()
+        .iter()
+        .map(|name| match Rank::from_taxon(name) {
+            Some(_) => taxon_taxids(&agent, name),
+            None => genome_taxids(&agent, name),
+        })
+        .map(|x| x.map(|x| x.to_fields()))
+        .collect::<Result<Vec<Vec<String>>>>()?;
+    let output = utils::render_table(&HEADER, &rows, &OutputFormat::Tsv);
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taxids_to_fields() {
+        let taxids = Taxids {
+            name: "g__Aminobacter".to_string(),
+            ncbi_taxid: Some("31988".to_string()),
+            ncbi_species_taxid: None,
+        };
+        assert_eq!(taxids.to_fields(), vec!["g__Aminobacter", "31988", ""]);
+    }
+}
@@ -1,13 +1,27 @@
-use anyhow::{bail, ensure, Result};
+use anyhow::{ensure, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use ureq::Agent;
 
 use crate::api::taxon::TaxonAPI;
 
 use crate::cli::taxon::TaxonArgs;
-use crate::utils;
+use crate::cmd::genome::{
+    fetch_genome_card, fetch_genome_cards, fetch_genome_histories, GenomeCard, GenomeTaxonHistory,
+    MetadataTypeMaterial,
+};
+use crate::cmd::search::{fetch_search_results, SearchResult};
+use crate::error::XgtError;
+use crate::interrupt;
+use crate::query::SearchQuery;
+use crate::taxonomy::{self, release_number, Rank};
+use crate::transport::{get_json, Transport};
+use crate::utils::{self, ItemErrors, OutputFormat};
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
 pub struct Taxon {
     taxon: String,
     total: Option<f32>,
@@ -31,19 +45,522 @@ pub struct Taxon {
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(transparent)]
+#[non_exhaustive]
 pub struct TaxonResult {
-    data: Vec<Taxon>,
+    pub(crate) data: Vec<Taxon>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[non_exhaustive]
 pub struct TaxonSearchResult {
-    matches: Vec<String>,
+    pub(crate) matches: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(transparent)]
+#[non_exhaustive]
 pub struct TaxonGenomes {
-    data: Vec<String>,
+    pub(crate) data: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// A taxon name and the range of releases in which it was seen
+#[non_exhaustive]
+pub struct TaxonRelease {
+    taxon: String,
+    first_seen: Option<String>,
+    last_seen: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(transparent)]
+// Previous releases of a taxon name, as returned by GTDB
+pub(crate) struct TaxonReleases {
+    rows: Vec<TaxonRelease>,
+}
+
+impl TaxonRelease {
+    /// Get taxon name
+    pub fn get_taxon(&self) -> String {
+        self.taxon.clone()
+    }
+
+    /// Get the first release in which the name was seen, e.g. "R80"
+    pub fn get_first_seen(&self) -> Option<String> {
+        self.first_seen.clone()
+    }
+
+    /// Get the last release in which the name was seen
+    pub fn get_last_seen(&self) -> Option<String> {
+        self.last_seen.clone()
+    }
+
+    /// Check if the name was in use in `release`
+    fn is_seen_in(&self, release: &str) -> bool {
+        let release = release_number(release);
+        let first = self.first_seen.as_deref().map_or(0, release_number);
+        let last = self.last_seen.as_deref().map_or(u32::MAX, release_number);
+        first <= release && release <= last
+    }
+}
+
+impl TaxonReleases {
+    /// Names given to the taxon `name` across releases, oldest first
+    pub(crate) fn into_history(self, name: &str) -> Result<Vec<TaxonRelease>, XgtError> {
+        let mut releases = self.rows;
+        if releases.is_empty() {
+            return Err(XgtError::NotFound(format!("No history found for {}", name)));
+        }
+        releases.sort_by_key(|x| x.first_seen.as_deref().map(release_number));
+        Ok(releases)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Composition summary of a clade
+pub struct TaxonStats {
+    taxon: String,
+    genomes: usize,
+    species_representatives: usize,
+    type_material: usize,
+    // Number of distinct descendant taxa at each rank below the taxon
+    descendants: BTreeMap<Rank, usize>,
+}
+
+impl TaxonStats {
+    /// Summarize the genomes of `taxon` from search results
+    fn from_search_results(taxon: &str, results: &[SearchResult]) -> Self {
+        let mut stats = TaxonStats {
+            taxon: taxon.to_string(),
+            ..Default::default()
+        };
+        let rank = Rank::from_taxon(taxon);
+        let mut descendants: BTreeMap<Rank, HashSet<String>> = BTreeMap::new();
+
+        for result in results {
+            let lineage = taxonomy::parse_lineage(&result.get_gtdb_taxonomy().unwrap_or_default());
+            // Search matches are partial, only keep genomes of the taxon itself
+            if rank.and_then(|r| lineage.get(&r)).map(String::as_str) != Some(taxon) {
+                continue;
+            }
+            stats.genomes += 1;
+            stats.species_representatives +=
+                usize::from(result.is_gtdb_species_rep() == Some(true));
+            stats.type_material += usize::from(result.is_ncbi_type_material() == Some(true));
+            for (r, name) in lineage {
+                if Some(r) > rank {
+                    descendants.entry(r).or_default().insert(name);
+                }
+            }
+        }
+
+        stats.descendants = descendants
+            .into_iter()
+            .map(|(r, names)| (r, names.len()))
+            .collect();
+        stats
+    }
+
+    fn fraction(&self, count: usize) -> String {
+        match self.genomes {
+            0 => "0".to_string(),
+            n => format!("{:.4}", count as f64 / n as f64),
+        }
+    }
+
+    /// Rows of taxon, metric and value
+    fn to_rows(&self) -> Vec<Vec<String>> {
+        let mut metrics = vec![
+            ("genomes".to_string(), self.genomes.to_string()),
+            (
+                "species_representatives".to_string(),
+                self.species_representatives.to_string(),
+            ),
+            (
+                "species_representatives_fraction".to_string(),
+                self.fraction(self.species_representatives),
+            ),
+            ("type_material".to_string(), self.type_material.to_string()),
+            (
+                "type_material_fraction".to_string(),
+                self.fraction(self.type_material),
+            ),
+        ];
+        metrics.extend(
+            self.descendants
+                .iter()
+                .map(|(rank, count)| (rank.to_string(), count.to_string())),
+        );
+        metrics
+            .into_iter()
+            .map(|(metric, value)| vec![self.taxon.clone(), metric, value])
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Number of validly published and placeholder names at a rank
+pub struct NameCounts {
+    published: usize,
+    placeholder: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Descendant taxa of a clade with placeholder names, i.e. undescribed
+pub struct NovelTaxa {
+    taxon: String,
+    // Number of published and placeholder descendant taxa at each rank
+    counts: BTreeMap<Rank, NameCounts>,
+    // Lineages of the descendant taxa with a placeholder name, from the
+    // domain down to the taxon
+    novel: Vec<String>,
+}
+
+impl NovelTaxa {
+    const HEADER: [&'static str; 4] = ["taxon", "rank", "name", "lineage"];
+
+    /// Find the placeholder descendants of `taxon` in search results
+    fn from_search_results(taxon: &str, results: &[SearchResult]) -> Self {
+        let rank = Rank::from_taxon(taxon);
+        let mut descendants: BTreeMap<Rank, BTreeMap<String, String>> = BTreeMap::new();
+
+        for result in results {
+            let lineage = taxonomy::parse_lineage(&result.get_gtdb_taxonomy().unwrap_or_default());
+            // Search matches are partial, only keep genomes of the taxon itself
+            if rank.and_then(|r| lineage.get(&r)).map(String::as_str) != Some(taxon) {
+                continue;
+            }
+            for (r, name) in &lineage {
+                if Some(*r) > rank {
+                    let mut parents = lineage.clone();
+                    parents.retain(|x, _| x <= r);
+                    descendants
+                        .entry(*r)
+                        .or_default()
+                        .insert(name.clone(), taxonomy::format_lineage(&parents));
+                }
+            }
+        }
+
+        let mut novel = NovelTaxa {
+            taxon: taxon.to_string(),
+            ..Default::default()
+        };
+        for (r, names) in descendants {
+            let counts = novel.counts.entry(r).or_default();
+            for (name, lineage) in names {
+                if taxonomy::is_placeholder(&name) {
+                    counts.placeholder += 1;
+                    novel.novel.push(lineage);
+                } else {
+                    counts.published += 1;
+                }
+            }
+        }
+        novel
+    }
+
+    /// Rows of taxon, rank, name and lineage of the placeholder descendants
+    fn to_rows(&self) -> Vec<Vec<String>> {
+        self.novel
+            .iter()
+            .filter_map(|lineage| {
+                let name = lineage.rsplit("; ").next()?;
+                let rank = Rank::from_taxon(name)?;
+                Some(vec![
+                    self.taxon.clone(),
+                    rank.to_string(),
+                    name.to_string(),
+                    lineage.clone(),
+                ])
+            })
+            .collect()
+    }
+
+    /// Summary of the counts, e.g. "g__Aminobacter: 3/5 species are placeholders"
+    fn summary(&self) -> String {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(rank, x)| format!("{}/{} {}", x.placeholder, x.placeholder + x.published, rank))
+            .collect();
+        format!("{}: {} are placeholders", self.taxon, counts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Type material of a species of a clade
+pub struct SpeciesTypeMaterial {
+    species: String,
+    genomes: usize,
+    has_type_material: bool,
+    // Genomes of the species which are type material
+    type_genomes: Vec<String>,
+    // Designations of the type genome, the species representative if it is one
+    gtdb_type_designation: Option<String>,
+    gtdb_type_designation_sources: Option<String>,
+    lpsn_type_designation: Option<String>,
+    dsmz_type_designation: Option<String>,
+}
+
+impl SpeciesTypeMaterial {
+    const HEADER: [&'static str; 8] = [
+        "species",
+        "genomes",
+        "has_type_material",
+        "type_genomes",
+        "gtdb_type_designation",
+        "gtdb_type_designation_sources",
+        "lpsn_type_designation",
+        "dsmz_type_designation",
+    ];
+
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.species.clone(),
+            self.genomes.to_string(),
+            self.has_type_material.to_string(),
+            self.type_genomes.join(";"),
+            self.gtdb_type_designation.clone().unwrap_or_default(),
+            self.gtdb_type_designation_sources
+                .clone()
+                .unwrap_or_default(),
+            self.lpsn_type_designation.clone().unwrap_or_default(),
+            self.dsmz_type_designation.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Genomes of the species of `taxon` in search results, by species
+fn species_genomes<'a>(
+    taxon: &str,
+    results: &'a [SearchResult],
+) -> BTreeMap<String, Vec<&'a SearchResult>> {
+    let rank = Rank::from_taxon(taxon);
+    let mut species: BTreeMap<String, Vec<&SearchResult>> = BTreeMap::new();
+    for result in results {
+        let mut lineage = taxonomy::parse_lineage(&result.get_gtdb_taxonomy().unwrap_or_default());
+        // Search matches are partial, only keep genomes of the taxon itself
+        if rank.and_then(|r| lineage.get(&r)).map(String::as_str) != Some(taxon) {
+            continue;
+        }
+        if let Some(name) = lineage.remove(&Rank::Species) {
+            species.entry(name).or_default().push(result);
+        }
+    }
+    species
+}
+
+/// Is a designation one of a type genome, e.g. "type strain of species"
+/// rather than "not type material"
+fn is_type_designation(designation: Option<&str>) -> bool {
+    designation.is_some_and(|x| !matches!(x.trim(), "" | "none" | "not type material"))
+}
+
+/// Type material of each species, from the type designations of the
+/// genomes whose card was fetched
+fn species_type_material(
+    species: &BTreeMap<String, Vec<&SearchResult>>,
+    designations: &HashMap<String, MetadataTypeMaterial>,
+) -> Vec<SpeciesTypeMaterial> {
+    species
+        .iter()
+        .map(|(name, genomes)| {
+            let mut type_genomes: Vec<&SearchResult> = genomes
+                .iter()
+                .copied()
+                .filter(|genome| {
+                    genome.is_ncbi_type_material() == Some(true)
+                        || designations.get(&genome.gid).is_some_and(|x| {
+                            is_type_designation(x.get_gtdb_type_designation().as_deref())
+                        })
+                })
+                .collect();
+            type_genomes.sort_by_key(|x| x.is_gtdb_species_rep() != Some(true));
+            let designation = type_genomes.iter().find_map(|x| designations.get(&x.gid));
+            SpeciesTypeMaterial {
+                species: name.clone(),
+                genomes: genomes.len(),
+                has_type_material: !type_genomes.is_empty(),
+                type_genomes: type_genomes.iter().map(|x| x.gid.clone()).collect(),
+                gtdb_type_designation: designation.and_then(|x| x.get_gtdb_type_designation()),
+                gtdb_type_designation_sources: designation
+                    .and_then(|x| x.get_gtdb_type_designation_sources()),
+                lpsn_type_designation: designation.and_then(|x| x.get_lpsn_type_designation()),
+                dsmz_type_designation: designation.and_then(|x| x.get_dsmz_type_designation()),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Species cluster of a clade with its size
+pub struct SpeciesCluster {
+    species: String,
+    representative: Option<String>,
+    // Number of genomes of the cluster, from the card of its representative
+    cluster_size: usize,
+    // Fraction of the genomes of the clade in the cluster
+    fraction: f64,
+}
+
+impl SpeciesCluster {
+    const HEADER: [&'static str; 4] = ["species", "representative", "cluster_size", "fraction"];
+
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.species.clone(),
+            self.representative.clone().unwrap_or_default(),
+            self.cluster_size.to_string(),
+            format!("{:.4}", self.fraction),
+        ]
+    }
+}
+
+/// Species clusters of a clade, largest first, sized from the
+/// `species_cluster_count` of their representative or, lacking it, from
+/// the number of their genomes in search results
+fn species_clusters(
+    species: &BTreeMap<String, Vec<&SearchResult>>,
+    sizes: &HashMap<String, i32>,
+) -> Vec<SpeciesCluster> {
+    let mut clusters: Vec<SpeciesCluster> = species
+        .iter()
+        .map(|(name, genomes)| {
+            let representative = genomes
+                .iter()
+                .find(|x| x.is_gtdb_species_rep() == Some(true))
+                .map(|x| x.gid.clone());
+            let cluster_size = representative
+                .as_ref()
+                .and_then(|x| sizes.get(x))
+                .and_then(|x| usize::try_from(*x).ok())
+                .unwrap_or(genomes.len());
+            SpeciesCluster {
+                species: name.clone(),
+                representative,
+                cluster_size,
+                fraction: 0.0,
+            }
+        })
+        .collect();
+
+    let total: usize = clusters.iter().map(|x| x.cluster_size).sum();
+    for cluster in clusters.iter_mut() {
+        if total > 0 {
+            cluster.fraction = cluster.cluster_size as f64 / total as f64;
+        }
+    }
+    // Stable sort, clusters of the same size stay in name order
+    clusters.sort_by_key(|x| Reverse(x.cluster_size));
+    clusters
+}
+
+/// Name of a Sankey node of the genomes which were not in a release
+const SANKEY_ABSENT: &str = "absent";
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+/// Name given to genomes in a release
+struct SankeyNode {
+    release: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+/// Number of genomes named after the source node in a release and after
+/// the target node in the next release
+struct SankeyLink {
+    source: usize,
+    target: usize,
+    value: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Redistribution of the genomes of a taxon among names across releases,
+/// as the nodes and links of a Sankey diagram
+pub struct Sankey {
+    nodes: Vec<SankeyNode>,
+    links: Vec<SankeyLink>,
+}
+
+impl Sankey {
+    /// Build the diagram from the histories of the genomes of a taxon,
+    /// genomes being named after their taxon at `rank`
+    fn from_histories(histories: &[GenomeTaxonHistory], rank: Rank) -> Self {
+        let mut releases: Vec<String> = histories
+            .iter()
+            .flat_map(|x| x.data.iter().filter_map(|x| x.get_release()))
+            .collect();
+        releases.sort_by_key(|x| release_number(x));
+        releases.dedup();
+
+        let mut counts: BTreeMap<(SankeyNode, SankeyNode), usize> = BTreeMap::new();
+        for history in histories {
+            let name = |release: &str| {
+                history
+                    .data
+                    .iter()
+                    .find(|x| x.get_release().as_deref() == Some(release))
+                    .and_then(|x| x.get_taxon(rank))
+            };
+            for pair in releases.windows(2) {
+                let (from, to) = (name(&pair[0]), name(&pair[1]));
+                if from.is_none() && to.is_none() {
+                    continue;
+                }
+                let node = |release: &str, name: Option<String>| SankeyNode {
+                    release: release.to_string(),
+                    name: name.unwrap_or_else(|| SANKEY_ABSENT.to_string()),
+                };
+                *counts
+                    .entry((node(&pair[0], from), node(&pair[1], to)))
+                    .or_default() += 1;
+            }
+        }
+
+        let mut nodes: Vec<SankeyNode> = counts
+            .keys()
+            .flat_map(|(source, target)| [source.clone(), target.clone()])
+            .collect();
+        nodes.sort_by(|a, b| {
+            (release_number(&a.release), &a.name).cmp(&(release_number(&b.release), &b.name))
+        });
+        nodes.dedup();
+        let index = |node: &SankeyNode| nodes.iter().position(|x| x == node).unwrap_or(0);
+        let mut links: Vec<SankeyLink> = counts
+            .iter()
+            .map(|((source, target), value)| SankeyLink {
+                source: index(source),
+                target: index(target),
+                value: *value,
+            })
+            .collect();
+        links.sort_by_key(|x| (x.source, x.target));
+        Sankey { nodes, links }
+    }
+
+    /// Render the diagram in the Mermaid sankey syntax
+    fn to_mermaid(&self) -> String {
+        let label = |index: usize| {
+            let node = &self.nodes[index];
+            let label = format!("{} {}", node.release, node.name);
+            match label.contains([',', '"']) {
+                true => format!("\"{}\"", label.replace('"', "\"\"")),
+                false => label,
+            }
+        };
+        let mut mermaid = String::from("sankey-beta\n\n");
+        for link in &self.links {
+            mermaid.push_str(&format!(
+                "{},{},{}\n",
+                label(link.source),
+                label(link.target),
+                link.value
+            ));
+        }
+        mermaid
+    }
 }
 
 // Struct for error 400 occuring from wrongly formatted
@@ -53,37 +570,717 @@ pub struct TaxonGenomesError {
     detail: String,
 }
 
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// A taxon genome joined with its search and genome card data
+pub struct GenomeDetail {
+    accession: String,
+    ncbi_organism_name: Option<String>,
+    gtdb_species: Option<String>,
+    gtdb_representative: bool,
+    ncbi_type_material: Option<bool>,
+    checkm_completeness: Option<String>,
+    checkm_contamination: Option<String>,
+    genome_size: Option<i64>,
+    contig_count: Option<i32>,
+}
+
+impl GenomeDetail {
+    const HEADER: [&'static str; 9] = [
+        "accession",
+        "ncbi_organism_name",
+        "gtdb_species",
+        "gtdb_representative",
+        "ncbi_type_material",
+        "checkm_completeness",
+        "checkm_contamination",
+        "genome_size",
+        "contig_count",
+    ];
+
+    fn new(accession: &str, search_result: Option<&SearchResult>, card: &GenomeCard) -> Self {
+        GenomeDetail {
+            accession: accession.to_string(),
+            ncbi_organism_name: search_result.and_then(|x| x.get_ncbi_org_name()),
+            gtdb_species: card.get_gtdb_species(),
+            gtdb_representative: card.is_gtdb_representative(),
+            ncbi_type_material: search_result.and_then(|x| x.is_ncbi_type_material()),
+            checkm_completeness: card.get_checkm_completeness(),
+            checkm_contamination: card.get_checkm_contamination(),
+            genome_size: card.get_genome_size(),
+            contig_count: card.get_contig_count(),
+        }
+    }
+
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.accession.clone(),
+            self.ncbi_organism_name.clone().unwrap_or_default(),
+            self.gtdb_species.clone().unwrap_or_default(),
+            self.gtdb_representative.to_string(),
+            self.ncbi_type_material
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            self.checkm_completeness.clone().unwrap_or_default(),
+            self.checkm_contamination.clone().unwrap_or_default(),
+            self.genome_size.map(|x| x.to_string()).unwrap_or_default(),
+            self.contig_count.map(|x| x.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+/// Nomenclature links and type designations of a taxon or genome
+pub struct Nomenclature {
+    query: String,
+    name: String,
+    type_designation: Option<String>,
+    type_designation_sources: Option<String>,
+    lpsn_type_designation: Option<String>,
+    dsmz_type_designation: Option<String>,
+    lpsn_priority_year: Option<i32>,
+    lpsn_url: Option<String>,
+    bergeys_url: Option<String>,
+    seqcode_url: Option<String>,
+}
+
+impl Nomenclature {
+    pub(crate) const HEADER: [&'static str; 10] = [
+        "query",
+        "name",
+        "type_designation",
+        "type_designation_sources",
+        "lpsn_type_designation",
+        "dsmz_type_designation",
+        "lpsn_priority_year",
+        "lpsn_url",
+        "bergeys_url",
+        "seqcode_url",
+    ];
+
+    /// Nomenclature of a child taxon of `parent`
+    fn from_taxon(parent: &str, taxon: &Taxon) -> Self {
+        Nomenclature {
+            query: parent.to_string(),
+            name: taxon.get_taxon(),
+            type_designation: taxon.get_type_material(),
+            lpsn_url: taxon.get_lpsn_url(),
+            bergeys_url: taxon.get_bergeys_url(),
+            seqcode_url: taxon.get_seq_code_url(),
+            ..Default::default()
+        }
+    }
+
+    /// Nomenclature of a genome, named after its GTDB species
+    pub(crate) fn from_card(accession: &str, card: &GenomeCard) -> Self {
+        let type_material = card.get_metadata_type_material();
+        Nomenclature {
+            query: accession.to_string(),
+            name: card.get_gtdb_species().unwrap_or_default(),
+            type_designation: card.get_gtdb_type_designation(),
+            type_designation_sources: type_material.get_gtdb_type_designation_sources(),
+            lpsn_type_designation: type_material.get_lpsn_type_designation(),
+            dsmz_type_designation: type_material.get_dsmz_type_designation(),
+            lpsn_priority_year: type_material.get_lpsn_priority_year(),
+            lpsn_url: card.get_lpsn_url(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn to_fields(&self) -> Vec<String> {
+        let to_string = |x: &Option<String>| x.clone().unwrap_or_default();
+        vec![
+            self.query.clone(),
+            self.name.clone(),
+            to_string(&self.type_designation),
+            to_string(&self.type_designation_sources),
+            to_string(&self.lpsn_type_designation),
+            to_string(&self.dsmz_type_designation),
+            self.lpsn_priority_year
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            to_string(&self.lpsn_url),
+            to_string(&self.bergeys_url),
+            to_string(&self.seqcode_url),
+        ]
+    }
+}
+
+impl Taxon {
+    const HEADER: [&'static str; 11] = [
+        "parent",
+        "taxon",
+        "genomes",
+        "descendants",
+        "is_genome",
+        "is_rep",
+        "type_material",
+        "ncbi_taxid",
+        "lpsn_url",
+        "bergeys_url",
+        "seqcode_url",
+    ];
+
+    fn to_fields(&self, parent: &str) -> Vec<String> {
+        let to_string = |x: &Option<String>| x.clone().unwrap_or_default();
+        vec![
+            parent.to_string(),
+            self.taxon.clone(),
+            self.get_genome_count().to_string(),
+            to_string(&self.n_desc_children),
+            self.is_genome().to_string(),
+            self.is_rep.map(|x| x.to_string()).unwrap_or_default(),
+            to_string(&self.type_material),
+            self.ncbi_tax_id.map(|x| x.to_string()).unwrap_or_default(),
+            to_string(&self.lpsn_url),
+            to_string(&self.bergeys_url),
+            to_string(&self.seq_code_url),
+        ]
+    }
+
+    /// Get taxon name, or accession of a genome
+    pub fn get_taxon(&self) -> String {
+        self.taxon.clone()
+    }
+
+    /// Get the NCBI taxid of the taxon
+    pub fn get_ncbi_tax_id(&self) -> Option<i32> {
+        self.ncbi_tax_id
+    }
+
+    /// Check if the taxon is a genome
+    pub fn is_genome(&self) -> bool {
+        self.is_genome.unwrap_or(false)
+    }
+
+    /// Number of genomes in the taxon, a genome counting as one
+    pub fn get_genome_count(&self) -> u64 {
+        match self.total {
+            Some(total) => total as u64,
+            None => u64::from(self.is_genome()),
+        }
+    }
+
+    /// Get number of descendant taxa
+    pub fn get_n_desc_children(&self) -> Option<String> {
+        self.n_desc_children.clone()
+    }
+
+    /// Check if the genome is a GTDB species representative
+    pub fn is_rep(&self) -> Option<bool> {
+        self.is_rep
+    }
+
+    /// Get type material status, e.g. "type species of genus"
+    pub fn get_type_material(&self) -> Option<String> {
+        self.type_material.clone()
+    }
+
+    /// Get link to the Bergey's Manual page of the taxon
+    pub fn get_bergeys_url(&self) -> Option<String> {
+        self.bergeys_url.clone()
+    }
+
+    /// Get link to the SeqCode Registry page of the taxon
+    pub fn get_seq_code_url(&self) -> Option<String> {
+        self.seq_code_url.clone()
+    }
+
+    /// Get link to the LPSN page of the taxon
+    pub fn get_lpsn_url(&self) -> Option<String> {
+        self.lpsn_url.clone()
+    }
+}
+
+impl TaxonResult {
+    /// Get the children of the taxon
+    pub fn get_data(&self) -> &[Taxon] {
+        &self.data
+    }
+}
+
+impl TaxonGenomes {
+    /// Get the accessions of the genomes of the taxon
+    pub fn get_data(&self) -> &[String] {
+        &self.data
+    }
+}
+
 impl TaxonSearchResult {
+    /// Get the taxa matching the query
+    pub fn get_matches(&self) -> &[String] {
+        &self.matches
+    }
+
     fn filter(&mut self, pattern: String) {
         self.matches.retain(|x| x == &pattern);
     }
 }
 
+pub(crate) fn fetch_taxon_children(
+    transport: &dyn Transport,
+    name: &str,
+) -> Result<TaxonResult, XgtError> {
+    let request_url = TaxonAPI::new(name).get_name_request();
+    get_json(transport, &request_url, format!("Taxon {} not found", name))
+}
+
+/// Query every taxon of `args` with `fetch`, `--jobs` taxa at a time,
+/// keeping going past the taxa which fail unless `--fail-fast`. Results
+/// are labeled by their taxon, in the order of the taxa.
+fn fetch_each_taxon<T, F>(args: &TaxonArgs, fetch: F) -> Result<(Vec<(String, T)>, ItemErrors)>
+where
+    T: Send,
+    F: Fn(&str) -> Result<T> + Sync,
+{
+    let names = args.get_name();
+    let mut errors = ItemErrors::new(names.len(), args.is_fail_fast());
+    let fetched = utils::parallel_map(names.clone(), args.get_jobs(), |name| {
+        interrupt::check()?;
+        fetch(&name)
+    });
+    let mut results = Vec::new();
+    for (name, result) in names.into_iter().zip(fetched) {
+        if let Some(result) = errors.check(&name, result)? {
+            results.push((name, result));
+        }
+    }
+    Ok((results, errors))
+}
+
+/// JSON of the results of the taxa of `args`: the result itself for a
+/// single taxon, and a single array for several taxa, with an object per
+/// taxon giving its name in `taxon` and its result in `key`
+fn taxa_json<T: Serialize>(args: &TaxonArgs, key: &str, results: &[(String, T)]) -> Result<String> {
+    if args.get_name().len() == 1 {
+        return Ok(results
+            .iter()
+            .map(|(_, result)| utils::to_json(result))
+            .collect::<serde_json::Result<String>>()?);
+    }
+    let labeled = results
+        .iter()
+        .map(|(taxon, result)| Ok(serde_json::json!({"taxon": taxon, key: result})))
+        .collect::<Result<Vec<serde_json::Value>>>()?;
+    Ok(utils::to_json(&labeled)?)
+}
+
 pub fn get_taxon_name(args: TaxonArgs) -> Result<()> {
     let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+
+    let (results, errors) =
+        fetch_each_taxon(&args, |name| Ok(fetch_taxon_children(&agent, name)?))?;
+    let output = if outfmt == OutputFormat::Json {
+        let children: Vec<(String, &Vec<Taxon>)> = results
+            .iter()
+            .map(|(name, x)| (name.clone(), &x.data))
+            .collect();
+        taxa_json(&args, "children", &children)?
+    } else {
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .flat_map(|(name, x)| x.data.iter().map(|x| x.to_fields(name)))
+            .collect();
+        utils::render_table(&Taxon::HEADER, &rows, &outfmt)
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    errors.finish()
+}
+
+/// Write the LPSN, Bergey's Manual and SeqCode links and the type material
+/// of the direct children of each taxon
+pub fn get_taxon_nomenclature(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
 
-    for name in args.get_name() {
-        let request_url = TaxonAPI::new(name.to_string()).get_name_request();
-        let response = match agent.get(&request_url).call() {
-            Ok(r) => r,
-            Err(ureq::Error::Status(400, _)) => bail!("Taxon {} not found", name),
-            Err(ureq::Error::Status(code, _)) => bail!("Unexpected status code: {}", code),
-            Err(_) => bail!("Error making the request or receiving the response."),
+    let (results, errors) =
+        fetch_each_taxon(&args, |name| Ok(fetch_taxon_children(&agent, name)?))?;
+    let nomenclature: Vec<Nomenclature> = results
+        .iter()
+        .flat_map(|(name, x)| x.data.iter().map(|x| Nomenclature::from_taxon(name, x)))
+        .collect();
+    let output = if outfmt == OutputFormat::Json {
+        utils::to_json(&nomenclature)?
+    } else {
+        let rows: Vec<Vec<String>> = nomenclature.iter().map(Nomenclature::to_fields).collect();
+        utils::render_table(&Nomenclature::HEADER, &rows, &outfmt)
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    errors.finish()
+}
+
+/// Format the direct children of a taxon as a table with their genome counts
+fn format_taxon_children(parent: &str, children: &TaxonResult, separator: &str) -> String {
+    let mut rows = String::new();
+    for child in &children.data {
+        let rank = match Rank::from_taxon(&child.taxon) {
+            Some(rank) => rank.to_string(),
+            None => "genome".to_string(),
         };
+        rows.push_str(
+            &[
+                parent.to_string(),
+                child.taxon.clone(),
+                rank,
+                child.get_genome_count().to_string(),
+            ]
+            .join(separator),
+        );
+        rows.push('\n');
+    }
+    rows
+}
+
+pub fn get_taxon_children(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let separator = match args.get_outfmt() {
+        OutputFormat::Csv => ",",
+        _ => "\t",
+    };
+
+    let header = format!(
+        "{}\n",
+        ["parent", "taxon", "rank", "genomes"].join(separator)
+    );
+    utils::write_to_output(header.as_bytes(), args.get_output())?;
+    utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let children = fetch_taxon_children(&agent, name)?;
+        let rows = format_taxon_children(name, &children, separator);
+        utils::write_to_output(rows.as_bytes(), args.get_output())?;
+        Ok(())
+    })
+}
+
+/// Resolve the parent lineage of a taxon from the genome card of one of
+/// its species representative genomes
+pub(crate) fn fetch_taxon_lineage(agent: &Agent, name: &str) -> Result<BTreeMap<Rank, String>> {
+    let genomes = fetch_taxon_genomes(agent, name, true)?;
+    let card = fetch_genome_card(agent, &genomes.data[0])?;
+    taxonomy::parent_lineage(&card.get_gtdb_taxonomy(), name)
+        .with_context(|| format!("Cannot resolve lineage of {}", name))
+}
+
+/// Format a parent lineage as a greengenes-style string or as one column per rank
+fn format_taxon_lineage(name: &str, lineage: &BTreeMap<Rank, String>, ranks: bool) -> String {
+    if ranks {
+        let columns: Vec<String> = Rank::ALL
+            .iter()
+            .map(|rank| lineage.get(rank).cloned().unwrap_or_default())
+            .collect();
+        format!("{}\t{}\n", name, columns.join("\t"))
+    } else {
+        let taxa: Vec<String> = lineage.values().cloned().collect();
+        format!("{}\t{}\n", name, taxa.join("; "))
+    }
+}
+
+pub fn get_taxon_lineage(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    if args.is_ranks() {
+        let mut header = vec!["taxon".to_string()];
+        header.extend(Rank::ALL.iter().map(Rank::to_string));
+        let header = format!("{}\n", header.join("\t"));
+        utils::write_to_output(header.as_bytes(), args.get_output())?;
+    }
+    utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let lineage = fetch_taxon_lineage(&agent, name)?;
+        let row = format_taxon_lineage(name, &lineage, args.is_ranks());
+        utils::write_to_output(row.as_bytes(), args.get_output())?;
+        Ok(())
+    })
+}
+
+pub(crate) fn fetch_taxon_history(
+    transport: &dyn Transport,
+    name: &str,
+) -> Result<Vec<TaxonRelease>, XgtError> {
+    let request_url = TaxonAPI::new(name).get_previous_releases_request();
+    let releases: TaxonReleases =
+        get_json(transport, &request_url, format!("Taxon {} not found", name))?;
+    releases.into_history(name)
+}
+
+/// Tabulate the history of a taxon name: the releases in which each name
+/// was seen, and whether it is the queried name or another name given to
+/// the same genomes. GTDB does not tell how the names relate, so other
+/// names are not labelled as renames.
+fn taxon_history_rows(name: &str, releases: &[TaxonRelease]) -> Vec<Vec<String>> {
+    releases
+        .iter()
+        .map(|release| {
+            let status = if release.taxon == name {
+                "same name"
+            } else {
+                "other_name"
+            };
+            vec![
+                name.to_string(),
+                release.taxon.clone(),
+                release.first_seen.clone().unwrap_or_default(),
+                release.last_seen.clone().unwrap_or_default(),
+                status.to_string(),
+            ]
+        })
+        .collect()
+}
+
+pub fn get_taxon_history(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+    let mut rows = Vec::new();
 
-        let taxon_data: TaxonResult = response.into_json()?;
-        let taxon_string = serde_json::to_string_pretty(&taxon_data)?;
-        utils::write_to_output(taxon_string.as_bytes(), args.get_output())?;
+    let result = utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let mut releases = fetch_taxon_history(&agent, name)?;
+        if let Some(release) = args.get_release() {
+            releases.retain(|x| x.is_seen_in(&release));
+        }
+        if outfmt == OutputFormat::Json {
+            let history_string = utils::to_json(&releases)?;
+            utils::write_to_output(history_string.as_bytes(), args.get_output())?;
+        } else {
+            rows.extend(taxon_history_rows(name, &releases));
+        }
+        Ok(())
+    });
+
+    if outfmt != OutputFormat::Json {
+        let header = ["query", "taxon", "first_seen", "last_seen", "status"];
+        let table = utils::render_table(&header, &rows, &outfmt);
+        utils::write_to_output(table.as_bytes(), args.get_output())?;
+    }
+
+    result
+}
+
+/// URLs of the first requests a taxon query would send. Queries which
+/// fetch genome cards send more requests, depending on the responses.
+pub fn request_urls(args: &TaxonArgs) -> Vec<String> {
+    args.get_name()
+        .into_iter()
+        .map(|name| {
+            let api = TaxonAPI::new(name.clone());
+            if args.is_search_all() {
+                api.get_search_all_request()
+            } else if args.is_search() {
+                api.get_search_request()
+            } else if args.is_genome() || args.is_lineage() {
+                api.get_genomes_request(args.is_reps_only() || args.is_lineage())
+            } else if args.is_history() {
+                api.get_previous_releases_request()
+            } else if args.is_stats() {
+                SearchQuery::taxon(&name).url()
+            } else {
+                api.get_name_request()
+            }
+        })
+        .collect()
+}
+
+pub fn get_taxon_stats(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+    let mut rows = Vec::new();
+
+    let result = utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let results = fetch_search_results(&agent, &SearchQuery::taxon(name))?.into_rows();
+        let stats = TaxonStats::from_search_results(name, &results);
+        ensure!(stats.genomes != 0, "No data found for {}", name);
+
+        if outfmt == OutputFormat::Json {
+            let stats_string = utils::to_json(&stats)?;
+            utils::write_to_output(stats_string.as_bytes(), args.get_output())?;
+        } else {
+            rows.extend(stats.to_rows());
+        }
+        Ok(())
+    });
+
+    if outfmt != OutputFormat::Json {
+        let table = utils::render_table(&["taxon", "metric", "value"], &rows, &outfmt);
+        utils::write_to_output(table.as_bytes(), args.get_output())?;
     }
 
-    Ok(())
+    result
+}
+
+/// Report the descendants of a clade with placeholder names versus
+/// validly published ones, with their counts at each rank and the lineages
+/// of the placeholder taxa
+pub fn get_taxon_novel(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+    let mut rows = Vec::new();
+
+    let result = utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let results = fetch_search_results(&agent, &SearchQuery::taxon(name))?.into_rows();
+        let novel = NovelTaxa::from_search_results(name, &results);
+        ensure!(!novel.counts.is_empty(), "No data found for {}", name);
+
+        if outfmt == OutputFormat::Json {
+            let novel_string = utils::to_json(&novel)?;
+            utils::write_to_output(novel_string.as_bytes(), args.get_output())?;
+        } else {
+            eprintln!("{}", novel.summary());
+            rows.extend(novel.to_rows());
+        }
+        Ok(())
+    });
+
+    if outfmt != OutputFormat::Json {
+        let table = utils::render_table(&NovelTaxa::HEADER, &rows, &outfmt);
+        utils::write_to_output(table.as_bytes(), args.get_output())?;
+    }
+
+    result
+}
+
+/// List the species of a clade with their type genomes and designations,
+/// and whether they have any type material. Type designations are read
+/// from the cards of the species representatives and the NCBI type
+/// material genomes.
+pub fn get_taxon_type_summary(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+    let mut rows = Vec::new();
+
+    let result = utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let results = fetch_search_results(&agent, &SearchQuery::taxon(name))?.into_rows();
+        let species = species_genomes(name, &results);
+        ensure!(!species.is_empty(), "No data found for {}", name);
+
+        let candidates: Vec<String> = species
+            .values()
+            .flatten()
+            .filter(|x| {
+                x.is_gtdb_species_rep() == Some(true) || x.is_ncbi_type_material() == Some(true)
+            })
+            .map(|x| x.gid.clone())
+            .collect();
+        let cards = fetch_genome_cards(
+            candidates.clone(),
+            args.get_jobs(),
+            args.get_disable_certificate_verification(),
+        )?;
+        let designations = candidates
+            .into_iter()
+            .zip(cards)
+            .map(|(accession, card)| Ok((accession, card?.get_metadata_type_material().clone())))
+            .collect::<Result<HashMap<String, MetadataTypeMaterial>>>()?;
+        let summary = species_type_material(&species, &designations);
+
+        if outfmt == OutputFormat::Json {
+            let summary_string = utils::to_json(&summary)?;
+            utils::write_to_output(summary_string.as_bytes(), args.get_output())?;
+        } else {
+            rows.extend(summary.iter().map(SpeciesTypeMaterial::to_fields));
+        }
+        Ok(())
+    });
+
+    if outfmt != OutputFormat::Json {
+        let table = utils::render_table(&SpeciesTypeMaterial::HEADER, &rows, &outfmt);
+        utils::write_to_output(table.as_bytes(), args.get_output())?;
+    }
+
+    result
+}
+
+/// Write the species clusters of a taxon with their representative and
+/// size, largest first
+pub fn get_taxon_clusters(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let outfmt = args.get_outfmt();
+    let mut rows = Vec::new();
+
+    let result = utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let results = fetch_search_results(&agent, &SearchQuery::taxon(name))?.into_rows();
+        let species = species_genomes(name, &results);
+        ensure!(!species.is_empty(), "No data found for {}", name);
+
+        let representatives: Vec<String> = species
+            .values()
+            .flatten()
+            .filter(|x| x.is_gtdb_species_rep() == Some(true))
+            .map(|x| x.gid.clone())
+            .collect();
+        let cards = fetch_genome_cards(
+            representatives.clone(),
+            args.get_jobs(),
+            args.get_disable_certificate_verification(),
+        )?;
+        let mut sizes = HashMap::new();
+        for (accession, card) in representatives.into_iter().zip(cards) {
+            if let Some(size) = card?.get_species_cluster_count() {
+                sizes.insert(accession, size);
+            }
+        }
+        let clusters = species_clusters(&species, &sizes);
+
+        if outfmt == OutputFormat::Json {
+            let clusters_string = utils::to_json(&clusters)?;
+            utils::write_to_output(clusters_string.as_bytes(), args.get_output())?;
+        } else {
+            rows.extend(clusters.iter().map(SpeciesCluster::to_fields));
+        }
+        Ok(())
+    });
+
+    if outfmt != OutputFormat::Json {
+        let table = utils::render_table(&SpeciesCluster::HEADER, &rows, &outfmt);
+        utils::write_to_output(table.as_bytes(), args.get_output())?;
+    }
+
+    result
+}
+
+/// Write how the genomes of a taxon were redistributed among names across
+/// GTDB releases, as the nodes and links of a Sankey diagram in JSON or
+/// in the Mermaid sankey syntax
+pub fn get_taxon_sankey(args: TaxonArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    utils::for_each_item(args.get_name(), args.is_fail_fast(), |name| {
+        let rank = Rank::from_taxon(name).context("Taxon name must be in greengenes format")?;
+        let genomes = fetch_taxon_genomes(&agent, name, false)?;
+        let histories = fetch_genome_histories(
+            genomes.data,
+            args.get_jobs(),
+            args.get_disable_certificate_verification(),
+        )?
+        .into_iter()
+        .collect::<Result<Vec<GenomeTaxonHistory>, XgtError>>()?;
+
+        let sankey = Sankey::from_histories(&histories, rank);
+        let output = match args.get_sankey().as_deref() {
+            Some("mermaid") => sankey.to_mermaid(),
+            _ => utils::to_json(&sankey)?,
+        };
+        utils::write_to_output(output.as_bytes(), args.get_output())?;
+        Ok(())
+    })
+}
+
+/// Fetch the taxa matching `name` from a taxon search request
+pub(crate) fn fetch_taxon_search(
+    transport: &dyn Transport,
+    request_url: &str,
+    name: &str,
+) -> Result<TaxonSearchResult, XgtError> {
+    get_json(
+        transport,
+        request_url,
+        format!("No match found for {}", name),
+    )
 }
 
 pub fn search_taxon(args: TaxonArgs) -> Result<()> {
     let is_whole_words_matching = args.is_whole_words_matching();
     let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
 
-    for name in args.get_name() {
+    let (results, errors) = fetch_each_taxon(&args, |name| {
         let search_api = TaxonAPI::new(name.to_string());
         let request_url = if args.is_search_all() {
             search_api.get_search_all_request()
@@ -91,14 +1288,7 @@ pub fn search_taxon(args: TaxonArgs) -> Result<()> {
             search_api.get_search_request()
         };
 
-        let response = match agent.get(&request_url).call() {
-            Ok(r) => r,
-            Err(ureq::Error::Status(400, _)) => bail!("No match found for {}", name),
-            Err(ureq::Error::Status(code, _)) => bail!("Unexpected status code: {}", code),
-            Err(_) => bail!("Error making the request or receiving the response."),
-        };
-
-        let mut taxon_data: TaxonSearchResult = response.into_json()?;
+        let mut taxon_data = fetch_taxon_search(&agent, &request_url, name)?;
         if is_whole_words_matching {
             taxon_data.filter(name.to_string());
         }
@@ -108,40 +1298,235 @@ pub fn search_taxon(args: TaxonArgs) -> Result<()> {
             "No match found for {}",
             name
         );
+        Ok(taxon_data)
+    })?;
+
+    // A single taxon keeps the matches object of the API
+    let output = match args.get_name().len() {
+        1 => taxa_json(&args, "matches", &results)?,
+        _ => {
+            let matches: Vec<(String, &Vec<String>)> = results
+                .iter()
+                .map(|(name, x)| (name.clone(), &x.matches))
+                .collect();
+            taxa_json(&args, "matches", &matches)?
+        }
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
 
-        let taxon_string = serde_json::to_string_pretty(&taxon_data)?;
+    errors.finish()
+}
 
-        utils::write_to_output(taxon_string.as_bytes(), args.get_output())?;
+pub(crate) fn fetch_taxon_genomes(
+    transport: &dyn Transport,
+    name: &str,
+    sp_reps_only: bool,
+) -> Result<TaxonGenomes, XgtError> {
+    let request_url = TaxonAPI::new(name).get_genomes_request(sp_reps_only);
+    let taxon_data: TaxonGenomes = get_json(
+        transport,
+        &request_url,
+        format!("No match found for {}", name),
+    )?;
+    if taxon_data.data.is_empty() {
+        return Err(XgtError::NotFound(format!("No data found for {}", name)));
     }
 
-    Ok(())
+    Ok(taxon_data)
 }
 
-pub fn get_taxon_genomes(args: TaxonArgs) -> Result<()> {
-    let sp_reps_only = args.is_reps_only();
-    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+/// Format taxon genomes as one accession per row, prefixed by the
+/// taxon name when `with_taxon` is set
+fn format_taxon_genomes(
+    name: &str,
+    genomes: &TaxonGenomes,
+    outfmt: &OutputFormat,
+    with_taxon: bool,
+) -> Result<String> {
+    let separator = match outfmt {
+        OutputFormat::Json => return Ok(utils::to_json(genomes)?),
+        OutputFormat::Csv => ",",
+        OutputFormat::Tsv | OutputFormat::Table | OutputFormat::Html => "\t",
+    };
 
-    for name in args.get_name() {
-        let search_api = TaxonAPI::new(name.to_string());
-        let request_url = search_api.get_genomes_request(sp_reps_only);
+    let mut rows = String::new();
+    for accession in &genomes.data {
+        if with_taxon {
+            rows.push_str(name);
+            rows.push_str(separator);
+        }
+        rows.push_str(accession);
+        rows.push('\n');
+    }
+    Ok(rows)
+}
 
-        let response = match agent.get(&request_url).call() {
-            Ok(r) => r,
-            Err(ureq::Error::Status(400, _)) => bail!("No match found for {}", name),
-            Err(ureq::Error::Status(code, _)) => bail!("Unexpected status code: {}", code),
-            Err(_) => bail!("Error making the request or receiving the response."),
-        };
+/// Format detailed taxon genomes as a JSON array or a CSV/TSV/aligned table
+fn format_genome_details(details: &[GenomeDetail], outfmt: &OutputFormat) -> Result<String> {
+    if *outfmt == OutputFormat::Json {
+        return Ok(utils::to_json(details)?);
+    }
+
+    let rows: Vec<Vec<String>> = details.iter().map(GenomeDetail::to_fields).collect();
+    Ok(utils::render_table(&GenomeDetail::HEADER, &rows, outfmt))
+}
 
-        let taxon_data: TaxonGenomes = response.into_json()?;
+/// Fetch search results of the genomes of a taxon, by accession
+fn fetch_search_results_by_accession(
+    agent: &Agent,
+    name: &str,
+) -> Result<HashMap<String, SearchResult>> {
+    Ok(fetch_search_results(agent, &SearchQuery::taxon(name))?
+        .into_rows()
+        .into_iter()
+        .map(|x| (x.get_accession().unwrap_or_else(|| x.gid.clone()), x))
+        .collect())
+}
 
-        ensure!(!taxon_data.data.is_empty(), "No data found for {}", name);
+/// Only keep genomes flagged as NCBI type material in search results
+fn filter_type_material(
+    genomes: &mut TaxonGenomes,
+    search_results: &HashMap<String, SearchResult>,
+) {
+    genomes.data.retain(|accession| {
+        search_results
+            .get(accession)
+            .and_then(|x| x.is_ncbi_type_material())
+            .unwrap_or(false)
+    });
+}
 
-        let taxon_string = serde_json::to_string_pretty(&taxon_data)?;
+/// Join taxon genomes with search results of the taxon and their genome cards
+fn get_genome_details(
+    genomes: TaxonGenomes,
+    search_results: &HashMap<String, SearchResult>,
+    jobs: usize,
+    disable_certificate_verification: bool,
+) -> Result<Vec<GenomeDetail>> {
+    let cards = fetch_genome_cards(genomes.data.clone(), jobs, disable_certificate_verification)?;
+    genomes
+        .data
+        .iter()
+        .zip(cards)
+        .map(|(accession, card)| {
+            Ok(GenomeDetail::new(
+                accession,
+                search_results.get(accession),
+                &card?,
+            ))
+        })
+        .collect()
+}
 
-        utils::write_to_output(taxon_string.as_bytes(), args.get_output())?;
+/// Genomes of a taxon, and their details with `--detail`
+struct GenomesOfTaxon {
+    genomes: TaxonGenomes,
+    details: Vec<GenomeDetail>,
+}
+
+/// Format the genomes of several taxa as a single table, or JSON array,
+/// labeled with their taxon
+fn format_taxa_genomes(args: &TaxonArgs, results: &[(String, GenomesOfTaxon)]) -> Result<String> {
+    let outfmt = args.get_outfmt();
+    if args.is_count() {
+        let counts: Vec<(String, usize)> = results
+            .iter()
+            .map(|(name, x)| (name.clone(), x.genomes.data.len()))
+            .collect();
+        return match outfmt {
+            OutputFormat::Json => taxa_json(args, "count", &counts),
+            _ => Ok(counts
+                .iter()
+                .map(|(name, count)| format!("{}\t{}\n", name, count))
+                .collect()),
+        };
     }
+    if args.is_detail() {
+        let details: Vec<(String, &Vec<GenomeDetail>)> = results
+            .iter()
+            .map(|(name, x)| (name.clone(), &x.details))
+            .collect();
+        if outfmt == OutputFormat::Json {
+            return taxa_json(args, "genomes", &details);
+        }
+        let header: Vec<&str> = ["taxon"].into_iter().chain(GenomeDetail::HEADER).collect();
+        let rows: Vec<Vec<String>> = details
+            .iter()
+            .flat_map(|(name, details)| {
+                details.iter().map(move |x| {
+                    let mut fields = vec![name.clone()];
+                    fields.extend(x.to_fields());
+                    fields
+                })
+            })
+            .collect();
+        return Ok(utils::render_table(&header, &rows, &outfmt));
+    }
+    match outfmt {
+        OutputFormat::Json => {
+            let genomes: Vec<(String, &Vec<String>)> = results
+                .iter()
+                .map(|(name, x)| (name.clone(), &x.genomes.data))
+                .collect();
+            taxa_json(args, "genomes", &genomes)
+        }
+        _ => results
+            .iter()
+            .map(|(name, x)| format_taxon_genomes(name, &x.genomes, &outfmt, true))
+            .collect(),
+    }
+}
+
+pub fn get_taxon_genomes(args: TaxonArgs) -> Result<()> {
+    let sp_reps_only = args.is_reps_only();
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let with_taxon = args.get_name().len() > 1;
+
+    let (results, errors) = fetch_each_taxon(&args, |name| {
+        let mut taxon_data = fetch_taxon_genomes(&agent, name, sp_reps_only)?;
+        let search_results = if args.is_detail() || args.is_type_material() {
+            fetch_search_results_by_accession(&agent, name)?
+        } else {
+            HashMap::new()
+        };
+        if args.is_type_material() {
+            filter_type_material(&mut taxon_data, &search_results);
+            ensure!(
+                !taxon_data.data.is_empty(),
+                "No type material genomes found for {}",
+                name
+            );
+        }
+
+        let details = match args.is_detail() {
+            true => get_genome_details(
+                taxon_data.clone(),
+                &search_results,
+                args.get_jobs(),
+                args.get_disable_certificate_verification(),
+            )?,
+            false => vec![],
+        };
+        Ok(GenomesOfTaxon {
+            genomes: taxon_data,
+            details,
+        })
+    })?;
+
+    let output = match (with_taxon, results.first()) {
+        (true, _) => format_taxa_genomes(&args, &results)?,
+        (false, Some((name, result))) if args.is_detail() => {
+            format_genome_details(&result.details, &args.get_outfmt())?
+        }
+        (false, Some((_, result))) if args.is_count() => format!("{}\n", result.genomes.data.len()),
+        (false, Some((name, result))) => {
+            format_taxon_genomes(name, &result.genomes, &args.get_outfmt(), false)?
+        }
+        (false, None) => String::new(),
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
 
-    Ok(())
+    errors.finish()
 }
 
 #[cfg(test)]
@@ -150,22 +1535,48 @@ mod tests {
     use mockito::Server;
     use std::fs;
 
+    #[test]
+    fn test_request_urls() {
+        let args = TaxonArgs {
+            name: vec!["g__Aminobacter".to_string()],
+            genomes: true,
+            reps_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            request_urls(&args),
+            vec!["https://api.gtdb.ecogenomic.org/taxon/g__Aminobacter/genomes?sp_reps_only=true"]
+        );
+
+        let args = TaxonArgs {
+            name: vec!["g__Aminobacter".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            request_urls(&args),
+            vec!["https://api.gtdb.ecogenomic.org/taxon/g__Aminobacter"]
+        );
+    }
+
     #[test]
     fn test_get_taxon_name_with_output() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("xgt-taxon-name-{}.json", std::process::id()));
+        let output = path.display().to_string();
         let args = TaxonArgs {
             name: vec!["g__Escherichia".to_string()],
-            output: Some("output.json".to_string()),
+            output: Some(output.clone()),
             is_whole_words_matching: false,
             search: false,
             search_all: false,
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         get_taxon_name(args.clone())?;
 
-        let expected_output = fs::read_to_string("output.json")?;
+        let expected_output = fs::read_to_string(&path)?;
         let expected_taxon_data: TaxonResult = serde_json::from_str(&expected_output)?;
 
         let actual_output = args.get_output().unwrap();
@@ -175,7 +1586,7 @@ mod tests {
         assert_eq!(expected_taxon_data, actual_taxon_data);
 
         // Clean up the output file
-        fs::remove_file("output.json")?;
+        fs::remove_file(&path)?;
 
         Ok(())
     }
@@ -191,6 +1602,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         get_taxon_name(args)?;
@@ -209,6 +1621,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = get_taxon_name(taxon_args);
         assert!(result.is_err());
@@ -231,6 +1644,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = get_taxon_name(taxon_args);
         assert!(result.is_err());
@@ -275,6 +1689,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = search_taxon(args);
         assert!(result.is_err());
@@ -295,6 +1710,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = search_taxon(args);
         assert!(result.is_ok());
@@ -311,6 +1727,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = search_taxon(args);
         assert!(result.is_ok());
@@ -327,6 +1744,7 @@ mod tests {
             genomes: false,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
         let result = search_taxon(args);
         assert!(result.is_ok());
@@ -337,24 +1755,513 @@ mod tests {
         std::fs::remove_file("test_search.json").unwrap();
     }
 
+    #[test]
+    fn test_format_taxon_genomes() -> Result<()> {
+        let genomes = TaxonGenomes {
+            data: vec!["GCA_001512625.1".to_string(), "GCF_018555685.1".to_string()],
+        };
+
+        let csv = format_taxon_genomes("g__UBA6186", &genomes, &OutputFormat::Csv, false)?;
+        assert_eq!(csv, "GCA_001512625.1\nGCF_018555685.1\n");
+
+        let tsv = format_taxon_genomes("g__UBA6186", &genomes, &OutputFormat::Tsv, true)?;
+        assert_eq!(
+            tsv,
+            "g__UBA6186\tGCA_001512625.1\ng__UBA6186\tGCF_018555685.1\n"
+        );
+
+        let json = format_taxon_genomes("g__UBA6186", &genomes, &OutputFormat::Json, true)?;
+        assert_eq!(serde_json::from_str::<TaxonGenomes>(&json)?, genomes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_taxa_genomes() -> Result<()> {
+        let genomes = |x: &[&str]| GenomesOfTaxon {
+            genomes: TaxonGenomes {
+                data: x.iter().map(|x| x.to_string()).collect(),
+            },
+            details: vec![],
+        };
+        let results = vec![
+            ("g__UBA6186".to_string(), genomes(&["GCA_001512625.1"])),
+            (
+                "g__Aminobacter".to_string(),
+                genomes(&["GCF_018555685.1", "GCF_900445235.1"]),
+            ),
+        ];
+        let mut args = TaxonArgs {
+            name: vec!["g__UBA6186".to_string(), "g__Aminobacter".to_string()],
+            outfmt: OutputFormat::Json,
+            ..Default::default()
+        };
+
+        // A single JSON document labeled with the taxa
+        let json: serde_json::Value = serde_json::from_str(&format_taxa_genomes(&args, &results)?)?;
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"taxon": "g__UBA6186", "genomes": ["GCA_001512625.1"]},
+                {"taxon": "g__Aminobacter", "genomes": ["GCF_018555685.1", "GCF_900445235.1"]}
+            ])
+        );
+
+        args.count = true;
+        let json: serde_json::Value = serde_json::from_str(&format_taxa_genomes(&args, &results)?)?;
+        assert_eq!(
+            json[1],
+            serde_json::json!({"taxon": "g__Aminobacter", "count": 2})
+        );
+
+        args.count = false;
+        args.detail = true;
+        args.outfmt = OutputFormat::Tsv;
+        let table = format_taxa_genomes(&args, &results)?;
+        assert!(table.starts_with("taxon\taccession\t"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_taxon_children() -> Result<()> {
+        let children: TaxonResult = serde_json::from_str(
+            r#"[
+                {"taxon": "s__Escherichia coli", "total": 35000, "nDescChildren": "2"},
+                {"taxon": "s__Escherichia fergusonii", "total": 120.0}
+            ]"#,
+        )?;
+        let rows = format_taxon_children("g__Escherichia", &children, "\t");
+        assert_eq!(
+            rows,
+            "g__Escherichia\ts__Escherichia coli\tspecies\t35000\ng__Escherichia\ts__Escherichia fergusonii\tspecies\t120\n"
+        );
+
+        let genomes: TaxonResult = serde_json::from_str(
+            r#"[{"taxon": "GCF_000005845.2", "isGenome": true, "isRep": true}]"#,
+        )?;
+        let rows = format_taxon_children("s__Escherichia coli", &genomes, ",");
+        assert_eq!(rows, "s__Escherichia coli,GCF_000005845.2,genome,1\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_taxon_to_fields() -> Result<()> {
+        let taxon: Taxon = serde_json::from_str(
+            r#"{"taxon": "s__Escherichia coli", "total": 35000, "nDescChildren": "35000",
+                "typeMaterial": "type species of genus", "ncbiTaxId": 562,
+                "lpsnUrl": "https://lpsn.dsmz.de/species/escherichia-coli"}"#,
+        )?;
+        let table = utils::render_table(
+            &Taxon::HEADER,
+            &[taxon.to_fields("g__Escherichia")],
+            &OutputFormat::Csv,
+        );
+        assert_eq!(
+            table.lines().nth(1),
+            Some("g__Escherichia,s__Escherichia coli,35000,35000,false,,type species of genus,562,https://lpsn.dsmz.de/species/escherichia-coli,,")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nomenclature_to_fields() -> Result<()> {
+        let taxon: Taxon = serde_json::from_str(
+            r#"{"taxon": "s__Escherichia coli", "typeMaterial": "type species of genus",
+                "lpsnUrl": "https://lpsn.dsmz.de/species/escherichia-coli",
+                "seqcodeUrl": "https://seqco.de/i:123"}"#,
+        )?;
+        assert_eq!(
+            Nomenclature::from_taxon("g__Escherichia", &taxon).to_fields(),
+            [
+                "g__Escherichia",
+                "s__Escherichia coli",
+                "type species of genus",
+                "",
+                "",
+                "",
+                "",
+                "https://lpsn.dsmz.de/species/escherichia-coli",
+                "",
+                "https://seqco.de/i:123",
+            ]
+        );
+
+        let card: GenomeCard = serde_json::from_str(&std::fs::read_to_string("test/card.json")?)?;
+        let fields = Nomenclature::from_card("GCA_001512625.1", &card).to_fields();
+        assert_eq!(fields.len(), Nomenclature::HEADER.len());
+        assert_eq!(
+            fields[..3],
+            [
+                "GCA_001512625.1",
+                "s__UBA6186 sp001512625",
+                "not type material"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_taxon_history_rows() -> Result<()> {
+        let releases: TaxonReleases = serde_json::from_str(
+            r#"[
+                {"taxon": "g__Aminobacter", "firstSeen": "R95", "lastSeen": "R220"},
+                {"taxon": "g__Chelatobacter", "firstSeen": "R80", "lastSeen": "R89"}
+            ]"#,
+        )?;
+        let releases = releases.into_history("g__Aminobacter")?;
+
+        let rows = taxon_history_rows("g__Aminobacter", &releases);
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    "g__Aminobacter",
+                    "g__Chelatobacter",
+                    "R80",
+                    "R89",
+                    "other_name"
+                ],
+                vec![
+                    "g__Aminobacter",
+                    "g__Aminobacter",
+                    "R95",
+                    "R220",
+                    "same name"
+                ],
+            ]
+        );
+
+        let releases: TaxonReleases = serde_json::from_str("[]")?;
+        assert!(releases.into_history("g__Aminobacter").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sankey() -> Result<()> {
+        let histories: Vec<GenomeTaxonHistory> = serde_json::from_str(
+            r#"[
+                [{"release": "R220", "g": "g__Aminobacter"},
+                 {"release": "R207", "g": "g__Aminobacter"},
+                 {"release": "R95", "g": "g__Mesorhizobium"}],
+                [{"release": "R220", "g": "g__Aminobacter"},
+                 {"release": "R207", "g": "g__Aminobacter"},
+                 {"release": "R95", "g": "g__Aminobacter"}],
+                [{"release": "R220", "g": "g__Aminobacter"}]
+            ]"#,
+        )?;
+        let sankey = Sankey::from_histories(&histories, Rank::Genus);
+        let nodes: Vec<String> = sankey
+            .nodes
+            .iter()
+            .map(|x| format!("{} {}", x.release, x.name))
+            .collect();
+        assert_eq!(
+            nodes,
+            [
+                "R95 g__Aminobacter",
+                "R95 g__Mesorhizobium",
+                "R207 absent",
+                "R207 g__Aminobacter",
+                "R220 g__Aminobacter"
+            ]
+        );
+        assert_eq!(
+            sankey.to_mermaid(),
+            "sankey-beta\n\n\
+             R95 g__Aminobacter,R207 g__Aminobacter,1\n\
+             R95 g__Mesorhizobium,R207 g__Aminobacter,1\n\
+             R207 absent,R220 g__Aminobacter,1\n\
+             R207 g__Aminobacter,R220 g__Aminobacter,2\n"
+        );
+        assert_eq!(
+            sankey.links[3],
+            SankeyLink {
+                source: 3,
+                target: 4,
+                value: 2
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_species_type_material() -> Result<()> {
+        let results: Vec<SearchResult> = serde_json::from_str(
+            r#"[
+                {"gid": "G1", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S1",
+                 "isGtdbSpeciesRep": false, "isNcbiTypeMaterial": true},
+                {"gid": "G2", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S1",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": false},
+                {"gid": "G3", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S2",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": false},
+                {"gid": "G4", "gtdbTaxonomy": "d__Bacteria; g__G11; s__S3",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": true}
+            ]"#,
+        )?;
+        let species = species_genomes("g__G1", &results);
+        assert_eq!(species.keys().collect::<Vec<_>>(), ["s__S1", "s__S2"]);
+
+        let designations: HashMap<String, MetadataTypeMaterial> = serde_json::from_str(
+            r#"{
+                "G1": {"gtdbTypeDesignation": "not type material"},
+                "G2": {"gtdbTypeDesignation": "type strain of species",
+                       "gtdbTypeDesignationSources": "LPSN, DSMZ",
+                       "lpsnTypeDesignation": "type strain of species"},
+                "G3": {"gtdbTypeDesignation": "not type material"}
+            }"#,
+        )?;
+        let summary = species_type_material(&species, &designations);
+        assert!(summary[0].has_type_material);
+        assert_eq!(summary[0].type_genomes, ["G2", "G1"]);
+        assert_eq!(
+            summary[0].to_fields(),
+            [
+                "s__S1",
+                "2",
+                "true",
+                "G2;G1",
+                "type strain of species",
+                "LPSN, DSMZ",
+                "type strain of species",
+                ""
+            ]
+        );
+        assert!(!summary[1].has_type_material);
+        assert!(summary[1].type_genomes.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_novel_taxa() -> Result<()> {
+        let results: Vec<SearchResult> = serde_json::from_str(
+            r#"[
+                {"gid": "G1", "gtdbTaxonomy": "d__Bacteria; f__Rhizobiaceae; g__Aminobacter; s__Aminobacter niigataensis"},
+                {"gid": "G2", "gtdbTaxonomy": "d__Bacteria; f__Rhizobiaceae; g__Aminobacter; s__Aminobacter sp001512625"},
+                {"gid": "G3", "gtdbTaxonomy": "d__Bacteria; f__Rhizobiaceae; g__DTU036; s__DTU036 sp002"},
+                {"gid": "G4", "gtdbTaxonomy": "d__Bacteria; f__Rhizobiaceae_A; g__UBA1; s__UBA1 sp003"}
+            ]"#,
+        )?;
+        let novel = NovelTaxa::from_search_results("f__Rhizobiaceae", &results);
+        assert_eq!(
+            novel.counts[&Rank::Species],
+            NameCounts {
+                published: 1,
+                placeholder: 2
+            }
+        );
+        assert_eq!(
+            novel.summary(),
+            "f__Rhizobiaceae: 1/2 genus, 2/3 species are placeholders"
+        );
+        assert_eq!(
+            novel.to_rows(),
+            [
+                vec![
+                    "f__Rhizobiaceae",
+                    "genus",
+                    "g__DTU036",
+                    "d__Bacteria; f__Rhizobiaceae; g__DTU036"
+                ],
+                vec![
+                    "f__Rhizobiaceae",
+                    "species",
+                    "s__Aminobacter sp001512625",
+                    "d__Bacteria; f__Rhizobiaceae; g__Aminobacter; s__Aminobacter sp001512625"
+                ],
+                vec![
+                    "f__Rhizobiaceae",
+                    "species",
+                    "s__DTU036 sp002",
+                    "d__Bacteria; f__Rhizobiaceae; g__DTU036; s__DTU036 sp002"
+                ]
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_species_clusters() -> Result<()> {
+        let results: Vec<SearchResult> = serde_json::from_str(
+            r#"[
+                {"gid": "G1", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S1",
+                 "isGtdbSpeciesRep": true},
+                {"gid": "G2", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S1",
+                 "isGtdbSpeciesRep": false},
+                {"gid": "G3", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S2",
+                 "isGtdbSpeciesRep": true},
+                {"gid": "G4", "gtdbTaxonomy": "d__Bacteria; g__G1; s__S3",
+                 "isGtdbSpeciesRep": false}
+            ]"#,
+        )?;
+        let species = species_genomes("g__G1", &results);
+        let sizes = HashMap::from([("G1".to_string(), 3), ("G3".to_string(), 6)]);
+        let clusters = species_clusters(&species, &sizes);
+
+        let rows: Vec<Vec<String>> = clusters.iter().map(SpeciesCluster::to_fields).collect();
+        assert_eq!(
+            rows,
+            [
+                ["s__S2", "G3", "6", "0.6000"],
+                ["s__S1", "G1", "3", "0.3000"],
+                ["s__S3", "", "1", "0.1000"]
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_taxon_stats() -> Result<()> {
+        let results: Vec<SearchResult> = serde_json::from_str(
+            r#"[
+                {"gid": "G1", "gtdbTaxonomy": "d__Bacteria; p__P1; c__C1; o__O1; f__F1; g__G1; s__S1",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": true},
+                {"gid": "G2", "gtdbTaxonomy": "d__Bacteria; p__P1; c__C1; o__O1; f__F1; g__G1; s__S1",
+                 "isGtdbSpeciesRep": false, "isNcbiTypeMaterial": false},
+                {"gid": "G3", "gtdbTaxonomy": "d__Bacteria; p__P1; c__C1; o__O1; f__F2; g__G2; s__S2",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": false},
+                {"gid": "G4", "gtdbTaxonomy": "d__Bacteria; p__P1; c__C1; o__O11; f__F3; g__G3; s__S3",
+                 "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": false}
+            ]"#,
+        )?;
+
+        let stats = TaxonStats::from_search_results("o__O1", &results);
+        assert_eq!(stats.genomes, 3);
+        assert_eq!(stats.species_representatives, 2);
+        assert_eq!(stats.type_material, 1);
+        assert_eq!(
+            stats
+                .descendants
+                .into_iter()
+                .collect::<Vec<(Rank, usize)>>(),
+            vec![(Rank::Family, 2), (Rank::Genus, 2), (Rank::Species, 2)]
+        );
+
+        let stats = TaxonStats::from_search_results("p__P1", &results);
+        let rows = stats.to_rows();
+        assert_eq!(rows[0], vec!["p__P1", "genomes", "4"]);
+        assert_eq!(
+            rows[2],
+            vec!["p__P1", "species_representatives_fraction", "0.7500"]
+        );
+        assert_eq!(rows[5], vec!["p__P1", "class", "1"]);
+        assert_eq!(rows.len(), 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_taxon_release_is_seen_in() -> Result<()> {
+        let release: TaxonRelease = serde_json::from_str(
+            r#"{"taxon": "g__Chelatobacter", "firstSeen": "R80", "lastSeen": "R89"}"#,
+        )?;
+        assert!(release.is_seen_in("R86"));
+        assert!(!release.is_seen_in("R95"));
+
+        let release: TaxonRelease =
+            serde_json::from_str(r#"{"taxon": "g__Aminobacter", "firstSeen": "R95"}"#)?;
+        assert!(release.is_seen_in("R220"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_taxon_lineage() {
+        let lineage = taxonomy::parent_lineage(
+            "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__UBA6186; f__UBA6186; g__UBA6186; s__UBA6186 sp001512625",
+            "o__UBA6186",
+        )
+        .unwrap();
+        assert_eq!(
+            format_taxon_lineage("o__UBA6186", &lineage, false),
+            "o__UBA6186\td__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__UBA6186\n"
+        );
+        assert_eq!(
+            format_taxon_lineage("o__UBA6186", &lineage, true),
+            "o__UBA6186\td__Bacteria\tp__Pseudomonadota\tc__Gammaproteobacteria\to__UBA6186\t\t\t\n"
+        );
+    }
+
+    #[test]
+    fn test_filter_type_material() -> Result<()> {
+        let results: Vec<SearchResult> = serde_json::from_str(
+            r#"[
+                {"gid": "GCA_000000001.1", "accession": "GCA_000000001.1", "isNcbiTypeMaterial": true},
+                {"gid": "GCA_000000002.1", "accession": "GCA_000000002.1", "isNcbiTypeMaterial": false}
+            ]"#,
+        )?;
+        let search_results: HashMap<String, SearchResult> =
+            results.into_iter().map(|x| (x.gid.clone(), x)).collect();
+        let mut genomes = TaxonGenomes {
+            data: vec![
+                "GCA_000000001.1".to_string(),
+                "GCA_000000002.1".to_string(),
+                "GCA_000000003.1".to_string(),
+            ],
+        };
+
+        filter_type_material(&mut genomes, &search_results);
+        assert_eq!(genomes.data, vec!["GCA_000000001.1".to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_genome_details() -> Result<()> {
+        let json = fs::read_to_string("test/card.json")?;
+        let card: GenomeCard = serde_json::from_str(&json)?;
+        let search_result: SearchResult = serde_json::from_str(
+            r#"{"gid": "GCA_001512625.1", "accession": "GCA_001512625.1",
+                "ncbiOrgName": "Gammaproteobacteria bacterium SCGC AAA011-G17",
+                "isGtdbSpeciesRep": true, "isNcbiTypeMaterial": false}"#,
+        )?;
+
+        let details = vec![
+            GenomeDetail::new("GCA_001512625.1", Some(&search_result), &card),
+            GenomeDetail::new("GCA_001512625.1", None, &card),
+        ];
+        let table = format_genome_details(&details, &OutputFormat::Tsv)?;
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines[0], GenomeDetail::HEADER.join("\t"));
+        assert_eq!(
+            lines[1],
+            "GCA_001512625.1\tGammaproteobacteria bacterium SCGC AAA011-G17\ts__UBA6186 sp001512625\ttrue\tfalse\t96.55\t1.72\t2105384\t123"
+        );
+        assert_eq!(
+            lines[2],
+            "GCA_001512625.1\t\ts__UBA6186 sp001512625\ttrue\t\t96.55\t1.72\t2105384\t123"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_get_genomes_with_output() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("xgt-taxon-genomes-{}.json", std::process::id()));
+        let output = path.display().to_string();
         let args = TaxonArgs {
             name: vec!["g__Escherichia".to_string()],
-            output: Some("output.json".to_string()),
+            output: Some(output.clone()),
             is_whole_words_matching: false,
             search: false,
             search_all: false,
             genomes: true,
             reps_only: false,
             disable_certificate_verification: true,
+            ..Default::default()
         };
 
         let actual_output = args.get_output().unwrap();
 
         get_taxon_genomes(args)?;
 
-        let expected_output = fs::read_to_string("output.json")?;
+        let expected_output = fs::read_to_string(&path)?;
         let expected_taxon_data: TaxonGenomes = serde_json::from_str(&expected_output)?;
 
         let actual_output = fs::read_to_string(actual_output)?;
@@ -363,7 +2270,7 @@ mod tests {
         assert_eq!(expected_taxon_data, actual_taxon_data);
 
         // Clean up the output file
-        fs::remove_file("output.json")?;
+        fs::remove_file(&path)?;
 
         Ok(())
     }
@@ -0,0 +1,181 @@
+use anyhow::{bail, ensure, Result};
+use std::collections::HashMap;
+use ureq::Agent;
+
+use crate::cli::translate::{TargetTaxonomy, TranslateArgs};
+use crate::cmd::search::{fetch_search_results, SearchResult};
+use crate::query::SearchQuery;
+use crate::taxonomy::{parse_lineage, Rank};
+use crate::utils::{self, SearchField};
+
+/// Name used for genomes without a taxon at the rank in the target taxonomy
+const UNMAPPED: &str = "unmapped";
+
+/// A counterpart of the translated taxon and its number of genomes
+#[derive(Debug, Clone, PartialEq)]
+struct Translation {
+    taxon: String,
+    genomes: usize,
+    percent: f64,
+}
+
+impl Translation {
+    fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.taxon.clone(),
+            self.genomes.to_string(),
+            format!("{:.2}", self.percent),
+        ]
+    }
+}
+
+/// Lineage of a genome in a taxonomy
+fn lineage_in(row: &SearchResult, taxonomy: TargetTaxonomy) -> Option<String> {
+    match taxonomy {
+        TargetTaxonomy::Ncbi => row.get_ncbi_taxonomy(),
+        TargetTaxonomy::Gtdb => row.get_gtdb_taxonomy(),
+    }
+}
+
+/// Translate `name` to the taxa at the same rank in the target taxonomy of
+/// the genomes it contains, most frequent first. Genomes without a taxon at
+/// that rank in the target taxonomy are grouped as "unmapped".
+fn translate_rows(rows: &[SearchResult], name: &str, to: TargetTaxonomy) -> Vec<Translation> {
+    let Some(rank) = Rank::from_taxon(name) else {
+        return vec![];
+    };
+    let source = match to {
+        TargetTaxonomy::Ncbi => TargetTaxonomy::Gtdb,
+        TargetTaxonomy::Gtdb => TargetTaxonomy::Ncbi,
+    };
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut total = 0;
+    for row in rows {
+        // The search is partial, only keep the genomes of the taxon itself
+        let in_taxon = lineage_in(row, source).is_some_and(|lineage| {
+            parse_lineage(&lineage).get(&rank).map(String::as_str) == Some(name)
+        });
+        if !in_taxon {
+            continue;
+        }
+        let taxon = lineage_in(row, to)
+            .and_then(|lineage| parse_lineage(&lineage).remove(&rank))
+            .unwrap_or_else(|| UNMAPPED.to_string());
+        *counts.entry(taxon).or_insert(0) += 1;
+        total += 1;
+    }
+
+    let mut translations: Vec<Translation> = counts
+        .into_iter()
+        .map(|(taxon, genomes)| Translation {
+            taxon,
+            genomes,
+            percent: 100.0 * genomes as f64 / total as f64,
+        })
+        .collect();
+    translations.sort_by(|a, b| b.genomes.cmp(&a.genomes).then(a.taxon.cmp(&b.taxon)));
+    translations
+}
+
+/// Translate a GTDB taxon name to its NCBI counterparts or vice versa,
+/// from the GTDB and NCBI taxonomies of its genomes
+pub fn translate(args: TranslateArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let name = args.get_name();
+    let (search_field, source, target) = match args.get_to() {
+        TargetTaxonomy::Ncbi => (SearchField::Gtdb, "GTDB", "NCBI"),
+        TargetTaxonomy::Gtdb => (SearchField::Ncbi, "NCBI", "GTDB"),
+    };
+
+    let rows =
+        fetch_search_results(&agent, &SearchQuery::new(&name).field(search_field))?.into_rows();
+    let translations = translate_rows(&rows, &name, args.get_to());
+    ensure!(
+        !translations.is_empty(),
+        "No genome of the {} taxon {} found in GTDB",
+        source,
+        name
+    );
+
+    let mapped = translations.iter().filter(|x| x.taxon != UNMAPPED).count();
+    match mapped {
+        0 => bail!("No {} counterpart found for {}", target, name),
+        1 => {}
+        n => eprintln!(
+            "Warning: {} is ambiguous, its genomes belong to {} {} taxa",
+            name, n, target
+        ),
+    }
+    if translations.iter().any(|x| x.taxon == UNMAPPED) {
+        eprintln!(
+            "Warning: some genomes of {} have no {} taxon at this rank",
+            name, target
+        );
+    }
+
+    let header = [
+        format!("{}_taxon", target.to_lowercase()),
+        "genomes".into(),
+        "percent".into(),
+    ];
+    let header: Vec<&str> = header.iter().map(String::as_str).collect();
+    let rows: Vec<Vec<String>> = translations.iter().map(Translation::to_fields).collect();
+    let output = utils::render_table(&header, &rows, &args.get_outfmt());
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(gtdb: &str, ncbi: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "gid": "GCF_000005845.2",
+            "gtdbTaxonomy": gtdb,
+            "ncbiTaxonomy": ncbi,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_translate_rows() {
+        let rows = vec![
+            row(
+                "d__Bacteria; g__Escherichia; s__Escherichia coli",
+                "d__Bacteria; g__Escherichia; s__Escherichia coli",
+            ),
+            row(
+                "d__Bacteria; g__Escherichia; s__Escherichia flexneri",
+                "d__Bacteria; g__Shigella; s__Shigella flexneri",
+            ),
+            row(
+                "d__Bacteria; g__Escherichia; s__Escherichia coli",
+                "d__Bacteria; g__Shigella; s__Shigella sonnei",
+            ),
+            row(
+                "d__Bacteria; g__Escherichia_A; s__Escherichia_A sp.",
+                "d__Bacteria; g__; s__",
+            ),
+        ];
+
+        let translations = translate_rows(&rows, "g__Escherichia", TargetTaxonomy::Ncbi);
+        assert_eq!(translations.len(), 2);
+        assert_eq!(translations[0].taxon, "g__Shigella");
+        assert_eq!(translations[0].genomes, 2);
+        assert_eq!(
+            translations[1].to_fields(),
+            vec!["g__Escherichia", "1", "33.33"]
+        );
+
+        let translations = translate_rows(&rows, "g__Shigella", TargetTaxonomy::Gtdb);
+        assert_eq!(translations.len(), 1);
+        assert_eq!(translations[0].taxon, "g__Escherichia");
+        assert_eq!(translations[0].percent, 100.0);
+
+        let translations = translate_rows(&rows, "g__Escherichia_A", TargetTaxonomy::Ncbi);
+        assert_eq!(translations[0].taxon, UNMAPPED);
+    }
+}
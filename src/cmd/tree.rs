@@ -0,0 +1,199 @@
+use anyhow::Result;
+use ureq::Agent;
+
+use crate::cli::tree::{TreeArgs, TreeFormat};
+use crate::cmd::taxon::fetch_taxon_children;
+use crate::utils;
+
+/// A taxon and its child taxa
+#[derive(Debug, Clone, PartialEq)]
+struct TreeNode {
+    taxon: String,
+    genomes: u64,
+    children: Vec<TreeNode>,
+}
+
+/// Recursively walk the children of `taxon` down to `depth` levels.
+/// Genomes are not included as nodes, species being the deepest level.
+fn build_tree(agent: &Agent, taxon: &str, genomes: u64, depth: usize) -> Result<TreeNode> {
+    let mut node = TreeNode {
+        taxon: taxon.to_string(),
+        genomes,
+        children: Vec::new(),
+    };
+    if depth == 0 {
+        return Ok(node);
+    }
+
+    for child in fetch_taxon_children(agent, taxon)?.data {
+        if child.is_genome() {
+            continue;
+        }
+        node.children.push(build_tree(
+            agent,
+            &child.get_taxon(),
+            child.get_genome_count(),
+            depth - 1,
+        )?);
+    }
+    Ok(node)
+}
+
+/// Render children of a node, `prefix` holding the branches of its ancestors
+fn render_children(node: &TreeNode, prefix: &str, output: &mut String) {
+    for (i, child) in node.children.iter().enumerate() {
+        let is_last = i == node.children.len() - 1;
+        let (branch, indent) = if is_last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        output.push_str(&format!(
+            "{}{}{} ({})\n",
+            prefix, branch, child.taxon, child.genomes
+        ));
+        render_children(child, &format!("{}{}", prefix, indent), output);
+    }
+}
+
+/// Render a tree as indented ASCII art with genome counts per node
+fn render_tree(root: &TreeNode) -> String {
+    let mut output = format!("{} ({})\n", root.taxon, root.genomes);
+    render_children(root, "", &mut output);
+    output
+}
+
+/// Escape a string to be used inside a quoted DOT identifier
+fn dot_escape(taxon: &str) -> String {
+    taxon.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Quote a DOT identifier
+fn dot_id(taxon: &str) -> String {
+    format!("\"{}\"", dot_escape(taxon))
+}
+
+/// Render a node and its edges to its children as DOT statements
+fn render_dot_node(node: &TreeNode, output: &mut String) {
+    // Node width grows with the order of magnitude of the genome count
+    let width = 0.75 + 0.5 * ((node.genomes + 1) as f64).log10();
+    output.push_str(&format!(
+        "  {} [label=\"{}\\n{} genomes\", width={:.2}];\n",
+        dot_id(&node.taxon),
+        dot_escape(&node.taxon),
+        node.genomes,
+        width
+    ));
+    for child in &node.children {
+        output.push_str(&format!(
+            "  {} -> {};\n",
+            dot_id(&node.taxon),
+            dot_id(&child.taxon)
+        ));
+        render_dot_node(child, output);
+    }
+}
+
+/// Render a tree as a Graphviz DOT graph, nodes labelled and sized by
+/// their genome counts
+fn render_dot(root: &TreeNode) -> String {
+    let mut output = format!(
+        "digraph {} {{\n  rankdir=LR;\n  node [shape=ellipse];\n",
+        dot_id(&root.taxon)
+    );
+    render_dot_node(root, &mut output);
+    output.push_str("}\n");
+    output
+}
+
+pub fn tree(args: TreeArgs) -> Result<()> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+
+    let mut root = build_tree(&agent, &args.get_name(), 0, args.get_depth())?;
+    // The API only gives genome counts of children, so the root is their sum
+    root.genomes = root.children.iter().map(|x| x.genomes).sum();
+
+    let output = match args.get_outfmt() {
+        TreeFormat::Ascii => render_tree(&root),
+        TreeFormat::Dot => render_dot(&root),
+    };
+    utils::write_to_output(output.as_bytes(), args.get_output())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(taxon: &str, genomes: u64) -> TreeNode {
+        TreeNode {
+            taxon: taxon.to_string(),
+            genomes,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let root = TreeNode {
+            taxon: "f__Rhizobiaceae".to_string(),
+            genomes: 6,
+            children: vec![
+                TreeNode {
+                    taxon: "g__Rhizobium".to_string(),
+                    genomes: 5,
+                    children: vec![
+                        leaf("s__Rhizobium etli", 3),
+                        leaf("s__Rhizobium leguminosarum", 2),
+                    ],
+                },
+                TreeNode {
+                    taxon: "g__Aminobacter".to_string(),
+                    genomes: 1,
+                    children: vec![leaf("s__Aminobacter aminovorans", 1)],
+                },
+            ],
+        };
+
+        assert_eq!(
+            render_tree(&root),
+            "f__Rhizobiaceae (6)
+├── g__Rhizobium (5)
+│   ├── s__Rhizobium etli (3)
+│   └── s__Rhizobium leguminosarum (2)
+└── g__Aminobacter (1)
+    └── s__Aminobacter aminovorans (1)
+"
+        );
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let root = TreeNode {
+            taxon: "g__Aminobacter".to_string(),
+            genomes: 9,
+            children: vec![leaf("s__Aminobacter aminovorans", 9)],
+        };
+
+        assert_eq!(
+            render_dot(&root),
+            r#"digraph "g__Aminobacter" {
+  rankdir=LR;
+  node [shape=ellipse];
+  "g__Aminobacter" [label="g__Aminobacter\n9 genomes", width=1.25];
+  "g__Aminobacter" -> "s__Aminobacter aminovorans";
+  "s__Aminobacter aminovorans" [label="s__Aminobacter aminovorans\n9 genomes", width=1.25];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_tree_single_node() {
+        assert_eq!(
+            render_tree(&leaf("s__Rhizobium etli", 3)),
+            "s__Rhizobium etli (3)\n"
+        );
+    }
+}
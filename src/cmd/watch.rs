@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use ureq::Agent;
+
+use crate::cli::watch::WatchArgs;
+use crate::cmd::releases::fetch_releases;
+use crate::interrupt;
+use crate::utils;
+
+/// Read the last release seen, None if no release was stored yet
+fn read_state(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+}
+
+fn write_state(path: &Path, release: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::write(path, format!("{}\n", release))
+        .with_context(|| format!("Failed to write to {}", path.display()))
+}
+
+/// Compare the current GTDB release to the stored one and store it.
+/// Returns the previous release when a new release appeared.
+fn check_release(agent: &Agent, state: &Path) -> Result<Option<String>> {
+    let releases = fetch_releases(agent)?;
+    let current = releases
+        .last()
+        .expect("releases is not empty")
+        .get_release();
+
+    let previous = read_state(state);
+    write_state(state, &current)?;
+    match previous {
+        Some(previous) if previous != current => {
+            println!("New GTDB release: {} (previous: {})", current, previous);
+            Ok(Some(previous))
+        }
+        _ => {
+            println!("GTDB release: {}", current);
+            Ok(None)
+        }
+    }
+}
+
+/// Check for a new GTDB release, once or every `--interval` until one
+/// appears. Returns true when a new release appeared.
+pub fn watch(args: WatchArgs) -> Result<bool> {
+    let agent: Agent = utils::get_agent(args.get_disable_certificate_verification())?;
+    let state = match args.get_state() {
+        Some(path) => PathBuf::from(path),
+        None => utils::cache_dir()?.join("release"),
+    };
+
+    loop {
+        if check_release(&agent, &state)?.is_some() {
+            return Ok(true);
+        }
+        match args.get_interval() {
+            Some(interval) => interrupt::sleep(interval)?,
+            None => return Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state() {
+        let path = Path::new("test_watch_state/release");
+        assert_eq!(read_state(path), None);
+        write_state(path, "R220").unwrap();
+        assert_eq!(read_state(path), Some("R220".to_string()));
+        fs::remove_dir_all("test_watch_state").unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+//! Decoding of GTDB API responses which tolerates changes of the API
+//!
+//! GTDB adds fields to its responses and sometimes changes their types.
+//! Rather than failing the whole query, unknown fields are ignored and
+//! reported, and optional fields of an unexpected type are dropped with a
+//! warning.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::mem;
+use std::sync::{Mutex, PoisonError};
+
+use crate::error::XgtError;
+use crate::utils;
+
+/// Most decoding passes of a response, each dropping a field whose type is
+/// not the one expected, before giving up
+const MAX_DECODE_PASSES: usize = 16;
+
+/// Changes already reported, so that each is reported once per run
+static REPORTED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Changes of a response compared to the types of xgt, with array indices
+/// elided from the paths of their fields, e.g. "rows[].ncbiOrgName"
+#[derive(Debug, Default, PartialEq)]
+struct Drift {
+    // Fields sent by GTDB which xgt does not know
+    unknown: BTreeSet<String>,
+    // Fields known by xgt which GTDB did not send
+    missing: BTreeSet<String>,
+    // Fields of an unexpected type, decoded as missing
+    dropped: BTreeSet<String>,
+}
+
+/// Decode a JSON response, reporting how it differs from `T`
+pub(crate) fn decode<T: DeserializeOwned + Serialize>(body: &[u8]) -> Result<T, XgtError> {
+    let (decoded, drift) = decode_lenient(body)?;
+    report(&drift);
+    Ok(decoded)
+}
+
+fn decode_lenient<T: DeserializeOwned + Serialize>(body: &[u8]) -> Result<(T, Drift), XgtError> {
+    let mut value: Value =
+        serde_json::from_slice(body).map_err(|e| XgtError::Decode(e.to_string()))?;
+    let mut drift = Drift::default();
+    for _ in 0..MAX_DECODE_PASSES {
+        let mut unknown = BTreeSet::new();
+        let mut callback = |path: serde_ignored::Path| {
+            unknown.insert(ignored_path(&path));
+        };
+        let deserializer = serde_ignored::Deserializer::new(&value, &mut callback);
+        match serde_path_to_error::deserialize::<_, T>(deserializer) {
+            Ok(decoded) => {
+                drift.unknown = unknown;
+                if let Ok(serialized) = serde_json::to_value(&decoded) {
+                    missing_fields(&value, &serialized, "", &mut drift.missing);
+                }
+                return Ok((decoded, drift));
+            }
+            Err(e) => {
+                let path: Vec<_> = e.path().iter().collect();
+                if !set_null(&mut value, &path) {
+                    return Err(XgtError::Decode(e.to_string()));
+                }
+                drift.dropped.insert(error_path(e.path()));
+            }
+        }
+    }
+    Err(XgtError::Decode(format!(
+        "too many fields of an unexpected type: {}",
+        drift.dropped.into_iter().collect::<Vec<_>>().join(", ")
+    )))
+}
+
+/// Path of an ignored field, e.g. "rows[].ncbiOrgName"
+fn ignored_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, .. } => format!("{}[]", ignored_path(parent)),
+        serde_ignored::Path::Map { parent, key } => join(&ignored_path(parent), key),
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_path(parent),
+    }
+}
+
+/// Path of a field which failed to decode, e.g. "rows[].isGtdbSpeciesRep"
+fn error_path(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .fold(String::new(), |path, segment| match segment {
+            serde_path_to_error::Segment::Seq { .. } => format!("{}[]", path),
+            serde_path_to_error::Segment::Map { key } => join(&path, key),
+            serde_path_to_error::Segment::Enum { variant } => join(&path, variant),
+            serde_path_to_error::Segment::Unknown => join(&path, "?"),
+        })
+}
+
+fn join(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key),
+    }
+}
+
+/// Field at `path`, none if there is no such field
+fn field_at<'a>(value: &'a Value, path: &[&serde_path_to_error::Segment]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |field, segment| match (segment, field) {
+            (serde_path_to_error::Segment::Seq { index }, Value::Array(values)) => {
+                values.get(*index)
+            }
+            (serde_path_to_error::Segment::Map { key }, Value::Object(map)) => map.get(key),
+            _ => None,
+        })
+}
+
+/// Replace the field at `path` by null, so that it is decoded as missing,
+/// returning false when there is no such field or it is already null. The
+/// field is replaced in every element of the arrays along the path where
+/// it has the same JSON type, so that a field whose type changed in every
+/// row of a response is dropped in a single pass.
+fn set_null(value: &mut Value, path: &[&serde_path_to_error::Segment]) -> bool {
+    let kind = match field_at(value, path) {
+        None | Some(Value::Null) => return false,
+        Some(field) => mem::discriminant(field),
+    };
+    set_null_of_kind(value, path, kind)
+}
+
+fn set_null_of_kind(
+    value: &mut Value,
+    path: &[&serde_path_to_error::Segment],
+    kind: mem::Discriminant<Value>,
+) -> bool {
+    let Some((segment, rest)) = path.split_first() else {
+        if mem::discriminant(value) != kind {
+            return false;
+        }
+        *value = Value::Null;
+        return true;
+    };
+    match (segment, value) {
+        (serde_path_to_error::Segment::Seq { .. }, Value::Array(values)) => {
+            let mut nulled = false;
+            for x in values {
+                nulled |= set_null_of_kind(x, rest, kind);
+            }
+            nulled
+        }
+        (serde_path_to_error::Segment::Map { key }, Value::Object(map)) => map
+            .get_mut(key)
+            .is_some_and(|x| set_null_of_kind(x, rest, kind)),
+        _ => false,
+    }
+}
+
+/// Field names compared regardless of case, as GTDB sends some fields
+/// in camel case and others in snake case
+fn normalize_key(key: &str) -> String {
+    key.replace('_', "").to_lowercase()
+}
+
+/// Collect the fields of `decoded`, the decoded response serialized back,
+/// which are not in `original`, the response
+fn missing_fields(original: &Value, decoded: &Value, path: &str, missing: &mut BTreeSet<String>) {
+    match (original, decoded) {
+        (Value::Object(original), Value::Object(decoded)) => {
+            for (key, value) in decoded {
+                let field = join(path, key);
+                match original
+                    .iter()
+                    .find(|(x, _)| normalize_key(x) == normalize_key(key))
+                {
+                    Some((_, original)) => missing_fields(original, value, &field, missing),
+                    None => {
+                        missing.insert(field);
+                    }
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(decoded)) => {
+            let path = format!("{}[]", path);
+            for (original, decoded) in original.iter().zip(decoded) {
+                missing_fields(original, decoded, &path, missing);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Report changes of the API not reported yet: new and dropped fields as
+/// warnings, and the lists of unknown and missing fields at `-v`
+fn report(drift: &Drift) {
+    if utils::verbosity() == 0 {
+        return;
+    }
+    let mut reported = REPORTED.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut new = |kind: &str, fields: &BTreeSet<String>| -> Vec<String> {
+        fields
+            .iter()
+            .filter(|x| reported.insert(format!("{} {}", kind, x)))
+            .cloned()
+            .collect()
+    };
+    let unknown = new("unknown", &drift.unknown);
+    let missing = new("missing", &drift.missing);
+    let dropped = new("dropped", &drift.dropped);
+
+    for field in dropped {
+        eprintln!(
+            "Warning: ignored {} of the GTDB response, whose type is not the one expected",
+            field
+        );
+    }
+    if utils::verbosity() > 1 {
+        if !unknown.is_empty() {
+            eprintln!(
+                "Unknown fields of the GTDB response: {}",
+                unknown.join(", ")
+            );
+        }
+        if !missing.is_empty() {
+            eprintln!(
+                "Missing fields of the GTDB response: {}",
+                missing.join(", ")
+            );
+        }
+    } else if !unknown.is_empty() {
+        eprintln!(
+            "Warning: the GTDB API sent {} new fields, ignored by xgt, run with -v to list them",
+            unknown.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Row {
+        gid: String,
+        ncbi_org_name: Option<String>,
+        is_gtdb_species_rep: Option<bool>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Rows {
+        rows: Vec<Row>,
+    }
+
+    #[test]
+    fn test_decode_lenient() {
+        let body = br#"{"rows": [
+            {"gid": "GCA_1", "ncbiOrgName": "Foo", "isGtdbSpeciesRep": true, "ncbiTaxid": 1},
+            {"gid": "GCA_2", "isGtdbSpeciesRep": "yes"}
+        ], "totalRows": 2}"#;
+        let (rows, drift) = decode_lenient::<Rows>(body).unwrap();
+        assert_eq!(rows.rows[1].is_gtdb_species_rep, None);
+        assert_eq!(
+            drift,
+            Drift {
+                unknown: ["rows[].ncbiTaxid", "totalRows"].map(String::from).into(),
+                missing: ["rows[].ncbiOrgName"].map(String::from).into(),
+                dropped: ["rows[].isGtdbSpeciesRep"].map(String::from).into(),
+            }
+        );
+
+        // A field whose type changed in every row is dropped in one pass,
+        // other rows keeping their values
+        let rows: Vec<String> = (0..1000)
+            .map(|i| format!(r#"{{"gid": "GCA_{}", "ncbiOrgName": {}}}"#, i, i))
+            .chain([r#"{"gid": "GCA_x", "ncbiOrgName": "Foo"}"#.to_string()])
+            .collect();
+        let body = format!(r#"{{"rows": [{}]}}"#, rows.join(","));
+        let (rows, drift) = decode_lenient::<Rows>(body.as_bytes()).unwrap();
+        assert!(rows.rows[..1000].iter().all(|x| x.ncbi_org_name.is_none()));
+        assert_eq!(rows.rows[1000].ncbi_org_name.as_deref(), Some("Foo"));
+        assert_eq!(
+            drift.dropped,
+            ["rows[].ncbiOrgName"].map(String::from).into()
+        );
+
+        // Required fields of an unexpected type still fail
+        let body = br#"{"rows": [{"gid": 1}]}"#;
+        assert!(matches!(
+            decode_lenient::<Rows>(body),
+            Err(XgtError::Decode(_))
+        ));
+        assert!(matches!(
+            decode_lenient::<Rows>(b"{"),
+            Err(XgtError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_fields_case() {
+        // Fields aliased in camel case are not missing
+        let original = serde_json::json!({"metadataTaxonomy": {"gtdbDomain": "d__Bacteria"}});
+        let decoded = serde_json::json!({"metadata_taxonomy": {"gtdb_domain": "d__Bacteria"}});
+        let mut missing = BTreeSet::new();
+        missing_fields(&original, &decoded, "", &mut missing);
+        assert!(missing.is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use std::io;
+use std::sync::{Mutex, PoisonError};
+use thiserror::Error;
+
+use crate::api;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tls::is_tls_error;
+
+/// URL of the last request which failed, reported by `--errors json`
+static FAILED_URL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Record the URL of a request which failed
+pub(crate) fn record_failed_url(url: &str) {
+    *FAILED_URL.lock().unwrap_or_else(PoisonError::into_inner) = Some(url.to_string());
+}
+
+/// Get the URL of the last request which failed
+pub fn failed_url() -> Option<String> {
+    FAILED_URL
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Errors of queries to the GTDB API, which library users can match on
+/// and the command line maps to exit codes
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum XgtError {
+    /// The queried genome or taxon is not in GTDB
+    #[error("{0}")]
+    NotFound(String),
+    /// GTDB refused the request as too many were sent
+    #[error("Too many requests sent to the GTDB API, please retry later")]
+    RateLimited,
+    /// The request could not be sent, or failed on the server
+    #[error("{0}")]
+    Network(String),
+    /// The SSL certificate of the server could not be verified
+    #[error("{0}, --insecure disables SSL certificate verification")]
+    Tls(String),
+    /// The response is not in the expected format
+    #[error("Failed to decode the response: {0}")]
+    Decode(String),
+    /// The server refused to send the response, or the response exceeds
+    /// the size xgt can read
+    #[error("The response is too large: {0}")]
+    TooLarge(String),
+    /// The query itself is invalid
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The user pressed Ctrl-C before the query completed
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl XgtError {
+    /// Error of a failed GTDB API request, `not_found` being the message
+    /// used when GTDB answers that the queried record does not exist
+    pub(crate) fn from_request(error: ureq::Error, not_found: impl Into<String>) -> Self {
+        let url = match &error {
+            ureq::Error::Status(_, response) => Some(response.get_url()),
+            ureq::Error::Transport(transport) => transport.url().map(|x| x.as_str()),
+        };
+        if let Some(url) = url {
+            record_failed_url(url);
+        }
+        match error {
+            ureq::Error::Status(code, _) => XgtError::from_status(code, not_found),
+            ureq::Error::Transport(transport) => match transport.kind() {
+                ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                    XgtError::InvalidInput(transport.to_string())
+                }
+                _ if is_tls_error(&transport) => XgtError::Tls(transport.to_string()),
+                _ => XgtError::Network(
+                    "There was an error making the request or receiving the response.".to_string(),
+                ),
+            },
+        }
+    }
+
+    /// Error of a GTDB API response with an error status code
+    pub(crate) fn from_status(code: u16, not_found: impl Into<String>) -> Self {
+        match code {
+            400 | 404 => XgtError::NotFound(not_found.into()),
+            413 => XgtError::TooLarge("the server refused to send it".to_string()),
+            429 => XgtError::RateLimited,
+            _ => XgtError::Network(format!(
+                "The server returned an unexpected status code ({})",
+                code
+            )),
+        }
+    }
+
+    /// Error of a failed request of the async client
+    #[cfg(feature = "async")]
+    pub(crate) fn from_reqwest(error: reqwest::Error, not_found: impl Into<String>) -> Self {
+        match error.status() {
+            Some(status) => XgtError::from_status(status.as_u16(), not_found),
+            None if error.is_builder() => XgtError::InvalidInput(error.to_string()),
+            None if error.is_decode() => XgtError::Decode(error.to_string()),
+            None if is_tls_error(&error) => XgtError::Tls(error.to_string()),
+            None => XgtError::Network(
+                "There was an error making the request or receiving the response.".to_string(),
+            ),
+        }
+    }
+
+    /// Exit code of the command line, 1 and 2 being used for other errors
+    /// and by commands reporting a result through their exit code
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            XgtError::NotFound(_) => 3,
+            XgtError::RateLimited => 4,
+            XgtError::Network(_) => 5,
+            XgtError::Tls(_) => 6,
+            XgtError::Decode(_) => 7,
+            XgtError::TooLarge(_) => 8,
+            XgtError::InvalidInput(_) => 9,
+            // Exit status of shells for processes killed by SIGINT
+            XgtError::Interrupted => 130,
+        }
+    }
+
+    /// Category of the error, as reported by `--errors json`
+    pub fn category(&self) -> &'static str {
+        match self {
+            XgtError::NotFound(_) => "not_found",
+            XgtError::RateLimited => "rate_limited",
+            XgtError::Network(_) => "network",
+            XgtError::Tls(_) => "tls",
+            XgtError::Decode(_) => "decode",
+            XgtError::TooLarge(_) => "too_large",
+            XgtError::InvalidInput(_) => "invalid_input",
+            XgtError::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// Exit code of an error, set by the `XgtError` it was caused by and 1 for
+/// other errors
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<XgtError>())
+        .map_or(1, XgtError::exit_code)
+}
+
+/// Error as a JSON object for workflow managers: its exit code and
+/// category, the URL and subject of the failed request if any, and its
+/// message
+pub fn error_json(error: &anyhow::Error) -> serde_json::Value {
+    let xgt_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<XgtError>());
+    let url = xgt_error.and_then(|_| failed_url());
+    let mut json = serde_json::json!({
+        "code": exit_code(error),
+        "category": xgt_error.map_or("other", XgtError::category),
+        "url": url,
+        "message": format!("{:#}", error),
+    });
+    if let Some((kind, subject)) = url.as_deref().and_then(api::query_subject) {
+        json[kind] = subject.into();
+    }
+    json
+}
+
+/// Whether an error was caused by the user pressing Ctrl-C
+pub(crate) fn is_interrupted(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|x| matches!(x.downcast_ref(), Some(XgtError::Interrupted)))
+}
+
+/// Browsers do not tell certificate errors apart from other failed fetches
+#[cfg(target_arch = "wasm32")]
+fn is_tls_error(_error: &dyn std::error::Error) -> bool {
+    false
+}
+
+/// Reading a JSON response fails with invalid data when it cannot be decoded
+impl From<io::Error> for XgtError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::InvalidData => XgtError::Decode(error.to_string()),
+            _ => XgtError::Network(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_request() {
+        let response = ureq::Response::new(400, "Bad Request", "").unwrap();
+        let error = XgtError::from_request(
+            ureq::Error::Status(400, response),
+            "Taxon g__Unknown not found",
+        );
+        assert!(matches!(error, XgtError::NotFound(_)));
+        assert_eq!(error.to_string(), "Taxon g__Unknown not found");
+        assert_eq!(error.exit_code(), 3);
+
+        let response = ureq::Response::new(429, "Too Many Requests", "").unwrap();
+        let error = XgtError::from_request(ureq::Error::Status(429, response), "");
+        assert!(matches!(error, XgtError::RateLimited));
+
+        let response = ureq::Response::new(500, "Internal Server Error", "").unwrap();
+        let error = XgtError::from_request(ureq::Error::Status(500, response), "");
+        assert_eq!(
+            error.to_string(),
+            "The server returned an unexpected status code (500)"
+        );
+    }
+
+    #[test]
+    fn test_error_json() {
+        let error = anyhow::Error::from(XgtError::NotFound("Genome GCA_1 not found".to_string()));
+        let json = error_json(&error);
+        assert_eq!(json["code"], 3);
+        assert_eq!(json["category"], "not_found");
+        assert_eq!(json["message"], "Genome GCA_1 not found");
+
+        let json = error_json(&anyhow::anyhow!("Failed to read manifest"));
+        assert_eq!(json["code"], 1);
+        assert_eq!(json["category"], "other");
+        assert_eq!(json["url"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let error =
+            anyhow::Error::from(XgtError::RateLimited).context("Failed to fetch g__Aminobacter");
+        assert_eq!(exit_code(&error), 4);
+        assert_eq!(exit_code(&anyhow::anyhow!("Invalid manifest")), 1);
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        let error = serde_json::from_str::<Vec<String>>("{").unwrap_err();
+        let error = XgtError::from(io::Error::new(io::ErrorKind::InvalidData, error));
+        assert!(matches!(error, XgtError::Decode(_)));
+        assert_eq!(error.exit_code(), 7);
+    }
+}
@@ -0,0 +1,37 @@
+use super::Genome;
+use crate::taxonomy::Rank;
+
+/// Anvi'o taxonomy import format: tab-delimited items followed by one
+/// `t_<rank>` column per rank, as read by `anvi-import-taxonomy-for-layers`
+pub fn to_anvio(genomes: &[Genome]) -> String {
+    let header: Vec<String> = Rank::ALL.iter().map(|rank| format!("t_{}", rank)).collect();
+    let mut output = format!("item\t{}\n", header.join("\t"));
+    for genome in genomes {
+        let ranks: Vec<&str> = Rank::ALL
+            .iter()
+            .map(|rank| genome.name_at(*rank).unwrap_or_default())
+            .collect();
+        output.push_str(&format!("{}\t{}\n", genome.accession, ranks.join("\t")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_anvio() {
+        let output = to_anvio(&genomes());
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some("item\tt_domain\tt_phylum\tt_class\tt_order\tt_family\tt_genus\tt_species")
+        );
+        assert_eq!(
+            lines.last(),
+            Some("GCF_000006945.2\tBacteria\tPseudomonadota\tGammaproteobacteria\tEnterobacterales\tEnterobacteriaceae\tSalmonella\tSalmonella enterica")
+        );
+    }
+}
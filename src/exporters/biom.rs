@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+use super::Genome;
+
+/// Observation metadata of a BIOM table row
+#[derive(Debug, Serialize)]
+struct ObservationMetadata<'a> {
+    taxonomy: Vec<&'a str>,
+}
+
+/// A BIOM table row: a feature ID and its metadata
+#[derive(Debug, Serialize)]
+struct Observation<'a> {
+    id: &'a str,
+    metadata: ObservationMetadata<'a>,
+}
+
+/// BIOM-compatible observation metadata: the `rows` of a BIOM table, one
+/// per accession, with its GTDB lineage as a list of taxa in `taxonomy`
+pub fn to_biom(genomes: &[Genome]) -> String {
+    let rows: Vec<Observation> = genomes
+        .iter()
+        .map(|genome| Observation {
+            id: &genome.accession,
+            metadata: ObservationMetadata {
+                taxonomy: genome.lineage.values().map(String::as_str).collect(),
+            },
+        })
+        .collect();
+    let mut output = serde_json::to_string_pretty(&rows).expect("rows serialize to JSON");
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_biom() {
+        let output = to_biom(&genomes());
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().map(Vec::len), Some(3));
+        assert_eq!(rows[2]["id"], "GCF_000006945.2");
+        assert_eq!(rows[2]["metadata"]["taxonomy"][0], "d__Bacteria");
+        assert_eq!(rows[2]["metadata"]["taxonomy"][6], "s__Salmonella enterica");
+    }
+}
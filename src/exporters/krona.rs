@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+use super::Genome;
+use crate::taxonomy::Rank;
+
+/// Krona text input: the number of genomes of each lineage, followed by
+/// the taxa of the lineage from domain to species, tab-separated
+pub fn to_krona(genomes: &[Genome]) -> String {
+    let mut counts: BTreeMap<Vec<&str>, usize> = BTreeMap::new();
+    for genome in genomes {
+        let lineage: Vec<&str> = Rank::ALL
+            .iter()
+            .map_while(|rank| genome.name_at(*rank))
+            .collect();
+        *counts.entry(lineage).or_insert(0) += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(lineage, count)| format!("{}\t{}\n", count, lineage.join("\t")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_krona() {
+        assert_eq!(
+            to_krona(&genomes()),
+            "2\tBacteria\tPseudomonadota\tGammaproteobacteria\tEnterobacterales\tEnterobacteriaceae\tEscherichia\tEscherichia coli\n\
+             1\tBacteria\tPseudomonadota\tGammaproteobacteria\tEnterobacterales\tEnterobacteriaceae\tSalmonella\tSalmonella enterica\n"
+        );
+    }
+}
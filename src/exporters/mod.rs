@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::taxonomy::{parse_lineage, strip_rank_prefix, Rank};
+
+pub mod anvio;
+pub mod biom;
+pub mod krona;
+pub mod network;
+pub mod newick;
+pub mod phyloseq;
+pub mod qiime;
+pub mod seqid2taxid;
+pub mod sourmash;
+pub mod taxdump;
+
+/// Formats genomes and their GTDB lineage can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    // Krona text input
+    #[default]
+    Krona,
+    // Taxonomy cladogram in Newick format
+    Newick,
+    // NCBI-style nodes.dmp and names.dmp files
+    Taxdump,
+    // Accession to taxid mapping
+    Seqid2taxid,
+    // QIIME 2 taxonomy TSV
+    Qiime,
+    // sourmash lineages CSV
+    SourmashLineages,
+    // Per-rank taxonomy table for phyloseq or dada2
+    Phyloseq,
+    // Anvi'o taxonomy import format
+    Anvio,
+    // Node table and edge list for Cytoscape or Gephi
+    Edges,
+    // BIOM observation metadata
+    Biom,
+}
+
+impl From<String> for ExportFormat {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "newick" => ExportFormat::Newick,
+            "taxdump" => ExportFormat::Taxdump,
+            "seqid2taxid" => ExportFormat::Seqid2taxid,
+            "qiime" => ExportFormat::Qiime,
+            "sourmash-lineages" => ExportFormat::SourmashLineages,
+            "phyloseq" => ExportFormat::Phyloseq,
+            "anvio" => ExportFormat::Anvio,
+            "edges" => ExportFormat::Edges,
+            "biom" => ExportFormat::Biom,
+            _ => ExportFormat::Krona,
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ExportFormat::Krona => "krona",
+            ExportFormat::Newick => "newick",
+            ExportFormat::Taxdump => "taxdump",
+            ExportFormat::Seqid2taxid => "seqid2taxid",
+            ExportFormat::Qiime => "qiime",
+            ExportFormat::SourmashLineages => "sourmash-lineages",
+            ExportFormat::Phyloseq => "phyloseq",
+            ExportFormat::Anvio => "anvio",
+            ExportFormat::Edges => "edges",
+            ExportFormat::Biom => "biom",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A genome and its GTDB lineage
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Genome {
+    pub accession: String,
+    pub lineage: BTreeMap<Rank, String>,
+}
+
+impl Genome {
+    /// Create a genome from its accession and greengenes-style lineage
+    pub fn new(accession: &str, lineage: &str) -> Self {
+        Genome {
+            accession: accession.to_string(),
+            lineage: parse_lineage(lineage),
+        }
+    }
+
+    /// Name of the taxon of the genome at `rank`, without its rank prefix
+    pub fn name_at(&self, rank: Rank) -> Option<&str> {
+        self.lineage.get(&rank).map(|x| strip_rank_prefix(x))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Genomes of two genera of Enterobacteriaceae
+    pub(crate) fn genomes() -> Vec<Genome> {
+        let lineage = "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__Enterobacterales; f__Enterobacteriaceae";
+        vec![
+            Genome::new(
+                "GCF_000005845.2",
+                &format!("{}; g__Escherichia; s__Escherichia coli", lineage),
+            ),
+            Genome::new(
+                "GCF_000008865.2",
+                &format!("{}; g__Escherichia; s__Escherichia coli", lineage),
+            ),
+            Genome::new(
+                "GCF_000006945.2",
+                &format!("{}; g__Salmonella; s__Salmonella enterica", lineage),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_genome_name_at() {
+        let genome = &genomes()[2];
+        assert_eq!(genome.name_at(Rank::Genus), Some("Salmonella"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use super::Genome;
+use crate::taxonomy::{strip_rank_prefix, Rank};
+use crate::utils::{self, OutputFormat};
+
+/// Node table and edge list of the taxonomy of the genomes, as CSV files
+/// importable in Cytoscape or Gephi. Nodes are the taxa, with their rank
+/// and number of genomes, and edges link each taxon to its child taxa.
+pub fn to_network(genomes: &[Genome]) -> (String, String) {
+    let mut nodes: BTreeMap<(Rank, &str), usize> = BTreeMap::new();
+    let mut edges: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for genome in genomes {
+        let mut parent: Option<&str> = None;
+        for (rank, taxon) in &genome.lineage {
+            *nodes.entry((*rank, taxon)).or_insert(0) += 1;
+            if let Some(parent) = parent {
+                *edges.entry((parent, taxon)).or_insert(0) += 1;
+            }
+            parent = Some(taxon);
+        }
+    }
+
+    let nodes: Vec<Vec<String>> = nodes
+        .into_iter()
+        .map(|((rank, taxon), count)| {
+            vec![
+                taxon.to_string(),
+                strip_rank_prefix(taxon).to_string(),
+                rank.to_string(),
+                count.to_string(),
+            ]
+        })
+        .collect();
+    let edges: Vec<Vec<String>> = edges
+        .into_iter()
+        .map(|((parent, child), count)| {
+            vec![
+                parent.to_string(),
+                child.to_string(),
+                "Directed".to_string(),
+                count.to_string(),
+            ]
+        })
+        .collect();
+
+    (
+        utils::render_table(
+            &["Id", "Label", "rank", "genomes"],
+            &nodes,
+            &OutputFormat::Csv,
+        ),
+        utils::render_table(
+            &["Source", "Target", "Type", "Weight"],
+            &edges,
+            &OutputFormat::Csv,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_network() {
+        let (nodes, edges) = to_network(&genomes());
+        let mut nodes = nodes.lines();
+        assert_eq!(nodes.next(), Some("Id,Label,rank,genomes"));
+        assert_eq!(nodes.next(), Some("d__Bacteria,Bacteria,domain,3"));
+        assert_eq!(
+            nodes.last(),
+            Some("s__Salmonella enterica,Salmonella enterica,species,1")
+        );
+
+        // A header and 8 edges between the 9 taxa
+        assert_eq!(edges.lines().count(), 9);
+        assert!(edges.starts_with("Source,Target,Type,Weight\n"));
+        assert!(edges.contains("g__Escherichia,s__Escherichia coli,Directed,2\n"));
+        assert!(edges.contains("f__Enterobacteriaceae,g__Salmonella,Directed,1\n"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use super::Genome;
+
+/// A clade of the taxonomy cladogram
+#[derive(Debug, Clone, Default)]
+struct Clade {
+    children: BTreeMap<String, Clade>,
+    genomes: Vec<String>,
+}
+
+impl Clade {
+    fn insert(&mut self, genome: &Genome) {
+        let clade = genome.lineage.values().fold(self, |clade, taxon| {
+            clade.children.entry(taxon.clone()).or_default()
+        });
+        clade.genomes.push(genome.accession.clone());
+    }
+
+    fn to_newick(&self) -> String {
+        let members: Vec<String> = self
+            .children
+            .iter()
+            .map(|(taxon, clade)| format!("{}{}", clade.to_newick(), quote_label(taxon)))
+            .chain(self.genomes.iter().map(|x| quote_label(x)))
+            .collect();
+        format!("({})", members.join(","))
+    }
+}
+
+/// Quote a Newick label containing blanks or Newick punctuation
+fn quote_label(label: &str) -> String {
+    if label.contains(|c: char| c.is_whitespace() || "()[]':;,".contains(c)) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label.to_string()
+    }
+}
+
+/// Taxonomy cladogram of the genomes in Newick format, with one internal
+/// node labelled with the taxon name per rank and genomes as leaves
+pub fn to_newick(genomes: &[Genome]) -> String {
+    let mut root = Clade::default();
+    for genome in genomes {
+        root.insert(genome);
+    }
+
+    // Use the taxon shared by all genomes as the root when there is one
+    let newick = match (root.children.len(), root.genomes.is_empty()) {
+        (1, true) => {
+            let (taxon, clade) = root.children.iter().next().expect("root has a child");
+            format!("{}{}", clade.to_newick(), quote_label(taxon))
+        }
+        _ => root.to_newick(),
+    };
+    format!("{};\n", newick)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_quote_label() {
+        assert_eq!(quote_label("g__Escherichia"), "g__Escherichia");
+        assert_eq!(quote_label("s__Escherichia coli"), "'s__Escherichia coli'");
+        assert_eq!(quote_label("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_to_newick() {
+        assert_eq!(
+            to_newick(&genomes()),
+            "(((((((GCF_000005845.2,GCF_000008865.2)'s__Escherichia coli')g__Escherichia,\
+             ((GCF_000006945.2)'s__Salmonella enterica')g__Salmonella)f__Enterobacteriaceae)\
+             o__Enterobacterales)c__Gammaproteobacteria)p__Pseudomonadota)d__Bacteria;\n"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use super::Genome;
+use crate::taxonomy::Rank;
+
+/// Per-rank taxonomy table, one row per accession and one column per rank
+/// from domain to species, ready for `phyloseq::tax_table` or dada2.
+/// Taxa are written without their rank prefix and missing ranks as NA.
+pub fn to_phyloseq(genomes: &[Genome]) -> String {
+    let mut output =
+        String::from("accession\tDomain\tPhylum\tClass\tOrder\tFamily\tGenus\tSpecies\n");
+    for genome in genomes {
+        let ranks: Vec<&str> = Rank::ALL
+            .iter()
+            .map(|rank| genome.name_at(*rank).unwrap_or("NA"))
+            .collect();
+        output.push_str(&format!("{}\t{}\n", genome.accession, ranks.join("\t")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_phyloseq() {
+        let mut genomes = genomes();
+        genomes.push(Genome::new("GCA_000000002.1", "d__Archaea; p__; c__"));
+        let output = to_phyloseq(&genomes);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[1],
+            "GCF_000005845.2\tBacteria\tPseudomonadota\tGammaproteobacteria\tEnterobacterales\tEnterobacteriaceae\tEscherichia\tEscherichia coli"
+        );
+        assert_eq!(lines[4], "GCA_000000002.1\tArchaea\tNA\tNA\tNA\tNA\tNA\tNA");
+    }
+}
@@ -0,0 +1,34 @@
+use super::Genome;
+use crate::taxonomy::format_lineage;
+
+/// QIIME 2 taxonomy TSV, importable as a FeatureData[Taxonomy] artifact
+/// with `qiime tools import --input-format TSVTaxonomyFormat`
+pub fn to_qiime(genomes: &[Genome]) -> String {
+    let mut output = String::from("Feature ID\tTaxon\n");
+    for genome in genomes {
+        output.push_str(&format!(
+            "{}\t{}\n",
+            genome.accession,
+            format_lineage(&genome.lineage)
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_qiime() {
+        let output = to_qiime(&genomes());
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("Feature ID\tTaxon"));
+        assert_eq!(
+            lines.next(),
+            Some("GCF_000005845.2\td__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__Enterobacterales; f__Enterobacteriaceae; g__Escherichia; s__Escherichia coli")
+        );
+        assert_eq!(lines.count(), 2);
+    }
+}
@@ -0,0 +1,50 @@
+use super::taxdump::assign_taxids;
+use super::Genome;
+
+/// Accession to taxid mapping, as the seqid2taxid.map files of Kraken2
+/// and Centrifuge database builds. Genomes are mapped to the taxid of
+/// their lowest rank, consistent with the taxdump export of the same genomes.
+/// With `with_lineage`, genomes are mapped to their GTDB lineage instead.
+pub fn to_seqid2taxid(genomes: &[Genome], with_lineage: bool) -> String {
+    let taxids = assign_taxids(genomes);
+    genomes
+        .iter()
+        .filter_map(|genome| {
+            let value = if with_lineage {
+                genome
+                    .lineage
+                    .values()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(";")
+            } else {
+                taxids[genome.lineage.values().last()?].to_string()
+            };
+            Some(format!("{}\t{}\n", genome.accession, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_seqid2taxid() {
+        let genomes = genomes();
+        let taxids = assign_taxids(&genomes);
+        let mapping = to_seqid2taxid(&genomes, false);
+        assert_eq!(
+            mapping.lines().next(),
+            Some(format!("GCF_000005845.2\t{}", taxids["s__Escherichia coli"]).as_str())
+        );
+        assert_eq!(mapping.lines().count(), 3);
+
+        let mapping = to_seqid2taxid(&genomes, true);
+        assert_eq!(
+            mapping.lines().last(),
+            Some("GCF_000006945.2\td__Bacteria;p__Pseudomonadota;c__Gammaproteobacteria;o__Enterobacterales;f__Enterobacteriaceae;g__Salmonella;s__Salmonella enterica")
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use super::Genome;
+use crate::taxonomy::Rank;
+use crate::utils::{self, OutputFormat};
+
+/// Lineages CSV read by the sourmash `tax` subcommands
+pub fn to_sourmash_lineages(genomes: &[Genome]) -> String {
+    let header = [
+        "ident",
+        "superkingdom",
+        "phylum",
+        "class",
+        "order",
+        "family",
+        "genus",
+        "species",
+    ];
+    let rows: Vec<Vec<String>> = genomes
+        .iter()
+        .map(|genome| {
+            std::iter::once(genome.accession.clone())
+                .chain(
+                    Rank::ALL
+                        .iter()
+                        .map(|rank| genome.lineage.get(rank).cloned().unwrap_or_default()),
+                )
+                .collect()
+        })
+        .collect();
+    utils::render_table(&header, &rows, &OutputFormat::Csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_to_sourmash_lineages() {
+        let output = to_sourmash_lineages(&genomes());
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some("ident,superkingdom,phylum,class,order,family,genus,species")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("GCF_000005845.2,d__Bacteria,p__Pseudomonadota,c__Gammaproteobacteria,o__Enterobacterales,f__Enterobacteriaceae,g__Escherichia,s__Escherichia coli")
+        );
+    }
+}
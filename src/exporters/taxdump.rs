@@ -0,0 +1,138 @@
+use md5::{Digest, Md5};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::Genome;
+use crate::taxonomy::{strip_rank_prefix, Rank};
+
+/// Taxid of the root of the taxonomy
+const ROOT_TAXID: u32 = 1;
+
+/// Preferred taxid of a GTDB taxon, derived from the md5 hash of its name
+fn hashed_taxid(taxon: &str) -> u32 {
+    let digest = Md5::digest(taxon.as_bytes());
+    let id = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) & 0x7fff_ffff;
+    // Keep the taxid of the root for the root
+    id.max(ROOT_TAXID + 1)
+}
+
+/// Assign a taxid to every taxon of the genomes. Taxa whose hashed taxid
+/// is already taken get the next free one, in taxon name order, so taxids
+/// depend on the taxa exported together: `taxdump` and `seqid2taxid`
+/// files must come from the same export.
+pub fn assign_taxids(genomes: &[Genome]) -> HashMap<String, u32> {
+    let taxa: BTreeSet<&String> = genomes.iter().flat_map(|x| x.lineage.values()).collect();
+    let mut used = HashSet::from([ROOT_TAXID]);
+    taxa.into_iter()
+        .map(|taxon| {
+            let mut id = hashed_taxid(taxon);
+            while !used.insert(id) {
+                id = if id == 0x7fff_ffff {
+                    ROOT_TAXID + 1
+                } else {
+                    id + 1
+                };
+            }
+            (taxon.clone(), id)
+        })
+        .collect()
+}
+
+/// NCBI rank name of a GTDB rank
+fn ncbi_rank(rank: Rank) -> String {
+    match rank {
+        Rank::Domain => "superkingdom".to_string(),
+        rank => rank.to_string(),
+    }
+}
+
+/// A node of the taxdump: its taxid, parent taxid, rank and name
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    taxid: u32,
+    parent: u32,
+    rank: String,
+    name: String,
+}
+
+/// Nodes of the taxonomy of the genomes, from the root down to species,
+/// in taxid order
+fn nodes(genomes: &[Genome]) -> Vec<Node> {
+    let taxids = assign_taxids(genomes);
+    let mut nodes = BTreeMap::new();
+    nodes.insert(
+        ROOT_TAXID,
+        Node {
+            taxid: ROOT_TAXID,
+            parent: ROOT_TAXID,
+            rank: "no rank".to_string(),
+            name: "root".to_string(),
+        },
+    );
+    for genome in genomes {
+        let mut parent = ROOT_TAXID;
+        for (rank, taxon) in &genome.lineage {
+            let taxid = taxids[taxon];
+            nodes.entry(taxid).or_insert_with(|| Node {
+                taxid,
+                parent,
+                rank: ncbi_rank(*rank),
+                name: strip_rank_prefix(taxon).to_string(),
+            });
+            parent = taxid;
+        }
+    }
+    nodes.into_values().collect()
+}
+
+/// nodes.dmp and names.dmp files of the taxonomy of the genomes, in the
+/// NCBI taxdump format read by Kraken2 and Bracken
+pub fn to_taxdump(genomes: &[Genome]) -> (String, String) {
+    let nodes = nodes(genomes);
+    let nodes_dmp = nodes
+        .iter()
+        .map(|x| format!("{}\t|\t{}\t|\t{}\t|\n", x.taxid, x.parent, x.rank))
+        .collect();
+    let names_dmp = nodes
+        .iter()
+        .map(|x| format!("{}\t|\t{}\t|\t\t|\tscientific name\t|\n", x.taxid, x.name))
+        .collect();
+    (nodes_dmp, names_dmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters::tests::genomes;
+
+    #[test]
+    fn test_assign_taxids() {
+        let taxids = assign_taxids(&genomes());
+        assert_eq!(taxids.len(), 9);
+        assert_eq!(taxids["g__Escherichia"], hashed_taxid("g__Escherichia"));
+        assert_ne!(taxids["g__Escherichia"], taxids["g__Salmonella"]);
+        assert!(taxids.values().all(|x| *x > ROOT_TAXID));
+    }
+
+    #[test]
+    fn test_to_taxdump() {
+        let (nodes_dmp, names_dmp) = to_taxdump(&genomes());
+        let taxid = |x: &str| hashed_taxid(x);
+        // Root, 5 shared ranks, 2 genera and 2 species
+        assert_eq!(nodes_dmp.lines().count(), 10);
+        assert_eq!(names_dmp.lines().count(), 10);
+        assert!(nodes_dmp.starts_with("1\t|\t1\t|\tno rank\t|\n"));
+        assert!(nodes_dmp.contains(&format!(
+            "{}\t|\t{}\t|\tspecies\t|\n",
+            taxid("s__Escherichia coli"),
+            taxid("g__Escherichia")
+        )));
+        assert!(nodes_dmp.contains(&format!(
+            "{}\t|\t1\t|\tsuperkingdom\t|\n",
+            taxid("d__Bacteria")
+        )));
+        assert!(names_dmp.contains(&format!(
+            "{}\t|\tEscherichia coli\t|\t\t|\tscientific name\t|\n",
+            taxid("s__Escherichia coli")
+        )));
+    }
+}
@@ -0,0 +1,65 @@
+//! Graceful handling of Ctrl-C
+//!
+//! The first Ctrl-C only sets a flag: long runs check it between items, so
+//! that requests in progress complete, output already fetched is written
+//! and caches and results files are saved before exiting. A second Ctrl-C
+//! exits at once.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::XgtError;
+
+/// Set when the user pressed Ctrl-C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the Ctrl-C handler of the command line
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(XgtError::Interrupted.exit_code());
+        }
+        eprintln!(
+            "Interrupted, finishing the requests in progress (press Ctrl-C again to exit now)"
+        );
+    })
+}
+
+/// Check if the user pressed Ctrl-C
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Forget a Ctrl-C once the run it stopped is over, e.g. after each
+/// query of `xgt shell`
+pub fn reset() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// Interval at which waits check whether the user pressed Ctrl-C
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleep for `duration`, failing with `XgtError::Interrupted` as soon as
+/// the user presses Ctrl-C
+pub fn sleep(duration: Duration) -> Result<(), XgtError> {
+    let end = Instant::now() + duration;
+    loop {
+        check()?;
+        let left = end.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(());
+        }
+        thread::sleep(left.min(POLL_INTERVAL));
+    }
+}
+
+/// Fail with `XgtError::Interrupted` if the user pressed Ctrl-C, to be
+/// called before starting the next item of a long run
+pub fn check() -> Result<(), XgtError> {
+    match is_interrupted() {
+        true => Err(XgtError::Interrupted),
+        false => Ok(()),
+    }
+}
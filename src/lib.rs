@@ -0,0 +1,37 @@
+//! xgt enables efficient querying and parsing of GTDB data.
+//!
+//! Besides the `xgt` command line tool, the crate can be used as a library
+//! through [`GtdbClient`], which sends requests to the GTDB API and returns
+//! their responses as the typed structs of [`types`]. With the `async` feature, enabled by
+//! default, `AsyncGtdbClient` provides the same queries as async functions.
+
+mod aggregate;
+pub mod api;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod cli;
+pub mod client;
+pub mod cmd;
+mod drift;
+pub mod error;
+#[cfg(feature = "exports")]
+mod exporters;
+pub mod interrupt;
+mod local;
+pub mod metrics;
+#[cfg(feature = "python")]
+mod python;
+pub mod query;
+mod report;
+pub mod taxonomy;
+#[cfg(not(target_arch = "wasm32"))]
+mod tls;
+pub mod transport;
+pub mod types;
+pub mod utils;
+mod values;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncGtdbClient;
+pub use client::GtdbClient;
+pub use error::XgtError;
@@ -0,0 +1,228 @@
+use anyhow::{bail, ensure, Context, Result};
+use flate2::read::MultiGzDecoder;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::api::ncbi::strip_gtdb_prefix;
+use crate::utils;
+
+/// Directory of the cached GTDB metadata files
+pub fn metadata_dir() -> Result<PathBuf> {
+    Ok(utils::cache_dir()?.join("metadata"))
+}
+
+/// Release number of a GTDB metadata file name, e.g. 220 for
+/// "bac120_metadata_r220.tsv.gz"
+pub(crate) fn metadata_release(name: &str) -> Option<u32> {
+    let (_, release) = name.split_once("_metadata_r")?;
+    release.strip_suffix(".tsv.gz")?.parse().ok()
+}
+
+/// Metadata files of `release` in `dir`, or of the latest release
+/// cached when no release is given
+pub fn metadata_files(dir: &Path, release: Option<u32>) -> Result<Vec<PathBuf>> {
+    let files: Vec<(u32, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    let release = metadata_release(path.file_name()?.to_str()?)?;
+                    Some((release, path))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let Some(release) = release.or_else(|| files.iter().map(|(r, _)| *r).max()) else {
+        bail!(
+            "No GTDB metadata found in {}, please run `xgt fetch-metadata` first",
+            dir.display()
+        );
+    };
+    let mut files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|(r, _)| *r == release)
+        .map(|(_, path)| path)
+        .collect();
+    ensure!(
+        !files.is_empty(),
+        "No GTDB metadata of R{} found in {}, please run `xgt fetch-metadata --release R{}` first",
+        release,
+        dir.display(),
+        release
+    );
+    files.sort();
+
+    Ok(files)
+}
+
+/// Open a GTDB release file, decompressing it if it is gzipped
+pub fn open_release_file(path: &Path) -> Result<BufReader<Box<dyn Read>>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open file {}", path.display()))?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|x| x == "gz") {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(BufReader::new(reader))
+}
+
+/// Column-oriented subset of the GTDB metadata, holding only the
+/// columns needed by a query
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataTable {
+    names: Vec<String>,
+    columns: Vec<Vec<String>>,
+}
+
+impl MetadataTable {
+    /// Read the columns `names` of a metadata TSV
+    pub fn read<R: BufRead>(reader: R, names: &[&str]) -> Result<Self> {
+        let mut lines = reader.lines();
+        let header = lines.next().context("Metadata file is empty")??;
+        let header: Vec<&str> = header.split('\t').collect();
+        let indices = names
+            .iter()
+            .map(|name| {
+                header
+                    .iter()
+                    .position(|x| x == name)
+                    .with_context(|| format!("{} column not found in metadata", name))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+
+        let mut columns = vec![Vec::new(); names.len()];
+        for line in lines {
+            let line = line?;
+            let fields: Vec<&str> = line.split('\t').collect();
+            for (column, index) in columns.iter_mut().zip(&indices) {
+                column.push(fields.get(*index).unwrap_or(&"").to_string());
+            }
+        }
+
+        Ok(MetadataTable {
+            names: names.iter().map(|x| x.to_string()).collect(),
+            columns,
+        })
+    }
+
+    /// Read the columns `names` of several metadata files into one table
+    pub fn read_files(paths: &[PathBuf], names: &[&str]) -> Result<Self> {
+        let mut table = MetadataTable {
+            names: names.iter().map(|x| x.to_string()).collect(),
+            columns: vec![Vec::new(); names.len()],
+        };
+        for path in paths {
+            let other = MetadataTable::read(open_release_file(path)?, names)?;
+            for (column, other) in table.columns.iter_mut().zip(other.columns) {
+                column.extend(other);
+            }
+        }
+        Ok(table)
+    }
+
+    /// Number of genomes in the table
+    pub fn len(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    /// Values of the column `name`
+    pub fn column(&self, name: &str) -> &[String] {
+        let index = self
+            .names
+            .iter()
+            .position(|x| x == name)
+            .unwrap_or_else(|| panic!("{} column was not read", name));
+        &self.columns[index]
+    }
+}
+
+/// Metadata record of a genome, as column name and value pairs
+pub type MetadataRecord = Vec<(String, String)>;
+
+/// Find the metadata records of `accessions` in a metadata TSV.
+/// Accessions are matched without their GTDB database prefix.
+pub fn find_records<R: BufRead>(
+    reader: R,
+    accessions: &HashSet<String>,
+) -> Result<HashMap<String, MetadataRecord>> {
+    let mut lines = reader.lines();
+    let header = lines.next().context("Metadata file is empty")??;
+    let header: Vec<String> = header.split('\t').map(String::from).collect();
+
+    let mut records = HashMap::new();
+    for line in lines {
+        let line = line?;
+        let accession = strip_gtdb_prefix(line.split('\t').next().unwrap_or(""));
+        if accessions.contains(accession) {
+            let record = header
+                .iter()
+                .cloned()
+                .zip(line.split('\t').map(String::from))
+                .collect();
+            records.insert(accession.to_string(), record);
+            if records.len() == accessions.len() {
+                break;
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Find the metadata records of `accessions` in several metadata files
+pub fn find_records_in_files(
+    paths: &[PathBuf],
+    accessions: &HashSet<String>,
+) -> Result<HashMap<String, MetadataRecord>> {
+    let mut records = HashMap::new();
+    for path in paths {
+        records.extend(find_records(open_release_file(path)?, accessions)?);
+        if records.len() == accessions.len() {
+            break;
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = "accession\tcheckm_completeness\tgtdb_taxonomy\n\
+        RS_GCF_000010525.1\t99.67\td__Bacteria; p__Pseudomonadota; g__Azorhizobium\n\
+        GB_GCA_002279595.1\t95.1\td__Bacteria; p__Pseudomonadota; g__Azorhizobium\n";
+
+    #[test]
+    fn test_metadata_release() {
+        assert_eq!(metadata_release("bac120_metadata_r220.tsv.gz"), Some(220));
+        assert_eq!(metadata_release("ar53_metadata_r95.tsv.gz"), Some(95));
+        assert_eq!(metadata_release("bac120_r220.tree.gz"), None);
+    }
+
+    #[test]
+    fn test_metadata_table_read() {
+        let table =
+            MetadataTable::read(METADATA.as_bytes(), &["accession", "gtdb_taxonomy"]).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            table.column("accession"),
+            &["RS_GCF_000010525.1", "GB_GCA_002279595.1"]
+        );
+        assert!(MetadataTable::read(METADATA.as_bytes(), &["ncbi_taxonomy"]).is_err());
+    }
+
+    #[test]
+    fn test_find_records() {
+        let accessions = HashSet::from(["GCA_002279595.1".to_string()]);
+        let records = find_records(METADATA.as_bytes(), &accessions).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records["GCA_002279595.1"][1],
+            ("checkm_completeness".to_string(), "95.1".to_string())
+        );
+    }
+}
@@ -1,16 +1,136 @@
-mod api;
-mod cli;
-mod cmd;
-mod utils;
-
 use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::process;
+use std::time::{Duration, Instant};
+
+use anyhow::{ensure, Context, Result};
+use xgt::api::genome::GenomeRequestType;
+#[cfg(feature = "tui")]
+use xgt::cmd::browse;
+#[cfg(feature = "exports")]
+use xgt::cmd::export;
+use xgt::cmd::{
+    ani, batch, convert, diff, discordance, exists, genome, grep, lineage, open, raw, releases,
+    schema, search, serve, shell, taxid, taxon, translate, tree, watch,
+};
+#[cfg(feature = "downloads")]
+use xgt::cmd::{download, fetch};
+use xgt::error::{error_json, exit_code};
+use xgt::metrics::RequestStats;
+use xgt::{cli, interrupt, utils};
 
-use anyhow::Result;
-use cmd::{genome, search, taxon};
+/// Subcommands which check for Ctrl-C to stop cleanly, the others are
+/// stopped at once by it
+const INTERRUPTIBLE: [&str; 8] = [
+    "search", "genome", "taxon", "lineage", "batch", "shell", "serve", "watch",
+];
 
-fn main() -> Result<()> {
+fn main() {
     let matches = cli::app::build_app().get_matches_from(env::args_os());
+    if matches
+        .subcommand_name()
+        .is_some_and(|x| INTERRUPTIBLE.contains(&x))
+    {
+        if let Err(e) = interrupt::install() {
+            eprintln!("Failed to install the Ctrl-C handler: {}", e);
+        }
+    }
+    let start = Instant::now();
+    let result = run(&matches);
+    if let Some(path) = matches.get_one::<String>("request-stats") {
+        if let Err(e) = report_request_stats(path, start.elapsed()) {
+            eprintln!("Failed to report request statistics: {:?}", e);
+        }
+    }
+    if let Err(e) = result {
+        match matches.get_one::<String>("errors").map(String::as_str) {
+            Some("json") => eprintln!("{}", error_json(&e)),
+            _ => eprintln!("Error: {:?}", e),
+        }
+        process::exit(exit_code(&e));
+    }
+}
+
+/// Print the statistics of the requests of the run on stderr, or write them
+/// as JSON to `path` unless it is "-"
+fn report_request_stats(path: &str, wallclock: Duration) -> Result<()> {
+    let stats = RequestStats::collect(wallclock);
+    match path {
+        "-" => eprint!("{}", stats.render()),
+        path => fs::write(path, format!("{}\n", serde_json::to_string(&stats)?))
+            .with_context(|| format!("Failed to write {}", path))?,
+    }
+    Ok(())
+}
+
+/// Run the subcommand of parsed command line arguments
+fn run(matches: &clap::ArgMatches) -> Result<()> {
+    configure(matches);
+    run_subcommand(matches)
+}
+
+/// Apply the global options, which are process-wide
+fn configure(matches: &clap::ArgMatches) {
+    utils::set_verbosity(1 + matches.get_count("verbose"));
+    utils::set_json_errors(
+        matches
+            .get_one::<String>("errors")
+            .is_some_and(|x| x == "json"),
+    );
+    utils::set_na_string(
+        matches
+            .get_one::<String>("na-string")
+            .map_or("", String::as_str),
+    );
+    utils::set_sort(matches.get_one::<(String, bool)>("sort").cloned());
+    utils::set_compact_json(matches.get_flag("compact") || !io::stdout().is_terminal());
+    let provenance = matches.get_flag("provenance").then(|| {
+        let args: Vec<String> = env::args().collect();
+        // Record the release served by GTDB rather than "latest"
+        let release = matches.get_one::<String>("release").cloned().or_else(|| {
+            let insecure = matches
+                .subcommand()
+                .is_some_and(|(_, x)| matches!(x.try_get_one::<bool>("insecure"), Ok(Some(true))));
+            let agent = utils::get_agent(insecure).ok()?;
+            releases::latest_release(&agent)
+        });
+        utils::Provenance::new(&args, release.as_deref())
+    });
+    utils::set_provenance(provenance);
+}
+
+/// Run the subcommand of parsed command line arguments with the global
+/// options already applied, e.g. for the jobs of a batch which share them
+fn run_subcommand(matches: &clap::ArgMatches) -> Result<()> {
     let subcommand = matches.subcommand();
+    if let Some((name, sub_matches)) = subcommand {
+        ensure_release_supported(name, sub_matches)?;
+        if matches!(name, "search" | "genome" | "taxon") && sub_matches.get_flag("url") {
+            for url in request_urls(name, sub_matches)? {
+                println!("{}", url);
+            }
+            return Ok(());
+        }
+        if matches!(name, "search" | "genome" | "taxon" | "download")
+            && sub_matches.get_flag("dry-run")
+        {
+            let urls = request_urls(name, sub_matches)?;
+            print!(
+                "{}",
+                format_dry_run(&urls, &target_files(name, sub_matches))
+            );
+            return Ok(());
+        }
+        if matches!(name, "search" | "genome" | "taxon" | "download" | "api")
+            && sub_matches.get_flag("as-curl")
+        {
+            for command in curl_commands(name, sub_matches)? {
+                println!("{}", command);
+            }
+            return Ok(());
+        }
+    }
 
     match subcommand {
         Some(("search", sub_matches)) => {
@@ -19,18 +139,237 @@ fn main() -> Result<()> {
         }
         Some(("genome", sub_matches)) => handle_genome_command(sub_matches)?,
         Some(("taxon", sub_matches)) => handle_taxon_command(sub_matches)?,
+        #[cfg(feature = "downloads")]
+        Some(("download", sub_matches)) => {
+            let args = cli::download::DownloadArgs::from_arg_matches(sub_matches);
+            download::download(args)?;
+        }
+        Some(("tree", sub_matches)) => {
+            let args = cli::tree::TreeArgs::from_arg_matches(sub_matches);
+            tree::tree(args)?;
+        }
+        Some(("diff", sub_matches)) => {
+            let args = cli::diff::DiffArgs::from_arg_matches(sub_matches);
+            diff::diff(args)?;
+        }
+        Some(("discordance", sub_matches)) => {
+            let args = cli::discordance::DiscordanceArgs::from_arg_matches(sub_matches);
+            discordance::discordance(args)?;
+        }
+        Some(("releases", sub_matches)) => {
+            let args = cli::releases::ReleasesArgs::from_arg_matches(sub_matches);
+            releases::releases(args)?;
+        }
+        Some(("schema", sub_matches)) => {
+            let args = cli::schema::SchemaArgs::from_arg_matches(sub_matches);
+            schema::schema(args)?;
+        }
+        #[cfg(feature = "downloads")]
+        Some(("fetch-metadata", sub_matches)) => {
+            let args = cli::fetch::FetchMetadataArgs::from_arg_matches(sub_matches);
+            fetch::fetch_metadata(args)?;
+        }
+        #[cfg(feature = "downloads")]
+        Some(("fetch-tree", sub_matches)) => {
+            let args = cli::fetch::FetchTreeArgs::from_arg_matches(sub_matches);
+            fetch::fetch_tree(args)?;
+        }
+        Some(("grep", sub_matches)) => {
+            let args = cli::grep::GrepArgs::from_arg_matches(sub_matches);
+            grep::grep(args)?;
+        }
+        Some(("ani", sub_matches)) => {
+            let args = cli::ani::AniArgs::from_arg_matches(sub_matches);
+            ani::ani(args)?;
+        }
+        #[cfg(feature = "exports")]
+        Some(("export", sub_matches)) => {
+            let args = cli::export::ExportArgs::from_arg_matches(sub_matches);
+            export::export(args)?;
+        }
+        Some(("watch", sub_matches)) => {
+            let args = cli::watch::WatchArgs::from_arg_matches(sub_matches);
+            if watch::watch(args)? {
+                // Distinguish a new release from errors, which exit with 1
+                process::exit(2);
+            }
+        }
+        Some(("convert", sub_matches)) => {
+            let args = cli::convert::ConvertArgs::from_arg_matches(sub_matches);
+            convert::convert(args)?;
+        }
+        Some(("translate", sub_matches)) => {
+            let args = cli::translate::TranslateArgs::from_arg_matches(sub_matches);
+            translate::translate(args)?;
+        }
+        Some(("taxid", sub_matches)) => {
+            let args = cli::taxid::TaxidArgs::from_arg_matches(sub_matches);
+            taxid::taxid(args)?;
+        }
+        Some(("lineage", sub_matches)) => {
+            let args = cli::lineage::LineageArgs::from_arg_matches(sub_matches);
+            lineage::lineage(args)?;
+        }
+        Some(("api", sub_matches)) => {
+            let args = cli::raw::RawArgs::from_arg_matches(sub_matches);
+            raw::raw(args)?;
+        }
+        Some(("open", sub_matches)) => {
+            let args = cli::open::OpenArgs::from_arg_matches(sub_matches);
+            open::open(args)?;
+        }
+        #[cfg(feature = "tui")]
+        Some(("browse", sub_matches)) => {
+            let args = cli::browse::BrowseArgs::from_arg_matches(sub_matches);
+            browse::browse(args)?;
+        }
+        Some(("shell", sub_matches)) => {
+            let args = cli::shell::ShellArgs::from_arg_matches(sub_matches);
+            shell::shell(args, run)?;
+        }
+        Some(("serve", sub_matches)) => {
+            let args = cli::serve::ServeArgs::from_arg_matches(sub_matches);
+            serve::serve(args)?;
+        }
+        Some(("batch", sub_matches)) => {
+            let args = cli::batch::BatchArgs::from_arg_matches(sub_matches);
+            batch::batch(args, run_subcommand)?;
+        }
+        Some(("exists", sub_matches)) => {
+            let args = cli::exists::ExistsArgs::from_arg_matches(sub_matches);
+            if !exists::exists(args)? {
+                // Distinguish missing genomes from errors, which exit with 1
+                process::exit(2);
+            }
+        }
         _ => unreachable!("Implemented correctly"),
     };
 
     Ok(())
 }
 
+/// Only histories, release files and local queries can be pinned to a
+/// release, every other endpoint of the GTDB API serves the current release
+fn ensure_release_supported(subcommand: &str, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let Some(release) = sub_matches.get_one::<String>("release") else {
+        return Ok(());
+    };
+    let supported = match subcommand {
+        "genome" => sub_matches.get_flag("history") || sub_matches.get_flag("local"),
+        "taxon" => sub_matches.get_flag("history"),
+        "search" => sub_matches.get_flag("local"),
+        "fetch-metadata" | "fetch-tree" => true,
+        _ => false,
+    };
+    ensure!(
+        supported,
+        "--release {} is only supported by genome --history, taxon --history, \
+         fetch-metadata, fetch-tree and --local queries: the GTDB API only serves the current release for this query",
+        release
+    );
+    Ok(())
+}
+
+/// URLs of the first requests a subcommand would send
+fn request_urls(subcommand: &str, sub_matches: &clap::ArgMatches) -> Result<Vec<String>> {
+    let urls = match subcommand {
+        "search" => search::request_urls(&cli::search::SearchArgs::from_arg_matches(sub_matches))?,
+        "genome" => {
+            let request_type = if sub_matches.get_flag("history") {
+                GenomeRequestType::TaxonHistory
+            } else if sub_matches.get_flag("metadata") {
+                GenomeRequestType::Metadata
+            } else {
+                GenomeRequestType::Card
+            };
+            let args = cli::genome::GenomeArgs::from_arg_matches(sub_matches);
+            genome::request_urls(&args, request_type)
+        }
+        "taxon" => taxon::request_urls(&cli::taxon::TaxonArgs::from_arg_matches(sub_matches)),
+        #[cfg(feature = "downloads")]
+        "download" => {
+            download::request_urls(&cli::download::DownloadArgs::from_arg_matches(sub_matches))?
+        }
+        _ => vec![],
+    };
+    Ok(urls)
+}
+
+/// Equivalent curl commands of the first requests a subcommand would send
+fn curl_commands(subcommand: &str, sub_matches: &clap::ArgMatches) -> Result<Vec<String>> {
+    let insecure = sub_matches.get_flag("insecure");
+    if subcommand == "api" {
+        let args = cli::raw::RawArgs::from_arg_matches(sub_matches);
+        return Ok(vec![utils::curl_command(
+            &args.get_method(),
+            &raw::request_url(&args),
+            args.get_data().as_deref(),
+            insecure,
+        )]);
+    }
+    Ok(request_urls(subcommand, sub_matches)?
+        .iter()
+        .map(|url| utils::curl_command("GET", url, None, insecure))
+        .collect())
+}
+
+/// Files a subcommand would write, none meaning standard output
+fn target_files(subcommand: &str, sub_matches: &clap::ArgMatches) -> Vec<String> {
+    match subcommand {
+        #[cfg(feature = "downloads")]
+        "download" => {
+            download::target_files(&cli::download::DownloadArgs::from_arg_matches(sub_matches))
+        }
+        "genome" => genome::target_files(&cli::genome::GenomeArgs::from_arg_matches(sub_matches)),
+        _ => sub_matches
+            .get_one::<String>("out")
+            .cloned()
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Summary of what a subcommand would do, printed by --dry-run
+fn format_dry_run(urls: &[String], files: &[String]) -> String {
+    let mut output = format!("Would send {} request(s)\n", urls.len());
+    for url in urls {
+        output.push_str(&format!("  GET {}\n", url));
+    }
+    if files.is_empty() {
+        output.push_str("Would write to standard output\n");
+    } else {
+        output.push_str(&format!("Would write {} file(s)\n", files.len()));
+        for file in files {
+            output.push_str(&format!("  {}\n", file));
+        }
+    }
+    output
+}
+
 fn handle_genome_command(sub_matches: &clap::ArgMatches) -> Result<()> {
     let args = cli::genome::GenomeArgs::from_arg_matches(sub_matches);
+    if args.is_local() {
+        return genome::get_genome_local(args);
+    }
+    let args = genome::filter_surveillance(args)?;
     if sub_matches.get_flag("history") {
         genome::get_genome_taxon_history(args)?;
     } else if sub_matches.get_flag("metadata") {
         genome::get_genome_metadata(args)?;
+    } else if sub_matches.get_flag("links") {
+        genome::get_genome_links(args)?;
+    } else if args.get_full().is_some() {
+        genome::get_genome_full(args)?;
+    } else if args.is_nomenclature() {
+        genome::get_genome_nomenclature(args)?;
+    } else if args.is_diff_cache() {
+        genome::diff_cached_cards(args)?;
+    } else if args.get_compare().is_some() {
+        genome::compare_genomes(args)?;
+    } else if args.is_taxonomy_only() {
+        genome::get_genome_taxonomy(args)?;
+    } else if args.is_discrepancies() {
+        genome::get_genome_discrepancies(args)?;
     } else {
         genome::get_genome_card(args)?
     }
@@ -43,6 +382,24 @@ fn handle_taxon_command(sub_matches: &clap::ArgMatches) -> Result<()> {
         taxon::search_taxon(args)?;
     } else if args.is_genome() {
         taxon::get_taxon_genomes(args)?;
+    } else if args.is_children() {
+        taxon::get_taxon_children(args)?;
+    } else if args.is_lineage() {
+        taxon::get_taxon_lineage(args)?;
+    } else if args.is_history() {
+        taxon::get_taxon_history(args)?;
+    } else if args.is_stats() {
+        taxon::get_taxon_stats(args)?;
+    } else if args.is_type_summary() {
+        taxon::get_taxon_type_summary(args)?;
+    } else if args.get_sankey().is_some() {
+        taxon::get_taxon_sankey(args)?;
+    } else if args.is_clusters() {
+        taxon::get_taxon_clusters(args)?;
+    } else if args.is_novel() {
+        taxon::get_taxon_novel(args)?;
+    } else if args.is_nomenclature() {
+        taxon::get_taxon_nomenclature(args)?;
     } else {
         taxon::get_taxon_name(args)?;
     }
@@ -92,6 +449,32 @@ mod tests {
         assert!(args.is_whole_words_matching());
     }
 
+    #[test]
+    fn test_ensure_release_supported() {
+        let matches = cli::app::build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_001512625.1",
+            "--history",
+            "--release",
+            "R207",
+        ]);
+        let sub_matches = matches.subcommand_matches("genome").unwrap();
+        assert!(ensure_release_supported("genome", sub_matches).is_ok());
+
+        let matches = cli::app::build_app().get_matches_from(vec![
+            "xgt",
+            "taxon",
+            "g__Aminobacter",
+            "--genomes",
+            "--release",
+            "R207",
+        ]);
+        let sub_matches = matches.subcommand_matches("taxon").unwrap();
+        let err = ensure_release_supported("taxon", sub_matches).unwrap_err();
+        assert!(err.to_string().contains("only serves the current release"));
+    }
+
     #[test]
     fn test_genome_command() {
         let args = vec![
@@ -105,7 +488,35 @@ mod tests {
         let matches = cli::app::build_app().get_matches_from(args);
         let sub_matches = matches.subcommand_matches("genome").unwrap();
         let args = cli::genome::GenomeArgs::from_arg_matches(sub_matches);
-        assert_eq!(args.accession, vec!["NC_000912.1".to_string()]);
-        assert_eq!(args.output, Some(String::from("met.json")));
+        assert_eq!(args.get_accession(), vec!["NC_000912.1".to_string()]);
+        assert_eq!(args.get_output(), Some(String::from("met.json")));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let matches = cli::app::build_app().get_matches_from(vec![
+            "xgt",
+            "genome",
+            "GCA_001512625.1",
+            "--metadata",
+            "--dry-run",
+            "-o",
+            "met.json",
+        ]);
+        let sub_matches = matches.subcommand_matches("genome").unwrap();
+        let urls = request_urls("genome", sub_matches).unwrap();
+
+        assert_eq!(
+            format_dry_run(&urls, &target_files("genome", sub_matches)),
+            "Would send 1 request(s)
+  GET https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/metadata
+Would write 1 file(s)
+  met.json
+"
+        );
+        assert_eq!(
+            format_dry_run(&[], &[]),
+            "Would send 0 request(s)\nWould write to standard output\n"
+        );
     }
 }
@@ -0,0 +1,198 @@
+//! Statistics of the requests sent during a run, reported by
+//! `--request-stats` to compare the performance of settings such as
+//! `--jobs`, and the log of the requests printed at `-v`
+
+use serde::Serialize;
+use std::cell::Cell;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::utils;
+
+/// Requests measured since the start of the run
+#[derive(Debug, Default)]
+struct Metrics {
+    latencies: Vec<Duration>,
+    bytes: u64,
+    cache_hits: u64,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    latencies: Vec::new(),
+    bytes: 0,
+    cache_hits: 0,
+});
+
+fn with_metrics(f: impl FnOnce(&mut Metrics)) {
+    f(&mut METRICS.lock().unwrap_or_else(PoisonError::into_inner));
+}
+
+thread_local! {
+    /// Number of times the request being sent by this thread was already
+    /// sent, e.g. after a truncated response
+    static RETRIES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Set the number of times the requests next sent by this thread were
+/// already sent
+pub(crate) fn set_retries(retries: usize) {
+    RETRIES.with(|x| x.set(retries));
+}
+
+/// Start time of a request. Browsers give no clock to wasm, so requests of
+/// the browser are not timed.
+pub(crate) fn now() -> Option<Instant> {
+    match cfg!(target_arch = "wasm32") {
+        true => None,
+        false => Some(Instant::now()),
+    }
+}
+
+/// Record a request of `url` sent at `start`, until its response headers
+/// were received with `status` (none when it failed before), and log it at
+/// `-v` so that stuck requests of long runs can be spotted
+pub(crate) fn record_request(url: &str, status: Option<u16>, start: Option<Instant>) {
+    let latency = start.map(|x| x.elapsed());
+    with_metrics(|metrics| metrics.latencies.push(latency.unwrap_or_default()));
+    if utils::verbosity() > 1 {
+        eprintln!(
+            "{}",
+            format_request(url, status, latency, RETRIES.with(Cell::get))
+        );
+    }
+}
+
+/// Log line of a request, e.g. "GET https://... 200 153 ms (retry 1)"
+fn format_request(
+    url: &str,
+    status: Option<u16>,
+    latency: Option<Duration>,
+    retries: usize,
+) -> String {
+    let mut line = match status {
+        Some(status) => format!("GET {} {}", url, status),
+        None => format!("GET {} failed", url),
+    };
+    if let Some(latency) = latency {
+        line.push_str(&format!(" {} ms", latency.as_millis()));
+    }
+    if retries > 0 {
+        line.push_str(&format!(" (retry {})", retries));
+    }
+    line
+}
+
+/// Record bytes received in response bodies
+pub(crate) fn record_bytes(bytes: u64) {
+    with_metrics(|metrics| metrics.bytes += bytes);
+}
+
+/// Record queries answered from a cache rather than by requests
+pub(crate) fn record_cache_hits(hits: u64) {
+    with_metrics(|metrics| metrics.cache_hits += hits);
+}
+
+/// Measure the requests of the blocking client, bytes being read from the
+/// Content-Length of responses as their bodies are read by the caller
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::result_large_err)] // signature of ureq middlewares
+pub(crate) fn measure(
+    request: ureq::Request,
+    next: ureq::MiddlewareNext,
+) -> Result<ureq::Response, ureq::Error> {
+    let url = request.url().to_string();
+    let start = now();
+    let response = next.handle(request);
+    let status = match &response {
+        Ok(response) => Some(response.status()),
+        Err(ureq::Error::Status(code, _)) => Some(*code),
+        Err(_) => None,
+    };
+    record_request(&url, status, start);
+    let length = match &response {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response.header("Content-Length"),
+        Err(_) => None,
+    };
+    record_bytes(length.and_then(|x| x.parse().ok()).unwrap_or(0));
+    response
+}
+
+/// Statistics of the requests of a run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestStats {
+    requests: usize,
+    cache_hits: u64,
+    bytes: u64,
+    latency_p50_ms: u64,
+    latency_p95_ms: u64,
+    wallclock_ms: u64,
+}
+
+/// Latency under which `percent` of the requests were answered
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    match sorted.len() {
+        0 => Duration::ZERO,
+        n => sorted[((n * percent + 99) / 100).max(1) - 1],
+    }
+}
+
+impl RequestStats {
+    /// Statistics of the requests of a run which took `wallclock`
+    pub fn collect(wallclock: Duration) -> Self {
+        let metrics = METRICS.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut latencies = metrics.latencies.clone();
+        latencies.sort();
+        RequestStats {
+            requests: latencies.len(),
+            cache_hits: metrics.cache_hits,
+            bytes: metrics.bytes,
+            latency_p50_ms: percentile(&latencies, 50).as_millis() as u64,
+            latency_p95_ms: percentile(&latencies, 95).as_millis() as u64,
+            wallclock_ms: wallclock.as_millis() as u64,
+        }
+    }
+
+    /// Render the statistics as lines of metric and value
+    pub fn render(&self) -> String {
+        format!(
+            "requests\t{}\ncache_hits\t{}\nbytes\t{}\nlatency_p50\t{} ms\nlatency_p95\t{} ms\nwallclock\t{} ms\n",
+            self.requests,
+            self.cache_hits,
+            self.bytes,
+            self.latency_p50_ms,
+            self.latency_p95_ms,
+            self.wallclock_ms
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 50), Duration::from_millis(10));
+        assert_eq!(percentile(&latencies, 95), Duration::from_millis(19));
+        assert_eq!(percentile(&latencies[..1], 50), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 95), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_format_request() {
+        let url = "https://api.gtdb.ecogenomic.org/genome/GCA_1/card";
+        assert_eq!(
+            format_request(url, Some(200), Some(Duration::from_millis(153)), 0),
+            format!("GET {} 200 153 ms", url)
+        );
+        assert_eq!(
+            format_request(url, None, Some(Duration::from_millis(30000)), 2),
+            format!("GET {} failed 30000 ms (retry 2)", url)
+        );
+        assert_eq!(
+            format_request(url, Some(404), None, 0),
+            format!("GET {} 404", url)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Python bindings of the GTDB API client
+//!
+//! Responses are returned as the dicts and lists of their JSON form, so
+//! that rows of search results can be passed directly to
+//! `pandas.DataFrame`.
+
+use pyo3::exceptions::{
+    PyConnectionError, PyKeyboardInterrupt, PyLookupError, PyRuntimeError, PyValueError,
+};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::XgtError;
+use crate::query::SearchQuery;
+use crate::utils::SearchField;
+use crate::GtdbClient;
+
+/// Python exception of an error of the client
+fn to_py_err(error: XgtError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        XgtError::NotFound(_) => PyLookupError::new_err(message),
+        XgtError::RateLimited | XgtError::Network(_) | XgtError::Tls(_) => {
+            PyConnectionError::new_err(message)
+        }
+        XgtError::Decode(_) | XgtError::InvalidInput(_) => PyValueError::new_err(message),
+        XgtError::TooLarge(_) => PyRuntimeError::new_err(message),
+        XgtError::Interrupted => PyKeyboardInterrupt::new_err(message),
+    }
+}
+
+/// Python object of a JSON value
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(f)) => f.into_py(py),
+            (None, None) => n.to_string().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(values) => {
+            let list = PyList::empty_bound(py);
+            for value in values {
+                list.append(to_py(py, value)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in map {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+/// Run a query of the client without holding the GIL, and convert its
+/// response to Python objects
+fn query<T, F>(py: Python<'_>, insecure: bool, f: F) -> PyResult<PyObject>
+where
+    T: Serialize,
+    F: FnOnce(&GtdbClient) -> Result<T, XgtError> + Send,
+    T: Send,
+{
+    let response = py.allow_threads(|| {
+        let client = match insecure {
+            true => GtdbClient::insecure()?,
+            false => GtdbClient::new()?,
+        };
+        f(&client)
+    });
+    let value = serde_json::to_value(response.map_err(to_py_err)?)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_py(py, &value)
+}
+
+/// Parse the name of a search field, as given to `xgt search --field`
+fn search_field(field: &str) -> PyResult<SearchField> {
+    match field {
+        "all" | "acc" | "org" | "gtdb" | "ncbi" => Ok(SearchField::from(field.to_string())),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid search field {}, expected all, acc, org, gtdb or ncbi",
+            field
+        ))),
+    }
+}
+
+/// Genomes matching `needle`, as a list of dicts
+#[pyfunction]
+#[pyo3(signature = (needle, field = "all", *, whole_words = false, reps_only = false, type_material_only = false, insecure = false))]
+fn search(
+    py: Python<'_>,
+    needle: &str,
+    field: &str,
+    whole_words: bool,
+    reps_only: bool,
+    type_material_only: bool,
+    insecure: bool,
+) -> PyResult<PyObject> {
+    let mut search_query = SearchQuery::new(needle).field(search_field(field)?);
+    if whole_words {
+        search_query = search_query.whole_words();
+    }
+    if reps_only {
+        search_query = search_query.reps_only();
+    }
+    if type_material_only {
+        search_query = search_query.type_material_only();
+    }
+    query(py, insecure, |client| client.search(&search_query))
+}
+
+/// Card of a genome, as a dict
+#[pyfunction]
+#[pyo3(signature = (accession, *, insecure = false))]
+fn genome_card(py: Python<'_>, accession: &str, insecure: bool) -> PyResult<PyObject> {
+    query(py, insecure, |client| client.genome_card(accession))
+}
+
+/// GTDB taxonomy of a genome in each release, as a list of dicts
+#[pyfunction]
+#[pyo3(signature = (accession, *, insecure = false))]
+fn genome_history(py: Python<'_>, accession: &str, insecure: bool) -> PyResult<PyObject> {
+    query(py, insecure, |client| client.genome_history(accession))
+}
+
+/// Direct children of a taxon, as a list of dicts
+#[pyfunction]
+#[pyo3(signature = (taxon, *, insecure = false))]
+fn taxon_children(py: Python<'_>, taxon: &str, insecure: bool) -> PyResult<PyObject> {
+    query(py, insecure, |client| client.taxon_children(taxon))
+}
+
+/// Accessions of the genomes of a taxon, as a list of str
+#[pyfunction]
+#[pyo3(signature = (taxon, *, reps_only = false, insecure = false))]
+fn taxon_genomes(
+    py: Python<'_>,
+    taxon: &str,
+    reps_only: bool,
+    insecure: bool,
+) -> PyResult<PyObject> {
+    query(py, insecure, |client| {
+        client.taxon_genomes(taxon, reps_only)
+    })
+}
+
+/// Names given to a taxon across releases, as a list of dicts
+#[pyfunction]
+#[pyo3(signature = (taxon, *, insecure = false))]
+fn taxon_history(py: Python<'_>, taxon: &str, insecure: bool) -> PyResult<PyObject> {
+    query(py, insecure, |client| client.taxon_history(taxon))
+}
+
+/// Query the GTDB API from Python
+#[pymodule]
+fn xgt(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add_function(wrap_pyfunction!(genome_card, m)?)?;
+    m.add_function(wrap_pyfunction!(genome_history, m)?)?;
+    m.add_function(wrap_pyfunction!(taxon_children, m)?)?;
+    m.add_function(wrap_pyfunction!(taxon_genomes, m)?)?;
+    m.add_function(wrap_pyfunction!(taxon_history, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_py() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let value = serde_json::json!({"gid": "GCA_001512625.1", "isGtdbSpeciesRep": true, "rows": [1, 2.5, null]});
+            let object = to_py(py, &value).unwrap();
+            let dict = object.downcast_bound::<PyDict>(py).unwrap();
+            let gid: String = dict.get_item("gid").unwrap().unwrap().extract().unwrap();
+            assert_eq!(gid, "GCA_001512625.1");
+            let rows = dict.get_item("rows").unwrap().unwrap();
+            assert_eq!(rows.len().unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn test_search_field() {
+        assert_eq!(search_field("gtdb").unwrap(), SearchField::Gtdb);
+        assert!(search_field("genus").is_err());
+    }
+}
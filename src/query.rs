@@ -0,0 +1,139 @@
+//! Fluent builders of GTDB API queries, independent of the command line
+
+use crate::api::search::SearchAPI;
+use crate::utils::SearchField;
+
+/// A search of the GTDB API, sent with
+/// [`GtdbClient::search`](crate::GtdbClient::search)
+///
+/// ```no_run
+/// use xgt::query::SearchQuery;
+/// use xgt::GtdbClient;
+///
+/// let query = SearchQuery::taxon("g__Aminobacter").reps_only();
+/// let genomes = GtdbClient::new()?.search(&query)?;
+/// # Ok::<(), xgt::XgtError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    needle: String,
+    field: SearchField,
+    whole_words: bool,
+    reps_only: bool,
+    type_material_only: bool,
+}
+
+impl SearchQuery {
+    /// Search `needle` in all fields
+    pub fn new(needle: &str) -> Self {
+        SearchQuery {
+            needle: needle.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Search the genomes of a GTDB taxon, e.g. "g__Aminobacter"
+    pub fn taxon(name: &str) -> Self {
+        SearchQuery::new(name).field(SearchField::Gtdb)
+    }
+
+    /// Search a genome by its accession
+    pub fn accession(accession: &str) -> Self {
+        SearchQuery::new(accession).field(SearchField::Acc)
+    }
+
+    /// Search genomes by NCBI organism name
+    pub fn organism(name: &str) -> Self {
+        SearchQuery::new(name).field(SearchField::Org)
+    }
+
+    /// Search `needle` in `field` only
+    pub fn field(mut self, field: SearchField) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Only keep genomes whose searched field is exactly the needle,
+    /// instead of containing it
+    pub fn whole_words(mut self) -> Self {
+        self.whole_words = true;
+        self
+    }
+
+    /// Only keep GTDB species representatives
+    pub fn reps_only(mut self) -> Self {
+        self.reps_only = true;
+        self
+    }
+
+    /// Only keep NCBI type material
+    pub fn type_material_only(mut self) -> Self {
+        self.type_material_only = true;
+        self
+    }
+
+    /// Get the searched text
+    pub fn get_needle(&self) -> &str {
+        &self.needle
+    }
+
+    /// Get the searched field
+    pub fn get_field(&self) -> SearchField {
+        self.field.clone()
+    }
+
+    /// Is whole words matching enabled
+    pub fn is_whole_words(&self) -> bool {
+        self.whole_words
+    }
+
+    /// Are only GTDB species representatives kept
+    pub fn is_reps_only(&self) -> bool {
+        self.reps_only
+    }
+
+    /// Is only NCBI type material kept
+    pub fn is_type_material_only(&self) -> bool {
+        self.type_material_only
+    }
+
+    /// Request of the query, returning JSON results
+    pub(crate) fn api(&self) -> SearchAPI {
+        SearchAPI::new()
+            .set_search(&self.needle)
+            .set_search_field(&self.field.to_string())
+            .set_gtdb_species_rep_only(self.reps_only)
+            .set_ncbi_type_material_only(self.type_material_only)
+            .set_outfmt("json")
+    }
+
+    /// URL of the JSON request of the query
+    pub fn url(&self) -> String {
+        self.api().request()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_query_builder() {
+        let query = SearchQuery::taxon("g__Aminobacter")
+            .reps_only()
+            .whole_words();
+        assert_eq!(query.get_needle(), "g__Aminobacter");
+        assert_eq!(query.get_field(), SearchField::Gtdb);
+        assert!(query.is_reps_only());
+        assert!(query.is_whole_words());
+        assert!(!query.is_type_material_only());
+        assert_eq!(
+            query.url(),
+            "https://api.gtdb.ecogenomic.org/search/gtdb?search=g__Aminobacter&page=1&itemsPerPage=1000000000&searchField=gtdb_tax&gtdbSpeciesRepOnly=true"
+        );
+        assert_eq!(
+            SearchQuery::accession("GCA_001512625.1").get_field(),
+            SearchField::Acc
+        );
+    }
+}
@@ -0,0 +1,168 @@
+/// Style of the report, inlined so that the file is self-contained
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+th { background: #eee; cursor: pointer; user-select: none; }
+th:after { content: \" \\2195\"; color: #999; }
+tr:nth-child(even) td { background: #f8f8f8; }
+.bar { display: flex; align-items: center; margin: 0.2em 0; }
+.bar span:first-child { width: 20em; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.bar div { background: #4a7ebb; height: 1em; margin: 0 0.5em; }
+.card { display: inline-block; vertical-align: top; border: 1px solid #ccc; border-radius: 4px; padding: 0.5em 1em; margin: 0.5em; min-width: 25em; }
+.card dt { font-weight: bold; }
+.card dd { margin: 0 0 0.4em 0; }
+";
+
+/// Sort a table by the clicked column, numerically when all values are numbers
+const SCRIPT: &str = "document.querySelectorAll('th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table');
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var ascending = th.dataset.order !== 'asc';
+    th.dataset.order = ascending ? 'asc' : 'desc';
+    var rows = Array.prototype.slice.call(body.rows);
+    var value = function (row) { return row.cells[index].textContent; };
+    var numeric = rows.every(function (row) { return value(row) !== '' && !isNaN(value(row)); });
+    rows.sort(function (a, b) {
+      var x = value(a), y = value(b);
+      var order = numeric ? x - y : x.localeCompare(y);
+      return ascending ? order : -order;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+";
+
+/// Escape text to be included in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A single-file HTML report made of sortable tables, composition charts
+/// and summary cards, which can be shared without running xgt
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    title: String,
+    sections: Vec<String>,
+}
+
+impl Report {
+    pub fn new(title: &str) -> Self {
+        Report {
+            title: title.to_string(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Add a table, sortable by clicking on its header
+    pub fn table(mut self, caption: &str, header: &[&str], rows: &[Vec<String>]) -> Self {
+        let mut section = format!("<h2>{}</h2>\n<table>\n<thead><tr>", escape(caption));
+        for field in header {
+            section.push_str(&format!("<th>{}</th>", escape(field)));
+        }
+        section.push_str("</tr></thead>\n<tbody>\n");
+        for row in rows {
+            section.push_str("<tr>");
+            for field in row {
+                section.push_str(&format!("<td>{}</td>", escape(field)));
+            }
+            section.push_str("</tr>\n");
+        }
+        section.push_str("</tbody>\n</table>\n");
+        self.sections.push(section);
+        self
+    }
+
+    /// Add a bar chart of the number of genomes of each taxon
+    pub fn composition(mut self, caption: &str, counts: &[(String, usize)]) -> Self {
+        let max = counts
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut section = format!("<h2>{}</h2>\n", escape(caption));
+        for (taxon, count) in counts {
+            section.push_str(&format!(
+                "<div class=\"bar\"><span title=\"{0}\">{0}</span><div style=\"width: {1:.1}em\"></div><span>{2}</span></div>\n",
+                escape(taxon),
+                30.0 * *count as f64 / max as f64,
+                count
+            ));
+        }
+        self.sections.push(section);
+        self
+    }
+
+    /// Add a card listing named fields
+    pub fn card(mut self, title: &str, fields: &[(&str, String)]) -> Self {
+        let mut section = format!("<div class=\"card\">\n<h2>{}</h2>\n<dl>\n", escape(title));
+        for (name, value) in fields {
+            section.push_str(&format!(
+                "<dt>{}</dt><dd>{}</dd>\n",
+                escape(name),
+                escape(value)
+            ));
+        }
+        section.push_str("</dl>\n</div>\n");
+        self.sections.push(section);
+        self
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}</style>\n</head>\n<body>\n<h1>{0}</h1>\n{2}<script>\n{3}</script>\n</body>\n</html>\n",
+            escape(&self.title),
+            STYLE,
+            self.sections.concat(),
+            SCRIPT
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn test_report_render() {
+        let html = Report::new("xgt search: g__Escherichia")
+            .table(
+                "Genomes",
+                &["accession", "genomes"],
+                &[vec!["GCF_000005845.2".to_string(), "1".to_string()]],
+            )
+            .composition(
+                "Genus",
+                &[
+                    ("g__Escherichia".to_string(), 4),
+                    ("g__Shigella".to_string(), 2),
+                ],
+            )
+            .card(
+                "GCF_000005845.2",
+                &[("GTDB taxonomy", "s__Escherichia coli".to_string())],
+            )
+            .render();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>xgt search: g__Escherichia</title>"));
+        assert!(html.contains("<thead><tr><th>accession</th><th>genomes</th></tr></thead>"));
+        assert!(html.contains("<tr><td>GCF_000005845.2</td><td>1</td></tr>"));
+        assert!(html.contains("<div style=\"width: 30.0em\"></div><span>4</span>"));
+        assert!(html.contains("<div style=\"width: 15.0em\"></div><span>2</span>"));
+        assert!(html.contains("<dt>GTDB taxonomy</dt><dd>s__Escherichia coli</dd>"));
+        assert!(html.contains("<script>"));
+    }
+}
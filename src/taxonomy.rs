@@ -0,0 +1,260 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Taxonomic ranks used by GTDB, from the highest to the lowest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rank {
+    Domain,
+    Phylum,
+    Class,
+    Order,
+    Family,
+    Genus,
+    Species,
+}
+
+impl Rank {
+    /// All ranks, from domain to species
+    pub const ALL: [Rank; 7] = [
+        Rank::Domain,
+        Rank::Phylum,
+        Rank::Class,
+        Rank::Order,
+        Rank::Family,
+        Rank::Genus,
+        Rank::Species,
+    ];
+
+    /// Rank directly below this one, None for species
+    pub fn child(&self) -> Option<Rank> {
+        Rank::ALL.into_iter().find(|rank| rank > self)
+    }
+
+    /// Greengenes-style rank prefix, e.g. "d__"
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Rank::Domain => "d__",
+            Rank::Phylum => "p__",
+            Rank::Class => "c__",
+            Rank::Order => "o__",
+            Rank::Family => "f__",
+            Rank::Genus => "g__",
+            Rank::Species => "s__",
+        }
+    }
+
+    /// Get a rank from its name, e.g. "genus"
+    pub fn from_name(name: &str) -> Option<Rank> {
+        Rank::ALL.into_iter().find(|rank| rank.to_string() == name)
+    }
+
+    /// Get the rank of a prefixed taxon name, e.g. "g__Escherichia"
+    pub fn from_taxon(taxon: &str) -> Option<Rank> {
+        Rank::ALL
+            .into_iter()
+            .find(|rank| taxon.starts_with(rank.prefix()))
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Rank::Domain => "domain",
+            Rank::Phylum => "phylum",
+            Rank::Class => "class",
+            Rank::Order => "order",
+            Rank::Family => "family",
+            Rank::Genus => "genus",
+            Rank::Species => "species",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Release number used to compare releases, e.g. 207 for "R207" or "207"
+pub fn release_number(release: &str) -> u32 {
+    release
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .unwrap_or(0)
+}
+
+/// Remove the rank prefix of a taxon name, e.g. "Escherichia" for "g__Escherichia"
+pub fn strip_rank_prefix(taxon: &str) -> &str {
+    match Rank::from_taxon(taxon) {
+        Some(rank) => &taxon[rank.prefix().len()..],
+        None => taxon,
+    }
+}
+
+/// Check if a taxon has an alphanumeric placeholder name rather than a
+/// validly published one, e.g. "g__DTU036" or "s__Aminobacter sp001512625".
+/// Alphabetic suffixes of split published names, as in "g__Escherichia_A",
+/// do not make a placeholder.
+pub fn is_placeholder(taxon: &str) -> bool {
+    strip_rank_prefix(taxon)
+        .split_whitespace()
+        .any(|x| x.contains(|c: char| c.is_ascii_digit()))
+}
+
+/// Parse a greengenes-style lineage, e.g. "d__Bacteria; p__Pseudomonadota; c__".
+/// Ranks without a name and unknown prefixes are left out.
+pub fn parse_lineage(lineage: &str) -> BTreeMap<Rank, String> {
+    lineage
+        .split(';')
+        .map(str::trim)
+        .filter_map(|taxon| {
+            let rank = Rank::from_taxon(taxon)?;
+            let name = &taxon[rank.prefix().len()..];
+            (!name.is_empty()).then(|| (rank, taxon.to_string()))
+        })
+        .collect()
+}
+
+/// Check that a lineage has every taxon of `query` at the same rank, e.g.
+/// "d__Bacteria; g__Escherichia" matches the lineages of the Escherichia
+/// genomes but not of genomes of a genus named after a substring of it
+pub fn lineage_matches(query: &BTreeMap<Rank, String>, lineage: &str) -> bool {
+    let lineage = parse_lineage(lineage);
+    query
+        .iter()
+        .all(|(rank, taxon)| lineage.get(rank) == Some(taxon))
+}
+
+/// Format a lineage as a greengenes-style string, e.g.
+/// "d__Bacteria; p__Pseudomonadota"
+pub fn format_lineage(lineage: &BTreeMap<Rank, String>) -> String {
+    lineage
+        .values()
+        .map(String::as_str)
+        .collect::<Vec<&str>>()
+        .join("; ")
+}
+
+/// Build a lineage from taxon names without rank prefix, one per rank
+/// from domain to species. Empty and "NA" names are left out.
+pub fn lineage_from_names(names: &[&str]) -> BTreeMap<Rank, String> {
+    Rank::ALL
+        .into_iter()
+        .zip(names.iter().map(|x| x.trim()))
+        .filter(|(_, name)| !name.is_empty() && *name != "NA")
+        .map(|(rank, name)| (rank, format!("{}{}", rank.prefix(), name)))
+        .collect()
+}
+
+/// Get the chain of parent taxa of `taxon` from a lineage containing it,
+/// from the domain down to `taxon` itself
+pub fn parent_lineage(lineage: &str, taxon: &str) -> Option<BTreeMap<Rank, String>> {
+    let rank = Rank::from_taxon(taxon)?;
+    let mut lineage = parse_lineage(lineage);
+    if lineage.get(&rank).map(String::as_str) != Some(taxon) {
+        return None;
+    }
+    lineage.retain(|r, _| *r <= rank);
+    Some(lineage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_from_taxon() {
+        assert_eq!(Rank::from_taxon("g__Escherichia"), Some(Rank::Genus));
+        assert_eq!(Rank::from_taxon("x__unclassified Pseudomonadota"), None);
+        assert_eq!(Rank::Species.to_string(), "species");
+        assert_eq!(Rank::Genus.child(), Some(Rank::Species));
+        assert_eq!(Rank::Species.child(), None);
+        assert_eq!(Rank::from_name("phylum"), Some(Rank::Phylum));
+        assert_eq!(Rank::from_name("strain"), None);
+        assert_eq!(strip_rank_prefix("g__Escherichia"), "Escherichia");
+        assert_eq!(strip_rank_prefix("x__unclassified"), "x__unclassified");
+    }
+
+    #[test]
+    fn test_release_number() {
+        assert_eq!(release_number("R207"), 207);
+        assert_eq!(release_number("80"), 80);
+        assert!(release_number("R95") < release_number("R207"));
+    }
+
+    #[test]
+    fn test_is_placeholder() {
+        assert!(is_placeholder("g__DTU036"));
+        assert!(is_placeholder("f__UBA1234"));
+        assert!(is_placeholder("s__Aminobacter sp001512625"));
+        assert!(is_placeholder("s__CAG-110 sp000435175"));
+        assert!(!is_placeholder("g__Escherichia_A"));
+        assert!(!is_placeholder("s__Escherichia coli"));
+    }
+
+    #[test]
+    fn test_parse_lineage() {
+        let lineage =
+            parse_lineage("d__Bacteria; p__Pseudomonadota; x__unclassified Pseudomonadota; c__");
+        assert_eq!(lineage.len(), 2);
+        assert_eq!(lineage.get(&Rank::Domain), Some(&"d__Bacteria".to_string()));
+        assert_eq!(
+            lineage.get(&Rank::Phylum),
+            Some(&"p__Pseudomonadota".to_string())
+        );
+        assert_eq!(lineage.get(&Rank::Class), None);
+    }
+
+    #[test]
+    fn test_lineage_matches() {
+        let lineage = "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__Enterobacterales; f__Enterobacteriaceae; g__Escherichia; s__Escherichia coli";
+        assert!(lineage_matches(
+            &parse_lineage("d__Bacteria;g__Escherichia"),
+            lineage
+        ));
+        assert!(lineage_matches(&parse_lineage(lineage), lineage));
+        assert!(!lineage_matches(
+            &parse_lineage("d__Bacteria;g__Escherichia_A"),
+            lineage
+        ));
+        assert!(!lineage_matches(
+            &parse_lineage("p__Bacillota;g__Escherichia"),
+            lineage
+        ));
+    }
+
+    #[test]
+    fn test_format_lineage() {
+        let lineage = "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria";
+        assert_eq!(format_lineage(&parse_lineage(lineage)), lineage);
+        assert_eq!(
+            format_lineage(&parse_lineage("d__Bacteria;p__Pseudomonadota;c__")),
+            "d__Bacteria; p__Pseudomonadota"
+        );
+        assert_eq!(format_lineage(&BTreeMap::new()), "");
+    }
+
+    #[test]
+    fn test_lineage_from_names() {
+        let lineage = lineage_from_names(&["Bacteria", " Pseudomonadota", "", "NA"]);
+        assert_eq!(format_lineage(&lineage), "d__Bacteria; p__Pseudomonadota");
+        assert!(lineage_from_names(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parent_lineage() {
+        let lineage = "d__Bacteria; p__Pseudomonadota; c__Gammaproteobacteria; o__Enterobacterales; f__Enterobacteriaceae; g__Escherichia; s__Escherichia coli";
+        let parents = parent_lineage(lineage, "f__Enterobacteriaceae").unwrap();
+        assert_eq!(
+            parents.values().cloned().collect::<Vec<String>>(),
+            vec![
+                "d__Bacteria",
+                "p__Pseudomonadota",
+                "c__Gammaproteobacteria",
+                "o__Enterobacterales",
+                "f__Enterobacteriaceae"
+            ]
+        );
+        assert_eq!(parent_lineage(lineage, "f__Vibrionaceae"), None);
+    }
+}
@@ -0,0 +1,151 @@
+//! TLS backends of the blocking client, selected with the `native-tls` and
+//! `rustls` features
+
+use std::error::Error;
+use std::io;
+use std::sync::Arc;
+
+use crate::error::XgtError;
+use crate::metrics;
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("xgt needs a TLS backend, please enable the native-tls or rustls feature");
+
+/// Agent sending requests with rustls
+#[cfg(feature = "rustls")]
+pub(crate) fn build_agent(disable_certificate_verification: bool) -> Result<ureq::Agent, XgtError> {
+    let agent = match disable_certificate_verification {
+        true => {
+            let config = rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(danger::NoVerification::new()))
+                .with_no_client_auth();
+            ureq::AgentBuilder::new()
+                .tls_config(Arc::new(config))
+                .middleware(metrics::measure)
+                .build()
+        }
+        false => ureq::AgentBuilder::new()
+            .middleware(metrics::measure)
+            .build(),
+    };
+    Ok(agent)
+}
+
+/// Agent sending requests with the TLS library of the system
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+pub(crate) fn build_agent(disable_certificate_verification: bool) -> Result<ureq::Agent, XgtError> {
+    let tls_connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(disable_certificate_verification)
+        .build()
+        .map_err(|e| XgtError::Tls(e.to_string()))?;
+    Ok(ureq::AgentBuilder::new()
+        .tls_connector(Arc::new(tls_connector))
+        .middleware(metrics::measure)
+        .build())
+}
+
+/// Check whether a request error was caused by the TLS backend
+pub(crate) fn is_tls_error(error: &dyn Error) -> bool {
+    let mut source = error.source();
+    while let Some(error) = source {
+        if is_backend_error(error) {
+            return true;
+        }
+        // rustls errors are wrapped in I/O errors, which do not give them
+        // as their source
+        source = error
+            .downcast_ref::<io::Error>()
+            .and_then(|e| e.get_ref())
+            .map(|e| e as &(dyn Error + 'static))
+            .or_else(|| error.source());
+    }
+    false
+}
+
+fn is_backend_error(error: &(dyn Error + 'static)) -> bool {
+    #[cfg(feature = "native-tls")]
+    if error.is::<native_tls::Error>() {
+        return true;
+    }
+    #[cfg(feature = "rustls")]
+    if error.is::<rustls::Error>() {
+        return true;
+    }
+    false
+}
+
+/// Certificate verifier of `--insecure`, which accepts any certificate but
+/// still checks the handshake signatures
+#[cfg(feature = "rustls")]
+mod danger {
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls::{DigitallySignedStruct, Error, SignatureScheme};
+
+    #[derive(Debug)]
+    pub(super) struct NoVerification(CryptoProvider);
+
+    impl NoVerification {
+        pub(super) fn new() -> Self {
+            NoVerification(ring::default_provider())
+        }
+    }
+
+    impl ServerCertVerifier for NoVerification {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_agent() {
+        assert!(build_agent(false).is_ok());
+        assert!(build_agent(true).is_ok());
+    }
+}
@@ -0,0 +1,313 @@
+//! HTTP layer of the GTDB API client, which can be swapped for another
+//! backend or a fake one in tests
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::drift;
+use crate::error::{self, XgtError};
+use crate::metrics;
+use crate::utils;
+
+/// Times a truncated response is requested again before failing
+const TRUNCATED_RETRIES: usize = 2;
+
+/// Status code and body of a HTTP response
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        HttpResponse { status, body }
+    }
+
+    /// Get the status code
+    pub fn get_status(&self) -> u16 {
+        self.status
+    }
+
+    /// Get the body
+    pub fn get_body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+/// Sends the GET requests of a [`GtdbClient`](crate::GtdbClient)
+///
+/// Responses with an error status code are returned as `Ok`, errors being
+/// failures to send the request or receive the response.
+pub trait Transport: fmt::Debug + Send + Sync {
+    fn get(&self, url: &str) -> Result<HttpResponse, XgtError>;
+}
+
+/// A response read in full, or the reason why it is truncated
+enum Fetched {
+    Complete(HttpResponse),
+    Truncated(String),
+}
+
+/// Separator of the records of a CSV or TSV request of the GTDB API, e.g.
+/// "/search/gtdb/csv?search=..."
+fn xsv_separator(url: &str) -> Option<char> {
+    let path = url.split('?').next().unwrap_or_default();
+    match path.rsplit('/').next() {
+        Some("csv") => Some(','),
+        Some("tsv") => Some('\t'),
+        _ => None,
+    }
+}
+
+/// Number of fields of a CSV or TSV record, none when a quoted field is not
+/// closed
+fn field_count(record: &str, separator: char) -> Option<usize> {
+    let mut quoted = false;
+    let mut count = 1;
+    for c in record.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => count += 1,
+            _ => (),
+        }
+    }
+    (!quoted).then_some(count)
+}
+
+/// Check that a successful response is complete: its body has the length
+/// announced by the `Content-Length` of the response, when it is not
+/// compressed, and the last record of a CSV or TSV body has as many fields
+/// as its header
+fn check_complete(url: &str, content_length: Option<u64>, body: &[u8]) -> Result<(), String> {
+    if let Some(length) = content_length {
+        if body.len() as u64 != length {
+            return Err(format!("received {} of {} bytes", body.len(), length));
+        }
+    }
+    if let Some(separator) = xsv_separator(url) {
+        let text = String::from_utf8_lossy(body);
+        let mut records = text.lines().filter(|x| !x.trim().is_empty());
+        if let (Some(header), Some(last)) = (records.next(), records.next_back()) {
+            let fields = field_count(header, separator);
+            if field_count(last, separator) != fields {
+                return Err(format!(
+                    "the last record is incomplete: {}",
+                    last.chars().take(80).collect::<String>()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send a request with `send` until its response is complete, requesting
+/// truncated responses again at most `TRUNCATED_RETRIES` times
+fn get_complete(
+    url: &str,
+    send: impl Fn() -> Result<Fetched, XgtError>,
+) -> Result<HttpResponse, XgtError> {
+    let mut retries = 0;
+    let response = loop {
+        metrics::set_retries(retries);
+        match send() {
+            Ok(Fetched::Truncated(reason)) if retries < TRUNCATED_RETRIES => {
+                retries += 1;
+                if utils::verbosity() > 0 {
+                    eprintln!(
+                        "Warning: truncated response of {} ({}), retrying",
+                        url, reason
+                    );
+                }
+            }
+            Ok(Fetched::Truncated(reason)) => {
+                break Err(XgtError::Network(format!(
+                    "The response was truncated: {}",
+                    reason
+                )))
+            }
+            Ok(Fetched::Complete(response)) => break Ok(response),
+            Err(e) => break Err(e),
+        }
+    };
+    metrics::set_retries(0);
+    response
+}
+
+/// Response of a request whose body was read in full, checked to be
+/// complete when it is successful
+fn fetched(
+    url: &str,
+    status: u16,
+    content_length: Option<u64>,
+    body: Vec<u8>,
+) -> Result<Fetched, XgtError> {
+    if !(200..300).contains(&status) {
+        return Ok(Fetched::Complete(HttpResponse::new(status, body)));
+    }
+    Ok(match check_complete(url, content_length, &body) {
+        Ok(()) => Fetched::Complete(HttpResponse::new(status, body)),
+        Err(reason) => Fetched::Truncated(reason),
+    })
+}
+
+impl Transport for ureq::Agent {
+    fn get(&self, url: &str) -> Result<HttpResponse, XgtError> {
+        get_complete(url, || {
+            let response = match ureq::Agent::get(self, url).call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(_, response)) => response,
+                Err(e) => return Err(XgtError::from_request(e, url)),
+            };
+            let status = response.status();
+            // Compressed bodies are decoded, their length is not the one
+            // announced
+            let content_length = match response.header("Content-Encoding") {
+                Some(_) => None,
+                None => response
+                    .header("Content-Length")
+                    .and_then(|x| x.parse().ok()),
+            };
+            let mut body = Vec::new();
+            match response.into_reader().read_to_end(&mut body) {
+                Ok(_) => fetched(url, status, content_length, body),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    Ok(Fetched::Truncated(e.to_string()))
+                }
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+}
+
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+impl Transport for reqwest::blocking::Client {
+    fn get(&self, url: &str) -> Result<HttpResponse, XgtError> {
+        get_complete(url, || {
+            let start = metrics::now();
+            let response = reqwest::blocking::Client::get(self, url).send();
+            let status = response.as_ref().ok().map(|x| x.status().as_u16());
+            metrics::record_request(url, status, start);
+            let response = response.map_err(|e| XgtError::from_reqwest(e, url))?;
+            let status = response.status().as_u16();
+            let content_length = response.content_length();
+            let body = response
+                .bytes()
+                .map_err(|e| XgtError::from_reqwest(e, url))?;
+            metrics::record_bytes(body.len() as u64);
+            fetched(url, status, content_length, body.to_vec())
+        })
+    }
+}
+
+/// Send a GET request and decode its JSON response, `not_found` being the
+/// message of the error when GTDB does not know the queried record
+pub(crate) fn get_json<T: DeserializeOwned + Serialize>(
+    transport: &dyn Transport,
+    url: &str,
+    not_found: String,
+) -> Result<T, XgtError> {
+    let decoded = transport.get(url).and_then(|response| {
+        if !(200..300).contains(&response.status) {
+            return Err(XgtError::from_status(response.status, not_found));
+        }
+        drift::decode(&response.body)
+    });
+    if decoded.is_err() {
+        error::record_failed_url(url);
+    }
+    decoded
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Transport answering from canned responses, and 404 for other URLs
+    #[derive(Debug, Default)]
+    pub(crate) struct FakeTransport {
+        responses: HashMap<String, HttpResponse>,
+    }
+
+    impl FakeTransport {
+        pub(crate) fn with(mut self, url: &str, status: u16, body: &str) -> Self {
+            self.responses.insert(
+                url.to_string(),
+                HttpResponse::new(status, body.as_bytes().to_vec()),
+            );
+            self
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn get(&self, url: &str) -> Result<HttpResponse, XgtError> {
+            Ok(self
+                .responses
+                .get(url)
+                .cloned()
+                .unwrap_or_else(|| HttpResponse::new(404, Vec::new())))
+        }
+    }
+
+    #[test]
+    fn test_get_json() {
+        let transport = FakeTransport::default()
+            .with("https://example.org/ok", 200, r#"["g__Aminobacter"]"#)
+            .with("https://example.org/bad", 200, "{")
+            .with("https://example.org/limited", 429, "");
+
+        let taxa: Vec<String> =
+            get_json(&transport, "https://example.org/ok", String::new()).unwrap();
+        assert_eq!(taxa, vec!["g__Aminobacter"]);
+
+        let error = get_json::<Vec<String>>(
+            &transport,
+            "https://example.org/missing",
+            "Taxon g__Unknown not found".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Taxon g__Unknown not found");
+        assert!(matches!(
+            get_json::<Vec<String>>(&transport, "https://example.org/bad", String::new()),
+            Err(XgtError::Decode(_))
+        ));
+        assert!(matches!(
+            get_json::<Vec<String>>(&transport, "https://example.org/limited", String::new()),
+            Err(XgtError::RateLimited)
+        ));
+    }
+
+    #[test]
+    fn test_check_complete() {
+        let url = "https://api.gtdb.ecogenomic.org/search/gtdb/csv?search=g__Aminobacter";
+        let csv = b"accession,ncbi_organism_name,gtdb_taxonomy\r\n\
+                    GCA_1,\"Aminobacter sp. MSH1, strain 1\",g__Aminobacter\r\n";
+        assert_eq!(check_complete(url, Some(csv.len() as u64), csv), Ok(()));
+        assert_eq!(
+            check_complete(url, Some(1000), csv),
+            Err(format!("received {} of 1000 bytes", csv.len()))
+        );
+        // Truncated within the quoted organism name, or before the last field
+        assert!(check_complete(url, None, &csv[..70]).is_err());
+        assert!(check_complete(url, None, &csv[..csv.len() - 17]).is_err());
+
+        let tsv_url = "https://api.gtdb.ecogenomic.org/search/gtdb/tsv?search=g__Aminobacter";
+        assert!(check_complete(
+            tsv_url,
+            None,
+            b"accession\tgtdb_taxonomy\nGCA_1\tg__Aminobacter\n"
+        )
+        .is_ok());
+        assert!(check_complete(tsv_url, None, b"accession\tgtdb_taxonomy\nGCA_1").is_err());
+        // JSON responses are only checked for their length
+        assert!(check_complete(
+            "https://api.gtdb.ecogenomic.org/genome/GCA_1/card",
+            None,
+            b"{"
+        )
+        .is_ok());
+    }
+}
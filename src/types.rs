@@ -0,0 +1,49 @@
+//! Responses of the GTDB API, as returned by [`GtdbClient`](crate::GtdbClient)
+//!
+//! They implement `Deserialize` and `Serialize`, so that GTDB data saved as
+//! JSON can be read back with the same types, and are `#[non_exhaustive]`
+//! as GTDB adds fields to its responses.
+
+pub use crate::cmd::genome::{
+    Genome, GenomeCard, GenomeMetadata, GenomeTaxonHistory, History, MetadataGene, MetadataNCBI,
+    MetadataNucleotide, MetadataTaxonomy, MetadataTypeMaterial, Taxon as NcbiTaxon, TaxonLink,
+};
+pub use crate::cmd::search::{SearchResult, SearchResults};
+pub use crate::cmd::taxon::{Taxon, TaxonGenomes, TaxonRelease, TaxonResult, TaxonSearchResult};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_search_results() {
+        let results: SearchResults = serde_json::from_str(
+            r#"{"rows": [{"gid": "GCA_001512625.1", "accession": "GCA_001512625.1",
+                "ncbiOrgName": "Aminobacter sp.", "ncbiTaxonomy": null,
+                "gtdbTaxonomy": "d__Bacteria; g__Aminobacter", "isGtdbSpeciesRep": true,
+                "isNcbiTypeMaterial": false}], "totalRows": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(results.get_total_rows(), 1);
+        let row = &results.get_rows()[0];
+        assert_eq!(row.get_gid(), "GCA_001512625.1");
+        assert_eq!(row.is_gtdb_species_rep(), Some(true));
+
+        let json = serde_json::to_string(&results).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SearchResults>(&json).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn test_deserialize_taxon_release() {
+        let release: TaxonRelease = serde_json::from_str(
+            r#"{"taxon": "g__Aminobacter", "firstSeen": "R80", "lastSeen": null}"#,
+        )
+        .unwrap();
+        assert_eq!(release.get_taxon(), "g__Aminobacter");
+        assert_eq!(release.get_first_seen().as_deref(), Some("R80"));
+        assert_eq!(release.get_last_seen(), None);
+    }
+}
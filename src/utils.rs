@@ -1,10 +1,26 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
+use std::collections::BTreeMap;
+use std::env;
 use std::fmt::Display;
 use std::fs::OpenOptions;
 
 use std::io::{self, Write};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::OnceLock;
+use std::sync::{mpsc, Mutex, PoisonError, RwLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::{self, XgtError};
+use crate::report::Report;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tls;
+use crate::values;
 
 /// Search field as provided by GTDB API
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
@@ -62,6 +78,10 @@ pub enum OutputFormat {
     Csv,
     Json,
     Tsv,
+    // Aligned columns for reading in a terminal
+    Table,
+    // Self-contained HTML report
+    Html,
 }
 
 impl Display for OutputFormat {
@@ -70,6 +90,8 @@ impl Display for OutputFormat {
             Self::Csv => write!(f, "csv"),
             Self::Json => write!(f, "json"),
             Self::Tsv => write!(f, "tsv"),
+            Self::Table => write!(f, "table"),
+            Self::Html => write!(f, "html"),
         }
     }
 }
@@ -80,16 +102,467 @@ impl From<String> for OutputFormat {
             Self::Tsv
         } else if value == "json" {
             Self::Json
+        } else if value == "table" {
+            Self::Table
+        } else if value == "html" {
+            Self::Html
         } else {
             Self::Csv
         }
     }
 }
 
+/// Quote a CSV field if it contains a separator, a quote or a newline
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Representation of missing values in CSV, TSV and table outputs, set by
+/// the global `--na-string` option
+static NA_STRING: RwLock<String> = RwLock::new(String::new());
+
+/// Set the representation of missing values
+pub fn set_na_string(na_string: &str) {
+    *NA_STRING.write().unwrap_or_else(PoisonError::into_inner) = na_string.to_string();
+}
+
+/// Get the representation of missing values, empty by default
+pub fn na_string() -> String {
+    NA_STRING
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Column tabular outputs are sorted by, and whether in descending order,
+/// set by the global `--sort` option
+static SORT: RwLock<Option<(String, bool)>> = RwLock::new(None);
+
+/// Whether the `--sort` column was missing from a tabular output, warned
+/// only once for outputs of many tables
+static SORT_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Parse a `--sort` key, `COLUMN`, `COLUMN:asc` or `COLUMN:desc`, into the
+/// column and whether it is sorted in descending order
+pub fn parse_sort(key: &str) -> Result<(String, bool), String> {
+    let (column, descending) = match key.rsplit_once(':') {
+        Some((column, "desc")) => (column, true),
+        Some((column, "asc")) => (column, false),
+        Some(_) => return Err("Sort order must be asc or desc, e.g. genome_size:desc".to_string()),
+        None => (key, false),
+    };
+    match column.is_empty() {
+        true => Err("Sort column must not be empty, e.g. genome_size:desc".to_string()),
+        false => Ok((column.to_string(), descending)),
+    }
+}
+
+/// Set the column tabular outputs are sorted by
+pub fn set_sort(sort: Option<(String, bool)>) {
+    *SORT.write().unwrap_or_else(PoisonError::into_inner) = sort;
+}
+
+/// Order of two values of the `--sort` column: numbers by value, before
+/// other values as text, and missing values last in both orders
+fn compare_cells(a: &str, b: &str, descending: bool) -> std::cmp::Ordering {
+    let missing = |x: &str| values::is_missing(x) || x == na_string();
+    match (missing(a), missing(b)) {
+        (true, true) => return std::cmp::Ordering::Equal,
+        (true, false) => return std::cmp::Ordering::Greater,
+        (false, true) => return std::cmp::Ordering::Less,
+        (false, false) => (),
+    }
+    let order = match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.total_cmp(&y),
+        (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+        (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    };
+    match descending {
+        true => order.reverse(),
+        false => order,
+    }
+}
+
+/// Column of `--sort`, and whether in descending order
+fn sort_key() -> Option<(String, bool)> {
+    SORT.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Sort the rows of a table of `header` by the `--sort` column, keeping
+/// the order of rows with equal values. Tables without the column are
+/// left as is, with a warning.
+pub(crate) fn sort_rows<T>(header: &[&str], rows: &mut [T], cell: impl Fn(&T, usize) -> &str) {
+    let Some((column, descending)) = sort_key() else {
+        return;
+    };
+    if !sort_rows_by(header, rows, cell, &column, descending)
+        && verbosity() > 0
+        && !SORT_WARNED.swap(true, Ordering::Relaxed)
+    {
+        eprintln!(
+            "Warning: no column {} to sort by, expected one of {}",
+            column,
+            header.join(", ")
+        );
+    }
+}
+
+/// Sort the rows of a table of `header` by `column`, false if the table
+/// has no such column
+fn sort_rows_by<T>(
+    header: &[&str],
+    rows: &mut [T],
+    cell: impl Fn(&T, usize) -> &str,
+    column: &str,
+    descending: bool,
+) -> bool {
+    let Some(index) = header.iter().position(|x| *x == column) else {
+        return false;
+    };
+    rows.sort_by(|a, b| compare_cells(cell(a, index), cell(b, index), descending));
+    true
+}
+
+/// A record of a CSV/TSV output: its fields and its line
+type XsvRecord<'a> = (Vec<&'a str>, &'a str);
+
+/// Sort the records of a CSV/TSV output with `sort`, called with the
+/// columns of its header, keeping the header first and its line endings
+fn sort_xsv_records(
+    text: &str,
+    separator: char,
+    sort: impl FnOnce(&[&str], &mut [XsvRecord]),
+) -> String {
+    let mut lines = text.split_inclusive('\n');
+    let Some(header) = lines.next() else {
+        return String::new();
+    };
+    let line_ending = &header[header.trim_end_matches(['\r', '\n']).len()..];
+    let columns = values::split_xsv_line(header.trim_end(), separator);
+    let mut records: Vec<XsvRecord> = lines
+        .map(|line| line.trim_end_matches(['\r', '\n']))
+        .filter(|x| !x.trim_end().is_empty())
+        .map(|line| (values::split_xsv_line(line, separator), line))
+        .collect();
+    sort(&columns, &mut records);
+
+    let mut output = String::with_capacity(text.len());
+    output.push_str(header);
+    for (_, line) in records {
+        output.push_str(line);
+        output.push_str(line_ending);
+    }
+    output
+}
+
+/// Cell of a CSV/TSV record, without its quotes
+fn xsv_cell<'a>((fields, _): &'a XsvRecord, index: usize) -> &'a str {
+    fields.get(index).map_or("", |x| x.trim_matches('"'))
+}
+
+/// Sort the records of a CSV/TSV output by the `--sort` column, keeping
+/// its header first and its line endings
+pub(crate) fn sort_xsv(text: &str, separator: char) -> String {
+    if sort_key().is_none() {
+        return text.to_string();
+    }
+    sort_xsv_records(text, separator, |columns, records| {
+        sort_rows(columns, records, xsv_cell)
+    })
+}
+
+/// Sort the records of a CSV/TSV output by `column`, then by their first
+/// column, keeping its header first and its line endings
+pub(crate) fn sort_xsv_by(text: &str, separator: char, column: &str) -> Result<String> {
+    let mut unknown = None;
+    let output = sort_xsv_records(text, separator, |columns, records| {
+        if let Some(first) = columns.first() {
+            sort_rows_by(columns, records, xsv_cell, first, false);
+        }
+        if !sort_rows_by(columns, records, xsv_cell, column, false) {
+            unknown = Some(columns.join(", "));
+        }
+    });
+    if let Some(expected) = unknown {
+        anyhow::bail!(
+            "No column {} to sort by, expected one of {}",
+            column,
+            expected
+        );
+    }
+    Ok(output)
+}
+
+/// Level of the messages printed on stderr: 0 for none, the default of the
+/// library, 1 for warnings, the default of the command line, and 2 for the
+/// details printed with `-v`
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Set the level of the messages printed on stderr
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// Get the level of the messages printed on stderr
+pub(crate) fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Whether errors are printed as JSON objects, set by `--errors json`
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Set whether errors are printed as JSON objects
+pub fn set_json_errors(json: bool) {
+    JSON_ERRORS.store(json, Ordering::Relaxed);
+}
+
+/// Errors of the items of a batch, e.g. the accessions of `--file`. The
+/// batch keeps going past failed items, which are reported on stderr, and
+/// fails once done; it stops at the first failure with `--fail-fast`, when
+/// there is a single item, or when interrupted.
+pub struct ItemErrors {
+    total: usize,
+    fail_fast: bool,
+    failed: usize,
+    first: Option<anyhow::Error>,
+}
+
+impl ItemErrors {
+    pub fn new(total: usize, fail_fast: bool) -> Self {
+        ItemErrors {
+            total,
+            fail_fast: fail_fast || total == 1,
+            failed: 0,
+            first: None,
+        }
+    }
+
+    /// Result of `item`, none if it failed and the batch keeps going
+    pub fn check<T>(&mut self, item: &str, result: Result<T>) -> Result<Option<T>> {
+        let error = match result {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) if self.fail_fast || error::is_interrupted(&e) => return Err(e),
+            Err(e) => e,
+        };
+        if JSON_ERRORS.load(Ordering::Relaxed) {
+            let mut json = error::error_json(&error);
+            json["item"] = item.into();
+            eprintln!("{}", json);
+        } else {
+            eprintln!("Error: {}: {:#}", item, error);
+        }
+        self.failed += 1;
+        self.first.get_or_insert(error);
+        Ok(None)
+    }
+
+    /// Fail with the first error if any item failed, so that the exit code
+    /// tells its cause
+    pub fn finish(self) -> Result<()> {
+        match self.first {
+            Some(error) => {
+                Err(error.context(format!("{} of {} queries failed", self.failed, self.total)))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Apply `f` to every item in turn, keeping going past failed items unless
+/// `fail_fast`, see `ItemErrors`
+pub fn for_each_item<F>(items: Vec<String>, fail_fast: bool, mut f: F) -> Result<()>
+where
+    F: FnMut(&str) -> Result<()>,
+{
+    let mut errors = ItemErrors::new(items.len(), fail_fast);
+    for item in &items {
+        errors.check(item, f(item))?;
+    }
+    errors.finish()
+}
+
+/// Whether JSON outputs are compact rather than pretty-printed, set by the
+/// global `--compact` option and when stdout is not a terminal
+static COMPACT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Set whether JSON outputs are compact
+pub fn set_compact_json(compact: bool) {
+    COMPACT_JSON.store(compact, Ordering::Relaxed);
+}
+
+/// Serialize a value as compact or pretty-printed JSON, following `--compact`
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    match COMPACT_JSON.load(Ordering::Relaxed) {
+        true => serde_json::to_string(value),
+        false => serde_json::to_string_pretty(value),
+    }
+}
+
+/// Render a header and rows as CSV, TSV, an aligned table or an HTML report.
+/// JSON is rendered by callers from their own structs, and falls back to TSV here.
+/// Empty fields are missing values, rendered as the `--na-string`.
+pub fn render_table(header: &[&str], rows: &[Vec<String>], outfmt: &OutputFormat) -> String {
+    render_table_with_na(header, rows, outfmt, &na_string())
+}
+
+fn render_table_with_na(
+    header: &[&str],
+    rows: &[Vec<String>],
+    outfmt: &OutputFormat,
+    na_string: &str,
+) -> String {
+    if *outfmt == OutputFormat::Html {
+        return Report::new("xgt").table("Results", header, rows).render();
+    }
+    let mut rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|x| match x.is_empty() {
+                    true => na_string.to_string(),
+                    false => x.clone(),
+                })
+                .collect()
+        })
+        .collect();
+    sort_rows(header, &mut rows, |row, i| {
+        row.get(i).map_or("", String::as_str)
+    });
+    let rows = &rows;
+    let mut output = String::new();
+    match outfmt {
+        OutputFormat::Csv => {
+            let header: Vec<String> = header.iter().map(|x| csv_field(x)).collect();
+            output.push_str(&header.join(","));
+            output.push('\n');
+            for row in rows {
+                let row: Vec<String> = row.iter().map(|x| csv_field(x)).collect();
+                output.push_str(&row.join(","));
+                output.push('\n');
+            }
+        }
+        OutputFormat::Json | OutputFormat::Tsv => {
+            output.push_str(&header.join("\t"));
+            output.push('\n');
+            for row in rows {
+                output.push_str(&row.join("\t"));
+                output.push('\n');
+            }
+        }
+        OutputFormat::Table => {
+            let mut widths: Vec<usize> = header.iter().map(|x| x.chars().count()).collect();
+            for row in rows {
+                for (width, field) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(field.chars().count());
+                }
+            }
+            let header: Vec<String> = header.iter().map(|x| x.to_string()).collect();
+            for row in std::iter::once(&header).chain(rows) {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(field, width)| format!("{:<width$}", field, width = width))
+                    .collect();
+                output.push_str(line.join("  ").trim_end());
+                output.push('\n');
+            }
+        }
+        OutputFormat::Html => unreachable!("HTML reports are rendered above"),
+    }
+    output
+}
+
+/// Provenance of output files, recorded at their start, or in a sidecar
+/// file for JSON, with the global `--provenance` option
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Provenance {
+    xgt_version: String,
+    gtdb_release: String,
+    command: String,
+    created_at: String,
+}
+
+impl Provenance {
+    /// Provenance of the outputs of `args`, the command line arguments, with
+    /// `release` the GTDB release they come from, or None if unknown
+    pub fn new(args: &[String], release: Option<&str>) -> Self {
+        let is_plain = |x: &str| {
+            !x.is_empty()
+                && x.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@".contains(c))
+        };
+        let command: Vec<String> = args
+            .iter()
+            .map(|x| match is_plain(x) {
+                true => x.clone(),
+                false => shell_quote(x),
+            })
+            .collect();
+        Provenance {
+            xgt_version: env!("CARGO_PKG_VERSION").to_string(),
+            gtdb_release: release.unwrap_or("unknown").to_string(),
+            command: command.join(" "),
+            created_at: utc_now(),
+        }
+    }
+
+    /// Header of an output starting with `content`: an HTML comment before
+    /// HTML and comment lines otherwise. JSON has none, as it would no
+    /// longer parse.
+    fn header(&self, content: &[u8]) -> Option<String> {
+        let lines = [
+            format!("xgt {}", self.xgt_version),
+            format!("GTDB release: {}", self.gtdb_release),
+            format!("command: {}", self.command),
+            format!("created: {}", self.created_at),
+        ];
+        match content.first() {
+            Some(b'{' | b'[') => None,
+            Some(b'<') => Some(format!("<!--\n{}\n-->\n", lines.join("\n"))),
+            _ => Some(lines.iter().map(|x| format!("# {}\n", x)).collect()),
+        }
+    }
+}
+
+/// Path of the sidecar file recording the provenance of a JSON output
+pub fn provenance_path(path: &str) -> String {
+    format!("{}.provenance.json", path)
+}
+
+static PROVENANCE: RwLock<Option<Provenance>> = RwLock::new(None);
+
+/// Set the provenance recorded in output files, or None to record nothing
+pub fn set_provenance(provenance: Option<Provenance>) {
+    *PROVENANCE.write().unwrap_or_else(PoisonError::into_inner) = provenance;
+}
+
 /// Write `buffer` to `output` which can either be stdout or a file name.
+/// New files start with the provenance of their content if requested, or
+/// get a sidecar provenance file for JSON.
 pub fn write_to_output(buffer: &[u8], output: Option<String>) -> Result<()> {
     let mut writer: Box<dyn Write> = match output {
-        Some(path) => Box::new(OpenOptions::new().append(true).create(true).open(path)?),
+        Some(path) => {
+            let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
+            let provenance = PROVENANCE.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(provenance) = provenance.as_ref() {
+                if file.metadata()?.len() == 0 {
+                    match provenance.header(buffer) {
+                        Some(header) => file.write_all(header.as_bytes())?,
+                        None => {
+                            let sidecar = provenance_path(&path);
+                            std::fs::write(&sidecar, to_json(provenance)?)
+                                .with_context(|| format!("Failed to write to {}", sidecar))?
+                        }
+                    }
+                }
+            }
+            Box::new(file)
+        }
         None => Box::new(io::stdout()),
     };
 
@@ -99,21 +572,231 @@ pub fn write_to_output(buffer: &[u8], output: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Current UTC time in ISO 8601, e.g. "2024-04-24T09:30:00Z"
+pub fn utc_now() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    format_utc(seconds)
+}
+
+/// Format seconds since the Unix epoch as an ISO 8601 UTC time
+fn format_utc(seconds: u64) -> String {
+    let (days, time) = ((seconds / 86400) as i64, seconds % 86400);
+    // Civil date of a number of days since the epoch, from
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Resolve the cache directory from $XGT_CACHE_DIR, $XDG_CACHE_HOME/xgt
+/// or $HOME/.cache/xgt, in this order of preference
+fn resolve_cache_dir(
+    xgt_cache_dir: Option<String>,
+    xdg_cache_home: Option<String>,
+    home: Option<String>,
+) -> Option<PathBuf> {
+    let non_empty = |x: Option<String>| x.filter(|x| !x.is_empty()).map(PathBuf::from);
+    non_empty(xgt_cache_dir)
+        .or_else(|| non_empty(xdg_cache_home).map(|x| x.join("xgt")))
+        .or_else(|| non_empty(home).map(|x| x.join(".cache").join("xgt")))
+}
+
+/// Directory where xgt caches GTDB release files
+pub fn cache_dir() -> Result<PathBuf> {
+    resolve_cache_dir(
+        env::var("XGT_CACHE_DIR").ok(),
+        env::var("XDG_CACHE_HOME").ok(),
+        env::var("HOME").ok(),
+    )
+    .context("Cannot find a cache directory, please set XGT_CACHE_DIR")
+}
+
+/// Agents shared by all requests of the process, so that connections are
+/// reused, e.g. across the queries of `xgt shell`
+#[cfg(not(target_arch = "wasm32"))]
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+#[cfg(not(target_arch = "wasm32"))]
+static INSECURE_AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
 /// Select agent request based on SSL peer verification activation
-pub fn get_agent(disable_certificate_verification: bool) -> anyhow::Result<ureq::Agent> {
-    match disable_certificate_verification {
-        true => {
-            let tls_connector = Arc::new(
-                native_tls::TlsConnector::builder()
-                    .danger_accept_invalid_certs(true)
-                    .build()?,
-            );
-            Ok(ureq::AgentBuilder::new()
-                .tls_connector(tls_connector)
-                .build())
-        }
-        false => Ok(ureq::AgentBuilder::new().build()),
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_agent(disable_certificate_verification: bool) -> Result<ureq::Agent, XgtError> {
+    let cell = match disable_certificate_verification {
+        true => &INSECURE_AGENT,
+        false => &AGENT,
+    };
+    if let Some(agent) = cell.get() {
+        return Ok(agent.clone());
+    }
+    let agent = tls::build_agent(disable_certificate_verification)?;
+    Ok(cell.get_or_init(|| agent).clone())
+}
+
+/// Browsers only let web pages send requests with fetch, which the async
+/// client uses
+#[cfg(target_arch = "wasm32")]
+pub fn get_agent(_disable_certificate_verification: bool) -> Result<ureq::Agent, XgtError> {
+    Err(XgtError::InvalidInput(
+        "Blocking requests are not available in the browser, please use AsyncGtdbClient"
+            .to_string(),
+    ))
+}
+
+/// Quote a string for a POSIX shell
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Equivalent curl command of a request sent by an agent from `get_agent`
+pub fn curl_command(
+    method: &str,
+    url: &str,
+    data: Option<&str>,
+    disable_certificate_verification: bool,
+) -> String {
+    let mut command = String::from("curl -sS");
+    if disable_certificate_verification {
+        command.push_str(" -k");
+    }
+    if method != "GET" {
+        command.push_str(&format!(" -X {}", method));
+    }
+    if let Some(data) = data {
+        command.push_str(&format!(
+            " -H {} -d {}",
+            shell_quote("Content-Type: application/json"),
+            shell_quote(data)
+        ));
     }
+    command.push_str(&format!(" {}", shell_quote(url)));
+    command
+}
+
+/// Apply `f` to every item using `jobs` worker threads.
+/// Results are returned in the same order as `items`.
+pub fn parallel_map<T, R, F>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            s.spawn(|| loop {
+                let next = queue.lock().expect("queue lock poisoned").next();
+                match next {
+                    Some((index, item)) => {
+                        let result = f(item);
+                        results
+                            .lock()
+                            .expect("results lock poisoned")
+                            .push((index, result));
+                    }
+                    None => break,
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("results lock poisoned");
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Apply `f` to every item using `jobs` worker threads, passing each result
+/// to `sink` as soon as the results of all the items before it were passed,
+/// so that output is written in the order of `items` while they are being
+/// fetched. Items are no longer started once `sink` fails.
+pub fn parallel_for_each_ordered<T, R, E, F, S>(
+    items: Vec<T>,
+    jobs: usize,
+    f: F,
+    mut sink: S,
+) -> Result<(), E>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    S: FnMut(R) -> Result<(), E>,
+{
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let stopped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            let sender = sender.clone();
+            let (queue, stopped, f) = (&queue, &stopped, &f);
+            s.spawn(move || loop {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let next = queue.lock().expect("queue lock poisoned").next();
+                match next {
+                    Some((index, item)) => {
+                        if sender.send((index, f(item))).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+
+        // Reorder buffer of the results which completed before the ones of
+        // earlier items
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next) {
+                next += 1;
+                if let Err(e) = sink(result) {
+                    stopped.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Await `f` on every item, at most `jobs` at a time, on a single thread.
+/// Results are returned in the same order as `items`.
+#[cfg(feature = "async")]
+pub fn concurrent_map<T, R, F, Fut>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    F: Fn(T) -> Fut,
+    Fut: std::future::Future<Output = R>,
+{
+    use futures_util::stream::{self, StreamExt};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the async runtime");
+    runtime.block_on(stream::iter(items).map(f).buffered(jobs.max(1)).collect())
 }
 
 #[cfg(test)]
@@ -167,6 +850,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<u64> = (0..50).collect();
+        let results = parallel_map(items.clone(), 8, |x| {
+            std::thread::sleep(std::time::Duration::from_millis(50 - x));
+            x * 2
+        });
+        assert_eq!(results, items.iter().map(|x| x * 2).collect::<Vec<u64>>());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_concurrent_map_keeps_order() {
+        let items: Vec<u64> = (0..50).collect();
+        let results = concurrent_map(items.clone(), 8, |x| async move {
+            tokio::task::yield_now().await;
+            x * 2
+        });
+        assert_eq!(results, items.iter().map(|x| x * 2).collect::<Vec<u64>>());
+    }
+
     #[test]
     fn test_search_field_from_string() {
         assert_eq!(SearchField::from("acc".to_string()), SearchField::Acc);
@@ -199,6 +903,8 @@ mod tests {
         assert_eq!(OutputFormat::from("csv".to_string()), OutputFormat::Csv);
         assert_eq!(OutputFormat::from("json".to_string()), OutputFormat::Json);
         assert_eq!(OutputFormat::from("tsv".to_string()), OutputFormat::Tsv);
+        assert_eq!(OutputFormat::from("table".to_string()), OutputFormat::Table);
+        assert_eq!(OutputFormat::from("html".to_string()), OutputFormat::Html);
         assert_eq!(OutputFormat::from("unknown".to_string()), OutputFormat::Csv);
         // Default to Csv
     }
@@ -208,5 +914,239 @@ mod tests {
         assert_eq!(OutputFormat::Csv.to_string(), "csv");
         assert_eq!(OutputFormat::Json.to_string(), "json");
         assert_eq!(OutputFormat::Tsv.to_string(), "tsv");
+        assert_eq!(OutputFormat::Table.to_string(), "table");
+        assert_eq!(OutputFormat::Html.to_string(), "html");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let header = ["taxon", "genomes"];
+        let rows = vec![
+            vec!["s__Escherichia coli".to_string(), "35000".to_string()],
+            vec!["s__Foo, bar".to_string(), "1".to_string()],
+        ];
+
+        assert_eq!(
+            render_table(&header, &rows, &OutputFormat::Tsv),
+            "taxon\tgenomes\ns__Escherichia coli\t35000\ns__Foo, bar\t1\n"
+        );
+        assert_eq!(
+            render_table(&header, &rows, &OutputFormat::Csv),
+            "taxon,genomes\ns__Escherichia coli,35000\n\"s__Foo, bar\",1\n"
+        );
+        assert_eq!(
+            render_table(&header, &rows, &OutputFormat::Table),
+            "taxon                genomes\ns__Escherichia coli  35000\ns__Foo, bar          1\n"
+        );
+
+        let rows = vec![vec!["s__Foo bar".to_string(), String::new()]];
+        assert_eq!(
+            render_table_with_na(&header, &rows, &OutputFormat::Csv, "NA"),
+            "taxon,genomes\ns__Foo bar,NA\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        assert_eq!(parse_sort("genome_size"), Ok(("genome_size".into(), false)));
+        assert_eq!(
+            parse_sort("genome_size:desc"),
+            Ok(("genome_size".into(), true))
+        );
+        assert!(parse_sort("genome_size:up").is_err());
+        assert!(parse_sort(":desc").is_err());
+
+        let header = ["taxon", "genomes"];
+        let mut rows = vec![
+            ["s__B", "9"],
+            ["s__A", ""],
+            ["s__C", "35000"],
+            ["s__D", "9"],
+            ["s__E", "none"],
+        ];
+        let taxa = |rows: &[[&'static str; 2]]| rows.iter().map(|x| x[0]).collect::<Vec<_>>();
+        assert!(sort_rows_by(
+            &header,
+            &mut rows,
+            |x, i| x[i],
+            "genomes",
+            false
+        ));
+        assert_eq!(taxa(&rows), ["s__B", "s__D", "s__C", "s__A", "s__E"]);
+        assert!(sort_rows_by(
+            &header,
+            &mut rows,
+            |x, i| x[i],
+            "genomes",
+            true
+        ));
+        assert_eq!(taxa(&rows), ["s__C", "s__B", "s__D", "s__A", "s__E"]);
+        assert!(sort_rows_by(&header, &mut rows, |x, i| x[i], "taxon", true));
+        assert_eq!(taxa(&rows), ["s__E", "s__D", "s__C", "s__B", "s__A"]);
+        assert!(!sort_rows_by(
+            &header,
+            &mut rows,
+            |x, i| x[i],
+            "size",
+            false
+        ));
+    }
+
+    #[test]
+    fn test_sort_xsv_by() {
+        let csv = "accession,ncbi_organism_name\r\nGCA_3,\"Foo, bar\"\r\nGCA_1,Foo\r\nGCA_2,Bar";
+        assert_eq!(
+            sort_xsv_by(csv, ',', "accession").unwrap(),
+            "accession,ncbi_organism_name\r\nGCA_1,Foo\r\nGCA_2,Bar\r\nGCA_3,\"Foo, bar\"\r\n"
+        );
+        let tsv = "accession\tncbi_organism_name\nGCA_3\tFoo\nGCA_1\tFoo\nGCA_2\tBar\n";
+        assert_eq!(
+            sort_xsv_by(tsv, '\t', "ncbi_organism_name").unwrap(),
+            "accession\tncbi_organism_name\nGCA_2\tBar\nGCA_1\tFoo\nGCA_3\tFoo\n"
+        );
+        assert!(sort_xsv_by(tsv, '\t', "genome_size").is_err());
+    }
+
+    #[test]
+    fn test_provenance_header() {
+        let args = ["xgt", "search", "s__Foo bar", "-o", "foo.csv"].map(String::from);
+        let provenance = Provenance {
+            created_at: "2024-04-24T09:30:00Z".to_string(),
+            ..Provenance::new(&args, Some("R220"))
+        };
+        assert_eq!(provenance.command, "xgt search 's__Foo bar' -o foo.csv");
+        let header = provenance.header(b"accession,gtdb_taxonomy").unwrap();
+        assert!(header.starts_with("# xgt "));
+        assert!(header.ends_with(
+            "# GTDB release: R220\n# command: xgt search 's__Foo bar' -o foo.csv\n# created: 2024-04-24T09:30:00Z\n"
+        ));
+        assert_eq!(provenance.header(b"{\"gid\": \"GCA_001512625.1\"}"), None);
+        assert!(provenance
+            .header(b"<!DOCTYPE html>")
+            .unwrap()
+            .starts_with("<!--\n"));
+        assert_eq!(provenance_path("out.json"), "out.json.provenance.json");
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(1713951000), "2024-04-24T09:30:00Z");
+        assert_eq!(format_utc(951868799), "2000-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn test_resolve_cache_dir() {
+        let some = |x: &str| Some(x.to_string());
+        assert_eq!(
+            resolve_cache_dir(some("/tmp/xgt"), some("/cache"), some("/home/u")),
+            Some(PathBuf::from("/tmp/xgt"))
+        );
+        assert_eq!(
+            resolve_cache_dir(some(""), some("/cache"), some("/home/u")),
+            Some(PathBuf::from("/cache/xgt"))
+        );
+        assert_eq!(
+            resolve_cache_dir(None, None, some("/home/u")),
+            Some(PathBuf::from("/home/u/.cache/xgt"))
+        );
+        assert_eq!(resolve_cache_dir(None, None, None), None);
+    }
+
+    #[test]
+    fn test_curl_command() {
+        assert_eq!(
+            curl_command(
+                "GET",
+                "https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/card",
+                None,
+                false
+            ),
+            "curl -sS 'https://api.gtdb.ecogenomic.org/genome/GCA_001512625.1/card'"
+        );
+        assert_eq!(
+            curl_command(
+                "POST",
+                "https://api.gtdb.ecogenomic.org/search",
+                Some(r#"{"q": "it's"}"#),
+                true
+            ),
+            r#"curl -sS -k -X POST -H 'Content-Type: application/json' -d '{"q": "it'\''s"}' 'https://api.gtdb.ecogenomic.org/search'"#
+        );
+    }
+
+    #[test]
+    fn test_for_each_item() {
+        let items: Vec<String> = ["GCA_1", "GCA_2", "GCA_3"].map(String::from).to_vec();
+        let fetch = |x: &str| -> Result<()> {
+            match x {
+                "GCA_2" => Err(XgtError::NotFound(format!("Genome {} not found", x)).into()),
+                _ => Ok(()),
+            }
+        };
+
+        // Failed items are reported, and the batch fails with the first error
+        let mut done = Vec::new();
+        let error = for_each_item(items.clone(), false, |x| {
+            fetch(x)?;
+            done.push(x.to_string());
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(done, ["GCA_1", "GCA_3"]);
+        assert_eq!(error.to_string(), "1 of 3 queries failed");
+        assert_eq!(error::exit_code(&error), 3);
+
+        let mut done = Vec::new();
+        let error = for_each_item(items.clone(), true, |x| {
+            fetch(x)?;
+            done.push(x.to_string());
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(done, ["GCA_1"]);
+        assert_eq!(error.to_string(), "Genome GCA_2 not found");
+
+        // Interrupted batches stop whatever the mode
+        let mut errors = ItemErrors::new(3, false);
+        let result: Result<()> = Err(XgtError::Interrupted.into());
+        assert!(errors.check("GCA_1", result).is_err());
+    }
+
+    #[test]
+    fn test_parallel_for_each_ordered() {
+        // Later items complete first, and are written after the earlier ones
+        let mut written = Vec::new();
+        let result: Result<(), String> = parallel_for_each_ordered(
+            (0..8).collect(),
+            4,
+            |x: u64| {
+                thread::sleep(std::time::Duration::from_millis(8 * (8 - x)));
+                x
+            },
+            |x| {
+                written.push(x);
+                Ok(())
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(written, (0..8).collect::<Vec<u64>>());
+
+        // Items are no longer written once the sink failed
+        let mut written = Vec::new();
+        let result = parallel_for_each_ordered(
+            (0..100).collect(),
+            2,
+            |x: u64| x,
+            |x| match x {
+                3 => Err(format!("Failed to write {}", x)),
+                x => {
+                    written.push(x);
+                    Ok(())
+                }
+            },
+        );
+        assert_eq!(result, Err("Failed to write 3".to_string()));
+        assert_eq!(written, [0, 1, 2]);
     }
 }
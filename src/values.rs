@@ -0,0 +1,345 @@
+//! Normalization of the loosely typed values of GTDB, which sends numbers
+//! as strings ("100.0") and booleans as "True"/"False" or "t"/"f"
+//!
+//! Fields are typed by their name, so that a column has the same type in
+//! every record whatever its values.
+
+use serde_json::{Number, Value};
+
+use crate::utils::OutputFormat;
+
+/// Suffixes and prefixes of the names of numeric fields, e.g. `contig_count`,
+/// `checkm_completeness`, `n50_scaffolds` or `ncbi_scaffold_l50`
+const NUMERIC_SUFFIXES: [&str; 11] = [
+    "_count",
+    "_completeness",
+    "_contamination",
+    "_heterogeneity",
+    "_density",
+    "_size",
+    "_length",
+    "_len",
+    "_percentage",
+    "_gaps",
+    "_bases",
+];
+const NUMERIC_PREFIXES: [&str; 4] = ["n50_", "l50_", "longest_", "mean_"];
+
+/// Names of boolean fields which are not named `is_*`
+const BOOLEAN_FIELDS: [&str; 7] = [
+    "gtdb_representative",
+    "gtdb_species_representative",
+    "gtdb_type_species_of_genus",
+    "ncbi_type_material",
+    "mimag_high_quality",
+    "mimag_medium_quality",
+    "mimag_low_quality",
+];
+
+/// Values standing for a missing value
+const MISSING_VALUES: [&str; 4] = ["", "none", "None", "null"];
+
+/// Check if a value is one of the encodings of missing values used by GTDB
+pub(crate) fn is_missing(value: &str) -> bool {
+    MISSING_VALUES.contains(&value.trim())
+}
+
+fn is_numeric_field(name: &str) -> bool {
+    NUMERIC_SUFFIXES.iter().any(|x| name.ends_with(x))
+        || NUMERIC_PREFIXES.iter().any(|x| name.starts_with(x))
+        || name == "ncbi_translation_table"
+        || name
+            .rsplit_once('_')
+            .is_some_and(|(_, x)| is_statistic_name(x))
+}
+
+/// Check names of assembly statistics such as "n50" or "l90"
+fn is_statistic_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some('n' | 'l'))
+        && !chars.as_str().is_empty()
+        && chars.all(|c| c.is_ascii_digit())
+}
+
+/// Boolean fields are also named `is_*`, or `isNcbiSurveillance` in camel case
+fn is_boolean_field(name: &str) -> bool {
+    let is_flag = name
+        .strip_prefix("is")
+        .is_some_and(|x| x.starts_with('_') || x.starts_with(|c: char| c.is_ascii_uppercase()));
+    is_flag || BOOLEAN_FIELDS.contains(&name)
+}
+
+/// Parse the encodings of booleans used by GTDB
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        "True" | "true" | "TRUE" | "t" => Some(true),
+        "False" | "false" | "FALSE" | "f" => Some(false),
+        _ => None,
+    }
+}
+
+/// Month names of NCBI dates, e.g. "13-Jan-2016"
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Date as (year, month, day), with the month and day missing from partial
+/// dates, e.g. "2020" or "2020-06"
+pub(crate) type PartialDate = (u32, Option<u32>, Option<u32>);
+
+/// Parse the date formats of GTDB and NCBI, e.g. "2016/01/13",
+/// "2016-01-13T00:00:00Z", "13-Jan-2016" or "Jan 13, 2016", and partial
+/// dates such as "2016" or "2016-01"
+pub(crate) fn parse_partial_date(value: &str) -> Option<PartialDate> {
+    // Times, e.g. "00:00:00Z", are ignored
+    let tokens: Vec<&str> = value
+        .split(['-', '/', '.', ',', 'T', ' '])
+        .filter(|x| !x.is_empty() && !x.contains(':'))
+        .collect();
+    let number = |x: &str| match x.chars().all(|c| c.is_ascii_digit()) {
+        true => x.parse::<u32>().ok(),
+        false => None,
+    };
+    let month_name = |x: &str| {
+        let x = x.to_lowercase();
+        MONTHS
+            .iter()
+            .position(|m| x.starts_with(m))
+            .map(|i| i as u32 + 1)
+    };
+
+    let date = match tokens.as_slice() {
+        [year] if year.len() == 4 => (number(year)?, None, None),
+        [year, month] if year.len() == 4 => (
+            number(year)?,
+            Some(number(month).or_else(|| month_name(month))?),
+            None,
+        ),
+        [year, month, day] if year.len() == 4 => (
+            number(year)?,
+            Some(number(month).or_else(|| month_name(month))?),
+            Some(number(day)?),
+        ),
+        [day, month, year] if year.len() == 4 && month_name(month).is_some() => {
+            (number(year)?, month_name(month), Some(number(day)?))
+        }
+        [month, day, year] if year.len() == 4 && month_name(month).is_some() => {
+            (number(year)?, month_name(month), Some(number(day)?))
+        }
+        _ => return None,
+    };
+    let is_valid = date.1.map_or(true, |m| (1..=12).contains(&m))
+        && date.2.map_or(true, |d| (1..=31).contains(&d));
+    is_valid.then_some(date)
+}
+
+/// Parse a full date as (year, month, day)
+pub(crate) fn parse_date(value: &str) -> Option<(u32, u32, u32)> {
+    match parse_partial_date(value)? {
+        (year, Some(month), Some(day)) => Some((year, month, day)),
+        _ => None,
+    }
+}
+
+/// Parse a number, keeping integers as integers
+fn parse_number(value: &str) -> Option<Number> {
+    let value = value.trim();
+    if let Ok(integer) = value.parse::<i64>() {
+        return Some(integer.into());
+    }
+    // Rust also parses "inf" and "NaN", which are not numbers in JSON
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'))
+    {
+        return None;
+    }
+    value.parse::<f64>().ok().and_then(Number::from_f64)
+}
+
+/// JSON value of a field, null when missing and the original string when
+/// it cannot be parsed as the type of the field
+pub(crate) fn typed_value(field: &str, value: &str) -> Value {
+    let is_numeric = is_numeric_field(field);
+    let is_boolean = is_boolean_field(field);
+    if (is_numeric || is_boolean) && MISSING_VALUES.contains(&value.trim()) {
+        return Value::Null;
+    }
+    let typed = if is_boolean {
+        parse_bool(value).map(Value::Bool)
+    } else if is_numeric {
+        parse_number(value).map(Value::Number)
+    } else {
+        None
+    };
+    typed.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// Replace the strings of the numeric and boolean fields of JSON objects,
+/// at any depth, by numbers and booleans
+pub(crate) fn normalize_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (field, value) in map.iter_mut() {
+                match value {
+                    Value::String(s) => *value = typed_value(field, s),
+                    _ => normalize_json(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize_json),
+        _ => (),
+    }
+}
+
+/// Representation of a field in CSV/TSV output: "true"/"false" for
+/// booleans, `na_string` for missing values and the value itself otherwise
+pub(crate) fn normalize_cell(field: &str, value: &str, na_string: &str) -> String {
+    match typed_value(field, value) {
+        Value::Bool(b) => b.to_string(),
+        Value::Null => na_string.to_string(),
+        _ if value.is_empty() => na_string.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Split a line of CSV/TSV in its raw fields, quotes included
+pub(crate) fn split_xsv_line(line: &str, separator: char) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                fields.push(&line[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+/// Normalize the fields of a CSV/TSV response of the GTDB API, keeping its
+/// line endings
+pub(crate) fn normalize_xsv(text: &str, outfmt: &OutputFormat, na_string: &str) -> String {
+    let separator = match outfmt {
+        OutputFormat::Csv => ',',
+        _ => '\t',
+    };
+    let mut lines = text.split_inclusive('\n');
+    let Some(header_line) = lines.next() else {
+        return String::new();
+    };
+    let header: Vec<&str> = split_xsv_line(header_line.trim_end(), separator);
+
+    let mut output = String::with_capacity(text.len());
+    output.push_str(header_line);
+    for line in lines {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let fields: Vec<String> = split_xsv_line(content, separator)
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match header.get(i) {
+                Some(field) => normalize_cell(field, value, na_string),
+                None => value.to_string(),
+            })
+            .collect();
+        output.push_str(&fields.join(&separator.to_string()));
+        output.push_str(&line[content.len()..]);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_value() {
+        assert_eq!(typed_value("checkm_completeness", "100.0"), json!(100.0));
+        assert_eq!(typed_value("protein_count", "2531"), json!(2531));
+        assert_eq!(typed_value("ncbi_scaffold_n50", "64210"), json!(64210));
+        assert_eq!(typed_value("ncbi_translation_table", "11"), json!(11));
+        assert_eq!(typed_value("gc_percentage", "1.2e1"), json!(12.0));
+        assert_eq!(typed_value("genome_size", "none"), Value::Null);
+        assert_eq!(typed_value("genome_size", ""), Value::Null);
+        assert_eq!(typed_value("genome_size", "NaN"), json!("NaN"));
+        assert_eq!(typed_value("gtdb_representative", "t"), json!(true));
+        assert_eq!(
+            typed_value("gtdb_species_representative", "False"),
+            json!(false)
+        );
+        assert_eq!(typed_value("isNcbiSurveillance", "True"), json!(true));
+        // Identifiers which look like numbers are left as strings
+        assert_eq!(typed_value("ncbi_taxid", "1977087"), json!("1977087"));
+        assert_eq!(typed_value("ncbi_strain_identifiers", "12"), json!("12"));
+        assert_eq!(typed_value("ncbi_isolate", "none"), json!("none"));
+    }
+
+    #[test]
+    fn test_normalize_json() {
+        let mut value = json!({
+            "metadata_gene": {"checkm_completeness": "99.45", "ssu_count": "1"},
+            "metadata_ncbi": {"ncbi_taxid": "1977087", "ncbi_cds_count": null},
+            "rows": [{"gtdb_representative": "f"}],
+            "genome_size": 5001
+        });
+        normalize_json(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "metadata_gene": {"checkm_completeness": 99.45, "ssu_count": 1},
+                "metadata_ncbi": {"ncbi_taxid": "1977087", "ncbi_cds_count": null},
+                "rows": [{"gtdb_representative": false}],
+                "genome_size": 5001
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        for date in [
+            "2016/01/13",
+            "2016-01-13",
+            "2016-01-13T00:00:00Z",
+            "2016-01-13 12:30:00",
+            "13-Jan-2016",
+            "Jan 13, 2016",
+            "13 January 2016",
+        ] {
+            assert_eq!(parse_date(date), Some((2016, 1, 13)), "{}", date);
+        }
+        assert_eq!(parse_partial_date("2020"), Some((2020, None, None)));
+        assert_eq!(parse_partial_date("2020-06"), Some((2020, Some(6), None)));
+        assert_eq!(parse_date("2020-06"), None);
+        assert_eq!(parse_date("2016-13-01"), None);
+        assert_eq!(parse_date("13/01/2016"), None);
+        assert_eq!(parse_date("none"), None);
+        assert_eq!(parse_date(""), None);
+    }
+
+    #[test]
+    fn test_normalize_xsv() {
+        let csv = "accession,ncbi_organism_name,gtdb_species_representative,ncbi_type_material\r\n\
+                   GCA_000016265.1,\"Agrobacterium radiobacter K84, True\",False,True\r\n";
+        assert_eq!(
+            normalize_xsv(csv, &OutputFormat::Csv, ""),
+            "accession,ncbi_organism_name,gtdb_species_representative,ncbi_type_material\r\n\
+             GCA_000016265.1,\"Agrobacterium radiobacter K84, True\",false,true\r\n"
+        );
+        let tsv = "accession\tgtdb_representative\nGCA_000016265.1\tt";
+        assert_eq!(
+            normalize_xsv(tsv, &OutputFormat::Tsv, ""),
+            "accession\tgtdb_representative\nGCA_000016265.1\ttrue"
+        );
+        let tsv = "accession\tgenome_size\tncbi_strain_identifiers\nGCA_000016265.1\tnone\t";
+        assert_eq!(
+            normalize_xsv(tsv, &OutputFormat::Tsv, "NA"),
+            "accession\tgenome_size\tncbi_strain_identifiers\nGCA_000016265.1\tNA\tNA"
+        );
+    }
+}